@@ -8,10 +8,19 @@ import type { ModelRegistryEntry } from "@signet/core";
 
 // When served by the daemon, use relative URLs.
 // When served by Tauri (frontendDist) or Vite dev server, use absolute URL.
+// The desktop app injects the daemon URL it resolved from its settings.
 const isDev = import.meta.env.DEV;
 const isTauri =
 	typeof window !== "undefined" && "__TAURI_INTERNALS__" in window;
-const API_BASE = isDev || isTauri ? "http://localhost:3850" : "";
+const injectedBase =
+	typeof window !== "undefined"
+		? (window as { __SIGNET_DAEMON_URL__?: string }).__SIGNET_DAEMON_URL__
+		: undefined;
+const API_BASE = isTauri
+	? (injectedBase ?? "http://localhost:3850")
+	: isDev
+		? "http://localhost:3850"
+		: "";
 
 export interface Memory {
 	id: string;
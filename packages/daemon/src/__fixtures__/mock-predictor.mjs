@@ -59,7 +59,7 @@ function handleLine(line) {
 					model_version: 1,
 					last_trained: null,
 					native_dimensions: Number.isFinite(nativeDim) ? nativeDim : 768,
-					feature_dimensions: 17,
+					feature_dimensions: 21,
 				};
 				break;
 
@@ -0,0 +1,59 @@
+/**
+ * Tests for the daemon-wide capture pause.
+ */
+
+import { afterEach, describe, expect, it } from "bun:test";
+import { existsSync, mkdtempSync, rmSync } from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { getCapturePause, initCapturePause, isCapturePaused, pauseCapture, resumeCapture } from "./capture-pause";
+
+const dirs: string[] = [];
+
+function statePath(): string {
+	const dir = mkdtempSync(join(tmpdir(), "signet-capture-pause-"));
+	dirs.push(dir);
+	return join(dir, ".daemon", "capture-pause.json");
+}
+
+afterEach(() => {
+	resumeCapture();
+	for (const dir of dirs.splice(0)) rmSync(dir, { recursive: true, force: true });
+});
+
+describe("capture pause", () => {
+	it("is not paused by default", () => {
+		initCapturePause(statePath());
+		expect(getCapturePause()).toEqual({ paused: false, until: null });
+	});
+
+	it("pauses for a number of minutes and expires", () => {
+		initCapturePause(statePath());
+		const now = Date.parse("2026-01-01T00:00:00.000Z");
+		const state = pauseCapture(60, now);
+		expect(state).toEqual({ paused: true, until: "2026-01-01T01:00:00.000Z" });
+		expect(isCapturePaused(now + 59 * 60_000)).toBe(true);
+		expect(isCapturePaused(now + 60 * 60_000)).toBe(false);
+	});
+
+	it("pauses until resumed when no duration is given", () => {
+		initCapturePause(statePath());
+		expect(pauseCapture()).toEqual({ paused: true, until: null });
+		expect(isCapturePaused(Date.now() + 365 * 24 * 60 * 60_000)).toBe(true);
+		expect(resumeCapture()).toEqual({ paused: false, until: null });
+		expect(isCapturePaused()).toBe(false);
+	});
+
+	it("survives a restart and removes its file on resume", () => {
+		const path = statePath();
+		initCapturePause(path);
+		pauseCapture();
+		expect(existsSync(path)).toBe(true);
+
+		initCapturePause(path);
+		expect(isCapturePaused()).toBe(true);
+
+		resumeCapture();
+		expect(existsSync(path)).toBe(false);
+	});
+});
@@ -0,0 +1,98 @@
+/**
+ * Capture Pause
+ *
+ * A daemon-wide switch that stops hook-driven memory capture (session-end
+ * extraction, /remember from hooks, compaction summaries) for a while,
+ * e.g. while the user is handling sensitive material. Recall keeps
+ * working and explicit saves through the API and MCP tools are untouched.
+ *
+ * The pause is written to disk so a daemon restart does not quietly
+ * resume capture before the user asked it to.
+ */
+
+import { existsSync, mkdirSync, readFileSync, rmSync, writeFileSync } from "node:fs";
+import { dirname } from "node:path";
+import { emitDaemonEvent } from "./daemon-events";
+import { logger } from "./logger";
+
+export interface CapturePauseState {
+	readonly paused: boolean;
+	/** ISO time the pause ends, or null when paused until resumed. */
+	readonly until: string | null;
+}
+
+interface StoredPause {
+	readonly until: number | null;
+}
+
+let statePath: string | null = null;
+let pause: StoredPause | null = null;
+
+/** Load any pause left by a previous run. Call once at startup. */
+export function initCapturePause(path: string): void {
+	statePath = path;
+	pause = null;
+	if (!existsSync(path)) return;
+	try {
+		const raw = JSON.parse(readFileSync(path, "utf-8")) as { until?: unknown };
+		pause = { until: typeof raw.until === "number" ? raw.until : null };
+	} catch (e) {
+		logger.warn("hooks", "Ignoring unreadable capture pause file", { path, error: String(e) });
+	}
+}
+
+function persist(): void {
+	if (!statePath) return;
+	try {
+		if (pause) {
+			mkdirSync(dirname(statePath), { recursive: true });
+			writeFileSync(statePath, JSON.stringify(pause));
+		} else if (existsSync(statePath)) {
+			rmSync(statePath);
+		}
+	} catch (e) {
+		logger.warn("hooks", "Failed to persist capture pause", { path: statePath, error: String(e) });
+	}
+}
+
+/** Current pause state. An expired pause is cleared here. */
+export function getCapturePause(now = Date.now()): CapturePauseState {
+	if (pause?.until != null && pause.until <= now) {
+		pause = null;
+		persist();
+		logger.info("hooks", "Capture pause expired");
+		emitDaemonEvent("capture-state", { paused: false });
+	}
+	if (!pause) return { paused: false, until: null };
+	return {
+		paused: true,
+		until: pause.until === null ? null : new Date(pause.until).toISOString(),
+	};
+}
+
+export function isCapturePaused(now = Date.now()): boolean {
+	return getCapturePause(now).paused;
+}
+
+/**
+ * Pause capture for `minutes`, or until resumed when omitted. Pausing
+ * again replaces the previous deadline.
+ */
+export function pauseCapture(minutes?: number, now = Date.now()): CapturePauseState {
+	pause = { until: minutes === undefined ? null : now + minutes * 60_000 };
+	persist();
+	logger.info("hooks", "Capture paused", { minutes: minutes ?? "until resumed" });
+	const state = getCapturePause(now);
+	emitDaemonEvent("capture-state", { paused: state.paused, until: state.until });
+	return state;
+}
+
+export function resumeCapture(): CapturePauseState {
+	if (pause) {
+		pause = null;
+		persist();
+		logger.info("hooks", "Capture resumed");
+		emitDaemonEvent("capture-state", { paused: false });
+	}
+	return { paused: false, until: null };
+}
@@ -0,0 +1,74 @@
+/**
+ * Tests for the daemon event bus behind /api/events.
+ */
+
+import { Database } from "bun:sqlite";
+import { afterEach, describe, expect, it } from "bun:test";
+import { runMigrations } from "@signet/core";
+import { pauseCapture, resumeCapture } from "./capture-pause";
+import { type DaemonEvent, emitDaemonEvent, onDaemonEvent } from "./daemon-events";
+import type { WriteDb } from "./db-accessor";
+import { txIngestEnvelope } from "./transactions";
+
+const unsubscribers: Array<() => void> = [];
+
+function collect(): DaemonEvent[] {
+	const events: DaemonEvent[] = [];
+	unsubscribers.push(onDaemonEvent((event) => events.push(event)));
+	return events;
+}
+
+afterEach(() => {
+	for (const unsubscribe of unsubscribers.splice(0)) unsubscribe();
+	resumeCapture();
+});
+
+describe("daemon events", () => {
+	it("delivers events to subscribers until they unsubscribe", () => {
+		const events: DaemonEvent[] = [];
+		const unsubscribe = onDaemonEvent((event) => events.push(event));
+		emitDaemonEvent("job-finished", { jobId: "j1", status: "completed" });
+		unsubscribe();
+		emitDaemonEvent("job-finished", { jobId: "j2", status: "completed" });
+
+		expect(events).toHaveLength(1);
+		expect(events[0]).toMatchObject({ type: "job-finished", jobId: "j1", status: "completed" });
+		expect(typeof events[0].at).toBe("string");
+	});
+
+	it("announces saved memories", () => {
+		const db = new Database(":memory:");
+		runMigrations(db);
+		const events = collect();
+		const now = new Date().toISOString();
+		txIngestEnvelope(db as unknown as WriteDb, {
+			id: "mem-1",
+			content: "the build uses bun",
+			contentHash: "hash-1",
+			who: "test",
+			why: "test",
+			project: null,
+			importance: 0.5,
+			type: "fact",
+			tags: null,
+			pinned: 0,
+			sourceType: "unit-test",
+			sourceId: "mem-1",
+			createdAt: now,
+		});
+
+		expect(events.map((e) => [e.type, e.id])).toEqual([["memory-created", "mem-1"]]);
+		db.close();
+	});
+
+	it("announces capture pause and resume", () => {
+		const events = collect();
+		pauseCapture();
+		resumeCapture();
+
+		expect(events.map((e) => [e.type, e.paused])).toEqual([
+			["capture-state", true],
+			["capture-state", false],
+		]);
+	});
+});
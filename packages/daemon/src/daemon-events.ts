@@ -0,0 +1,46 @@
+/**
+ * Daemon Events
+ *
+ * In-process bus behind `GET /api/events`. Events tell clients (the tray)
+ * that something they display changed so they can re-read it instead of
+ * waiting for their next poll. They carry ids and states, never memory
+ * content.
+ *
+ * Publishers may emit from inside a write transaction; a rolled-back write
+ * then costs a listener one redundant read, nothing more.
+ */
+
+import { EventEmitter } from "node:events";
+
+export type DaemonEventType =
+	/** A memory row was inserted */
+	| "memory-created"
+	/** A pipeline job completed or went dead */
+	| "job-finished"
+	/** Capture was paused or resumed */
+	| "capture-state";
+
+export interface DaemonEvent {
+	readonly type: DaemonEventType;
+	readonly at: string;
+	readonly [key: string]: string | number | boolean | null;
+}
+
+const bus = new EventEmitter();
+// One listener per open stream; don't warn past the default of 10
+bus.setMaxListeners(0);
+
+export function emitDaemonEvent(
+	type: DaemonEventType,
+	data: Readonly<Record<string, string | number | boolean | null>> = {},
+): void {
+	bus.emit("event", { ...data, type, at: new Date().toISOString() } satisfies DaemonEvent);
+}
+
+/** Subscribe to every event. Returns the unsubscribe function. */
+export function onDaemonEvent(listener: (event: DaemonEvent) => void): () => void {
+	bus.on("event", listener);
+	return () => {
+		bus.off("event", listener);
+	};
+}
@@ -79,7 +79,8 @@ import {
 	touchAgentPresence,
 	upsertAgentPresence,
 } from "./cross-agent";
-import { closeDbAccessor, getDbAccessor, initDbAccessor } from "./db-accessor";
+import { emitDaemonEvent, onDaemonEvent } from "./daemon-events";
+import { type DbAccessor, closeDbAccessor, getDbAccessor, initDbAccessor } from "./db-accessor";
 import { syncVecDeleteBySourceId, syncVecInsert, vectorToBlob } from "./db-helpers";
 import {
 	type DiagnosticsReport,
@@ -96,6 +97,15 @@ import {
 } from "./embedding-fetch";
 import { detectDrift } from "./predictor-comparison";
 import { getPredictorState } from "./predictor-state";
+import {
+	TrainingDataError,
+	fetchHardNegatives,
+	fetchTrainingBatch,
+	listTrainingSessions,
+	parseBatchRequest,
+	parseHardNegativesRequest,
+	parseSessionsRequest,
+} from "./predictor-training-data";
 import { buildEmbeddingHealth } from "./embedding-health";
 import { type EmbeddingTrackerHandle, startEmbeddingTracker } from "./embedding-tracker";
 import { getAllFeatureFlags, initFeatureFlags } from "./feature-flags";
@@ -172,6 +182,7 @@ import {
 	getEmbeddingGapStats,
 	pruneChunkGroupEntities,
 	pruneSingletonExtractedEntities,
+	purgeDeadJobs,
 	reclassifyEntities,
 	reembedMissingMemories,
 	releaseStaleLeases,
@@ -1312,6 +1323,12 @@ app.use("/api/memory/forget", async (c, next) => {
 	});
 });
 
+// Capture pause — anyone who can remember can read it; changing it is
+// a remember-level action too, since it only withholds captures
+app.use("/api/capture/pause", async (c, next) => {
+	return requirePermission("remember", authConfig)(c, next);
+});
+
 // Recover
 app.use("/api/memory/:id/recover", async (c, next) => {
 	return requirePermission("recover", authConfig)(c, next);
@@ -1377,6 +1394,11 @@ app.use("/api/predictor/*", async (c, next) => {
 	return requirePermission("analytics", authConfig)(c, next);
 });
 
+// Predictor training data exports memory content — admin only
+app.use("/api/predictor/training-data/*", async (c, next) => {
+	return requirePermission("admin", authConfig)(c, next);
+});
+
 // Timeline — read-only (uses analytics permission)
 app.use("/api/timeline/*", async (c, next) => {
 	return requirePermission("analytics", authConfig)(c, next);
@@ -1478,6 +1500,49 @@ app.get("/api/logs/stream", (c) => {
 	});
 });
 
+// Keepalive comments let clients tell a quiet stream from a dead one
+const EVENTS_KEEPALIVE_MS = 15_000;
+
+app.get("/api/events", (c) => {
+	const encoder = new TextEncoder();
+	let cleanup = () => {};
+
+	const stream = new ReadableStream({
+		start(controller) {
+			const send = (chunk: string) => {
+				try {
+					controller.enqueue(encoder.encode(chunk));
+				} catch {
+					cleanup();
+				}
+			};
+			const unsubscribe = onDaemonEvent((event) => {
+				send(`event: ${event.type}\ndata: ${JSON.stringify(event)}\n\n`);
+			});
+			const keepalive = setInterval(() => send(": keepalive\n\n"), EVENTS_KEEPALIVE_MS);
+			cleanup = () => {
+				unsubscribe();
+				clearInterval(keepalive);
+			};
+
+			send(`event: connected\ndata: {"type":"connected"}\n\n`);
+
+			c.req.raw.signal.addEventListener("abort", () => cleanup());
+		},
+		cancel() {
+			cleanup();
+		},
+	});
+
+	return new Response(stream, {
+		headers: {
+			"Content-Type": "text/event-stream",
+			"Cache-Control": "no-cache",
+			Connection: "keep-alive",
+		},
+	});
+});
+
 // ============================================================================
 // Config API
 // ============================================================================
@@ -1574,11 +1639,14 @@ app.get("/api/memories", (c) => {
 	try {
 		const limit = Number.parseInt(c.req.query("limit") || "100", 10);
 		const offset = Number.parseInt(c.req.query("offset") || "0", 10);
+		const sinceParam = c.req.query("since");
+		const sinceMs = sinceParam ? Date.parse(sinceParam) : Number.NaN;
+		const since = Number.isNaN(sinceMs) ? null : new Date(sinceMs).toISOString();
 
 		const result = getDbAccessor().withReadDb((db) => {
 			const memories = db
 				.prepare(`
-      SELECT id, content, created_at, who, importance, tags, source_type, pinned, type
+      SELECT id, content, created_at, who, importance, tags, source_type, pinned, type, project
       FROM memories
       ORDER BY created_at DESC
       LIMIT ? OFFSET ?
@@ -1596,6 +1664,12 @@ app.get("/api/memories", (c) => {
 			const critResult = db.prepare("SELECT COUNT(*) as count FROM memories WHERE importance >= 0.9").get() as {
 				count: number;
 			};
+			const sinceResult =
+				since === null
+					? null
+					: (db.prepare("SELECT COUNT(*) as count FROM memories WHERE created_at >= ?").get(since) as {
+							count: number;
+						});
 
 			return {
 				memories,
@@ -1603,6 +1677,7 @@ app.get("/api/memories", (c) => {
 					total: totalResult?.count ?? 0,
 					withEmbeddings: embeddingsCount,
 					critical: critResult?.count ?? 0,
+					...(sinceResult ? { since: sinceResult.count } : {}),
 				},
 			};
 		});
@@ -4880,6 +4955,8 @@ import {
 	unbypassSession,
 } from "./session-tracker.js";
 
+import { getCapturePause, initCapturePause, isCapturePaused, pauseCapture, resumeCapture } from "./capture-pause.js";
+
 /** Read the runtime path from header or body, preferring header. */
 function resolveRuntimePath(c: Context, body?: { runtimePath?: string }): RuntimePath | undefined {
 	const header = c.req.header("x-signet-runtime-path");
@@ -4909,6 +4986,9 @@ function checkSessionClaim(
 // Start session cleanup timer
 startSessionCleanup();
 
+// Restore a capture pause left by the previous run
+initCapturePause(join(DAEMON_DIR, "capture-pause.json"));
+
 // Harness last-seen registry — in-memory, resets on daemon restart
 const harnessLastSeen = new Map<string, string>();
 
@@ -5066,6 +5146,14 @@ app.post("/api/hooks/session-end", async (c) => {
 			return c.json({ memoriesSaved: 0, bypassed: true });
 		}
 
+		if (isCapturePaused()) {
+			if (sessionKey) {
+				releaseSession(sessionKey);
+				removeAgentPresence(sessionKey);
+			}
+			return c.json({ memoriesSaved: 0, capturePaused: true });
+		}
+
 	try {
 		const result = await handleSessionEnd(body);
 		return c.json(result);
@@ -5104,6 +5192,10 @@ app.post("/api/hooks/remember", async (c) => {
 			return c.json({ success: true, memories: [], bypassed: true });
 		}
 
+		if (isCapturePaused()) {
+			return c.json({ success: true, memories: [], capturePaused: true });
+		}
+
 		const result = handleRemember(body);
 		return c.json(result);
 	} catch (e) {
@@ -5191,6 +5283,10 @@ app.post("/api/hooks/compaction-complete", async (c) => {
 			return c.json({ success: true, bypassed: true });
 		}
 
+		if (isCapturePaused()) {
+			return c.json({ success: true, capturePaused: true });
+		}
+
 		// Save the summary as a memory
 		if (!existsSync(MEMORY_DB)) {
 			return c.json({ error: "Memory database not found" }, 500);
@@ -5216,6 +5312,7 @@ app.post("/api/hooks/compaction-complete", async (c) => {
 				"system",
 			);
 		});
+		emitDaemonEvent("memory-created", { id: summaryId });
 
 		logger.info("hooks", "Compaction summary saved", {
 			harness: body.harness,
@@ -5735,6 +5832,28 @@ app.post("/api/sessions/:key/bypass", async (c) => {
 	return c.json({ key, bypassed: enabled });
 });
 
+// ============================================================================
+// Capture pause
+// ============================================================================
+
+// Daemon-wide pause of hook-driven capture
+app.get("/api/capture/pause", (c) => {
+	return c.json(getCapturePause());
+});
+
+app.post("/api/capture/pause", async (c) => {
+	const body = await readOptionalJsonObject(c);
+	const minutes = body?.minutes;
+	if (minutes !== undefined && minutes !== null && (typeof minutes !== "number" || !(minutes > 0))) {
+		return c.json({ error: "minutes must be a positive number, or omitted to pause until resumed" }, 400);
+	}
+	return c.json(pauseCapture(typeof minutes === "number" ? minutes : undefined));
+});
+
+app.delete("/api/capture/pause", (c) => {
+	return c.json(resumeCapture());
+});
+
 // Session summaries DAG
 app.get("/api/sessions/summaries", (c) => {
 	const accessor = getDbAccessor();
@@ -6472,6 +6591,7 @@ app.get("/api/status", (c) => {
 		},
 		activeSessions: activeSessionCount(),
 		bypassedSessions: getBypassedSessionKeys().size,
+		capturePause: getCapturePause(),
 		agentCreatedAt,
 		...(health ? { health } : {}),
 		update: {
@@ -6717,10 +6837,26 @@ function resolveRepairContext(c: Context): RepairContext {
 	return { reason, actor, actorType, requestId };
 }
 
-app.post("/api/repair/requeue-dead", (c) => {
+app.post("/api/repair/requeue-dead", async (c) => {
+	const cfg = loadMemoryConfig(AGENTS_DIR);
+	const ctx = resolveRepairContext(c);
+	let maxBatch: number | undefined;
+	try {
+		const body = await c.req.json();
+		if (typeof body?.maxBatch === "number" && body.maxBatch > 0) {
+			maxBatch = Math.floor(body.maxBatch);
+		}
+	} catch {
+		// no body or invalid JSON — use the default batch
+	}
+	const result = requeueDeadJobs(getDbAccessor(), cfg.pipelineV2, ctx, repairLimiter, maxBatch);
+	return c.json(result, result.success ? 200 : 429);
+});
+
+app.post("/api/repair/purge-dead", (c) => {
 	const cfg = loadMemoryConfig(AGENTS_DIR);
 	const ctx = resolveRepairContext(c);
-	const result = requeueDeadJobs(getDbAccessor(), cfg.pipelineV2, ctx, repairLimiter);
+	const result = purgeDeadJobs(getDbAccessor(), cfg.pipelineV2, ctx, repairLimiter);
 	return c.json(result, result.success ? 200 : 429);
 });
 
@@ -7512,6 +7648,39 @@ app.get("/api/predictor/training-pairs-count", (c) => {
 	return c.json({ count });
 });
 
+// Rows for a predictor sidecar that trains through the daemon instead of
+// opening the database file (the `daemon` param of train_from_db/evaluate)
+async function trainingDataRoute<T, R extends object>(
+	c: Context,
+	parse: (body: unknown) => T,
+	load: (accessor: DbAccessor, req: T) => R,
+) {
+	let body: unknown;
+	try {
+		body = await c.req.json();
+	} catch {
+		return c.json({ error: "Invalid JSON body" }, 400);
+	}
+	try {
+		return c.json(load(getDbAccessor(), parse(body)));
+	} catch (err) {
+		if (err instanceof TrainingDataError) {
+			return c.json({ error: err.message }, 400);
+		}
+		throw err;
+	}
+}
+
+app.post("/api/predictor/training-data/sessions", (c) =>
+	trainingDataRoute(c, parseSessionsRequest, listTrainingSessions),
+);
+
+app.post("/api/predictor/training-data/batch", (c) => trainingDataRoute(c, parseBatchRequest, fetchTrainingBatch));
+
+app.post("/api/predictor/training-data/hard-negatives", (c) =>
+	trainingDataRoute(c, parseHardNegativesRequest, fetchHardNegatives),
+);
+
 app.post("/api/predictor/train", async (c) => {
 	const cfg = loadMemoryConfig(AGENTS_DIR);
 	const predictorCfg = cfg.pipelineV2.predictor;
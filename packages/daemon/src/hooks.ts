@@ -37,6 +37,7 @@ import {
 	maybeExplore,
 	runPredictorScoring,
 } from "./predictor-scoring";
+import { emitDaemonEvent } from "./daemon-events";
 import { propagateMemoryStatus } from "./knowledge-graph";
 import { resolveFocalEntities, setTraversalStatus, traverseKnowledgeGraph } from "./pipeline/graph-traversal";
 import {
@@ -1098,9 +1099,10 @@ export async function handleSessionStart(req: SessionStartRequest): Promise<Sess
 	const candidateIdsForFeatures = mergedCandidates.map((c) => c.id);
 	const structuralById = getStructuralFeatures(getDbAccessor(), candidateIdsForFeatures, agentId, candidateSourceById);
 
-	// Build candidate feature vectors using the canonical 17-element FeatureVector shape
+	// Build candidate feature vectors using the canonical FeatureVector shape
 	// (same contract as buildCandidateFeatures / structural-features.ts).
-	// The inline 10-element version was wrong — the Rust sidecar expects 17D.
+	// The inline 10-element version was wrong — the Rust sidecar expects
+	// PREDICTOR_FEATURE_DIMENSIONS.
 	const featureNow = new Date();
 	const sessionGapDays = (() => {
 		try {
@@ -1131,10 +1133,14 @@ export async function handleSessionStart(req: SessionStartRequest): Promise<Sess
 					pinned: c.pinned === 1,
 					isSuperseded: false,
 					source: candidateSourceById.get(c.id),
+					project: c.project,
+					content: c.content,
+					tags: c.tags,
 				})),
 				agentId,
 				{
 					projectSlot: 0,
+					project: req.project,
 					timeOfDay: featureNow.getHours() + featureNow.getMinutes() / 60,
 					dayOfWeek: featureNow.getDay(),
 					monthOfYear: featureNow.getMonth(),
@@ -2388,6 +2394,7 @@ export function handleRemember(req: RememberRequest): RememberResponse {
 				now,
 				req.who || req.harness || "hooks",
 			);
+			emitDaemonEvent("memory-created", { id });
 
 			return id;
 		});
@@ -9,6 +9,7 @@
  * calls inside write locks.
  */
 
+import { emitDaemonEvent } from "../daemon-events";
 import type { DbAccessor, WriteDb } from "../db-accessor";
 import type { EmbeddingConfig, PipelineV2Config } from "../memory-config";
 import { normalizeAndHashContent } from "../content-normalization";
@@ -125,6 +126,7 @@ function completeJob(db: WriteDb, jobId: string): void {
 		 SET status = 'completed', completed_at = ?, updated_at = ?
 		 WHERE id = ?`,
 	).run(now, now, jobId);
+	emitDaemonEvent("job-finished", { jobId, status: "completed" });
 }
 
 function failJob(
@@ -141,6 +143,7 @@ function failJob(
 		 SET status = ?, error = ?, failed_at = ?, updated_at = ?
 		 WHERE id = ?`,
 	).run(nextStatus, error, now, now, jobId);
+	if (nextStatus === "dead") emitDaemonEvent("job-finished", { jobId, status: "dead" });
 }
 
 // ---------------------------------------------------------------------------
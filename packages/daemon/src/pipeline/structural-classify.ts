@@ -7,6 +7,7 @@
  * write locks.
  */
 
+import { emitDaemonEvent } from "../daemon-events";
 import type { DbAccessor, WriteDb, ReadDb } from "../db-accessor";
 import type { PipelineV2Config } from "../memory-config";
 import type { LlmProvider } from "./provider";
@@ -132,6 +133,7 @@ function completeJob(db: WriteDb, jobId: string): void {
 		 SET status = 'completed', completed_at = ?, updated_at = ?
 		 WHERE id = ?`,
 	).run(now, now, jobId);
+	emitDaemonEvent("job-finished", { jobId, status: "completed" });
 }
 
 function failJob(
@@ -148,6 +150,7 @@ function failJob(
 		 SET status = ?, error = ?, failed_at = ?, updated_at = ?
 		 WHERE id = ?`,
 	).run(status, error, now, now, jobId);
+	if (status === "dead") emitDaemonEvent("job-finished", { jobId, status: "dead" });
 }
 
 // ---------------------------------------------------------------------------
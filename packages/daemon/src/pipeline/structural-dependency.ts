@@ -7,6 +7,7 @@
  */
 
 import { DEPENDENCY_TYPES, type DependencyType } from "@signet/core";
+import { emitDaemonEvent } from "../daemon-events";
 import type { DbAccessor, WriteDb, ReadDb } from "../db-accessor";
 import type { PipelineV2Config } from "../memory-config";
 import type { LlmProvider } from "./provider";
@@ -105,6 +106,7 @@ function completeJob(db: WriteDb, jobId: string): void {
 		 SET status = 'completed', completed_at = ?, updated_at = ?
 		 WHERE id = ?`,
 	).run(now, now, jobId);
+	emitDaemonEvent("job-finished", { jobId, status: "completed" });
 }
 
 function failJob(
@@ -121,6 +123,7 @@ function failJob(
 		 SET status = ?, error = ?, failed_at = ?, updated_at = ?
 		 WHERE id = ?`,
 	).run(status, error, now, now, jobId);
+	if (status === "dead") emitDaemonEvent("job-finished", { jobId, status: "dead" });
 }
 
 // ---------------------------------------------------------------------------
@@ -24,6 +24,7 @@ import {
 	resolveDefaultOllamaFallbackMaxContextTokens,
 } from "./provider";
 
+import { emitDaemonEvent } from "../daemon-events";
 import { isDuplicate, inferType } from "../hooks";
 import { loadMemoryConfig } from "../memory-config";
 import { logger } from "../logger";
@@ -891,6 +892,7 @@ export function insertSummaryFacts(
 				now,
 				SUMMARY_WORKER_UPDATED_BY,
 			);
+			emitDaemonEvent("memory-created", { id });
 			count++;
 		}
 		return count;
@@ -7,6 +7,7 @@
  * controlled-write mode it applies ADD/NONE decisions with safety gates.
  */
 
+import { emitDaemonEvent } from "../daemon-events";
 import type { DbAccessor, WriteDb } from "../db-accessor";
 import type { PipelineV2Config } from "../memory-config";
 import type { LlmProvider } from "./provider";
@@ -256,6 +257,7 @@ function completeJob(db: WriteDb, jobId: string, result: string | null): void {
 		 SET status = 'completed', result = ?, completed_at = ?, updated_at = ?
 		 WHERE id = ?`,
 	).run(result, now, now, jobId);
+	emitDaemonEvent("job-finished", { jobId, status: "completed" });
 }
 
 function failJob(
@@ -276,6 +278,7 @@ function failJob(
 		 SET status = ?, error = ?, failed_at = ?, updated_at = ?
 		 WHERE id = ?`,
 	).run(nextStatus, error, now, now, jobId);
+	if (nextStatus === "dead") emitDaemonEvent("job-finished", { jobId, status: "dead" });
 }
 
 function updateExtractionStatus(
@@ -108,7 +108,7 @@ class MockPredictorProcess extends EventEmitter {
 				model_version: 1,
 				last_trained: null,
 				native_dimensions: this.nativeDimensions,
-				feature_dimensions: 17,
+				feature_dimensions: 21,
 			});
 			return;
 		}
@@ -200,7 +200,7 @@ describe("PredictorClient", () => {
 		expect(status?.trained).toBe(false);
 		expect(status?.model_version).toBe(1);
 		expect(status?.native_dimensions).toBe(1536);
-		expect(status?.feature_dimensions).toBe(17);
+		expect(status?.feature_dimensions).toBe(21);
 	});
 
 	it("score request returns results", async () => {
@@ -0,0 +1,334 @@
+/**
+ * Contract tests for the predictor training-data endpoints.
+ *
+ * The predictor's daemon data source (packages/predictor/src/data/daemon.rs)
+ * deserializes these responses, so field names and types here are the wire
+ * contract. Verifies:
+ * - Session listing honours the confidence gate, cursor and project filters
+ * - Batches fold embedding chunks and carry feedback, deletions and gaps
+ * - Hard negatives exclude surfaced, deleted and later memories
+ * - Malformed bodies are rejected
+ */
+
+import { Database } from "bun:sqlite";
+import { beforeEach, describe, expect, it } from "bun:test";
+import { runMigrations } from "@signet/core";
+import type { DbAccessor, ReadDb, WriteDb } from "./db-accessor";
+import {
+	TrainingDataError,
+	fetchHardNegatives,
+	fetchTrainingBatch,
+	listTrainingSessions,
+	parseBatchRequest,
+	parseHardNegativesRequest,
+	parseSessionsRequest,
+} from "./predictor-training-data";
+
+// ---------------------------------------------------------------------------
+// Test DB helper
+// ---------------------------------------------------------------------------
+
+function createTestDb(): { db: Database; accessor: DbAccessor } {
+	const db = new Database(":memory:");
+	runMigrations(db);
+
+	const accessor: DbAccessor = {
+		withWriteTx<T>(fn: (db: WriteDb) => T): T {
+			db.exec("BEGIN IMMEDIATE");
+			try {
+				const result = fn(db);
+				db.exec("COMMIT");
+				return result;
+			} catch (err) {
+				db.exec("ROLLBACK");
+				throw err;
+			}
+		},
+		withReadDb<T>(fn: (db: ReadDb) => T): T {
+			return fn(db);
+		},
+		close(): void {
+			db.close();
+		},
+	};
+
+	return { db, accessor };
+}
+
+function f32Blob(values: number[]): Uint8Array {
+	return new Uint8Array(new Float32Array(values).buffer);
+}
+
+function insertSession(
+	db: Database,
+	key: string,
+	createdAt: string,
+	opts: { project?: string | null; score?: number; confidence?: number | null } = {},
+): void {
+	db.prepare(
+		`INSERT INTO session_scores (id, session_key, project, harness, score, confidence, created_at)
+		 VALUES (?, ?, ?, 'claude-code', ?, ?, ?)`,
+	).run(`score-${key}`, key, opts.project ?? "proj", opts.score ?? 0.8, opts.confidence ?? 0.9, createdAt);
+}
+
+function insertMemory(
+	db: Database,
+	id: string,
+	opts: { project?: string | null; importance?: number; createdAt?: string; deleted?: boolean } = {},
+): void {
+	const createdAt = opts.createdAt ?? "2026-01-01T00:00:00.000Z";
+	db.prepare(
+		`INSERT INTO memories
+		 (id, content, type, importance, access_count, project, tags, pinned, is_deleted,
+		  created_at, updated_at, updated_by)
+		 VALUES (?, ?, 'fact', ?, 4, ?, '["rust"]', 1, ?, ?, ?, 'test')`,
+	).run(
+		id,
+		`content of ${id}`,
+		opts.importance ?? 0.5,
+		opts.project === undefined ? "proj" : opts.project,
+		opts.deleted ? 1 : 0,
+		createdAt,
+		createdAt,
+	);
+}
+
+function insertEmbedding(db: Database, id: string, sourceType: string, sourceId: string, values: number[]): void {
+	db.prepare(
+		`INSERT INTO embeddings (id, content_hash, vector, dimensions, source_type, source_id, chunk_text, created_at)
+		 VALUES (?, ?, ?, ?, ?, ?, '', '2026-01-01T00:00:00.000Z')`,
+	).run(id, `hash-${id}`, f32Blob(values), values.length, sourceType, sourceId);
+}
+
+function insertSessionMemory(
+	db: Database,
+	sessionKey: string,
+	memoryId: string,
+	rank: number,
+	opts: { injected?: boolean; feedbackCount?: number; agentScore?: number } = {},
+): void {
+	db.prepare(
+		`INSERT INTO session_memories
+		 (id, session_key, memory_id, source, effective_score, final_score, rank, was_injected,
+		  relevance_score, fts_hit_count, agent_relevance_score, agent_feedback_count, created_at)
+		 VALUES (?, ?, ?, 'effective', 0.7, 0.7, ?, ?, 0.6, 2, ?, ?, '2026-01-01T00:00:00.000Z')`,
+	).run(
+		`${sessionKey}-${memoryId}`,
+		sessionKey,
+		memoryId,
+		rank,
+		opts.injected === false ? 0 : 1,
+		opts.agentScore ?? null,
+		opts.feedbackCount ?? 0,
+	);
+}
+
+const SESSION_FIELDS = ["confidence", "created_at", "novel_context_count", "project", "score", "session_key"];
+
+// Every field of WireCandidate in data/daemon.rs
+const CANDIDATE_FIELDS = [
+	"access_count",
+	"aspect_slot",
+	"content",
+	"content_hash",
+	"created_at",
+	"effective_score",
+	"embeddings",
+	"entity_slot",
+	"feedback",
+	"fts_hit_count",
+	"importance",
+	"is_constraint",
+	"is_deleted",
+	"memory_id",
+	"pinned",
+	"project",
+	"relevance_score",
+	"source",
+	"structural_density",
+	"tags",
+	"was_injected",
+];
+
+function sessionsRequest(overrides: Record<string, unknown> = {}) {
+	return parseSessionsRequest({
+		limit: 100,
+		min_confidence: 0.5,
+		since: null,
+		harness_filter: [],
+		project_filter: { include: [], exclude: [] },
+		...overrides,
+	});
+}
+
+// ---------------------------------------------------------------------------
+// Sessions
+// ---------------------------------------------------------------------------
+
+describe("listTrainingSessions", () => {
+	let db: Database;
+	let accessor: DbAccessor;
+
+	beforeEach(() => {
+		({ db, accessor } = createTestDb());
+		insertSession(db, "s1", "2026-01-01T00:00:00.000Z");
+		insertSession(db, "s2", "2026-01-02T00:00:00.000Z");
+		insertSession(db, "s3", "2026-01-03T00:00:00.000Z", { project: "other" });
+		insertSession(db, "low", "2026-01-04T00:00:00.000Z", { confidence: 0.1 });
+	});
+
+	it("returns the listing shape the predictor reads", () => {
+		const result = listTrainingSessions(accessor, sessionsRequest());
+		expect(Object.keys(result).sort()).toEqual(["journal_mode", "schema_warnings", "sessions", "sessions_skipped"]);
+		expect(Object.keys(result.sessions[0]).sort()).toEqual(SESSION_FIELDS);
+		expect(result.schema_warnings).toEqual([]);
+		expect(typeof result.journal_mode).toBe("string");
+	});
+
+	it("gates on confidence and lists the newest sessions first", () => {
+		const result = listTrainingSessions(accessor, sessionsRequest());
+		expect(result.sessions.map((s) => s.session_key)).toEqual(["s3", "s2", "s1"]);
+		expect(result.sessions_skipped).toBe(1);
+	});
+
+	it("walks forward from a cursor oldest-first", () => {
+		const result = listTrainingSessions(
+			accessor,
+			sessionsRequest({ since: { created_at: "2026-01-01T00:00:00.000Z", session_key: "s1" } }),
+		);
+		expect(result.sessions.map((s) => s.session_key)).toEqual(["s2", "s3"]);
+	});
+
+	it("applies project include and exclude lists", () => {
+		const included = listTrainingSessions(accessor, sessionsRequest({ project_filter: { include: ["other"] } }));
+		expect(included.sessions.map((s) => s.session_key)).toEqual(["s3"]);
+
+		const excluded = listTrainingSessions(accessor, sessionsRequest({ project_filter: { exclude: ["other"] } }));
+		expect(excluded.sessions.map((s) => s.session_key)).toEqual(["s2", "s1"]);
+	});
+});
+
+// ---------------------------------------------------------------------------
+// Batches
+// ---------------------------------------------------------------------------
+
+describe("fetchTrainingBatch", () => {
+	let db: Database;
+	let accessor: DbAccessor;
+
+	beforeEach(() => {
+		({ db, accessor } = createTestDb());
+		insertSession(db, "s1", "2026-01-01T00:00:00.000Z");
+		insertSession(db, "s2", "2026-01-02T00:00:00.000Z");
+		insertMemory(db, "m1");
+		insertMemory(db, "m2");
+		insertEmbedding(db, "e1a", "memory", "m1", [1, 0]);
+		insertEmbedding(db, "e1b", "memory", "m1", [0, 1]);
+		insertEmbedding(db, "es2", "session", "s2", [0.5, 0.5]);
+		insertSessionMemory(db, "s2", "m1", 1, { feedbackCount: 1, agentScore: 0.9 });
+		insertSessionMemory(db, "s2", "m2", 2, { injected: false });
+		db.prepare(
+			`INSERT INTO memory_history (id, memory_id, event, changed_by, session_id, created_at)
+			 VALUES ('h1', 'm2', 'deleted', 'test', 's2', '2026-01-02T00:00:00.000Z')`,
+		).run();
+	});
+
+	it("returns candidates in rank order with every wire field", () => {
+		const batch = fetchTrainingBatch(accessor, parseBatchRequest({ session_keys: ["s2"], include_deleted: true }));
+		expect(Object.keys(batch).sort()).toEqual(["candidates", "deleted", "previous", "session_embeddings"]);
+
+		const candidates = batch.candidates.s2;
+		expect(candidates.map((c) => c.memory_id)).toEqual(["m1", "m2"]);
+		expect(Object.keys(candidates[0]).sort()).toEqual(CANDIDATE_FIELDS);
+		expect(candidates[0].was_injected).toBe(true);
+		expect(candidates[0].pinned).toBe(true);
+		expect(candidates[0].tags).toBe('["rust"]');
+		expect(candidates[0].feedback).toBeCloseTo(0.9);
+		expect(candidates[1].was_injected).toBe(false);
+		expect(candidates[1].feedback).toBeNull();
+	});
+
+	it("folds embedding chunks into one candidate", () => {
+		const batch = fetchTrainingBatch(accessor, parseBatchRequest({ session_keys: ["s2"] }));
+		const m1 = batch.candidates.s2[0];
+		expect(m1.embeddings).toHaveLength(2);
+		expect(m1.embeddings.map((v) => v.length)).toEqual([2, 2]);
+		expect(batch.candidates.s2[1].embeddings).toEqual([]);
+	});
+
+	it("carries deletions, session embeddings and project gaps", () => {
+		const batch = fetchTrainingBatch(
+			accessor,
+			parseBatchRequest({ session_keys: ["s1", "s2"], include_deleted: true }),
+		);
+		expect(batch.deleted).toEqual({ s2: ["m2"] });
+		expect(batch.session_embeddings).toEqual({ s2: [0.5, 0.5] });
+		expect(batch.previous).toEqual([
+			{
+				session_key: "s2",
+				created_at: "2026-01-02T00:00:00.000Z",
+				previous_created_at: "2026-01-01T00:00:00.000Z",
+			},
+		]);
+	});
+
+	it("leaves deletions out unless asked", () => {
+		const batch = fetchTrainingBatch(accessor, parseBatchRequest({ session_keys: ["s2"] }));
+		expect(batch.deleted).toEqual({});
+	});
+});
+
+// ---------------------------------------------------------------------------
+// Hard negatives
+// ---------------------------------------------------------------------------
+
+describe("fetchHardNegatives", () => {
+	it("returns live, earlier, same-project memories the session never surfaced", () => {
+		const { db, accessor } = createTestDb();
+		insertSession(db, "s1", "2026-01-05T00:00:00.000Z");
+		insertMemory(db, "surfaced");
+		insertMemory(db, "neg-high", { importance: 0.9 });
+		insertMemory(db, "neg-low", { importance: 0.2 });
+		insertMemory(db, "deleted", { deleted: true });
+		insertMemory(db, "later", { createdAt: "2026-02-01T00:00:00.000Z" });
+		insertMemory(db, "elsewhere", { project: "other" });
+		insertSessionMemory(db, "s1", "surfaced", 1);
+
+		const result = fetchHardNegatives(
+			accessor,
+			parseHardNegativesRequest({
+				session_key: "s1",
+				project: "proj",
+				created_at: "2026-01-05T00:00:00.000Z",
+				limit: 10,
+			}),
+		);
+		expect(result.candidates.map((c) => c.memory_id)).toEqual(["neg-high", "neg-low"]);
+		expect(Object.keys(result.candidates[0]).sort()).toEqual(CANDIDATE_FIELDS);
+		expect(result.candidates[0].source).toBe("hard_negative");
+		expect(result.candidates[0].was_injected).toBe(false);
+	});
+});
+
+// ---------------------------------------------------------------------------
+// Request bodies
+// ---------------------------------------------------------------------------
+
+describe("request parsing", () => {
+	it("rejects malformed bodies", () => {
+		expect(() => parseSessionsRequest(null)).toThrow(TrainingDataError);
+		expect(() => parseSessionsRequest({ limit: -1 })).toThrow(TrainingDataError);
+		expect(() => parseSessionsRequest({ limit: 5, min_confidence: "high" })).toThrow(TrainingDataError);
+		expect(() => parseBatchRequest({ session_keys: [1] })).toThrow(TrainingDataError);
+		expect(() => parseHardNegativesRequest({ session_key: "s1", created_at: "x", limit: 1, project: 3 })).toThrow(
+			TrainingDataError,
+		);
+	});
+
+	it("accepts the predictor's request bodies", () => {
+		expect(parseSessionsRequest({ limit: 5, min_confidence: 0.6, since: null }).harness_filter).toEqual([]);
+		expect(
+			parseHardNegativesRequest({ session_key: "s1", project: null, created_at: "x", limit: 4 }).project,
+		).toBeNull();
+	});
+});
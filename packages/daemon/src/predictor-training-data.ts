@@ -0,0 +1,666 @@
+/**
+ * Training data for a predictor sidecar that can't open the database file
+ * (a container, another host). Serves the rows the predictor's own SQLite
+ * loader selects (packages/predictor/src/data.rs), with the same column
+ * fallbacks, so labels and features come out the same either way.
+ *
+ * Vectors are sent as float arrays, one per embedding chunk.
+ */
+
+import type { DbAccessor, ReadDb } from "./db-accessor";
+
+export interface TrainingCursor {
+	readonly created_at: string;
+	readonly session_key: string;
+}
+
+export interface ProjectFilter {
+	readonly include: ReadonlyArray<string>;
+	readonly exclude: ReadonlyArray<string>;
+}
+
+export interface SessionsRequest {
+	readonly limit: number;
+	readonly min_confidence: number;
+	readonly since: TrainingCursor | null;
+	readonly harness_filter: ReadonlyArray<string>;
+	readonly project_filter: ProjectFilter;
+}
+
+export interface BatchRequest {
+	readonly session_keys: ReadonlyArray<string>;
+	readonly include_deleted: boolean;
+}
+
+export interface HardNegativesRequest {
+	readonly session_key: string;
+	readonly project: string | null;
+	readonly created_at: string;
+	readonly limit: number;
+}
+
+export interface SchemaWarning {
+	readonly table: string;
+	readonly column: string;
+	readonly fallback: string;
+}
+
+export interface TrainingSession {
+	readonly session_key: string;
+	readonly project: string | null;
+	readonly score: number;
+	readonly confidence: number | null;
+	readonly novel_context_count: number | null;
+	readonly created_at: string;
+}
+
+export interface TrainingCandidate {
+	readonly memory_id: string;
+	readonly effective_score: number | null;
+	readonly was_injected: boolean;
+	readonly relevance_score: number | null;
+	readonly fts_hit_count: number;
+	readonly source: string;
+	readonly importance: number | null;
+	readonly created_at: string;
+	readonly access_count: number | null;
+	readonly is_deleted: boolean;
+	readonly project: string | null;
+	readonly pinned: boolean;
+	readonly content: string;
+	readonly tags: string | null;
+	readonly feedback: number | null;
+	readonly content_hash: string | null;
+	readonly embeddings: number[][];
+	readonly entity_slot: number | null;
+	readonly aspect_slot: number | null;
+	readonly is_constraint: boolean;
+	readonly structural_density: number | null;
+}
+
+export class TrainingDataError extends Error {}
+
+// ---------------------------------------------------------------------------
+// Schema: the loader's required, degradable and optional columns
+// ---------------------------------------------------------------------------
+
+const REQUIRED_COLUMNS: ReadonlyArray<readonly [string, string]> = [
+	["session_scores", "session_key"],
+	["session_scores", "score"],
+	["session_scores", "created_at"],
+	["session_memories", "session_key"],
+	["session_memories", "memory_id"],
+	["memories", "id"],
+	["memories", "content"],
+	["memories", "created_at"],
+	["embeddings", "source_id"],
+	["embeddings", "source_type"],
+	["embeddings", "vector"],
+];
+
+const DEGRADABLE_COLUMNS: ReadonlyArray<readonly [string, string, string]> = [
+	["session_scores", "project", "NULL"],
+	["session_scores", "harness", "NULL"],
+	// Without a confidence column every scored session passes the gate
+	["session_scores", "confidence", "1.0"],
+	["session_scores", "novel_context_count", "NULL"],
+	["session_memories", "source", "'unknown'"],
+	["session_memories", "effective_score", "NULL"],
+	["session_memories", "was_injected", "0"],
+	["session_memories", "relevance_score", "NULL"],
+	["session_memories", "fts_hit_count", "0"],
+	["session_memories", "rank", "NULL"],
+	["session_memories", "entity_slot", "NULL"],
+	["session_memories", "aspect_slot", "NULL"],
+	["session_memories", "is_constraint", "0"],
+	["session_memories", "structural_density", "NULL"],
+	["memories", "importance", "NULL"],
+	["memories", "access_count", "NULL"],
+	["memories", "is_deleted", "0"],
+	["memories", "project", "NULL"],
+	["memories", "pinned", "0"],
+	["memories", "tags", "NULL"],
+	["embeddings", "dimensions", "NULL"],
+	["embeddings", "created_at", "NULL"],
+];
+
+const SCHEMA_TABLES = ["session_scores", "session_memories", "memories", "embeddings", "memory_history"];
+
+class LoaderSchema {
+	readonly warnings: ReadonlyArray<SchemaWarning>;
+
+	private constructor(private readonly present: ReadonlySet<string>) {
+		this.warnings = DEGRADABLE_COLUMNS.filter(([table, column]) => !this.has(table, column)).map(
+			([table, column, fallback]) => ({ table, column, fallback }),
+		);
+	}
+
+	static introspect(db: ReadDb): LoaderSchema {
+		const present = new Set<string>();
+		for (const table of SCHEMA_TABLES) {
+			const rows = db.prepare(`PRAGMA table_info(${table})`).all() as ReadonlyArray<{ name: string }>;
+			for (const row of rows) present.add(`${table}.${row.name}`);
+		}
+		const missing = REQUIRED_COLUMNS.filter(([table, column]) => !present.has(`${table}.${column}`));
+		if (missing.length > 0) {
+			throw new TrainingDataError(
+				`missing required columns: ${missing.map(([table, column]) => `${table}.${column}`).join(", ")}`,
+			);
+		}
+		return new LoaderSchema(present);
+	}
+
+	has(table: string, column: string): boolean {
+		return this.present.has(`${table}.${column}`);
+	}
+
+	/** `alias.column`, or the column's fallback when this database lacks it. */
+	col(alias: string, table: string, column: string): string {
+		if (this.has(table, column)) return `${alias}.${column}`;
+		const degradable = DEGRADABLE_COLUMNS.find(([t, c]) => t === table && c === column);
+		return degradable?.[2] ?? "NULL";
+	}
+
+	agentFeedback(): string {
+		return this.has("session_memories", "agent_feedback_count")
+			? "CASE WHEN sm.agent_feedback_count > 0 THEN sm.agent_relevance_score END"
+			: "NULL";
+	}
+
+	historySessions(): boolean {
+		return this.has("memory_history", "session_id") && this.has("memory_history", "event");
+	}
+}
+
+// ---------------------------------------------------------------------------
+// Vectors
+// ---------------------------------------------------------------------------
+
+type VectorFormat = "f32" | "f16" | "int8";
+
+function formatFromTag(tag: string | null): VectorFormat | null {
+	switch (tag?.trim().toLowerCase()) {
+		case "f32":
+		case "float32":
+			return "f32";
+		case "f16":
+		case "float16":
+		case "half":
+			return "f16";
+		case "int8":
+		case "i8":
+		case "q8":
+			return "int8";
+		default:
+			return null;
+	}
+}
+
+/** Size-based guess, f32 winning ties, as the predictor infers it. */
+function inferFormat(length: number, dims: number): VectorFormat | null {
+	if (length === dims * 4) return "f32";
+	if (length === dims * 2) return "f16";
+	if (length === dims || length === dims + 4) return "int8";
+	return null;
+}
+
+function f16ToNumber(bits: number): number {
+	const sign = bits & 0x8000 ? -1 : 1;
+	const exponent = (bits >> 10) & 0x1f;
+	const mantissa = bits & 0x3ff;
+	if (exponent === 0) return sign * 2 ** -14 * (mantissa / 1024);
+	if (exponent === 0x1f) return mantissa === 0 ? sign * Number.POSITIVE_INFINITY : Number.NaN;
+	return sign * 2 ** (exponent - 15) * (1 + mantissa / 1024);
+}
+
+/**
+ * Decode an embedding blob the way the predictor's loader does: a known
+ * format tag is trusted, otherwise the format is inferred from the size.
+ * Without a stored width the blob is read as f32, the format the daemon
+ * writes. Vectors holding NaN/Inf are left out since JSON can't carry them.
+ */
+function blobToVector(blob: Uint8Array | null, dims: number | null, tag: string | null): number[] | null {
+	if (!blob) return null;
+	const width = dims !== null && dims > 0 ? dims : Math.floor(blob.byteLength / 4);
+	const format = formatFromTag(tag) ?? inferFormat(blob.byteLength, width);
+	const view = new DataView(blob.buffer, blob.byteOffset, blob.byteLength);
+	const vector: number[] = [];
+	switch (format) {
+		case "f32":
+			if (blob.byteLength !== width * 4) return null;
+			for (let i = 0; i < width; i++) vector.push(view.getFloat32(i * 4, true));
+			break;
+		case "f16":
+			if (blob.byteLength !== width * 2) return null;
+			for (let i = 0; i < width; i++) vector.push(f16ToNumber(view.getUint16(i * 2, true)));
+			break;
+		case "int8": {
+			let scale = 1 / 127;
+			if (blob.byteLength === width + 4) scale = view.getFloat32(width, true);
+			else if (blob.byteLength !== width) return null;
+			for (let i = 0; i < width; i++) vector.push(view.getInt8(i) * scale);
+			break;
+		}
+		default:
+			return null;
+	}
+	return vector.every(Number.isFinite) ? vector : null;
+}
+
+interface CandidateRow {
+	readonly memory_id: string;
+	readonly effective_score: number | null;
+	readonly was_injected: number | null;
+	readonly relevance_score: number | null;
+	readonly fts_hit_count: number | null;
+	readonly source: string | null;
+	readonly importance: number | null;
+	readonly mem_created_at: string;
+	readonly access_count: number | null;
+	readonly is_deleted: number | null;
+	readonly mem_project: string | null;
+	readonly pinned: number | null;
+	readonly mem_content: string;
+	readonly embedding_blob: Uint8Array | null;
+	readonly embedding_dims: number | null;
+	readonly entity_slot: number | null;
+	readonly aspect_slot: number | null;
+	readonly is_constraint: number | null;
+	readonly structural_density: number | null;
+	readonly embedding_format: string | null;
+	readonly tags: string | null;
+	readonly feedback: number | null;
+	readonly content_hash: string | null;
+	readonly session_key?: string;
+}
+
+/**
+ * Fold rows into candidates, one per memory, collecting each memory's
+ * embedding chunks. Queries order a memory's chunk rows next to each other.
+ */
+function foldCandidates(rows: ReadonlyArray<CandidateRow>): TrainingCandidate[] {
+	const out: Array<TrainingCandidate & { embeddings: number[][] }> = [];
+	for (const row of rows) {
+		const vector = blobToVector(row.embedding_blob, row.embedding_dims, row.embedding_format);
+		const last = out[out.length - 1];
+		if (last && last.memory_id === row.memory_id) {
+			if (vector) last.embeddings.push(vector);
+			continue;
+		}
+		out.push({
+			memory_id: row.memory_id,
+			effective_score: row.effective_score,
+			was_injected: (row.was_injected ?? 0) !== 0,
+			relevance_score: row.relevance_score,
+			fts_hit_count: row.fts_hit_count ?? 0,
+			source: row.source ?? "unknown",
+			importance: row.importance,
+			created_at: row.mem_created_at,
+			access_count: row.access_count,
+			is_deleted: (row.is_deleted ?? 0) !== 0,
+			project: row.mem_project,
+			pinned: (row.pinned ?? 0) !== 0,
+			content: row.mem_content,
+			tags: row.tags,
+			feedback: row.feedback,
+			content_hash: row.content_hash,
+			embeddings: vector ? [vector] : [],
+			entity_slot: row.entity_slot,
+			aspect_slot: row.aspect_slot,
+			is_constraint: (row.is_constraint ?? 0) !== 0,
+			structural_density: row.structural_density,
+		});
+	}
+	return out;
+}
+
+// ---------------------------------------------------------------------------
+// Sessions
+// ---------------------------------------------------------------------------
+
+type Bindings = Array<string | number>;
+
+function listClause(expr: string, list: ReadonlyArray<string>, exclude: boolean, bound: Bindings): string {
+	if (list.length === 0) return "";
+	bound.push(JSON.stringify(list));
+	const idx = bound.length;
+	return exclude
+		? `\n AND (${expr} IS NULL OR ${expr} NOT IN (SELECT value FROM json_each(?${idx})))`
+		: `\n AND ${expr} IN (SELECT value FROM json_each(?${idx}))`;
+}
+
+function sessionFilters(req: SessionsRequest, schema: LoaderSchema, bound: Bindings): string {
+	const harness = schema.col("ss", "session_scores", "harness");
+	const project = schema.col("ss", "session_scores", "project");
+	return (
+		listClause(harness, req.harness_filter, false, bound) +
+		listClause(project, req.project_filter.include, false, bound) +
+		listClause(project, req.project_filter.exclude, true, bound)
+	);
+}
+
+/**
+ * Qualifying scored sessions. Incremental loads walk forward from the
+ * cursor oldest-first; full loads take the most recent sessions.
+ */
+export function listTrainingSessions(
+	accessor: DbAccessor,
+	req: SessionsRequest,
+): {
+	sessions: TrainingSession[];
+	sessions_skipped: number;
+	schema_warnings: ReadonlyArray<SchemaWarning>;
+	journal_mode: string;
+} {
+	return accessor.withReadDb((db) => {
+		const schema = LoaderSchema.introspect(db);
+		const confidence = schema.col("ss", "session_scores", "confidence");
+		const project = schema.col("ss", "session_scores", "project");
+		const novel = schema.col("ss", "session_scores", "novel_context_count");
+
+		const bound: Bindings = [req.min_confidence, req.limit];
+		let cursorClause = "";
+		let order = "ss.created_at DESC";
+		if (req.since) {
+			bound.push(req.since.created_at, req.since.session_key);
+			cursorClause = "\n AND (ss.created_at > ?3 OR (ss.created_at = ?3 AND ss.session_key > ?4))";
+			order = "ss.created_at ASC, ss.session_key ASC";
+		}
+		const filters = sessionFilters(req, schema, bound);
+		const sessions = db
+			.prepare(
+				`SELECT ss.session_key, ${project} AS project, ss.score, ${confidence} AS confidence,
+				        ${novel} AS novel_context_count, ss.created_at
+				 FROM session_scores ss
+				 WHERE ${confidence} IS NOT NULL
+				   AND ss.score IS NOT NULL
+				   AND ${confidence} >= ?1${cursorClause}${filters}
+				 ORDER BY ${order}
+				 LIMIT ?2`,
+			)
+			.all(...bound) as TrainingSession[];
+
+		const skippedBound: Bindings = [req.min_confidence];
+		const skippedFilters = sessionFilters(req, schema, skippedBound);
+		const skipped = db
+			.prepare(
+				`SELECT COUNT(*) AS count
+				 FROM session_scores ss
+				 WHERE ${confidence} IS NOT NULL
+				   AND ss.score IS NOT NULL
+				   AND ${confidence} < ?1${skippedFilters}`,
+			)
+			.get(...skippedBound) as { count: number };
+
+		const journal = db.prepare("PRAGMA journal_mode").get() as { journal_mode?: string } | undefined;
+		return {
+			sessions,
+			sessions_skipped: skipped.count,
+			schema_warnings: schema.warnings,
+			journal_mode: journal?.journal_mode ?? "unknown",
+		};
+	});
+}
+
+// ---------------------------------------------------------------------------
+// Batches
+// ---------------------------------------------------------------------------
+
+/**
+ * Candidates, deletions, newest prompt embedding and previous-session gap
+ * for a batch of session keys.
+ */
+export function fetchTrainingBatch(
+	accessor: DbAccessor,
+	req: BatchRequest,
+): {
+	candidates: Record<string, TrainingCandidate[]>;
+	deleted: Record<string, string[]>;
+	session_embeddings: Record<string, number[]>;
+	previous: Array<{ session_key: string; created_at: string; previous_created_at: string }>;
+} {
+	const candidates: Record<string, TrainingCandidate[]> = {};
+	const deleted: Record<string, string[]> = {};
+	const sessionEmbeddings: Record<string, number[]> = {};
+	const previous: Array<{ session_key: string; created_at: string; previous_created_at: string }> = [];
+	if (req.session_keys.length === 0) {
+		return { candidates, deleted, session_embeddings: sessionEmbeddings, previous };
+	}
+	const keys = JSON.stringify(req.session_keys);
+
+	return accessor.withReadDb((db) => {
+		const schema = LoaderSchema.introspect(db);
+		const sm = (column: string) => schema.col("sm", "session_memories", column);
+		const m = (column: string) => schema.col("m", "memories", column);
+		const e = (column: string) => schema.col("e", "embeddings", column);
+
+		const rows = db
+			.prepare(
+				`SELECT sm.memory_id, ${sm("effective_score")} AS effective_score,
+				        ${sm("was_injected")} AS was_injected, ${sm("relevance_score")} AS relevance_score,
+				        ${sm("fts_hit_count")} AS fts_hit_count, ${sm("source")} AS source,
+				        ${m("importance")} AS importance, m.created_at AS mem_created_at,
+				        ${m("access_count")} AS access_count, ${m("is_deleted")} AS is_deleted,
+				        ${m("project")} AS mem_project, ${m("pinned")} AS pinned, m.content AS mem_content,
+				        e.vector AS embedding_blob, ${e("dimensions")} AS embedding_dims,
+				        ${sm("entity_slot")} AS entity_slot, ${sm("aspect_slot")} AS aspect_slot,
+				        ${sm("is_constraint")} AS is_constraint, ${sm("structural_density")} AS structural_density,
+				        ${e("vector_format")} AS embedding_format, ${m("tags")} AS tags,
+				        ${schema.agentFeedback()} AS feedback, ${m("content_hash")} AS content_hash,
+				        sm.session_key
+				 FROM session_memories sm
+				 JOIN memories m ON sm.memory_id = m.id
+				 LEFT JOIN embeddings e
+				   ON e.source_id = m.id AND e.source_type = 'memory'
+				 WHERE sm.session_key IN (SELECT value FROM json_each(?1))
+				 ORDER BY sm.session_key, ${sm("rank")} ASC, sm.memory_id`,
+			)
+			.all(keys) as CandidateRow[];
+		const bySession = new Map<string, CandidateRow[]>();
+		for (const row of rows) {
+			const key = row.session_key ?? "";
+			const list = bySession.get(key) ?? [];
+			list.push(row);
+			bySession.set(key, list);
+		}
+		for (const [key, list] of bySession) {
+			candidates[key] = foldCandidates(list);
+		}
+
+		if (req.include_deleted && schema.historySessions()) {
+			const deletions = db
+				.prepare(
+					`SELECT DISTINCT session_id, memory_id FROM memory_history
+					 WHERE session_id IN (SELECT value FROM json_each(?1))
+					   AND event = 'deleted'`,
+				)
+				.all(keys) as ReadonlyArray<{ session_id: string; memory_id: string }>;
+			for (const row of deletions) {
+				(deleted[row.session_id] ??= []).push(row.memory_id);
+			}
+		}
+
+		// The newest row wins when a session was re-embedded
+		const embeddings = db
+			.prepare(
+				`SELECT source_id, vector, dims, format FROM (
+				   SELECT e.source_id, e.vector, ${e("dimensions")} AS dims, ${e("vector_format")} AS format,
+				          ROW_NUMBER() OVER (
+				            PARTITION BY e.source_id ORDER BY ${e("created_at")} DESC
+				          ) AS newest
+				   FROM embeddings e
+				   WHERE e.source_type = 'session'
+				     AND e.source_id IN (SELECT value FROM json_each(?1))
+				 )
+				 WHERE newest = 1`,
+			)
+			.all(keys) as ReadonlyArray<{
+			source_id: string;
+			vector: Uint8Array | null;
+			dims: number | null;
+			format: string | null;
+		}>;
+		for (const row of embeddings) {
+			const vector = blobToVector(row.vector, row.dims, row.format);
+			if (vector) sessionEmbeddings[row.source_id] = vector;
+		}
+
+		// Gap to the previous session in the same project, over distinct
+		// timestamps so sessions sharing one still look back to an earlier one
+		if (schema.has("session_scores", "project")) {
+			const gaps = db
+				.prepare(
+					`WITH stamps AS (
+					   SELECT project, created_at,
+					          LAG(created_at) OVER (
+					            PARTITION BY project ORDER BY created_at
+					          ) AS prev_created_at
+					   FROM (SELECT DISTINCT project, created_at
+					         FROM session_scores WHERE project IS NOT NULL)
+					 )
+					 SELECT ss.session_key, ss.created_at, st.prev_created_at AS previous_created_at
+					 FROM session_scores ss
+					 JOIN stamps st
+					   ON st.project = ss.project AND st.created_at = ss.created_at
+					 WHERE ss.session_key IN (SELECT value FROM json_each(?1))
+					   AND st.prev_created_at IS NOT NULL`,
+				)
+				.all(keys) as Array<{ session_key: string; created_at: string; previous_created_at: string }>;
+			previous.push(...gaps);
+		}
+
+		return { candidates, deleted, session_embeddings: sessionEmbeddings, previous };
+	});
+}
+
+// ---------------------------------------------------------------------------
+// Hard negatives
+// ---------------------------------------------------------------------------
+
+/**
+ * Plausible-but-unrecalled memories for a session: live, existing at
+ * session time, in the same project, never surfaced for it. The predictor
+ * shuffles and trims the pool itself.
+ */
+export function fetchHardNegatives(
+	accessor: DbAccessor,
+	req: HardNegativesRequest,
+): { candidates: TrainingCandidate[] } {
+	return accessor.withReadDb((db) => {
+		const schema = LoaderSchema.introspect(db);
+		const m = (column: string) => schema.col("m", "memories", column);
+		const e = (column: string) => schema.col("e", "embeddings", column);
+		const importance = m("importance");
+		const accessCount = m("access_count");
+		const isDeleted = m("is_deleted");
+		const project = m("project");
+		const rows = db
+			.prepare(
+				`SELECT m.id AS memory_id, 0.0 AS effective_score, 0 AS was_injected,
+				        NULL AS relevance_score, 0 AS fts_hit_count, 'hard_negative' AS source,
+				        ${importance} AS importance, m.created_at AS mem_created_at,
+				        ${accessCount} AS access_count, ${isDeleted} AS is_deleted,
+				        ${project} AS mem_project, ${m("pinned")} AS pinned, m.content AS mem_content,
+				        e.vector AS embedding_blob, ${e("dimensions")} AS embedding_dims,
+				        NULL AS entity_slot, NULL AS aspect_slot, 0 AS is_constraint,
+				        NULL AS structural_density, ${e("vector_format")} AS embedding_format,
+				        ${m("tags")} AS tags, NULL AS feedback, ${m("content_hash")} AS content_hash
+				 FROM (
+				   SELECT m.* FROM memories m
+				   WHERE ${isDeleted} = 0
+				     AND ${project} IS ?2
+				     AND m.created_at <= ?3
+				     AND m.id NOT IN (
+				       SELECT sm.memory_id FROM session_memories sm
+				       WHERE sm.session_key = ?1
+				     )
+				   ORDER BY ${importance} DESC, ${accessCount} DESC
+				   LIMIT ?4
+				 ) m
+				 LEFT JOIN embeddings e
+				   ON e.source_id = m.id AND e.source_type = 'memory'
+				 ORDER BY ${importance} DESC, ${accessCount} DESC, m.id`,
+			)
+			.all(req.session_key, req.project, req.created_at, req.limit) as CandidateRow[];
+		return { candidates: foldCandidates(rows) };
+	});
+}
+
+// ---------------------------------------------------------------------------
+// Request bodies
+// ---------------------------------------------------------------------------
+
+function asRecord(body: unknown): Record<string, unknown> {
+	if (typeof body !== "object" || body === null || Array.isArray(body)) {
+		throw new TrainingDataError("request body must be a JSON object");
+	}
+	return body as Record<string, unknown>;
+}
+
+function count(value: unknown, field: string): number {
+	if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+		throw new TrainingDataError(`${field} must be a non-negative integer`);
+	}
+	return value;
+}
+
+function stringList(value: unknown, field: string): string[] {
+	if (value === undefined || value === null) return [];
+	if (!Array.isArray(value) || !value.every((item) => typeof item === "string")) {
+		throw new TrainingDataError(`${field} must be an array of strings`);
+	}
+	return value;
+}
+
+function requiredString(value: unknown, field: string): string {
+	if (typeof value !== "string") throw new TrainingDataError(`${field} must be a string`);
+	return value;
+}
+
+export function parseSessionsRequest(body: unknown): SessionsRequest {
+	const raw = asRecord(body);
+	const minConfidence = raw.min_confidence ?? 0;
+	if (typeof minConfidence !== "number" || !Number.isFinite(minConfidence)) {
+		throw new TrainingDataError("min_confidence must be a finite number");
+	}
+	let since: TrainingCursor | null = null;
+	if (raw.since !== undefined && raw.since !== null) {
+		const cursor = asRecord(raw.since);
+		since = {
+			created_at: requiredString(cursor.created_at, "since.created_at"),
+			session_key: requiredString(cursor.session_key, "since.session_key"),
+		};
+	}
+	const projects = raw.project_filter === undefined || raw.project_filter === null ? {} : asRecord(raw.project_filter);
+	return {
+		limit: count(raw.limit, "limit"),
+		min_confidence: minConfidence,
+		since,
+		harness_filter: stringList(raw.harness_filter, "harness_filter"),
+		project_filter: {
+			include: stringList(projects.include, "project_filter.include"),
+			exclude: stringList(projects.exclude, "project_filter.exclude"),
+		},
+	};
+}
+
+export function parseBatchRequest(body: unknown): BatchRequest {
+	const raw = asRecord(body);
+	return {
+		session_keys: stringList(raw.session_keys, "session_keys"),
+		include_deleted: raw.include_deleted === true,
+	};
+}
+
+export function parseHardNegativesRequest(body: unknown): HardNegativesRequest {
+	const raw = asRecord(body);
+	if (raw.project !== undefined && raw.project !== null && typeof raw.project !== "string") {
+		throw new TrainingDataError("project must be a string or null");
+	}
+	return {
+		session_key: requiredString(raw.session_key, "session_key"),
+		project: typeof raw.project === "string" ? raw.project : null,
+		created_at: requiredString(raw.created_at, "created_at"),
+		limit: count(raw.limit, "limit"),
+	};
+}
@@ -13,6 +13,7 @@ import {
 	createRateLimiter,
 	deduplicateMemories,
 	getDedupStats,
+	purgeDeadJobs,
 	reembedMissingMemories,
 	releaseStaleLeases,
 	requeueDeadJobs,
@@ -346,6 +347,41 @@ describe("requeueDeadJobs", () => {
 	});
 });
 
+// ---------------------------------------------------------------------------
+// purgeDeadJobs
+// ---------------------------------------------------------------------------
+
+describe("purgeDeadJobs", () => {
+	let db: Database;
+	let accessor: DbAccessor;
+
+	beforeEach(() => {
+		db = new Database(":memory:");
+		runMigrations(db as unknown as Parameters<typeof runMigrations>[0]);
+		accessor = asAccessor(db);
+	});
+
+	afterEach(() => {
+		db.close();
+	});
+
+	it("deletes dead jobs and leaves the rest", () => {
+		insertMemory(db, "mem-p");
+		insertJob(db, "job-p-1", "mem-p", "dead");
+		insertJob(db, "job-p-2", "mem-p", "dead");
+		insertJob(db, "job-p-3", "mem-p", "pending");
+
+		const limiter = createRateLimiter();
+		const result = purgeDeadJobs(accessor, TEST_CFG, CTX_OPERATOR, limiter);
+
+		expect(result.success).toBe(true);
+		expect(result.affected).toBe(2);
+
+		const rows = db.prepare("SELECT id FROM memory_jobs WHERE memory_id = 'mem-p'").all() as Array<{ id: string }>;
+		expect(rows.map((r) => r.id)).toEqual(["job-p-3"]);
+	});
+});
+
 // ---------------------------------------------------------------------------
 // releaseStaleLeases
 // ---------------------------------------------------------------------------
@@ -272,6 +272,68 @@ export function requeueDeadJobs(
 	};
 }
 
+/**
+ * Delete dead jobs outright. Use when they are known to be unrecoverable
+ * (e.g. the memory they point at was removed) and retrying is pointless.
+ * Shares the requeue cooldown and budget.
+ */
+export function purgeDeadJobs(
+	accessor: DbAccessor,
+	cfg: PipelineV2Config,
+	ctx: RepairContext,
+	limiter: RateLimiter,
+): RepairResult {
+	const action = "purgeDeadJobs";
+	const gate = checkRepairGate(cfg, ctx, limiter, action, cfg.repair.requeueCooldownMs, cfg.repair.requeueHourlyBudget);
+
+	if (!gate.allowed) {
+		return {
+			action,
+			success: false,
+			affected: 0,
+			message: gate.reason ?? "denied by policy gate",
+		};
+	}
+
+	const { memoryCount, summaryCount } = accessor.withWriteTx((db) => {
+		const memoryCount = countChanges(db.prepare("DELETE FROM memory_jobs WHERE status = 'dead'").run());
+		if (memoryCount > 0) {
+			writeRepairAudit(db, action, ctx, memoryCount, `purged ${memoryCount} dead job(s)`);
+		}
+
+		let summaryCount = 0;
+		const tableExists = db
+			.prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'summary_jobs'")
+			.get();
+		if (tableExists) {
+			summaryCount = countChanges(db.prepare("DELETE FROM summary_jobs WHERE status = 'dead'").run());
+			if (summaryCount > 0) {
+				writeRepairAudit(db, action, ctx, summaryCount, `purged ${summaryCount} dead summary job(s)`);
+			}
+		}
+
+		return { memoryCount, summaryCount };
+	});
+
+	const totalAffected = memoryCount + summaryCount;
+
+	limiter.record(action);
+	logger.info("pipeline", "repair: purged dead jobs", {
+		memoryJobs: memoryCount,
+		summaryJobs: summaryCount,
+		total: totalAffected,
+		actor: ctx.actor,
+		reason: ctx.reason,
+	});
+
+	return {
+		action,
+		success: true,
+		affected: totalAffected,
+		message: `purged ${memoryCount} dead memory job(s) and ${summaryCount} dead summary job(s)`,
+	};
+}
+
 /**
  * Release jobs stuck in 'leased' state past the lease timeout.
  */
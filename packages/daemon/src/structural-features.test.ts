@@ -124,7 +124,7 @@ describe("getStructuralFeatures", () => {
 });
 
 describe("buildCandidateFeatures", () => {
-	it("builds 17-element feature vectors with structural signals", () => {
+	it("builds 21-element feature vectors with structural signals", () => {
 		const now = new Date().toISOString();
 		const vectors = buildCandidateFeatures(
 			getDbAccessor(),
@@ -161,7 +161,7 @@ describe("buildCandidateFeatures", () => {
 		);
 
 		expect(vectors).toHaveLength(2);
-		expect(vectors[0]).toHaveLength(17);
+		expect(vectors[0]).toHaveLength(21);
 		expect(vectors[0][10]).toBe(1);
 		expect(vectors[0][14]).toBe(0);
 		expect(vectors[0][16]).toBe(1);
@@ -170,6 +170,41 @@ describe("buildCandidateFeatures", () => {
 		expect(vectors[1][15]).toBe(0);
 	});
 
+	it("emits pinned, project match, content length and tag overlap", () => {
+		const now = new Date().toISOString();
+		const base = {
+			importance: 0.5,
+			createdAt: now,
+			accessCount: 0,
+			lastAccessed: null,
+			isSuperseded: false,
+			source: "effective",
+		};
+		const vectors = buildCandidateFeatures(
+			getDbAccessor(),
+			[
+				{ ...base, id: "mem-1", pinned: true, project: "signet", content: "abc", tags: '["Rust", "db"]' },
+				{ ...base, id: "mem-2", pinned: false, project: "other", content: "", tags: "rust" },
+				{ ...base, id: "mem-3", pinned: false, project: null, tags: null },
+			],
+			"default",
+			{
+				projectSlot: 0,
+				project: "signet",
+				timeOfDay: 12,
+				dayOfWeek: 3,
+				monthOfYear: 2,
+				sessionGapDays: 0,
+			},
+		);
+
+		expect(vectors.map((v) => v[17])).toEqual([1, 0, 0]);
+		expect(vectors.map((v) => v[18])).toEqual([1, 0, 0]);
+		expect(vectors[0][19]).toBeCloseTo(Math.log(4));
+		expect(vectors[1][19]).toBe(0);
+		expect(vectors.map((v) => v[20])).toEqual([0.5, 1, 0]);
+	});
+
 	it("clamps negative session gap days before log transform", () => {
 		const now = new Date().toISOString();
 		const [vector] = buildCandidateFeatures(
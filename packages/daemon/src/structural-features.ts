@@ -1,7 +1,7 @@
 import type { DbAccessor } from "./db-accessor";
 import { getStructuralDensity } from "./knowledge-graph";
 
-export const PREDICTOR_FEATURE_DIMENSIONS = 17;
+export const PREDICTOR_FEATURE_DIMENSIONS = 21;
 
 export type StructuralCandidateSource = "effective" | "fts_only" | "ka_traversal" | "ka_traversal_pinned";
 
@@ -40,6 +40,50 @@ function hashSlot(value: string): number {
 	return (hash >>> 0) % 256;
 }
 
+/**
+ * Normalized tag set: a JSON array or comma-separated list, trimmed,
+ * lowercased and deduplicated. Matches the predictor's `parse_tags`.
+ */
+function parseTags(raw: string | null | undefined): ReadonlyArray<string> {
+	const trimmed = raw?.trim();
+	if (!trimmed) return [];
+	let tags: unknown[] = [];
+	if (trimmed.startsWith("[")) {
+		try {
+			const parsed: unknown = JSON.parse(trimmed);
+			if (Array.isArray(parsed)) tags = parsed;
+		} catch {
+			tags = [];
+		}
+	} else {
+		tags = trimmed.split(",");
+	}
+	const normalized = tags
+		.filter((tag): tag is string => typeof tag === "string")
+		.map((tag) => tag.trim().toLowerCase())
+		.filter((tag) => tag.length > 0);
+	return [...new Set(normalized)];
+}
+
+/**
+ * Per candidate, the share of its tags that also appear on another
+ * candidate in the pool. Matches the predictor's `tag_overlaps`.
+ */
+function tagOverlaps(tagLists: ReadonlyArray<string | null | undefined>): ReadonlyArray<number> {
+	const tags = tagLists.map(parseTags);
+	const counts = new Map<string, number>();
+	for (const own of tags) {
+		for (const tag of own) {
+			counts.set(tag, (counts.get(tag) ?? 0) + 1);
+		}
+	}
+	return tags.map((own) => {
+		if (own.length === 0) return 0;
+		const shared = own.filter((tag) => (counts.get(tag) ?? 0) > 1).length;
+		return shared / own.length;
+	});
+}
+
 function daysSince(iso: string, nowMs: number): number {
 	const ts = Date.parse(iso);
 	if (Number.isNaN(ts)) return 0;
@@ -136,10 +180,14 @@ export function buildCandidateFeatures(
 		readonly pinned: boolean;
 		readonly isSuperseded: boolean;
 		readonly source?: string;
+		readonly project?: string | null;
+		readonly content?: string;
+		readonly tags?: string | null;
 	}>,
 	agentId: string,
 	sessionContext: {
 		readonly projectSlot: number;
+		readonly project?: string;
 		readonly timeOfDay: number;
 		readonly dayOfWeek: number;
 		readonly monthOfYear: number;
@@ -175,8 +223,9 @@ export function buildCandidateFeatures(
 	const dowAngle = (2 * Math.PI * sessionContext.dayOfWeek) / 7;
 	const moyAngle = (2 * Math.PI * sessionContext.monthOfYear) / 12;
 	const safeSessionGapDays = Math.max(0, sessionContext.sessionGapDays);
+	const overlaps = tagOverlaps(candidates.map((candidate) => candidate.tags));
 
-	return candidates.map((candidate) => {
+	return candidates.map((candidate, index) => {
 		const structural = structuralById.get(candidate.id) ?? null;
 		const source = structural?.candidateSource ?? candidate.source ?? null;
 		const vector = [
@@ -197,6 +246,11 @@ export function buildCandidateFeatures(
 			structural?.isConstraint ?? 0,
 			Math.log((structural?.structuralDensity ?? 0) + 1),
 			source === "ka_traversal" ? 1 : 0,
+			candidate.pinned ? 1 : 0,
+			candidate.project && candidate.project === sessionContext.project ? 1 : 0,
+			// Code points, like Rust's chars().count()
+			Math.log([...(candidate.content ?? "")].length + 1),
+			overlaps[index] ?? 0,
 		];
 		if (vector.length !== PREDICTOR_FEATURE_DIMENSIONS) {
 			throw new Error(
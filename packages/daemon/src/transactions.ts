@@ -6,6 +6,7 @@
  * external provider calls.
  */
 
+import { emitDaemonEvent } from "./daemon-events";
 import type { WriteDb } from "./db-accessor";
 import { vectorToBlob, syncVecInsert, syncVecDeleteBySourceId, syncVecDeleteBySourceExceptHash } from "./db-helpers";
 
@@ -242,6 +243,7 @@ export function txIngestEnvelope(db: WriteDb, mem: IngestEnvelope): string {
 
 	// FTS sync handled by memories_ai AFTER INSERT trigger (migration 001)
 
+	if (!mem.isDeleted) emitDaemonEvent("memory-created", { id: mem.id });
 	return mem.id;
 }
 
@@ -104,6 +104,11 @@ enum Op {
         inputs: Vec<Act>,
         out: Act,
     },
+    WeightedPool {
+        inputs: Vec<Act>,
+        weights: Vec<f64>,
+        out: Act,
+    },
     FeatureConcat {
         inputs: Vec<Act>,
         out: Act,
@@ -117,6 +122,13 @@ enum Op {
     },
 }
 
+/// Position on a tape, from [`Tape::mark`].
+#[derive(Debug, Clone, Copy)]
+pub struct TapeMark {
+    acts: usize,
+    ops: usize,
+}
+
 #[derive(Debug)]
 pub struct Tape {
     params: Vec<Param>,
@@ -155,6 +167,15 @@ impl Tape {
         &mut self.params
     }
 
+    /// Copy of the parameters with nothing recorded, so another thread can
+    /// run forward passes while this tape keeps training.
+    pub fn snapshot(&self) -> Self {
+        Self {
+            params: self.params.clone(),
+            ..Self::new()
+        }
+    }
+
     pub fn reset(&mut self) {
         self.act_data.clear();
         self.act_grad.clear();
@@ -164,6 +185,21 @@ impl Tape {
         }
     }
 
+    pub fn mark(&self) -> TapeMark {
+        TapeMark {
+            acts: self.act_data.len(),
+            ops: self.ops.len(),
+        }
+    }
+
+    /// Drop everything recorded after `mark`. A forward-only loop over many
+    /// inputs stays at one input's worth of activations this way.
+    pub fn truncate(&mut self, mark: TapeMark) {
+        self.act_data.truncate(mark.acts);
+        self.act_grad.truncate(mark.acts);
+        self.ops.truncate(mark.ops);
+    }
+
     pub fn alloc(&mut self, size: usize) -> Act {
         let idx = self.act_data.len();
         self.act_data.push(vec![0.0; size]);
@@ -342,6 +378,37 @@ impl Tape {
         out
     }
 
+    /// Weighted sum of `inputs`; `weights` are normalized to sum to 1, so
+    /// equal weights reduce to [`mean_pool`](Self::mean_pool).
+    pub fn weighted_pool(&mut self, inputs: &[Act], weights: &[f64]) -> Act {
+        assert!(
+            !inputs.is_empty(),
+            "weighted_pool requires at least one input"
+        );
+        assert_eq!(inputs.len(), weights.len(), "weighted_pool weight count");
+        let total: f64 = weights.iter().sum();
+        assert!(total > 0.0, "weighted_pool weights must sum to > 0");
+        let width = self.act_data[inputs[0]].len();
+        let weights: Vec<f64> = weights.iter().map(|w| w / total).collect();
+        let out = self.alloc(width);
+        for (input, weight) in inputs.iter().zip(&weights) {
+            assert_eq!(
+                self.act_data[*input].len(),
+                width,
+                "weighted_pool shape mismatch"
+            );
+            for i in 0..width {
+                self.act_data[out][i] += self.act_data[*input][i] * weight;
+            }
+        }
+        self.ops.push(Op::WeightedPool {
+            inputs: inputs.to_vec(),
+            weights,
+            out,
+        });
+        out
+    }
+
     pub fn feature_concat(&mut self, inputs: &[Act]) -> Act {
         assert!(
             !inputs.is_empty(),
@@ -473,6 +540,17 @@ impl Tape {
                         }
                     }
                 }
+                Op::WeightedPool {
+                    inputs,
+                    weights,
+                    out,
+                } => {
+                    for (input, weight) in inputs.into_iter().zip(weights) {
+                        for i in 0..self.act_data[out].len() {
+                            self.act_grad[input][i] += self.act_grad[out][i] * weight;
+                        }
+                    }
+                }
                 Op::FeatureConcat { inputs, out } => {
                     let mut offset = 0;
                     for input in inputs {
@@ -532,6 +610,22 @@ mod tests {
         assert!((a - b).abs() <= tol, "{} != {} (tol {})", a, b, tol);
     }
 
+    #[test]
+    fn truncate_rewinds_to_mark() {
+        let mut tape = Tape::new();
+        let a = tape.constant(vec![1.0, 2.0]);
+        let mark = tape.mark();
+        let b = tape.constant(vec![3.0, 4.0]);
+        let sum = tape.vec_add(a, b);
+        assert_eq!(tape.value(sum), &[4.0, 6.0]);
+
+        tape.truncate(mark);
+        let c = tape.constant(vec![5.0, 5.0]);
+        assert_eq!(c, b, "slot reused after rewind");
+        let sum = tape.vec_add(a, c);
+        assert_eq!(tape.value(sum), &[6.0, 7.0]);
+    }
+
     #[test]
     fn sigmoid_backward_matches_reference() {
         let mut tape = Tape::new();
@@ -567,6 +661,21 @@ mod tests {
         approx_eq(gb[1], 0.25, 1e-8);
     }
 
+    #[test]
+    fn weighted_pool_scales_gradient_by_weight() {
+        let mut tape = Tape::new();
+        let a = tape.constant(vec![2.0, 4.0]);
+        let b = tape.constant(vec![6.0, 8.0]);
+        let pooled = tape.weighted_pool(&[a, b], &[3.0, 1.0]);
+        assert_eq!(tape.value(pooled), &[3.0, 5.0]);
+        let ones = tape.constant(vec![1.0, 1.0]);
+        let sum = tape.dot(pooled, ones);
+
+        tape.backward(sum);
+        approx_eq(tape.grad(a)[0], 0.75, 1e-8);
+        approx_eq(tape.grad(b)[1], 0.25, 1e-8);
+    }
+
     #[test]
     fn feature_concat_routes_gradient_to_each_slice() {
         let mut tape = Tape::new();
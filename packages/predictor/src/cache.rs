@@ -0,0 +1,359 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{Hash, Hasher},
+    time::{Duration, Instant},
+};
+
+use crate::protocol::ScoreParams;
+
+/// Least-recently-used map with a fixed capacity. Capacity 0 stores
+/// nothing, which is how a cache is switched off.
+#[derive(Debug, Clone)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+    entries: HashMap<K, (V, u64)>,
+    /// Last use of each key, oldest first
+    order: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.tick += 1;
+        let (value, used) = self.entries.get_mut(key)?;
+        self.order.remove(used);
+        *used = self.tick;
+        self.order.insert(self.tick, key.clone());
+        Some(value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.order.remove(&used);
+        }
+        self.order.insert(self.tick, key);
+        while self.entries.len() > self.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// Streaming [`fnv1a_hash`](crate::tokenizer::fnv1a_hash), for keys built
+/// from many slices without joining them first.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn write_floats(&mut self, values: &[f64]) {
+        self.write_usize(values.len());
+        for value in values {
+            self.write(&value.to_bits().to_le_bytes());
+        }
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Hash of what a candidate is encoded from: its embedding when it has one
+/// the model accepts, else its text.
+pub fn content_hash(embedding: Option<&[f64]>, text: Option<&str>) -> u64 {
+    let mut hasher = Fnv1a::new();
+    if let Some(embedding) = embedding {
+        hasher.write_u8(b'e');
+        hasher.write_floats(embedding);
+    } else if let Some(text) = text {
+        hasher.write_u8(b't');
+        hasher.write(text.as_bytes());
+    }
+    hasher.finish()
+}
+
+/// Hash of everything in a `score` request that affects its result; the
+/// cache controls themselves are left out.
+pub fn score_request_hash(params: &ScoreParams) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write_floats(&params.context_embedding);
+    params.candidate_ids.hash(&mut hasher);
+    hasher.write_usize(params.candidate_embeddings.len());
+    for embedding in &params.candidate_embeddings {
+        hasher.write_floats(embedding);
+    }
+    params.candidate_embedding_blobs.hash(&mut hasher);
+    params.embedding_format.hash(&mut hasher);
+    params.candidate_texts.hash(&mut hasher);
+    hasher.write_usize(params.candidate_features.len());
+    for features in &params.candidate_features {
+        hasher.write_floats(features);
+    }
+    hasher.write_usize(params.project_slot);
+    params.top_k.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Whole `score` results for a short time. Entries older than `ttl` are
+/// never returned; a caller may ask for fresher still.
+#[derive(Debug, Clone)]
+pub struct TtlCache<V> {
+    ttl: Duration,
+    entries: LruCache<u64, (Instant, V)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<V> TtlCache<V> {
+    /// `ttl` of zero turns the cache off.
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        let capacity = if ttl.is_zero() { 0 } else { capacity };
+        Self {
+            ttl,
+            entries: LruCache::new(capacity),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.entries.capacity() > 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&mut self, key: u64, now: Instant, max_age: Option<Duration>) -> Option<&V> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let max_age = max_age.map_or(self.ttl, |age| age.min(self.ttl));
+        match self.entries.get(&key) {
+            Some((stored, value)) if now.saturating_duration_since(*stored) <= max_age => {
+                self.hits += 1;
+                Some(value)
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: u64, now: Instant, value: V) {
+        self.entries.insert(key, (now, value));
+    }
+
+    /// Hits and misses since the last call.
+    pub fn take_counts(&mut self) -> (u64, u64) {
+        let counts = (self.hits, self.misses);
+        self.hits = 0;
+        self.misses = 0;
+        counts
+    }
+}
+
+/// Candidate encodings from the current weights, keyed by memory id and
+/// [`content_hash`] so an edited memory misses rather than reusing the old
+/// vector. Counts hits and misses until they are taken for `metrics`.
+#[derive(Debug, Clone)]
+pub struct EncodingCache {
+    entries: LruCache<(String, u64), Vec<f64>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl EncodingCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: LruCache::new(capacity),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&mut self, id: &str, content: u64) -> Option<&[f64]> {
+        if self.entries.capacity() == 0 {
+            return None;
+        }
+        match self.entries.get(&(id.to_string(), content)) {
+            Some(encoded) => {
+                self.hits += 1;
+                Some(encoded.as_slice())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, id: &str, content: u64, encoded: Vec<f64>) {
+        self.entries.insert((id.to_string(), content), encoded);
+    }
+
+    /// Hits and misses since the last call.
+    pub fn take_counts(&mut self) -> (u64, u64) {
+        let counts = (self.hits, self.misses);
+        self.hits = 0;
+        self.misses = 0;
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_evicts_the_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        cache.insert("c", 3);
+        assert_eq!(cache.get(&"b"), None, "b was used least recently");
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.get(&"c"), Some(&3));
+        cache.insert("c", 4);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"c"), Some(&4));
+
+        let mut off = LruCache::new(0);
+        off.insert("a", 1);
+        assert!(off.is_empty());
+    }
+
+    #[test]
+    fn edited_content_misses() {
+        let mut cache = EncodingCache::new(8);
+        let before = content_hash(Some(&[0.1, 0.2]), None);
+        let after = content_hash(Some(&[0.1, 0.3]), None);
+        assert_ne!(before, after);
+        assert_ne!(
+            content_hash(None, Some("dark mode")),
+            content_hash(None, Some("light mode"))
+        );
+
+        assert!(cache.get("m1", before).is_none());
+        cache.insert("m1", before, vec![1.0]);
+        assert_eq!(cache.get("m1", before), Some(&[1.0][..]));
+        assert!(cache.get("m1", after).is_none());
+        assert!(cache.get("m2", before).is_none());
+        assert_eq!(cache.take_counts(), (1, 3));
+        assert_eq!(cache.take_counts(), (0, 0));
+    }
+
+    #[test]
+    fn score_entries_expire() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut cache = TtlCache::new(Duration::from_millis(500), 4);
+        cache.insert(1, at(0), "scores");
+
+        assert_eq!(cache.get(1, at(400), None), Some(&"scores"));
+        assert_eq!(
+            cache.get(1, at(400), Some(Duration::from_millis(100))),
+            None,
+            "caller wants fresher"
+        );
+        assert_eq!(
+            cache.get(1, at(600), Some(Duration::from_secs(10))),
+            None,
+            "max age cannot extend the ttl"
+        );
+        assert_eq!(cache.take_counts(), (1, 2));
+
+        let mut off = TtlCache::new(Duration::ZERO, 4);
+        off.insert(1, at(0), "scores");
+        assert!(!off.is_enabled());
+        assert_eq!(off.get(1, at(0), None), None);
+    }
+
+    #[test]
+    fn request_hash_ignores_cache_controls() {
+        let params = |extra: serde_json::Value| {
+            let mut value = serde_json::json!({
+                "context_embedding": [0.1, 0.2],
+                "candidate_ids": ["a", "b"],
+                "candidate_texts": ["dark mode", null],
+            });
+            value
+                .as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            serde_json::from_value::<ScoreParams>(value).unwrap()
+        };
+        let base = score_request_hash(&params(serde_json::json!({})));
+        assert_eq!(
+            base,
+            score_request_hash(&params(serde_json::json!({ "cache": "refresh" })))
+        );
+        assert_ne!(
+            base,
+            score_request_hash(&params(serde_json::json!({ "project_slot": 1 })))
+        );
+        assert_ne!(
+            base,
+            score_request_hash(&params(
+                serde_json::json!({ "candidate_texts": [null, "dark mode"] })
+            ))
+        );
+    }
+}
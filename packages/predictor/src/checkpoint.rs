@@ -1,19 +1,106 @@
 use std::{
-    fs::File,
+    fs::{self, File},
     io::{Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use crate::{autograd::Tape, model::CrossAttentionScorer};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    autograd::{Rng, Tape},
+    data::TrainingCursor,
+    embedding::{f16_to_f32, f32_to_f16},
+    features::{FeatureSchema, V1_FEATURE_DIM},
+    model::{CrossAttentionScorer, FeatureStats, ScorerConfig, TokenStats},
+    training::{AdamState, Hyperparameters},
+};
 
 const MAGIC: &[u8; 4] = b"SGPT";
-const VERSION: u32 = 1;
+/// v2 appends the feature standardization section after the parameters.
+/// v3 appends the incremental training cursor as length-prefixed JSON.
+/// v4 appends the optional Adam state: a parameter count (0 when absent),
+/// the step count, then each parameter's first and second moments.
+/// v5 appends training metadata as length-prefixed JSON.
+/// v6 honours the parameter storage flags below; earlier files are f64.
+/// v7 may zstd-compress everything after the flags word.
+/// v8 appends token document frequencies: a bucket count (0 when absent),
+/// the document count, then one u64 per bucket.
+///
+/// Older files are read section by section up to their version, then
+/// brought forward by [`migrate`]. Files newer than this are refused.
+pub const VERSION: u32 = 8;
+
+/// Oldest file version [`load`] still reads.
+pub const MIN_VERSION: u32 = 1;
+
+/// Store parameters as f32. Round-trips within the model's tolerance at
+/// half the size.
+pub const FLAG_F32_PARAMS: u32 = 1 << 0;
+/// Store parameters as IEEE half precision, a quarter of the f64 size.
+/// Lossy: meant for distribution, not for resuming training.
+pub const FLAG_F16_PARAMS: u32 = 1 << 1;
+/// zstd-compress the payload after the header. Lossless, and combines with
+/// either storage flag; young models with large hash tables shrink most.
+pub const FLAG_ZSTD_PAYLOAD: u32 = 1 << 2;
+
+const ZSTD_LEVEL: i32 = 3;
+
+/// Encoding of the parameter section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamStorage {
+    F64,
+    F32,
+    F16,
+}
+
+impl ParamStorage {
+    fn from_flags(flags: u32) -> Result<Self, CheckpointError> {
+        match (flags & FLAG_F32_PARAMS != 0, flags & FLAG_F16_PARAMS != 0) {
+            (false, false) => Ok(Self::F64),
+            (true, false) => Ok(Self::F32),
+            (false, true) => Ok(Self::F16),
+            (true, true) => Err(CheckpointError::InvalidFormat(
+                "f32 and f16 storage flags are exclusive".to_string(),
+            )),
+        }
+    }
+
+    fn write(self, writer: &mut dyn Write, value: f64) -> std::io::Result<()> {
+        match self {
+            Self::F64 => writer.write_all(&value.to_le_bytes()),
+            Self::F32 => writer.write_all(&(value as f32).to_le_bytes()),
+            Self::F16 => writer.write_all(&f32_to_f16(value as f32).to_le_bytes()),
+        }
+    }
+
+    fn read(self, reader: &mut dyn Read) -> Result<f64, CheckpointError> {
+        match self {
+            Self::F64 => read_f64(reader),
+            Self::F32 => {
+                let mut bytes = [0_u8; 4];
+                reader.read_exact(&mut bytes)?;
+                Ok(f32::from_le_bytes(bytes) as f64)
+            }
+            Self::F16 => {
+                let mut bytes = [0_u8; 2];
+                reader.read_exact(&mut bytes)?;
+                Ok(f16_to_f32(u16::from_le_bytes(bytes)) as f64)
+            }
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum CheckpointError {
     Io(std::io::Error),
     InvalidFormat(String),
     Json(serde_json::Error),
+    /// Written by a newer predictor than this one
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
 }
 
 impl From<std::io::Error> for CheckpointError {
@@ -28,12 +115,32 @@ impl From<serde_json::Error> for CheckpointError {
     }
 }
 
+/// Training lineage, so `status` survives a restart.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointMetadata {
+    pub model_version: u64,
+    pub train_steps: u64,
+    pub training_pairs: usize,
+    pub last_trained: Option<String>,
+    /// Runtime config from `set_config`; absent before it existed
+    #[serde(default)]
+    pub hyperparameters: Option<Hyperparameters>,
+    /// Encoder weights are held fixed, as set by `clone_model`
+    #[serde(default)]
+    pub encoder_frozen: bool,
+}
+
 #[derive(Debug)]
 pub struct LoadedCheckpoint {
     pub version: u32,
     pub flags: u32,
-    pub config: crate::model::ScorerConfig,
+    pub config: ScorerConfig,
     pub params: Vec<Vec<f64>>,
+    pub feature_stats: Option<FeatureStats>,
+    pub cursor: Option<TrainingCursor>,
+    pub optimizer: Option<AdamState>,
+    pub metadata: Option<CheckpointMetadata>,
+    pub token_stats: Option<TokenStats>,
 }
 
 pub fn save(
@@ -41,13 +148,71 @@ pub fn save(
     model: &CrossAttentionScorer,
     tape: &Tape,
     flags: u32,
+    cursor: Option<&TrainingCursor>,
+    optimizer: Option<&AdamState>,
+    metadata: Option<&CheckpointMetadata>,
 ) -> Result<(), CheckpointError> {
-    let mut file = File::create(path)?;
-    let config_json = serde_json::to_vec(&model.config())?;
+    let storage = ParamStorage::from_flags(flags)?;
+    replace_file(path, |file| {
+        file.write_all(MAGIC)?;
+        file.write_all(&VERSION.to_le_bytes())?;
+        file.write_all(&flags.to_le_bytes())?;
+
+        if flags & FLAG_ZSTD_PAYLOAD != 0 {
+            let mut encoder = zstd::Encoder::new(file, ZSTD_LEVEL)?;
+            write_payload(
+                &mut encoder,
+                model,
+                tape,
+                storage,
+                cursor,
+                optimizer,
+                metadata,
+            )?;
+            encoder.finish()?;
+        } else {
+            write_payload(file, model, tape, storage, cursor, optimizer, metadata)?;
+        }
+        Ok(())
+    })
+}
 
-    file.write_all(MAGIC)?;
-    file.write_all(&VERSION.to_le_bytes())?;
-    file.write_all(&flags.to_le_bytes())?;
+/// Write `path` through `<path>.tmp`, synced and then renamed over it, so
+/// a process killed mid-write leaves the previous file whole.
+fn replace_file(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> Result<(), CheckpointError>,
+) -> Result<(), CheckpointError> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let tmp = path.with_file_name(name);
+    let written = File::create(&tmp)
+        .map_err(CheckpointError::from)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.sync_all()?;
+            Ok(())
+        });
+    if let Err(err) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(err);
+    }
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Everything after the header: config, parameters, then the sections
+/// each version appended.
+fn write_payload(
+    file: &mut dyn Write,
+    model: &CrossAttentionScorer,
+    tape: &Tape,
+    storage: ParamStorage,
+    cursor: Option<&TrainingCursor>,
+    optimizer: Option<&AdamState>,
+    metadata: Option<&CheckpointMetadata>,
+) -> Result<(), CheckpointError> {
+    let config_json = serde_json::to_vec(&model.config())?;
     file.write_all(&(config_json.len() as u32).to_le_bytes())?;
     file.write_all(&config_json)?;
 
@@ -57,14 +222,71 @@ pub fn save(
         let param = &tape.params()[param_idx];
         file.write_all(&(param.data.len() as u32).to_le_bytes())?;
         for value in &param.data {
-            file.write_all(&value.to_le_bytes())?;
+            storage.write(file, *value)?;
+        }
+    }
+
+    match model.feature_stats() {
+        Some(stats) => {
+            file.write_all(&(stats.dim() as u32).to_le_bytes())?;
+            for value in stats.mean.iter().chain(&stats.std) {
+                file.write_all(&value.to_le_bytes())?;
+            }
         }
+        None => file.write_all(&0_u32.to_le_bytes())?,
+    }
+
+    let cursor_json = match cursor {
+        Some(cursor) => serde_json::to_vec(cursor)?,
+        None => Vec::new(),
+    };
+    file.write_all(&(cursor_json.len() as u32).to_le_bytes())?;
+    file.write_all(&cursor_json)?;
+
+    match optimizer {
+        Some(state) => {
+            file.write_all(&(state.m.len() as u32).to_le_bytes())?;
+            file.write_all(&state.step.to_le_bytes())?;
+            for (m, v) in state.m.iter().zip(&state.v) {
+                file.write_all(&(m.len() as u32).to_le_bytes())?;
+                for value in m.iter().chain(v) {
+                    file.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+        None => file.write_all(&0_u32.to_le_bytes())?,
+    }
+
+    let metadata_json = match metadata {
+        Some(metadata) => serde_json::to_vec(metadata)?,
+        None => Vec::new(),
+    };
+    file.write_all(&(metadata_json.len() as u32).to_le_bytes())?;
+    file.write_all(&metadata_json)?;
+
+    match model.token_stats() {
+        Some(stats) => {
+            file.write_all(&(stats.df.len() as u32).to_le_bytes())?;
+            file.write_all(&stats.documents.to_le_bytes())?;
+            for count in &stats.df {
+                file.write_all(&count.to_le_bytes())?;
+            }
+        }
+        None => file.write_all(&0_u32.to_le_bytes())?,
     }
 
     Ok(())
 }
 
+/// Read a checkpoint of any supported version and migrate it to the
+/// current in-memory form. `version` still reports the on-disk version.
 pub fn load(path: &Path) -> Result<LoadedCheckpoint, CheckpointError> {
+    migrate(read(path)?)
+}
+
+/// Read a checkpoint exactly as written. Sections are appended per version,
+/// so a vN file holds every section up to and including vN's.
+fn read(path: &Path) -> Result<LoadedCheckpoint, CheckpointError> {
     let mut file = File::open(path)?;
     let mut magic = [0_u8; 4];
     file.read_exact(&mut magic)?;
@@ -73,37 +295,184 @@ pub fn load(path: &Path) -> Result<LoadedCheckpoint, CheckpointError> {
     }
 
     let version = read_u32(&mut file)?;
+    if !(MIN_VERSION..=VERSION).contains(&version) {
+        return Err(CheckpointError::UnsupportedVersion {
+            found: version,
+            supported: VERSION,
+        });
+    }
     let flags = read_u32(&mut file)?;
+    let mut file: Box<dyn Read> = if version >= 7 && flags & FLAG_ZSTD_PAYLOAD != 0 {
+        Box::new(zstd::Decoder::new(file)?)
+    } else {
+        Box::new(file)
+    };
     let config_len = read_u32(&mut file)? as usize;
 
     let mut config_bytes = vec![0_u8; config_len];
     file.read_exact(&mut config_bytes)?;
-    let config: crate::model::ScorerConfig = serde_json::from_slice(&config_bytes)?;
+    let config: ScorerConfig = serde_json::from_slice(&config_bytes)?;
 
+    let storage = if version >= 6 {
+        ParamStorage::from_flags(flags)?
+    } else {
+        ParamStorage::F64
+    };
     let param_count = read_u32(&mut file)? as usize;
     let mut params = Vec::with_capacity(param_count);
     for _ in 0..param_count {
         let len = read_u32(&mut file)? as usize;
         let mut values = Vec::with_capacity(len);
         for _ in 0..len {
-            values.push(read_f64(&mut file)?);
+            values.push(storage.read(&mut file)?);
         }
         params.push(values);
     }
 
+    let feature_stats = if version >= 2 {
+        let dim = read_u32(&mut file)? as usize;
+        if dim == 0 {
+            None
+        } else {
+            let mut mean = Vec::with_capacity(dim);
+            for _ in 0..dim {
+                mean.push(read_f64(&mut file)?);
+            }
+            let mut std = Vec::with_capacity(dim);
+            for _ in 0..dim {
+                std.push(read_f64(&mut file)?);
+            }
+            Some(FeatureStats { mean, std })
+        }
+    } else {
+        None
+    };
+
+    let cursor = if version >= 3 {
+        read_json_section(&mut file)?
+    } else {
+        None
+    };
+
+    let optimizer = if version >= 4 {
+        let count = read_u32(&mut file)? as usize;
+        if count == 0 {
+            None
+        } else {
+            let step = read_u64(&mut file)?;
+            let mut m = Vec::with_capacity(count);
+            let mut v = Vec::with_capacity(count);
+            for _ in 0..count {
+                let len = read_u32(&mut file)? as usize;
+                m.push(read_f64_vec(&mut file, len)?);
+                v.push(read_f64_vec(&mut file, len)?);
+            }
+            Some(AdamState { step, m, v })
+        }
+    } else {
+        None
+    };
+
+    let metadata = if version >= 5 {
+        read_json_section(&mut file)?
+    } else {
+        None
+    };
+
+    let token_stats = if version >= 8 {
+        let buckets = read_u32(&mut file)? as usize;
+        if buckets == 0 {
+            None
+        } else {
+            let documents = read_u64(&mut file)?;
+            let mut df = Vec::with_capacity(buckets);
+            for _ in 0..buckets {
+                df.push(read_u64(&mut file)?);
+            }
+            Some(TokenStats { documents, df })
+        }
+    } else {
+        None
+    };
+
     Ok(LoadedCheckpoint {
         version,
         flags,
         config,
         params,
+        feature_stats,
+        cursor,
+        optimizer,
+        metadata,
+        token_stats,
     })
 }
 
+/// One upgrade step, applied to checkpoints read from a version below `to`.
+struct Migration {
+    to: u32,
+    apply: fn(&mut LoadedCheckpoint),
+}
+
+/// Upgrade steps in version order. Sections missing from an older file
+/// already read as absent, so only changes in meaning need a step.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 2,
+        apply: record_v1_feature_schema,
+    },
+    Migration {
+        to: 6,
+        apply: clear_storage_flags,
+    },
+    Migration {
+        to: 7,
+        apply: clear_compression_flag,
+    },
+];
+
+/// Models saved before schemas were recorded used the v1 feature set.
+/// Naming it lets a matching `--disable-features` model load them and gives
+/// a precise error otherwise.
+fn record_v1_feature_schema(loaded: &mut LoadedCheckpoint) {
+    if loaded.config.feature_schema.is_none() && loaded.config.extra_features == V1_FEATURE_DIM {
+        loaded.config.feature_schema = Some(FeatureSchema::v1());
+    }
+}
+
+/// Before v6 the flag bits carried no storage meaning and the data is f64.
+fn clear_storage_flags(loaded: &mut LoadedCheckpoint) {
+    loaded.flags &= !(FLAG_F32_PARAMS | FLAG_F16_PARAMS);
+}
+
+/// Before v7 the compression bit carried no meaning either.
+fn clear_compression_flag(loaded: &mut LoadedCheckpoint) {
+    loaded.flags &= !FLAG_ZSTD_PAYLOAD;
+}
+
+/// Bring a checkpoint read from an older version up to [`VERSION`].
+pub fn migrate(mut loaded: LoadedCheckpoint) -> Result<LoadedCheckpoint, CheckpointError> {
+    if loaded.version > VERSION {
+        return Err(CheckpointError::UnsupportedVersion {
+            found: loaded.version,
+            supported: VERSION,
+        });
+    }
+    let from = loaded.version;
+    for step in MIGRATIONS.iter().filter(|step| from < step.to) {
+        (step.apply)(&mut loaded);
+    }
+    Ok(loaded)
+}
+
 pub fn apply_checkpoint(
     loaded: &LoadedCheckpoint,
-    model: &CrossAttentionScorer,
+    model: &mut CrossAttentionScorer,
     tape: &mut Tape,
 ) -> Result<(), CheckpointError> {
+    check_feature_schema(&loaded.config, model.config())?;
+    check_tokenizer(&loaded.config, model.config())?;
+
     let param_indices = model.param_indices();
     if loaded.params.len() != param_indices.len() {
         return Err(CheckpointError::InvalidFormat(
@@ -124,6 +493,81 @@ pub fn apply_checkpoint(
         target.data.copy_from_slice(&loaded.params[slot]);
     }
 
+    model
+        .set_feature_stats(loaded.feature_stats.clone())
+        .map_err(CheckpointError::InvalidFormat)?;
+    model
+        .set_token_stats(loaded.token_stats.clone())
+        .map_err(CheckpointError::InvalidFormat)?;
+
+    Ok(())
+}
+
+/// Build a model shaped by the checkpoint's own config and load its
+/// weights into it, so a model trained with non-default dimensions, slots
+/// or features comes back as it was saved rather than failing to fit the
+/// caller's defaults.
+pub fn rebuild_from_checkpoint(
+    loaded: &LoadedCheckpoint,
+    rng: &mut Rng,
+) -> Result<(Tape, CrossAttentionScorer), CheckpointError> {
+    let mut tape = Tape::new();
+    let mut model = CrossAttentionScorer::new(&mut tape, rng, loaded.config.clone());
+    apply_checkpoint(loaded, &mut model, &mut tape)?;
+    Ok((tape, model))
+}
+
+/// The hash table only means something under the tokenization it was
+/// trained with, so a model must tokenize exactly as the checkpoint did.
+fn check_tokenizer(saved: &ScorerConfig, current: &ScorerConfig) -> Result<(), CheckpointError> {
+    if let Some(ref descriptor) = saved.tokenizer {
+        descriptor
+            .validate()
+            .map_err(CheckpointError::InvalidFormat)?;
+    }
+    let settings = |c: &ScorerConfig| (c.tokenizer.clone(), c.max_tokens, c.bigram_buckets);
+    if settings(saved) != settings(current) {
+        return Err(CheckpointError::InvalidFormat(format!(
+            "tokenizer mismatch: checkpoint {:?}, model {:?}",
+            settings(saved),
+            settings(current)
+        )));
+    }
+    Ok(())
+}
+
+/// A checkpoint only applies to a model built for the same feature vector.
+/// Legacy checkpoints without a schema are checked by width alone.
+fn check_feature_schema(
+    saved: &ScorerConfig,
+    current: &ScorerConfig,
+) -> Result<(), CheckpointError> {
+    if saved.extra_features != current.extra_features {
+        return Err(CheckpointError::InvalidFormat(format!(
+            "feature width mismatch: checkpoint has {}, model expects {}",
+            saved.extra_features, current.extra_features
+        )));
+    }
+    let Some(ref schema) = saved.feature_schema else {
+        return Ok(());
+    };
+    schema.validate().map_err(CheckpointError::InvalidFormat)?;
+    if schema.dim() != saved.extra_features {
+        return Err(CheckpointError::InvalidFormat(format!(
+            "feature schema lists {} features but config has {}",
+            schema.dim(),
+            saved.extra_features
+        )));
+    }
+    if let Some(ref expected) = current.feature_schema {
+        if schema.features != expected.features {
+            return Err(CheckpointError::InvalidFormat(format!(
+                "feature schema mismatch: checkpoint [{}], model [{}]",
+                schema.features.join(", "),
+                expected.features.join(", ")
+            )));
+        }
+    }
     Ok(())
 }
 
@@ -133,8 +577,812 @@ fn read_u32(reader: &mut dyn Read) -> Result<u32, CheckpointError> {
     Ok(u32::from_le_bytes(bytes))
 }
 
+/// Snapshots kept beside the live checkpoint unless told otherwise
+pub const DEFAULT_KEEP_CHECKPOINTS: usize = 5;
+
+/// When online `train` updates are written to the live checkpoint without
+/// waiting for a batch retrain. Either bound triggers a save; neither set
+/// means no autosave.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AutosavePolicy {
+    pub every_steps: Option<u64>,
+    pub every: Option<Duration>,
+}
+
+impl AutosavePolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.every_steps.is_some() || self.every.is_some()
+    }
+
+    /// Whether `unsaved_steps` taken over `since_save` warrant a save.
+    pub fn due(&self, unsaved_steps: u64, since_save: Duration) -> bool {
+        unsaved_steps > 0
+            && (self.every_steps.is_some_and(|n| unsaved_steps >= n)
+                || self.every.is_some_and(|interval| since_save >= interval))
+    }
+}
+
+/// A timestamped copy of the live checkpoint, named
+/// `<stem>.<epoch ms>.<ext>` next to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub path: String,
+    pub created_ms: u64,
+    pub size_bytes: u64,
+    /// The newest snapshot saved after passing the canary checks. Retention
+    /// never deletes it, however many snapshots follow.
+    pub known_good: bool,
+}
+
+/// (directory, `<stem>.`, `.<ext>` or empty) of a checkpoint path
+fn snapshot_parts(path: &Path) -> Result<(PathBuf, String, String), CheckpointError> {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| CheckpointError::InvalidFormat("checkpoint path has no file name".into()))?;
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| format!(".{e}"))
+        .unwrap_or_default();
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    Ok((dir.to_path_buf(), format!("{stem}."), ext))
+}
+
+/// Records which snapshot is the last known good one
+fn good_marker(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".good");
+    path.with_file_name(name)
+}
+
+/// Copy the checkpoint at `path` to a new timestamped snapshot, then drop
+/// all but the newest `keep` snapshots (and the last known good one).
+pub fn snapshot(path: &Path, keep: usize, known_good: bool) -> Result<PathBuf, CheckpointError> {
+    let (dir, prefix, ext) = snapshot_parts(path)?;
+    let mut ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let mut target = dir.join(format!("{prefix}{ms:013}{ext}"));
+    while target.exists() {
+        ms += 1;
+        target = dir.join(format!("{prefix}{ms:013}{ext}"));
+    }
+    fs::copy(path, &target)?;
+    if known_good {
+        let name = target
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        fs::write(good_marker(path), name)?;
+    }
+
+    for stale in list_snapshots(path)?.iter().skip(keep) {
+        if !stale.known_good {
+            fs::remove_file(&stale.path)?;
+        }
+    }
+    Ok(target)
+}
+
+/// Snapshots of the checkpoint at `path`, newest first.
+pub fn list_snapshots(path: &Path) -> Result<Vec<SnapshotInfo>, CheckpointError> {
+    let (dir, prefix, ext) = snapshot_parts(path)?;
+    let good = fs::read_to_string(good_marker(path)).unwrap_or_default();
+    let mut out = Vec::new();
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(out),
+        Err(err) => return Err(err.into()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        let Some(stamp) = name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(&ext))
+        else {
+            continue;
+        };
+        if stamp.len() != 13 || !stamp.bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        out.push(SnapshotInfo {
+            path: entry.path().to_string_lossy().into_owned(),
+            created_ms: stamp.parse().unwrap_or(0),
+            size_bytes: entry.metadata()?.len(),
+            known_good: name == good.trim(),
+        });
+    }
+    out.sort_by_key(|s| std::cmp::Reverse(s.created_ms));
+    Ok(out)
+}
+
+/// Make `snapshot` the live checkpoint at `path`. Only snapshots of this
+/// checkpoint are accepted, and the file must load before anything is
+/// overwritten.
+pub fn restore_snapshot(path: &Path, snapshot: &Path) -> Result<LoadedCheckpoint, CheckpointError> {
+    let known = list_snapshots(path)?
+        .into_iter()
+        .any(|info| Path::new(&info.path) == snapshot);
+    if !known {
+        return Err(CheckpointError::InvalidFormat(format!(
+            "{} is not a snapshot of {}",
+            snapshot.display(),
+            path.display()
+        )));
+    }
+    let loaded = load(snapshot)?;
+    replace_file(path, |file| {
+        std::io::copy(&mut File::open(snapshot)?, file)?;
+        Ok(())
+    })?;
+    Ok(loaded)
+}
+
+/// One stored parameter: the shape its config implies and the number of
+/// values actually present, which differ only in a damaged file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParamShape {
+    pub name: String,
+    pub rows: usize,
+    pub cols: usize,
+    pub stored: usize,
+}
+
+/// What a checkpoint holds, read without loading it into a live model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointSummary {
+    pub path: String,
+    pub size_bytes: u64,
+    /// Version the file was written at
+    pub file_version: u32,
+    pub flags: u32,
+    /// "f64", "f32" or "f16"
+    pub storage: String,
+    /// Payload is zstd-compressed; `size_bytes` is the compressed size
+    pub compressed: bool,
+    pub config: ScorerConfig,
+    pub params: Vec<ParamShape>,
+    pub total_params: usize,
+    pub feature_stats: bool,
+    /// Training texts behind the IDF weights, when recorded
+    pub token_documents: Option<u64>,
+    pub cursor: Option<TrainingCursor>,
+    /// Adam step count, when optimizer state was saved
+    pub optimizer_step: Option<u64>,
+    pub metadata: Option<CheckpointMetadata>,
+}
+
+/// Summarise the checkpoint at `path`.
+pub fn inspect(path: &Path) -> Result<CheckpointSummary, CheckpointError> {
+    let size_bytes = fs::metadata(path)?.len();
+    let loaded = load(path)?;
+
+    let shapes = param_shapes(&loaded);
+
+    let storage = match ParamStorage::from_flags(loaded.flags)? {
+        ParamStorage::F64 => "f64",
+        ParamStorage::F32 => "f32",
+        ParamStorage::F16 => "f16",
+    };
+    Ok(CheckpointSummary {
+        path: path.display().to_string(),
+        size_bytes,
+        file_version: loaded.version,
+        flags: loaded.flags,
+        storage: storage.to_string(),
+        compressed: loaded.flags & FLAG_ZSTD_PAYLOAD != 0,
+        total_params: loaded.params.iter().map(Vec::len).sum(),
+        params: shapes,
+        config: loaded.config,
+        feature_stats: loaded.feature_stats.is_some(),
+        token_documents: loaded.token_stats.as_ref().map(|stats| stats.documents),
+        cursor: loaded.cursor,
+        optimizer_step: loaded.optimizer.map(|state| state.step),
+        metadata: loaded.metadata,
+    })
+}
+
+/// Name and config shape of every stored parameter. Shapes come from a
+/// scratch model built from the saved config, so they match what
+/// `apply_checkpoint` would expect.
+pub(crate) fn param_shapes(loaded: &LoadedCheckpoint) -> Vec<ParamShape> {
+    let mut tape = Tape::new();
+    let mut rng = Rng::new(0);
+    let model = CrossAttentionScorer::new(&mut tape, &mut rng, loaded.config.clone());
+    let mut shapes: Vec<ParamShape> = model
+        .param_names()
+        .into_iter()
+        .zip(model.param_indices())
+        .zip(&loaded.params)
+        .map(|((name, idx), stored)| {
+            let param = &tape.params()[idx];
+            ParamShape {
+                name,
+                rows: param.rows,
+                cols: param.cols,
+                stored: stored.len(),
+            }
+        })
+        .collect();
+    // Stored parameters the config does not account for
+    for (slot, stored) in loaded.params.iter().enumerate().skip(shapes.len()) {
+        shapes.push(ParamShape {
+            name: format!("unknown_{slot}"),
+            rows: 0,
+            cols: 0,
+            stored: stored.len(),
+        });
+    }
+    shapes
+}
+
+/// How far one parameter moved between two checkpoints.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParamDelta {
+    pub name: String,
+    /// L2 norm of `after - before`
+    pub delta_l2: f64,
+    /// L2 norm of `before`, for scale
+    pub before_l2: f64,
+    /// `delta_l2 / before_l2`, or 0 when `before` is all zeros
+    pub relative: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointDiff {
+    pub before: String,
+    pub after: String,
+    pub params: Vec<ParamDelta>,
+    /// L2 norm of the delta across every parameter
+    pub total_delta_l2: f64,
+}
+
+/// Per-parameter movement from the checkpoint at `before` to the one at
+/// `after`. Both must hold the same parameter shapes.
+pub fn diff(before: &Path, after: &Path) -> Result<CheckpointDiff, CheckpointError> {
+    let old = load(before)?;
+    let new = load(after)?;
+    if old.params.len() != new.params.len() {
+        return Err(CheckpointError::InvalidFormat(format!(
+            "parameter count mismatch: {} != {}",
+            old.params.len(),
+            new.params.len()
+        )));
+    }
+
+    let mut params = Vec::with_capacity(old.params.len());
+    let mut total_sq = 0.0;
+    for ((shape, a), b) in param_shapes(&old)
+        .into_iter()
+        .zip(&old.params)
+        .zip(&new.params)
+    {
+        if a.len() != b.len() {
+            return Err(CheckpointError::InvalidFormat(format!(
+                "parameter {} size mismatch: {} != {}",
+                shape.name,
+                a.len(),
+                b.len()
+            )));
+        }
+        let delta_sq: f64 = a.iter().zip(b).map(|(x, y)| (y - x) * (y - x)).sum();
+        let before_l2 = a.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let delta_l2 = delta_sq.sqrt();
+        total_sq += delta_sq;
+        params.push(ParamDelta {
+            name: shape.name,
+            delta_l2,
+            before_l2,
+            relative: if before_l2 > 0.0 {
+                delta_l2 / before_l2
+            } else {
+                0.0
+            },
+        });
+    }
+
+    Ok(CheckpointDiff {
+        before: before.display().to_string(),
+        after: after.display().to_string(),
+        params,
+        total_delta_l2: total_sq.sqrt(),
+    })
+}
+
+/// A length-prefixed JSON value; a zero length means absent.
+fn read_json_section<T: serde::de::DeserializeOwned>(
+    reader: &mut dyn Read,
+) -> Result<Option<T>, CheckpointError> {
+    let len = read_u32(reader)? as usize;
+    if len == 0 {
+        return Ok(None);
+    }
+    let mut bytes = vec![0_u8; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(serde_json::from_slice(&bytes)?))
+}
+
+fn read_u64(reader: &mut dyn Read) -> Result<u64, CheckpointError> {
+    let mut bytes = [0_u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
 fn read_f64(reader: &mut dyn Read) -> Result<f64, CheckpointError> {
     let mut bytes = [0_u8; 8];
     reader.read_exact(&mut bytes)?;
     Ok(f64::from_le_bytes(bytes))
 }
+
+fn read_f64_vec(reader: &mut dyn Read, len: usize) -> Result<Vec<f64>, CheckpointError> {
+    let mut values = Vec::with_capacity(len);
+    for _ in 0..len {
+        values.push(read_f64(reader)?);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tokenizer::TokenizerDescriptor, training::Adam};
+
+    fn small_config() -> ScorerConfig {
+        ScorerConfig {
+            native_dim: 4,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 3,
+            hash_buckets: 16,
+            project_slots: 2,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        }
+    }
+
+    #[test]
+    fn roundtrip_preserves_feature_stats_and_cursor() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(11);
+        let mut model = CrossAttentionScorer::new(&mut tape, &mut rng, small_config());
+        let stats = FeatureStats {
+            mean: vec![0.5, 1.0, 2.0],
+            std: vec![1.0, 0.5, 4.0],
+        };
+        model.set_feature_stats(Some(stats.clone())).unwrap();
+        model.observe_texts(["deploy the api", "sqlite vacuum"]);
+        let token_stats = model.token_stats().cloned().unwrap();
+
+        let path = std::env::temp_dir().join("predictor_test_ckpt_stats.bin");
+        let cursor = TrainingCursor {
+            created_at: "2026-02-20T14:00:00Z".to_string(),
+            session_key: "s1".to_string(),
+        };
+        let metadata = CheckpointMetadata {
+            model_version: 7,
+            train_steps: 120,
+            training_pairs: 3_400,
+            last_trained: Some("2026-02-20T14:05:00Z".to_string()),
+            hyperparameters: Some(Hyperparameters {
+                learning_rate: 5e-4,
+                ..Hyperparameters::default()
+            }),
+            encoder_frozen: true,
+        };
+        save(
+            &path,
+            &model,
+            &tape,
+            0,
+            Some(&cursor),
+            None,
+            Some(&metadata),
+        )
+        .unwrap();
+        let loaded = load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded.version, VERSION);
+        assert_eq!(loaded.feature_stats.as_ref(), Some(&stats));
+        assert_eq!(loaded.cursor, Some(cursor));
+        assert!(loaded.optimizer.is_none());
+        assert_eq!(loaded.metadata, Some(metadata));
+        assert_eq!(loaded.token_stats.as_ref(), Some(&token_stats));
+        let older: CheckpointMetadata = serde_json::from_str(
+            r#"{"model_version":1,"train_steps":0,"training_pairs":0,"last_trained":null}"#,
+        )
+        .unwrap();
+        assert!(older.hyperparameters.is_none());
+        assert!(!older.encoder_frozen);
+
+        let mut tape2 = Tape::new();
+        let mut rng2 = Rng::new(99);
+        let mut fresh = CrossAttentionScorer::new(&mut tape2, &mut rng2, small_config());
+        apply_checkpoint(&loaded, &mut fresh, &mut tape2).unwrap();
+        assert_eq!(fresh.feature_stats(), Some(&stats));
+        assert_eq!(fresh.token_stats(), Some(&token_stats));
+        assert_eq!(tape2.params()[0].data, tape.params()[0].data);
+    }
+
+    #[test]
+    fn apply_rejects_mismatched_feature_schema() {
+        let schema = |disabled: &[&str]| {
+            let disabled: Vec<String> = disabled.iter().map(|s| s.to_string()).collect();
+            let schema = FeatureSchema::without(&disabled).unwrap();
+            ScorerConfig {
+                extra_features: schema.dim(),
+                feature_schema: Some(schema),
+                ..small_config()
+            }
+        };
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(5);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, schema(&["importance"]));
+        let path = std::env::temp_dir().join("predictor_test_ckpt_schema.bin");
+        save(&path, &model, &tape, 0, None, None, None).unwrap();
+        let loaded = load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        // Same width, different features
+        let mut tape2 = Tape::new();
+        let mut other = CrossAttentionScorer::new(&mut tape2, &mut rng, schema(&["recency"]));
+        let err = apply_checkpoint(&loaded, &mut other, &mut tape2).unwrap_err();
+        assert!(format!("{err:?}").contains("feature schema mismatch"));
+
+        let mut tape3 = Tape::new();
+        let mut same = CrossAttentionScorer::new(&mut tape3, &mut rng, schema(&["importance"]));
+        apply_checkpoint(&loaded, &mut same, &mut tape3).unwrap();
+    }
+
+    #[test]
+    fn roundtrip_restores_optimizer_state() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(3);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, small_config());
+        let indices = model.param_indices();
+        let state = AdamState {
+            step: 42,
+            m: indices
+                .iter()
+                .map(|&i| vec![0.25; tape.params()[i].data.len()])
+                .collect(),
+            v: indices
+                .iter()
+                .map(|&i| vec![0.5; tape.params()[i].data.len()])
+                .collect(),
+        };
+        let path = std::env::temp_dir().join("predictor_test_ckpt_adam.bin");
+        save(&path, &model, &tape, 0, None, Some(&state), None).unwrap();
+        let loaded = load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded.optimizer.as_ref(), Some(&state));
+
+        let mut adam = Adam::new(&tape, 1e-3);
+        adam.restore_state(loaded.optimizer.as_ref().unwrap(), &indices)
+            .unwrap();
+        assert_eq!(adam.state(&indices), state);
+    }
+
+    #[test]
+    fn reduced_precision_storage_roundtrips() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(17);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, small_config());
+        let original = tape.params()[model.param_indices()[0]].data.clone();
+        let full = std::env::temp_dir().join("predictor_test_ckpt_f64.bin");
+        save(&full, &model, &tape, 0, None, None, None).unwrap();
+        let full_size = std::fs::metadata(&full).unwrap().len();
+        let _ = std::fs::remove_file(&full);
+
+        for (flags, name) in [(FLAG_F32_PARAMS, "f32"), (FLAG_F16_PARAMS, "f16")] {
+            let path = std::env::temp_dir().join(format!("predictor_test_ckpt_{name}.bin"));
+            save(&path, &model, &tape, flags, None, None, None).unwrap();
+            let size = std::fs::metadata(&path).unwrap().len();
+            let loaded = load(&path).unwrap();
+            let _ = std::fs::remove_file(&path);
+            assert_eq!(loaded.flags, flags);
+            assert!(size < full_size);
+            for (restored, value) in loaded.params[0].iter().zip(&original) {
+                if flags == FLAG_F32_PARAMS {
+                    assert_eq!(*restored, *value as f32 as f64);
+                } else {
+                    assert!((restored - value).abs() <= value.abs() * 1e-3 + 1e-4);
+                }
+            }
+        }
+
+        let path = std::env::temp_dir().join("predictor_test_ckpt_bad_flags.bin");
+        let err = save(
+            &path,
+            &model,
+            &tape,
+            FLAG_F32_PARAMS | FLAG_F16_PARAMS,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert!(format!("{err:?}").contains("exclusive"));
+    }
+
+    #[test]
+    fn inspect_reports_shapes_and_metadata() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(29);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, small_config());
+        let metadata = CheckpointMetadata {
+            model_version: 7,
+            train_steps: 120,
+            training_pairs: 40,
+            last_trained: Some("2026-01-01T00:00:00Z".to_string()),
+            hyperparameters: None,
+            encoder_frozen: false,
+        };
+        let path = std::env::temp_dir().join("predictor_test_ckpt_inspect.bin");
+        save(
+            &path,
+            &model,
+            &tape,
+            FLAG_F16_PARAMS,
+            None,
+            None,
+            Some(&metadata),
+        )
+        .unwrap();
+        let summary = inspect(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(summary.file_version, VERSION);
+        assert_eq!(summary.storage, "f16");
+        assert_eq!(summary.metadata, Some(metadata));
+        assert_eq!(summary.optimizer_step, None);
+        assert_eq!(summary.params.len(), model.param_indices().len());
+        let down = &summary.params[0];
+        assert_eq!(down.name, "down_proj");
+        assert_eq!((down.rows, down.cols), (4, 4));
+        assert!(summary.params.iter().all(|p| p.rows * p.cols == p.stored));
+        assert_eq!(
+            summary.total_params,
+            summary.params.iter().map(|p| p.stored).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn compressed_payload_roundtrips() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(37);
+        let config = ScorerConfig {
+            hash_buckets: 512,
+            ..small_config()
+        };
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, config);
+        // A fresh hash table that has barely moved compresses well
+        let hash = model.param_indices()[5];
+        tape.params_mut()[hash].data.fill(0.0);
+        let plain = std::env::temp_dir().join("predictor_test_ckpt_plain.bin");
+        let packed = std::env::temp_dir().join("predictor_test_ckpt_zstd.bin");
+        save(&plain, &model, &tape, FLAG_F32_PARAMS, None, None, None).unwrap();
+        save(
+            &packed,
+            &model,
+            &tape,
+            FLAG_F32_PARAMS | FLAG_ZSTD_PAYLOAD,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let plain_size = std::fs::metadata(&plain).unwrap().len();
+        let packed_size = std::fs::metadata(&packed).unwrap().len();
+        let expected = load(&plain).unwrap();
+        let loaded = load(&packed).unwrap();
+        let _ = std::fs::remove_file(&plain);
+        let _ = std::fs::remove_file(&packed);
+
+        assert!(packed_size < plain_size / 2);
+        assert_eq!(loaded.flags, FLAG_F32_PARAMS | FLAG_ZSTD_PAYLOAD);
+        assert_eq!(loaded.params, expected.params);
+    }
+
+    #[test]
+    fn diff_reports_only_the_parameters_that_moved() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(41);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, small_config());
+        let before = std::env::temp_dir().join("predictor_test_ckpt_diff_a.bin");
+        let after = std::env::temp_dir().join("predictor_test_ckpt_diff_b.bin");
+        save(&before, &model, &tape, 0, None, None, None).unwrap();
+        let q = model.param_indices()[1];
+        tape.params_mut()[q].data[0] += 3.0;
+        tape.params_mut()[q].data[1] -= 4.0;
+        save(&after, &model, &tape, 0, None, None, None).unwrap();
+        let report = diff(&before, &after).unwrap();
+        let _ = std::fs::remove_file(&before);
+        let _ = std::fs::remove_file(&after);
+
+        let moved: Vec<&str> = report
+            .params
+            .iter()
+            .filter(|p| p.delta_l2 > 0.0)
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(moved, ["q_proj"]);
+        assert!((report.params[1].delta_l2 - 5.0).abs() < 1e-9);
+        assert!((report.total_delta_l2 - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rebuild_keeps_a_non_default_config() {
+        let config = ScorerConfig {
+            project_slots: 5,
+            adapter_dims: vec![6],
+            ..small_config()
+        };
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(47);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, config.clone());
+        let path = std::env::temp_dir().join("predictor_test_ckpt_rebuild.bin");
+        save(&path, &model, &tape, 0, None, None, None).unwrap();
+        let loaded = load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let mut default_tape = Tape::new();
+        let mut default_model =
+            CrossAttentionScorer::new(&mut default_tape, &mut rng, small_config());
+        assert!(apply_checkpoint(&loaded, &mut default_model, &mut default_tape).is_err());
+
+        let (rebuilt_tape, rebuilt) = rebuild_from_checkpoint(&loaded, &mut rng).unwrap();
+        assert_eq!(rebuilt.config(), &config);
+        for (a, b) in model.param_indices().iter().zip(rebuilt.param_indices()) {
+            assert_eq!(tape.params()[*a].data, rebuilt_tape.params()[b].data);
+        }
+    }
+
+    #[test]
+    fn apply_rejects_a_different_tokenizer() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(53);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, small_config());
+        let path = std::env::temp_dir().join("predictor_test_ckpt_tokenizer.bin");
+        save(&path, &model, &tape, 0, None, None, None).unwrap();
+        let loaded = load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let current = ScorerConfig {
+            tokenizer: Some(TokenizerDescriptor::default()),
+            ..small_config()
+        };
+        let mut tape2 = Tape::new();
+        let mut other = CrossAttentionScorer::new(&mut tape2, &mut rng, current);
+        let err = apply_checkpoint(&loaded, &mut other, &mut tape2).unwrap_err();
+        assert!(format!("{err:?}").contains("tokenizer mismatch"));
+
+        // Rebuilding adopts the checkpoint's tokenizer instead
+        let (_, rebuilt) = rebuild_from_checkpoint(&loaded, &mut rng).unwrap();
+        assert_eq!(rebuilt.config().tokenizer, None);
+    }
+
+    #[test]
+    fn refuses_files_from_a_newer_version() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(23);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, small_config());
+        let path = std::env::temp_dir().join("predictor_test_ckpt_future.bin");
+        save(&path, &model, &tape, 0, None, None, None).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[4..8].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        std::fs::write(&path, bytes).unwrap();
+        let err = load(&path).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(
+            err,
+            CheckpointError::UnsupportedVersion { found, supported }
+                if found == VERSION + 1 && supported == VERSION
+        ));
+    }
+
+    #[test]
+    fn migrate_upgrades_legacy_checkpoints() {
+        let legacy = LoadedCheckpoint {
+            version: 1,
+            flags: FLAG_F32_PARAMS,
+            config: ScorerConfig {
+                extra_features: V1_FEATURE_DIM,
+                ..small_config()
+            },
+            params: Vec::new(),
+            feature_stats: None,
+            cursor: None,
+            optimizer: None,
+            metadata: None,
+            token_stats: None,
+        };
+        let migrated = migrate(legacy).unwrap();
+        assert_eq!(migrated.version, 1);
+        assert_eq!(migrated.flags, 0);
+        let schema = migrated.config.feature_schema.unwrap();
+        assert_eq!(schema.version, 1);
+        assert_eq!(schema.dim(), V1_FEATURE_DIM);
+        assert!(schema.validate().is_ok());
+    }
+
+    #[test]
+    fn save_replaces_the_checkpoint_through_a_temp_file() {
+        let dir = std::env::temp_dir().join("predictor_test_ckpt_replace");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("model.sgpt");
+        let tmp = dir.join("model.sgpt.tmp");
+
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(53);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, small_config());
+        save(&path, &model, &tape, 0, None, None, None).unwrap();
+
+        // Leftovers of a save that was killed halfway
+        std::fs::write(&tmp, b"SGPT").unwrap();
+        save(&path, &model, &tape, FLAG_ZSTD_PAYLOAD, None, None, None).unwrap();
+        assert!(!tmp.exists());
+        assert_eq!(load(&path).unwrap().flags, FLAG_ZSTD_PAYLOAD);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn snapshots_rotate_but_keep_the_last_known_good() {
+        let dir = std::env::temp_dir().join("predictor_test_ckpt_rotation");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("model.sgpt");
+
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(31);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, small_config());
+        save(&path, &model, &tape, 0, None, None, None).unwrap();
+
+        let good = snapshot(&path, 2, true).unwrap();
+        for _ in 0..3 {
+            snapshot(&path, 2, false).unwrap();
+        }
+        let listed = list_snapshots(&path).unwrap();
+        assert_eq!(listed.len(), 3);
+        assert!(listed[0].created_ms > listed[1].created_ms);
+        assert_eq!(
+            listed.iter().filter(|s| s.known_good).count(),
+            1,
+            "good snapshot survives rotation"
+        );
+        assert_eq!(Path::new(&listed[2].path), good);
+
+        restore_snapshot(&path, &good).unwrap();
+        assert!(restore_snapshot(&path, &dir.join("other.sgpt")).is_err());
+        assert!(!dir.join("model.sgpt.tmp").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn autosave_is_due_on_either_bound() {
+        let policy = AutosavePolicy {
+            every_steps: Some(10),
+            every: Some(Duration::from_secs(60)),
+        };
+        assert!(!policy.due(0, Duration::from_secs(600)), "nothing to save");
+        assert!(!policy.due(9, Duration::from_secs(59)));
+        assert!(policy.due(10, Duration::ZERO));
+        assert!(policy.due(1, Duration::from_secs(60)));
+        assert!(!AutosavePolicy::default().due(100, Duration::from_secs(600)));
+    }
+}
@@ -1,15 +1,109 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::f64::consts::PI;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
+use rayon::prelude::*;
 use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
 
+use crate::autograd::Rng;
+use crate::embedding::{decode_embedding, EmbeddingFormat};
+use crate::features::FeatureSchema;
+use crate::model::{FeatureStats, FeatureStatsBuilder};
+use crate::sanitize::{sanitize_sample, SanitizeReport, DEFAULT_MAX_CORRUPT_FRACTION};
 use crate::tokenizer::fnv1a_hash;
+use crate::training::SampleSource;
+
+mod daemon;
+
+pub use daemon::DaemonSource;
 
 /// Configuration for data loading and label construction
+#[derive(Clone)]
 pub struct DataConfig {
     pub min_scorer_confidence: f64,
     pub loss_temperature: f64,
     pub native_dim: usize,
+    /// Only load sessions strictly after this cursor (incremental training)
+    pub since: Option<TrainingCursor>,
+    /// Extra label-0 candidates drawn per session from memories that were
+    /// never surfaced for it (0 disables mining)
+    pub hard_negatives_per_session: usize,
+    /// How long SQLite waits on a locked database before returning BUSY
+    pub busy_timeout_ms: u64,
+    /// Whole-load retries after BUSY/LOCKED, with doubling backoff
+    pub lock_retries: u32,
+    /// Features emitted per candidate; must match the model's schema
+    pub feature_schema: FeatureSchema,
+    /// How explicit feedback combines with the heuristic label
+    pub feedback_mode: FeedbackMode,
+    /// Collapse candidates sharing a `content_hash` to a single instance
+    pub dedup_content_hash: bool,
+    /// Only train on sessions from these harnesses (empty = all)
+    pub harness_filter: Vec<String>,
+    /// Restrict sessions and candidate memories to a set of projects
+    pub project_filter: ProjectFilter,
+    /// Halve a session's training weight every this many days of age,
+    /// measured back from the newest session in the load (None disables
+    /// decay)
+    pub label_half_life_days: Option<f64>,
+    /// Read-only connections loading sessions in parallel (1 = sequential)
+    pub load_workers: usize,
+    /// Hashed project slots; must match the model's `project_slots`
+    pub project_slots: usize,
+    /// How the chunks of a multi-chunk memory combine into one vector
+    pub chunk_aggregation: ChunkAggregation,
+    /// Samples with a larger share of NaN/Inf inputs are dropped; below it
+    /// the bad values are zeroed
+    pub max_corrupt_fraction: f64,
+}
+
+/// Project isolation. With an `include` list only those projects' sessions
+/// and memories are used, and unscoped memories are dropped too, so no
+/// cross-project signal reaches the model. `exclude` always wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl ProjectFilter {
+    pub fn allows(&self, project: Option<&str>) -> bool {
+        match project {
+            Some(project) => {
+                (self.include.is_empty() || self.include.iter().any(|p| p == project))
+                    && !self.exclude.iter().any(|p| p == project)
+            }
+            None => self.include.is_empty(),
+        }
+    }
+}
+
+/// Precedence of explicit feedback (agent relevance scores, memories the
+/// user deleted during the session) over the heuristic `compute_label`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackMode {
+    /// Explicit feedback replaces the heuristic label
+    #[default]
+    Override,
+    /// Average of the explicit and heuristic labels
+    Blend,
+    /// Heuristic labels only
+    Ignore,
+}
+
+/// Combination of a chunked memory's embedding rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkAggregation {
+    /// Mean of all chunk vectors
+    #[default]
+    Mean,
+    /// The chunk most similar to the session query
+    MaxSim,
 }
 
 impl Default for DataConfig {
@@ -18,12 +112,49 @@ impl Default for DataConfig {
             min_scorer_confidence: 0.6,
             loss_temperature: 0.5,
             native_dim: 768,
+            since: None,
+            hard_negatives_per_session: 0,
+            busy_timeout_ms: 5_000,
+            lock_retries: 3,
+            feature_schema: FeatureSchema::default(),
+            feedback_mode: FeedbackMode::default(),
+            dedup_content_hash: true,
+            harness_filter: Vec::new(),
+            project_filter: ProjectFilter::default(),
+            label_half_life_days: None,
+            load_workers: default_load_workers(),
+            project_slots: 32,
+            chunk_aggregation: ChunkAggregation::default(),
+            max_corrupt_fraction: DEFAULT_MAX_CORRUPT_FRACTION,
         }
     }
 }
 
+/// Upper bound on default loader parallelism; SQLite readers scale poorly
+/// past a handful of connections.
+const MAX_DEFAULT_LOAD_WORKERS: usize = 4;
+
+/// Most loader connections a request may ask for. Each one is a reader on
+/// the daemon's live database.
+pub const MAX_LOAD_WORKERS: usize = 8;
+
+fn default_load_workers() -> usize {
+    std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_DEFAULT_LOAD_WORKERS)
+}
+
+/// Position of the newest session already trained on. Sessions are ordered
+/// by `(created_at, session_key)` so ties on timestamp stay deterministic.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TrainingCursor {
+    pub created_at: String,
+    pub session_key: String,
+}
+
 /// Raw row from session_memories + memories + embeddings join
 #[allow(dead_code)]
+#[derive(Clone)]
 struct CandidateRow {
     memory_id: String,
     effective_score: f64,
@@ -38,16 +169,33 @@ struct CandidateRow {
     mem_project: Option<String>,
     pinned: bool,
     mem_content: String,
+    /// Raw `memories.tags`: a JSON array or comma-separated list
+    mem_tags: Option<String>,
+    /// Explicit feedback in [-1, 1], if any was recorded for this session
+    feedback: Option<f64>,
+    content_hash: Option<String>,
     embedding_blob: Option<Vec<u8>>,
     embedding_dims: Option<i64>,
+    embedding_format: Option<EmbeddingFormat>,
+    /// Chunks after the first, for memories embedded in several pieces
+    extra_chunks: Vec<EmbeddingChunk>,
     entity_slot: Option<i64>,
     aspect_slot: Option<i64>,
     is_constraint: bool,
     structural_density: Option<i64>,
 }
 
+/// One further embedding row of a chunked memory
+#[derive(Clone)]
+struct EmbeddingChunk {
+    blob: Vec<u8>,
+    dims: Option<i64>,
+    format: Option<EmbeddingFormat>,
+}
+
 /// Raw row from session_scores
 #[allow(dead_code)]
+#[derive(Deserialize)]
 struct SessionRow {
     session_key: String,
     project: Option<String>,
@@ -66,12 +214,45 @@ pub struct TrainingSample {
     pub candidate_features: Vec<Vec<f64>>,
     pub project_slot: usize,
     pub labels: Vec<f64>,
+    /// Learning-rate multiplier; below 1.0 for sessions decayed by age
+    pub weight: f64,
+}
+
+impl TrainingSample {
+    /// Rough heap footprint, used to bound streaming chunks.
+    pub fn approx_bytes(&self) -> usize {
+        let f64s = self.query_embedding.len()
+            + self
+                .candidate_embeddings
+                .iter()
+                .map(Vec::len)
+                .sum::<usize>()
+            + self.candidate_features.iter().map(Vec::len).sum::<usize>()
+            + self.labels.len();
+        let texts: usize = self
+            .candidate_texts
+            .iter()
+            .map(|t| t.as_ref().map_or(0, String::len))
+            .sum();
+        f64s * std::mem::size_of::<f64>() + texts + self.session_id.len()
+    }
+
+    /// Candidate texts that are present.
+    pub fn texts(&self) -> impl Iterator<Item = &str> {
+        self.candidate_texts.iter().flatten().map(String::as_str)
+    }
 }
 
 #[derive(Debug)]
 pub enum DataError {
     Sql(rusqlite::Error),
     NoData(String),
+    /// The loader thread pool could not be started
+    Worker(String),
+    /// The database lacks columns the loader cannot do without
+    Schema(String),
+    /// The daemon HTTP API failed or answered with something unusable
+    Remote(String),
 }
 
 impl From<rusqlite::Error> for DataError {
@@ -84,21 +265,128 @@ impl From<rusqlite::Error> for DataError {
 pub struct LoadResult {
     pub samples: Vec<TrainingSample>,
     pub sessions_skipped: usize,
+    /// Per-feature mean/std over every loaded candidate row
+    pub feature_stats: Option<FeatureStats>,
+    /// Newest qualifying session seen by this load
+    pub cursor: Option<TrainingCursor>,
+    /// Mined hard negatives added across all samples
+    pub hard_negatives: usize,
+    /// Decoded candidate vectors per embedding width
+    pub embedding_dims: BTreeMap<usize, usize>,
+    /// Samples whose query is the stored session prompt embedding rather
+    /// than the mean of injected candidates
+    pub session_embeddings: usize,
+    /// Candidate labels taken (or blended) from explicit feedback
+    pub feedback_labels: usize,
+    /// Candidates dropped as `content_hash` duplicates
+    pub duplicates_removed: usize,
+    /// Candidates dropped because their memory's project is filtered out
+    pub project_filtered: usize,
+    /// Columns this database lacks and the fallbacks read in their place
+    pub schema_warnings: Vec<SchemaWarning>,
+    pub project_slots: ProjectSlotStats,
+    /// Non-finite inputs zeroed, and samples or vectors dropped as corrupt
+    pub sanitized: SanitizeReport,
+    /// Journal mode of the source database (e.g. "wal", "delete")
+    pub journal_mode: String,
+    /// Load attempts that hit lock contention before succeeding
+    pub lock_retries: u32,
+    /// Time lost to failed attempts and backoff
+    pub lock_wait_ms: u64,
 }
 
 // ---------------------------------------------------------------------------
 // Embedding blob parsing
 // ---------------------------------------------------------------------------
 
-fn parse_embedding_blob(blob: &[u8], expected_dims: usize) -> Option<Vec<f64>> {
-    if blob.len() != expected_dims * 4 {
-        return None;
+/// Decode one chunk at its stored width. The `dimensions` column wins over
+/// `fallback_dims` so vectors from an earlier embedding provider survive a
+/// model switch instead of failing the size check.
+fn decode_chunk(
+    blob: &[u8],
+    dims: Option<i64>,
+    format: Option<EmbeddingFormat>,
+    fallback_dims: usize,
+) -> Option<Vec<f64>> {
+    let dims = dims
+        .filter(|d| *d > 0)
+        .map(|d| d as usize)
+        .unwrap_or(fallback_dims);
+    decode_embedding(blob, dims, format)
+}
+
+/// Every decodable chunk of a candidate at the width of the first one, so
+/// chunks left over from an earlier provider don't get mixed in.
+fn candidate_chunks(row: &CandidateRow, fallback_dims: usize) -> Vec<Vec<f64>> {
+    let first = row.embedding_blob.as_deref().and_then(|blob| {
+        decode_chunk(
+            blob,
+            row.embedding_dims,
+            row.embedding_format,
+            fallback_dims,
+        )
+    });
+    let mut chunks: Vec<Vec<f64>> =
+        first
+            .into_iter()
+            .chain(row.extra_chunks.iter().filter_map(|chunk| {
+                decode_chunk(&chunk.blob, chunk.dims, chunk.format, fallback_dims)
+            }))
+            .collect();
+    if let Some(width) = chunks.first().map(Vec::len) {
+        chunks.retain(|chunk| chunk.len() == width);
+    }
+    chunks
+}
+
+/// A candidate's vector: the mean over its chunks.
+fn parse_candidate_embedding(row: &CandidateRow, fallback_dims: usize) -> Option<Vec<f64>> {
+    let chunks = candidate_chunks(row, fallback_dims);
+    let first = chunks.first()?;
+    let n = chunks.len() as f64;
+    let mut mean = vec![0.0; first.len()];
+    for chunk in &chunks {
+        for (acc, val) in mean.iter_mut().zip(chunk) {
+            *acc += val / n;
+        }
+    }
+    Some(mean)
+}
+
+/// A candidate's vector under `mode`. Max-sim falls back to the mean when
+/// the query is a different width from the chunks.
+fn aggregate_candidate_embedding(
+    row: &CandidateRow,
+    fallback_dims: usize,
+    query: &[f64],
+    mode: ChunkAggregation,
+) -> Option<Vec<f64>> {
+    if mode == ChunkAggregation::Mean || row.extra_chunks.is_empty() {
+        return parse_candidate_embedding(row, fallback_dims);
+    }
+    let chunks = candidate_chunks(row, fallback_dims);
+    if chunks
+        .first()
+        .is_some_and(|chunk| chunk.len() != query.len())
+    {
+        return parse_candidate_embedding(row, fallback_dims);
+    }
+    chunks
+        .into_iter()
+        .map(|chunk| (cosine_similarity(&chunk, query), chunk))
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, chunk)| chunk)
+}
+
+fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f64>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
     }
-    Some(
-        blob.chunks_exact(4)
-            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f64)
-            .collect(),
-    )
 }
 
 // ---------------------------------------------------------------------------
@@ -198,10 +486,15 @@ fn parse_month(s: &str) -> f64 {
 }
 
 // ---------------------------------------------------------------------------
-// Feature vector construction (17 dimensions)
+// Feature vector construction (21 dimensions)
 // ---------------------------------------------------------------------------
 
-fn build_features(row: &CandidateRow, session: &SessionRow, session_gap_days: f64) -> Vec<f64> {
+fn build_features(
+    row: &CandidateRow,
+    session: &SessionRow,
+    session_gap_days: f64,
+    tag_overlap: f64,
+) -> Vec<f64> {
     let age_days = days_between(&row.mem_created_at, &session.created_at);
     let hour = parse_hour(&session.created_at);
     let dow = parse_day_of_week(&session.created_at);
@@ -225,6 +518,12 @@ fn build_features(row: &CandidateRow, session: &SessionRow, session_gap_days: f6
         0.0
     };
     let safe_session_gap_days = session_gap_days.max(0.0);
+    let pinned = if row.pinned { 1.0 } else { 0.0 };
+    let project_match = match (&row.mem_project, &session.project) {
+        (Some(mem), Some(sess)) if mem == sess => 1.0,
+        _ => 0.0,
+    };
+    let content_length = (row.mem_content.chars().count() as f64 + 1.0).ln();
 
     vec![
         (age_days + 1.0).ln(),                // [0] recency
@@ -244,14 +543,90 @@ fn build_features(row: &CandidateRow, session: &SessionRow, session_gap_days: f6
         is_constraint,                        // [14] constraint marker
         structural_density,                   // [15] log structural density
         is_ka_traversal,                      // [16] traversal source marker
+        pinned,                               // [17] pinned flag
+        project_match,                        // [18] memory project == session project
+        content_length,                       // [19] log content length
+        tag_overlap,                          // [20] tag overlap with the session
     ]
 }
 
+fn parse_tags(raw: Option<&str>) -> Vec<String> {
+    let Some(raw) = raw.map(str::trim).filter(|r| !r.is_empty()) else {
+        return Vec::new();
+    };
+    let tags: Vec<String> = if raw.starts_with('[') {
+        serde_json::from_str(raw).unwrap_or_default()
+    } else {
+        raw.split(',').map(str::to_string).collect()
+    };
+    let mut out: Vec<String> = tags
+        .into_iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Per candidate, the fraction of its tags that also appear on some other
+/// candidate in the session. Uses only the candidate pool, not which ones
+/// were injected, so it cannot leak the label.
+fn tag_overlaps(candidates: &[CandidateRow]) -> Vec<f64> {
+    let tags: Vec<Vec<String>> = candidates
+        .iter()
+        .map(|c| parse_tags(c.mem_tags.as_deref()))
+        .collect();
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for tag in tags.iter().flatten() {
+        *counts.entry(tag.as_str()).or_insert(0) += 1;
+    }
+    tags.iter()
+        .map(|own| {
+            if own.is_empty() {
+                return 0.0;
+            }
+            let shared = own.iter().filter(|tag| counts[tag.as_str()] > 1).count();
+            shared as f64 / own.len() as f64
+        })
+        .collect()
+}
+
 // ---------------------------------------------------------------------------
 // Label construction
 // ---------------------------------------------------------------------------
 
+/// `source` marker for candidates mined from the global memory pool.
+const HARD_NEGATIVE_SOURCE: &str = "hard_negative";
+
+/// Map explicit feedback in [-1, 1] onto the heuristic label range. Negative
+/// feedback is halved so it stays on the scale of the deleted penalty.
+fn feedback_label(feedback: f64) -> f64 {
+    let feedback = feedback.clamp(-1.0, 1.0);
+    if feedback < 0.0 {
+        feedback * 0.5
+    } else {
+        feedback
+    }
+}
+
+/// The training label for a candidate, and whether explicit feedback
+/// contributed to it.
+fn resolve_label(row: &CandidateRow, session: &SessionRow, mode: FeedbackMode) -> (f64, bool) {
+    let heuristic = compute_label(row, session);
+    match (mode, row.feedback) {
+        (FeedbackMode::Override, Some(feedback)) => (feedback_label(feedback), true),
+        (FeedbackMode::Blend, Some(feedback)) => {
+            (0.5 * (heuristic + feedback_label(feedback)), true)
+        }
+        _ => (heuristic, false),
+    }
+}
+
 fn compute_label(row: &CandidateRow, session: &SessionRow) -> f64 {
+    if row.source == HARD_NEGATIVE_SOURCE {
+        return 0.0;
+    }
     if row.is_deleted {
         return -0.3;
     }
@@ -301,11 +676,8 @@ fn compute_query_embedding(candidates: &[CandidateRow], native_dim: usize) -> Ve
     let injected: Vec<Vec<f64>> = candidates
         .iter()
         .filter(|c| c.was_injected)
-        .filter_map(|c| {
-            c.embedding_blob
-                .as_ref()
-                .and_then(|blob| parse_embedding_blob(blob, native_dim))
-        })
+        .filter_map(|c| parse_candidate_embedding(c, native_dim))
+        .filter(|emb| emb.len() == native_dim)
         .collect();
     if injected.is_empty() {
         return vec![0.0; native_dim];
@@ -326,277 +698,1573 @@ fn compute_query_embedding(candidates: &[CandidateRow], native_dim: usize) -> Ve
 
 fn project_to_slot(project: Option<&str>, num_slots: usize) -> usize {
     match project {
-        Some(p) if !p.is_empty() => (fnv1a_hash(p.as_bytes()) as usize) % num_slots,
+        Some(p) if !p.is_empty() => (fnv1a_hash(p.as_bytes()) as usize) % num_slots.max(1),
         _ => 0,
     }
 }
 
+/// How the loaded sessions' projects spread over the hashed slots. Projects
+/// sharing a slot also share its learned prior.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectSlotStats {
+    pub slots: usize,
+    /// Distinct named projects across the loaded sessions
+    pub projects: usize,
+    pub slots_used: usize,
+    /// Projects whose slot is shared with at least one other project
+    pub colliding_projects: usize,
+}
+
+fn project_slot_stats(sessions: &[SessionRow], num_slots: usize) -> ProjectSlotStats {
+    let projects: BTreeSet<&str> = sessions
+        .iter()
+        .filter_map(|s| s.project.as_deref())
+        .filter(|p| !p.is_empty())
+        .collect();
+    let mut per_slot: BTreeMap<usize, usize> = BTreeMap::new();
+    for project in &projects {
+        *per_slot
+            .entry(project_to_slot(Some(project), num_slots))
+            .or_insert(0) += 1;
+    }
+    ProjectSlotStats {
+        slots: num_slots,
+        projects: projects.len(),
+        slots_used: per_slot.len(),
+        colliding_projects: per_slot.values().filter(|n| **n > 1).sum(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Candidate rows
+// ---------------------------------------------------------------------------
+
+/// Map a row selected in the canonical candidate column order.
+fn read_candidate_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CandidateRow> {
+    Ok(CandidateRow {
+        memory_id: row.get(0)?,
+        effective_score: row.get::<_, Option<f64>>(1)?.unwrap_or(0.0),
+        was_injected: row.get::<_, i64>(2)? != 0,
+        relevance_score: row.get(3)?,
+        fts_hit_count: row.get(4)?,
+        source: row.get(5)?,
+        importance: row.get::<_, Option<f64>>(6)?.unwrap_or(0.5),
+        mem_created_at: row.get(7)?,
+        access_count: row.get::<_, Option<i64>>(8)?.unwrap_or(0),
+        is_deleted: row.get::<_, i64>(9)? != 0,
+        mem_project: row.get(10)?,
+        pinned: row.get::<_, i64>(11)? != 0,
+        mem_content: row.get(12)?,
+        embedding_blob: row.get(13)?,
+        embedding_dims: row.get(14)?,
+        embedding_format: row
+            .get::<_, Option<String>>(19)?
+            .as_deref()
+            .and_then(EmbeddingFormat::from_tag),
+        extra_chunks: Vec::new(),
+        mem_tags: row.get(20)?,
+        feedback: row.get(21)?,
+        content_hash: row.get(22)?,
+        entity_slot: row.get(15)?,
+        aspect_slot: row.get(16)?,
+        is_constraint: row.get::<_, Option<i64>>(17)?.unwrap_or(0) != 0,
+        structural_density: row.get(18)?,
+    })
+}
+
+/// Append a candidate row, folding it into the previous one when it is
+/// another embedding chunk of the same memory. Queries order chunk rows of a
+/// memory next to each other.
+fn push_candidate_row(rows: &mut Vec<CandidateRow>, row: CandidateRow) {
+    if let Some(last) = rows.last_mut() {
+        if last.memory_id == row.memory_id {
+            if let Some(blob) = row.embedding_blob {
+                last.extra_chunks.push(EmbeddingChunk {
+                    blob,
+                    dims: row.embedding_dims,
+                    format: row.embedding_format,
+                });
+            }
+            return;
+        }
+    }
+    rows.push(row);
+}
+
+/// Plausible-but-unrecalled memories for a session: live, existing at
+/// session time, in the same project, never surfaced for this session. The
+/// pool is the most important such memories; a per-session shuffle picks
+/// `count` of them so the same few memories don't dominate every sample.
+fn mine_hard_negatives(
+    stmt: &mut rusqlite::Statement<'_>,
+    session: &SessionRow,
+    count: usize,
+) -> Result<Vec<CandidateRow>, DataError> {
+    let pool_size = hard_negative_pool_size(count);
+    let mut rows = stmt.query(rusqlite::params![
+        &session.session_key,
+        &session.project,
+        &session.created_at,
+        pool_size as i64
+    ])?;
+    let mut pool = Vec::new();
+    while let Some(row) = rows.next()? {
+        push_candidate_row(&mut pool, read_candidate_row(row)?);
+    }
+    Ok(pick_hard_negatives(pool, session, count))
+}
+
+/// Hard-negative pool size requested for `count` picks
+fn hard_negative_pool_size(count: usize) -> usize {
+    count.saturating_mul(4)
+}
+
+/// Shuffle a hard-negative pool deterministically per session and keep
+/// `count` of it.
+fn pick_hard_negatives(
+    mut pool: Vec<CandidateRow>,
+    session: &SessionRow,
+    count: usize,
+) -> Vec<CandidateRow> {
+    let mut rng = Rng::new(fnv1a_hash(session.session_key.as_bytes()) | 1);
+    for i in (1..pool.len()).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        pool.swap(i, j);
+    }
+    pool.truncate(count);
+    pool
+}
+
+/// Column names of `table`; empty when the table does not exist.
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>, DataError> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(columns)
+}
+
 // ---------------------------------------------------------------------------
 // Main loader
 // ---------------------------------------------------------------------------
 
+impl LoadResult {
+    /// Telemetry without the samples themselves
+    pub fn summary(&self) -> LoadSummary {
+        LoadSummary {
+            sessions_skipped: self.sessions_skipped,
+            cursor: self.cursor.clone(),
+            hard_negatives: self.hard_negatives,
+            embedding_dims: self.embedding_dims.clone(),
+            session_embeddings: self.session_embeddings,
+            feedback_labels: self.feedback_labels,
+            duplicates_removed: self.duplicates_removed,
+            project_filtered: self.project_filtered,
+            schema_warnings: self.schema_warnings.clone(),
+            project_slots: self.project_slots.clone(),
+            sanitized: self.sanitized,
+            journal_mode: self.journal_mode.clone(),
+            lock_retries: self.lock_retries,
+            lock_wait_ms: self.lock_wait_ms,
+        }
+    }
+}
+
+/// Load telemetry shared by the in-memory and streaming loaders
+#[derive(Debug, Clone, Default)]
+pub struct LoadSummary {
+    pub sessions_skipped: usize,
+    pub cursor: Option<TrainingCursor>,
+    pub hard_negatives: usize,
+    pub embedding_dims: BTreeMap<usize, usize>,
+    pub session_embeddings: usize,
+    pub feedback_labels: usize,
+    pub duplicates_removed: usize,
+    pub project_filtered: usize,
+    pub schema_warnings: Vec<SchemaWarning>,
+    pub project_slots: ProjectSlotStats,
+    pub sanitized: SanitizeReport,
+    pub journal_mode: String,
+    pub lock_retries: u32,
+    pub lock_wait_ms: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Connection handling
+// ---------------------------------------------------------------------------
+
+const LOCK_BACKOFF_BASE_MS: u64 = 50;
+
+fn is_lock_contention(err: &DataError) -> bool {
+    match err {
+        DataError::Sql(rusqlite::Error::SqliteFailure(e, _)) => matches!(
+            e.code,
+            rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
+        ),
+        _ => false,
+    }
+}
+
+/// Open the live daemon database read-only. The daemon keeps writing while
+/// we read, so SQLite is told to wait on locks instead of failing fast.
+fn open_read_only(db_path: &Path, config: &DataConfig) -> Result<Connection, DataError> {
+    let conn = Connection::open_with_flags(
+        db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.busy_timeout(Duration::from_millis(config.busy_timeout_ms))?;
+    Ok(conn)
+}
+
+fn journal_mode(conn: &Connection) -> Result<String, DataError> {
+    Ok(conn.query_row("PRAGMA journal_mode", [], |row| row.get::<_, String>(0))?)
+}
+
+/// Load training samples, retrying the whole read when the database stays
+/// locked past `busy_timeout_ms`. Each retry runs on a fresh connection so
+/// a WAL reader picks up a new snapshot.
 pub fn load_training_samples(
     db_path: &Path,
     limit: usize,
     config: &DataConfig,
 ) -> Result<LoadResult, DataError> {
-    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
-
-    // Count sessions excluded by confidence gate (for telemetry)
-    let sessions_skipped: usize = {
-        let mut count_stmt = conn.prepare(
-            "SELECT COUNT(*)
-             FROM session_scores ss
-             WHERE ss.confidence IS NOT NULL
-               AND ss.score IS NOT NULL
-               AND ss.confidence < ?1",
-        )?;
-        count_stmt.query_row(rusqlite::params![config.min_scorer_confidence], |row| {
-            row.get::<_, i64>(0)
-        })? as usize
-    };
-
-    // Query 1: scored sessions — confidence filter in SQL so LIMIT
-    // applies to qualifying rows, not all rows
-    let mut stmt = conn.prepare(
-        "SELECT ss.session_key, ss.project, ss.score, ss.confidence,
-                ss.novel_context_count, ss.created_at
-         FROM session_scores ss
-         WHERE ss.confidence IS NOT NULL
-           AND ss.score IS NOT NULL
-           AND ss.confidence >= ?1
-         ORDER BY ss.created_at DESC
-         LIMIT ?2",
-    )?;
-
-    let qualifying: Vec<SessionRow> = {
-        let mut rows = stmt.query(rusqlite::params![
-            config.min_scorer_confidence,
-            limit as i64
-        ])?;
-        let mut out = Vec::new();
-        while let Some(row) = rows.next()? {
-            out.push(SessionRow {
-                session_key: row.get(0)?,
-                project: row.get(1)?,
-                score: row.get(2)?,
-                confidence: row.get(3)?,
-                novel_context_count: row.get(4)?,
-                created_at: row.get(5)?,
-            });
-        }
-        out
-    };
+    let (mut result, lock_retries, lock_wait_ms) = with_lock_retries(config, || {
+        open_read_only(db_path, config)
+            .and_then(|conn| load_from_connection(conn, db_path, limit, config))
+    })?;
+    result.lock_retries = lock_retries;
+    result.lock_wait_ms = lock_wait_ms;
+    Ok(result)
+}
 
-    // Query 2 & 3 prep
-    let mut candidates_stmt = conn.prepare(
-        "SELECT sm.memory_id, sm.effective_score, sm.was_injected,
-                sm.relevance_score, sm.fts_hit_count, sm.source,
-                m.importance, m.created_at AS mem_created_at,
-                m.access_count, m.is_deleted, m.project AS mem_project,
-                m.pinned, m.content AS mem_content,
-                e.vector AS embedding_blob, e.dimensions AS embedding_dims,
-                sm.entity_slot, sm.aspect_slot, sm.is_constraint,
-                sm.structural_density
-         FROM session_memories sm
-         JOIN memories m ON sm.memory_id = m.id
-         LEFT JOIN embeddings e
-           ON e.source_id = m.id AND e.source_type = 'memory'
-         WHERE sm.session_key = ?1
-         ORDER BY sm.rank ASC",
-    )?;
+/// Load training samples through the daemon's HTTP API instead of its
+/// database file. Sessions are fetched sequentially; `load_workers` only
+/// applies to SQLite.
+pub fn load_daemon_samples(
+    daemon: &DaemonSource,
+    limit: usize,
+    config: &DataConfig,
+) -> Result<LoadResult, DataError> {
+    let mut client = daemon.client()?;
+    let listing = client.sessions(limit, config)?;
+    let cursor = newest_cursor(&listing.sessions);
+    let (samples, telemetry) =
+        load_sessions(&mut client, &listing.sessions, config, cursor.as_ref())?;
+    Ok(LoadResult::assemble(
+        samples,
+        telemetry,
+        &listing.sessions,
+        listing.sessions_skipped,
+        cursor,
+        listing.schema_warnings,
+        listing.journal_mode,
+        config,
+    ))
+}
 
-    let mut gap_stmt = conn.prepare(
-        "SELECT MAX(ss2.created_at) AS prev_created_at
-         FROM session_scores ss2
-         WHERE ss2.project = ?1
-           AND ss2.created_at < ?2",
-    )?;
+/// Where a load reads sessions from
+#[derive(Debug, Clone, Copy)]
+pub enum DataSource<'a> {
+    /// The daemon database file, opened read-only
+    Sqlite(&'a Path),
+    /// The daemon's HTTP API
+    Daemon(&'a DaemonSource),
+}
 
-    let mut samples = Vec::new();
+impl DataSource<'_> {
+    pub fn load(&self, limit: usize, config: &DataConfig) -> Result<LoadResult, DataError> {
+        match *self {
+            Self::Sqlite(db_path) => load_training_samples(db_path, limit, config),
+            Self::Daemon(daemon) => load_daemon_samples(daemon, limit, config),
+        }
+    }
 
-    for session in &qualifying {
-        // Fetch candidates
-        let candidates: Vec<CandidateRow> = {
-            let mut rows = candidates_stmt.query(rusqlite::params![&session.session_key])?;
-            let mut out = Vec::new();
-            while let Some(row) = rows.next()? {
-                out.push(CandidateRow {
-                    memory_id: row.get(0)?,
-                    effective_score: row.get::<_, Option<f64>>(1)?.unwrap_or(0.0),
-                    was_injected: row.get::<_, i64>(2)? != 0,
-                    relevance_score: row.get(3)?,
-                    fts_hit_count: row.get(4)?,
-                    source: row.get(5)?,
-                    importance: row.get::<_, Option<f64>>(6)?.unwrap_or(0.5),
-                    mem_created_at: row.get(7)?,
-                    access_count: row.get::<_, Option<i64>>(8)?.unwrap_or(0),
-                    is_deleted: row.get::<_, i64>(9)? != 0,
-                    mem_project: row.get(10)?,
-                    pinned: row.get::<_, i64>(11)? != 0,
-                    mem_content: row.get(12)?,
-                    embedding_blob: row.get(13)?,
-                    embedding_dims: row.get(14)?,
-                    entity_slot: row.get(15)?,
-                    aspect_slot: row.get(16)?,
-                    is_constraint: row.get::<_, Option<i64>>(17)?.unwrap_or(0) != 0,
-                    structural_density: row.get(18)?,
-                });
+    pub fn stream(
+        &self,
+        limit: usize,
+        config: &DataConfig,
+        max_chunk_bytes: usize,
+    ) -> Result<SampleStream, DataError> {
+        match *self {
+            Self::Sqlite(db_path) => SampleStream::open(db_path, limit, config, max_chunk_bytes),
+            Self::Daemon(daemon) => {
+                SampleStream::open_daemon(daemon, limit, config, max_chunk_bytes)
             }
-            out
-        };
-
-        if candidates.is_empty() {
-            continue;
         }
+    }
+}
 
-        // Session gap (query 3)
-        let session_gap_days = if let Some(ref proj) = session.project {
-            let prev: Option<String> = gap_stmt
-                .query_row(rusqlite::params![proj, &session.created_at], |row| {
-                    row.get(0)
-                })?;
-            match prev {
-                Some(prev_ts) => days_between(&prev_ts, &session.created_at),
-                None => 0.0,
+/// Run `attempt` until it succeeds, fails for a reason other than lock
+/// contention, or the retry budget runs out. Returns the retry count and the
+/// time spent on failed attempts alongside the result.
+fn with_lock_retries<T>(
+    config: &DataConfig,
+    mut attempt: impl FnMut() -> Result<T, DataError>,
+) -> Result<(T, u32, u64), DataError> {
+    let mut lock_retries = 0u32;
+    let mut lock_wait = Duration::ZERO;
+    loop {
+        let attempt_start = Instant::now();
+        match attempt() {
+            Ok(value) => return Ok((value, lock_retries, lock_wait.as_millis() as u64)),
+            Err(err) if is_lock_contention(&err) && lock_retries < config.lock_retries => {
+                let backoff = Duration::from_millis(LOCK_BACKOFF_BASE_MS << lock_retries);
+                std::thread::sleep(backoff);
+                lock_wait += attempt_start.elapsed();
+                lock_retries += 1;
             }
-        } else {
-            0.0
-        };
+            Err(err) => return Err(err),
+        }
+    }
+}
 
-        // Build features, labels, embeddings
-        let query_embedding = compute_query_embedding(&candidates, config.native_dim);
-        let mut candidate_embeddings = Vec::with_capacity(candidates.len());
-        let mut candidate_texts = Vec::with_capacity(candidates.len());
-        let mut candidate_features = Vec::with_capacity(candidates.len());
-        let mut labels = Vec::with_capacity(candidates.len());
-
-        for cand in &candidates {
-            // Always parse at native_dim so the model receives correctly-sized
-            // embeddings. If the DB stores a different dimension, the blob won't
-            // parse and we fall through to the text path.
-            let parsed = cand
-                .embedding_blob
-                .as_ref()
-                .and_then(|b| parse_embedding_blob(b, config.native_dim));
-            match parsed {
-                Some(emb) => {
-                    candidate_embeddings.push(emb);
-                    candidate_texts.push(None);
-                }
-                None => {
-                    candidate_embeddings.push(Vec::new());
-                    candidate_texts.push(Some(cand.mem_content.clone()));
-                }
-            }
-            candidate_features.push(build_features(cand, session, session_gap_days));
-            labels.push(compute_label(cand, session));
+fn load_from_connection(
+    conn: Connection,
+    db_path: &Path,
+    limit: usize,
+    config: &DataConfig,
+) -> Result<LoadResult, DataError> {
+    let journal_mode = journal_mode(&conn)?;
+    let schema = LoaderSchema::introspect(&conn)?;
+    let sessions_skipped = count_skipped_sessions(&conn, config, &schema)?;
+    let qualifying = query_sessions(&conn, limit, config, &schema)?;
+    let cursor = newest_cursor(&qualifying);
+
+    let workers = config
+        .load_workers
+        .min(MAX_LOAD_WORKERS)
+        .clamp(1, qualifying.len().max(1));
+    let (samples, telemetry) = if workers == 1 {
+        let mut rows = SqliteRows {
+            conn,
+            schema: schema.clone(),
+        };
+        load_sessions(&mut rows, &qualifying, config, cursor.as_ref())?
+    } else {
+        // Each worker reads a contiguous slice on its own connection, so
+        // concatenating the slices keeps the session order.
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(workers)
+            .build()
+            .map_err(|e| DataError::Worker(e.to_string()))?;
+        let slice_len = qualifying.len().div_ceil(workers);
+        let parts = pool.install(|| {
+            qualifying
+                .par_chunks(slice_len)
+                .map(|slice| {
+                    let mut rows = SqliteRows {
+                        conn: open_read_only(db_path, config)?,
+                        schema: schema.clone(),
+                    };
+                    load_sessions(&mut rows, slice, config, cursor.as_ref())
+                })
+                .collect::<Result<Vec<_>, DataError>>()
+        })?;
+        let mut samples = Vec::new();
+        let mut telemetry = LoadTelemetry::default();
+        for (part, part_telemetry) in parts {
+            samples.extend(part);
+            telemetry.merge(part_telemetry);
         }
+        (samples, telemetry)
+    };
 
-        let project_slot = project_to_slot(session.project.as_deref(), 32);
+    Ok(LoadResult::assemble(
+        samples,
+        telemetry,
+        &qualifying,
+        sessions_skipped,
+        cursor,
+        schema.warnings,
+        journal_mode,
+        config,
+    ))
+}
 
-        samples.push(TrainingSample {
-            session_id: session.session_key.clone(),
-            query_embedding,
-            candidate_embeddings,
-            candidate_texts,
-            candidate_features,
-            project_slot,
-            labels,
-        });
+impl LoadResult {
+    #[allow(clippy::too_many_arguments)]
+    fn assemble(
+        samples: Vec<TrainingSample>,
+        telemetry: LoadTelemetry,
+        sessions: &[SessionRow],
+        sessions_skipped: usize,
+        cursor: Option<TrainingCursor>,
+        schema_warnings: Vec<SchemaWarning>,
+        journal_mode: String,
+        config: &DataConfig,
+    ) -> Self {
+        let feature_stats = FeatureStats::from_rows(
+            samples
+                .iter()
+                .flat_map(|s| s.candidate_features.iter().map(Vec::as_slice)),
+            config.feature_schema.dim(),
+        );
+        Self {
+            samples,
+            sessions_skipped,
+            feature_stats,
+            cursor,
+            hard_negatives: telemetry.hard_negatives,
+            embedding_dims: telemetry.embedding_dims,
+            session_embeddings: telemetry.session_embeddings,
+            feedback_labels: telemetry.feedback_labels,
+            duplicates_removed: telemetry.duplicates_removed,
+            project_filtered: telemetry.project_filtered,
+            schema_warnings,
+            project_slots: project_slot_stats(sessions, config.project_slots),
+            sanitized: telemetry.sanitized,
+            journal_mode,
+            lock_retries: 0,
+            lock_wait_ms: 0,
+        }
     }
+}
 
-    Ok(LoadResult {
-        samples,
-        sessions_skipped,
-    })
+fn load_sessions(
+    rows: &mut dyn RowSource,
+    sessions: &[SessionRow],
+    config: &DataConfig,
+    newest: Option<&TrainingCursor>,
+) -> Result<(Vec<TrainingSample>, LoadTelemetry), DataError> {
+    let mut samples = Vec::new();
+    let mut telemetry = LoadTelemetry::default();
+    for chunk in sessions.chunks(SESSION_BATCH) {
+        let batch = rows.fetch_batch(chunk, config)?;
+        for session in chunk {
+            if let Some(mut sample) =
+                load_session_sample(rows, session, &batch, config, &mut telemetry)?
+            {
+                sample.weight = session_weight(session, newest, config);
+                samples.push(sample);
+            }
+        }
+    }
+    Ok((samples, telemetry))
 }
 
 // ---------------------------------------------------------------------------
-// Tests
+// Row sources
 // ---------------------------------------------------------------------------
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rusqlite::Connection;
+/// Where per-session rows come from once the qualifying sessions are known.
+/// Sample construction is shared; only the reads differ between the
+/// database file and the daemon's HTTP API.
+trait RowSource: Send {
+    /// Candidates, deletions, prompt embeddings and project gaps for a
+    /// batch of sessions
+    fn fetch_batch(
+        &mut self,
+        sessions: &[SessionRow],
+        config: &DataConfig,
+    ) -> Result<SessionBatch, DataError>;
+
+    /// The shuffled hard-negative pool for one session, `count` long at most
+    fn hard_negatives(
+        &mut self,
+        session: &SessionRow,
+        count: usize,
+    ) -> Result<Vec<CandidateRow>, DataError>;
+}
 
-    fn create_test_db() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
-        conn.execute_batch(
-            "
-            CREATE TABLE session_scores (
-                id TEXT PRIMARY KEY, session_key TEXT NOT NULL, project TEXT,
-                harness TEXT, score REAL NOT NULL, memories_recalled INTEGER,
-                memories_used INTEGER, novel_context_count INTEGER,
-                reasoning TEXT, created_at TEXT NOT NULL, confidence REAL,
-                continuity_reasoning TEXT
-            );
-            CREATE TABLE memories (
-                id TEXT PRIMARY KEY, type TEXT NOT NULL DEFAULT 'fact',
-                category TEXT, content TEXT NOT NULL, confidence REAL DEFAULT 1.0,
-                importance REAL DEFAULT 0.5, source_id TEXT, source_type TEXT,
-                tags TEXT, who TEXT, why TEXT, project TEXT,
-                created_at TEXT NOT NULL, updated_at TEXT NOT NULL,
-                updated_by TEXT NOT NULL DEFAULT 'system', last_accessed TEXT,
-                access_count INTEGER DEFAULT 0, vector_clock TEXT NOT NULL DEFAULT '{}',
-                version INTEGER DEFAULT 1, manual_override INTEGER DEFAULT 0,
-                pinned INTEGER DEFAULT 0, is_deleted INTEGER DEFAULT 0,
-                deleted_at TEXT, content_hash TEXT
-            );
-            CREATE TABLE session_memories (
-                id TEXT PRIMARY KEY, session_key TEXT NOT NULL,
-                memory_id TEXT NOT NULL, source TEXT NOT NULL,
-                effective_score REAL, predictor_score REAL,
-                final_score REAL NOT NULL, rank INTEGER NOT NULL,
-                was_injected INTEGER NOT NULL, relevance_score REAL,
-                fts_hit_count INTEGER NOT NULL DEFAULT 0,
-                agent_preference TEXT, created_at TEXT NOT NULL,
-                entity_slot INTEGER, aspect_slot INTEGER,
-                is_constraint INTEGER NOT NULL DEFAULT 0,
-                structural_density INTEGER,
-                UNIQUE(session_key, memory_id)
-            );
-            CREATE TABLE embeddings (
-                id TEXT PRIMARY KEY, content_hash TEXT NOT NULL UNIQUE,
-                vector BLOB NOT NULL, dimensions INTEGER NOT NULL,
-                source_type TEXT NOT NULL, source_id TEXT NOT NULL,
-                chunk_text TEXT NOT NULL, created_at TEXT NOT NULL
-            );
-        ",
-        )
-        .unwrap();
-        conn
+/// Rows read straight from the daemon database
+struct SqliteRows {
+    conn: Connection,
+    schema: LoaderSchema,
+}
+
+impl RowSource for SqliteRows {
+    fn fetch_batch(
+        &mut self,
+        sessions: &[SessionRow],
+        config: &DataConfig,
+    ) -> Result<SessionBatch, DataError> {
+        SessionBatch::fetch(&self.conn, sessions, config, &self.schema)
     }
 
-    fn make_f32_blob(values: &[f32]) -> Vec<u8> {
-        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    fn hard_negatives(
+        &mut self,
+        session: &SessionRow,
+        count: usize,
+    ) -> Result<Vec<CandidateRow>, DataError> {
+        let m = |column| self.schema.col("m", "memories", column);
+        let e = |column| self.schema.col("e", "embeddings", column);
+        let mut negatives_stmt = self.conn.prepare_cached(&format!(
+            "SELECT m.id, 0.0, 0, NULL, 0, 'hard_negative',
+                    {importance}, m.created_at, {access_count}, {is_deleted},
+                    {project}, {}, m.content,
+                    e.vector, {},
+                    NULL, NULL, 0, NULL, {}, {}, NULL, {}
+             FROM (
+               SELECT m.* FROM memories m
+               WHERE {is_deleted} = 0
+                 AND {project} IS ?2
+                 AND m.created_at <= ?3
+                 AND m.id NOT IN (
+                   SELECT sm.memory_id FROM session_memories sm
+                   WHERE sm.session_key = ?1
+                 )
+               ORDER BY {importance} DESC, {access_count} DESC
+               LIMIT ?4
+             ) m
+             LEFT JOIN embeddings e
+               ON e.source_id = m.id AND e.source_type = 'memory'
+             ORDER BY {importance} DESC, {access_count} DESC, m.id",
+            m("pinned"),
+            e("dimensions"),
+            e("vector_format"),
+            m("tags"),
+            m("content_hash"),
+            importance = m("importance"),
+            access_count = m("access_count"),
+            is_deleted = m("is_deleted"),
+            project = m("project"),
+        ))?;
+        mine_hard_negatives(&mut negatives_stmt, session, count)
     }
+}
 
-    #[test]
-    fn parse_embedding_blob_valid() {
-        let dims = 4;
-        let blob = make_f32_blob(&[1.0, 2.0, 3.0, 4.0]);
-        let result = parse_embedding_blob(&blob, dims).unwrap();
-        assert_eq!(result.len(), 4);
-        assert!((result[0] - 1.0).abs() < 1e-6);
-        assert!((result[3] - 4.0).abs() < 1e-6);
+// ---------------------------------------------------------------------------
+// Streaming loader
+// ---------------------------------------------------------------------------
+
+/// Chunked reader over the qualifying sessions. Only the session list is
+/// held in memory; candidates are loaded per chunk, with each chunk capped
+/// at roughly `max_chunk_bytes`. Epochs re-read from the source via
+/// [`SampleSource::rewind`].
+pub struct SampleStream {
+    rows: Box<dyn RowSource>,
+    config: DataConfig,
+    schema_warnings: Vec<SchemaWarning>,
+    sessions: Vec<SessionRow>,
+    /// Prefetched rows for `sessions[batch_start..batch_end]`
+    batch: SessionBatch,
+    batch_start: usize,
+    batch_end: usize,
+    /// First session of a training pass (after any held-out canaries)
+    start: usize,
+    next: usize,
+    /// Sessions before this index were already counted in `telemetry`
+    counted: usize,
+    max_chunk_bytes: usize,
+    telemetry: LoadTelemetry,
+    sessions_skipped: usize,
+    cursor: Option<TrainingCursor>,
+    project_slots: ProjectSlotStats,
+    journal_mode: String,
+    lock_retries: u32,
+    lock_wait_ms: u64,
+}
+
+impl SampleStream {
+    pub fn open(
+        db_path: &Path,
+        limit: usize,
+        config: &DataConfig,
+        max_chunk_bytes: usize,
+    ) -> Result<Self, DataError> {
+        let (mut stream, lock_retries, lock_wait_ms) = with_lock_retries(config, || {
+            let conn = open_read_only(db_path, config)?;
+            let journal_mode = journal_mode(&conn)?;
+            let schema = LoaderSchema::introspect(&conn)?;
+            let sessions_skipped = count_skipped_sessions(&conn, config, &schema)?;
+            let sessions = query_sessions(&conn, limit, config, &schema)?;
+            let schema_warnings = schema.warnings.clone();
+            Ok(Self::new(
+                Box::new(SqliteRows { conn, schema }),
+                sessions,
+                sessions_skipped,
+                schema_warnings,
+                journal_mode,
+                config,
+                max_chunk_bytes,
+            ))
+        })?;
+        stream.lock_retries = lock_retries;
+        stream.lock_wait_ms = lock_wait_ms;
+        Ok(stream)
     }
 
-    #[test]
-    fn parse_embedding_blob_wrong_size() {
-        let blob = make_f32_blob(&[1.0, 2.0]);
-        assert!(parse_embedding_blob(&blob, 3).is_none());
+    /// Stream sessions from the daemon's HTTP API. Each chunk is fetched on
+    /// demand, so every epoch asks the daemon again.
+    pub fn open_daemon(
+        daemon: &DaemonSource,
+        limit: usize,
+        config: &DataConfig,
+        max_chunk_bytes: usize,
+    ) -> Result<Self, DataError> {
+        let mut client = daemon.client()?;
+        let listing = client.sessions(limit, config)?;
+        Ok(Self::new(
+            Box::new(client),
+            listing.sessions,
+            listing.sessions_skipped,
+            listing.schema_warnings,
+            listing.journal_mode,
+            config,
+            max_chunk_bytes,
+        ))
     }
 
-    #[test]
+    fn new(
+        rows: Box<dyn RowSource>,
+        sessions: Vec<SessionRow>,
+        sessions_skipped: usize,
+        schema_warnings: Vec<SchemaWarning>,
+        journal_mode: String,
+        config: &DataConfig,
+        max_chunk_bytes: usize,
+    ) -> Self {
+        let cursor = newest_cursor(&sessions);
+        let project_slots = project_slot_stats(&sessions, config.project_slots);
+        Self {
+            rows,
+            config: config.clone(),
+            schema_warnings,
+            sessions,
+            batch: SessionBatch::default(),
+            batch_start: 0,
+            batch_end: 0,
+            start: 0,
+            next: 0,
+            counted: 0,
+            max_chunk_bytes: max_chunk_bytes.max(1),
+            telemetry: LoadTelemetry::default(),
+            sessions_skipped,
+            cursor,
+            project_slots,
+            journal_mode,
+            lock_retries: 0,
+            lock_wait_ms: 0,
+        }
+    }
+
+    fn load_next(&mut self) -> Result<Option<TrainingSample>, DataError> {
+        while self.next < self.sessions.len() {
+            let idx = self.next;
+            self.next += 1;
+            // Later passes re-read the same sessions; count them only once.
+            let mut scratch = LoadTelemetry::default();
+            let telemetry = if idx >= self.counted {
+                self.counted = idx + 1;
+                &mut self.telemetry
+            } else {
+                &mut scratch
+            };
+            if !(self.batch_start..self.batch_end).contains(&idx) {
+                let end = (idx + SESSION_BATCH).min(self.sessions.len());
+                self.batch = self
+                    .rows
+                    .fetch_batch(&self.sessions[idx..end], &self.config)?;
+                self.batch_start = idx;
+                self.batch_end = end;
+            }
+            let session = &self.sessions[idx];
+            let sample = load_session_sample(
+                self.rows.as_mut(),
+                session,
+                &self.batch,
+                &self.config,
+                telemetry,
+            )?;
+            if let Some(mut sample) = sample {
+                sample.weight = session_weight(session, self.cursor.as_ref(), &self.config);
+                return Ok(Some(sample));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Take the first `count` samples out of the stream for evaluation.
+    /// Subsequent passes start after them, unless the holdout consumed the
+    /// whole stream, in which case it is trained on as well.
+    pub fn take_holdout(&mut self, count: usize) -> Result<Vec<TrainingSample>, DataError> {
+        self.next = 0;
+        let mut out = Vec::with_capacity(count);
+        while out.len() < count {
+            match self.load_next()? {
+                Some(sample) => out.push(sample),
+                None => break,
+            }
+        }
+        self.start = if self.next >= self.sessions.len() {
+            0
+        } else {
+            self.next
+        };
+        self.next = self.start;
+        Ok(out)
+    }
+
+    /// One full pass over the samples without holding them, leaving the
+    /// read position where it was.
+    pub fn scan(&mut self, mut visit: impl FnMut(&TrainingSample)) -> Result<(), DataError> {
+        let resume = self.next;
+        self.next = self.start;
+        while let Some(sample) = self.load_next()? {
+            visit(&sample);
+        }
+        self.next = resume;
+        Ok(())
+    }
+
+    /// One full pass to gather feature statistics without holding the data.
+    pub fn feature_stats(&mut self) -> Result<Option<FeatureStats>, DataError> {
+        let mut builder = FeatureStatsBuilder::new(self.config.feature_schema.dim());
+        self.scan(|sample| {
+            for row in &sample.candidate_features {
+                builder.add(row);
+            }
+        })?;
+        Ok(builder.finish())
+    }
+
+    pub fn summary(&self) -> LoadSummary {
+        LoadSummary {
+            sessions_skipped: self.sessions_skipped,
+            cursor: self.cursor.clone(),
+            hard_negatives: self.telemetry.hard_negatives,
+            embedding_dims: self.telemetry.embedding_dims.clone(),
+            session_embeddings: self.telemetry.session_embeddings,
+            feedback_labels: self.telemetry.feedback_labels,
+            duplicates_removed: self.telemetry.duplicates_removed,
+            project_filtered: self.telemetry.project_filtered,
+            schema_warnings: self.schema_warnings.clone(),
+            project_slots: self.project_slots.clone(),
+            sanitized: self.telemetry.sanitized,
+            journal_mode: self.journal_mode.clone(),
+            lock_retries: self.lock_retries,
+            lock_wait_ms: self.lock_wait_ms,
+        }
+    }
+}
+
+impl SampleSource for SampleStream {
+    fn rewind(&mut self) {
+        self.next = self.start;
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Vec<TrainingSample>>, String> {
+        let mut chunk = Vec::new();
+        let mut bytes = 0usize;
+        while bytes < self.max_chunk_bytes {
+            match self.load_next().map_err(|e| format!("{e:?}"))? {
+                Some(sample) => {
+                    bytes += sample.approx_bytes();
+                    chunk.push(sample);
+                }
+                None => break,
+            }
+        }
+        Ok(if chunk.is_empty() { None } else { Some(chunk) })
+    }
+}
+
+/// Count sessions excluded by confidence gate (for telemetry)
+fn count_skipped_sessions(
+    conn: &Connection,
+    config: &DataConfig,
+    schema: &LoaderSchema,
+) -> Result<usize, DataError> {
+    let filters = SessionFilters::new(config, schema);
+    let confidence = schema.col("ss", "session_scores", "confidence");
+    let mut bound: Vec<&dyn rusqlite::ToSql> = vec![&config.min_scorer_confidence];
+    let filter_clauses = filters.clauses(&mut bound);
+    let mut count_stmt = conn.prepare(&format!(
+        "SELECT COUNT(*)
+         FROM session_scores ss
+         WHERE {confidence} IS NOT NULL
+           AND ss.score IS NOT NULL
+           AND {confidence} < ?1{filter_clauses}"
+    ))?;
+    Ok(
+        count_stmt.query_row(rusqlite::params_from_iter(bound), |row| {
+            row.get::<_, i64>(0)
+        })? as usize,
+    )
+}
+
+/// A filter list as a JSON array for `json_each`, or `None` when empty.
+fn json_list(values: &[String]) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        serde_json::to_string(values).ok()
+    }
+}
+
+/// `AND expr [NOT] IN (...)` with the list bound as the next positional
+/// parameter, or an empty clause without a list. Exclusion keeps NULLs.
+fn list_clause<'a>(
+    expr: &str,
+    list: &'a Option<String>,
+    exclude: bool,
+    bound: &mut Vec<&'a dyn rusqlite::ToSql>,
+) -> String {
+    let Some(json) = list else {
+        return String::new();
+    };
+    bound.push(json);
+    let idx = bound.len();
+    if exclude {
+        format!(
+            "\n           AND ({expr} IS NULL OR {expr} NOT IN (SELECT value FROM json_each(?{idx})))"
+        )
+    } else {
+        format!("\n           AND {expr} IN (SELECT value FROM json_each(?{idx}))")
+    }
+}
+
+/// Harness and project filters shared by the session queries.
+struct SessionFilters {
+    harness: String,
+    project: String,
+    harnesses: Option<String>,
+    include_projects: Option<String>,
+    exclude_projects: Option<String>,
+}
+
+impl SessionFilters {
+    fn new(config: &DataConfig, schema: &LoaderSchema) -> Self {
+        Self {
+            harness: schema.col("ss", "session_scores", "harness"),
+            project: schema.col("ss", "session_scores", "project"),
+            harnesses: json_list(&config.harness_filter),
+            include_projects: json_list(&config.project_filter.include),
+            exclude_projects: json_list(&config.project_filter.exclude),
+        }
+    }
+
+    fn clauses<'a>(&'a self, bound: &mut Vec<&'a dyn rusqlite::ToSql>) -> String {
+        let mut sql = list_clause(&self.harness, &self.harnesses, false, bound);
+        sql.push_str(&list_clause(
+            &self.project,
+            &self.include_projects,
+            false,
+            bound,
+        ));
+        sql.push_str(&list_clause(
+            &self.project,
+            &self.exclude_projects,
+            true,
+            bound,
+        ));
+        sql
+    }
+}
+
+/// Query 1: scored sessions — confidence filter in SQL so LIMIT applies to
+/// qualifying rows, not all rows. Incremental loads walk forward from the
+/// cursor oldest-first so a capped batch never leaves a gap behind it; full
+/// loads take the most recent sessions.
+fn query_sessions(
+    conn: &Connection,
+    limit: usize,
+    config: &DataConfig,
+    schema: &LoaderSchema,
+) -> Result<Vec<SessionRow>, DataError> {
+    let limit = limit as i64;
+    let filters = SessionFilters::new(config, schema);
+    let confidence = schema.col("ss", "session_scores", "confidence");
+    let mut bound: Vec<&dyn rusqlite::ToSql> = vec![&config.min_scorer_confidence, &limit];
+    let (cursor_clause, order) = match config.since {
+        Some(ref cursor) => {
+            bound.push(&cursor.created_at);
+            bound.push(&cursor.session_key);
+            (
+                "\n           AND (ss.created_at > ?3
+                OR (ss.created_at = ?3 AND ss.session_key > ?4))",
+                "ss.created_at ASC, ss.session_key ASC",
+            )
+        }
+        None => ("", "ss.created_at DESC"),
+    };
+    let filter_clauses = filters.clauses(&mut bound);
+    let mut stmt = conn.prepare(&format!(
+        "SELECT ss.session_key, {project}, ss.score, {confidence},
+                {novel_context_count}, ss.created_at
+         FROM session_scores ss
+         WHERE {confidence} IS NOT NULL
+           AND ss.score IS NOT NULL
+           AND {confidence} >= ?1{cursor_clause}{filter_clauses}
+         ORDER BY {order}
+         LIMIT ?2",
+        project = filters.project,
+        novel_context_count = schema.col("ss", "session_scores", "novel_context_count"),
+    ))?;
+    let mut rows = stmt.query(rusqlite::params_from_iter(bound))?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(SessionRow {
+            session_key: row.get(0)?,
+            project: row.get(1)?,
+            score: row.get(2)?,
+            confidence: row.get(3)?,
+            novel_context_count: row.get(4)?,
+            created_at: row.get(5)?,
+        });
+    }
+    Ok(out)
+}
+
+/// Exponential age decay relative to the newest session in the load, so a
+/// long break in usage does not discount everything at once.
+fn session_weight(
+    session: &SessionRow,
+    newest: Option<&TrainingCursor>,
+    config: &DataConfig,
+) -> f64 {
+    match (newest, config.label_half_life_days) {
+        (Some(newest), Some(half_life)) if half_life > 0.0 => {
+            let age_days = days_between(&session.created_at, &newest.created_at);
+            0.5_f64.powf(age_days / half_life)
+        }
+        _ => 1.0,
+    }
+}
+
+fn newest_cursor(sessions: &[SessionRow]) -> Option<TrainingCursor> {
+    sessions
+        .iter()
+        .map(|session| TrainingCursor {
+            created_at: session.created_at.clone(),
+            session_key: session.session_key.clone(),
+        })
+        .max()
+}
+
+/// Which candidates survive deduplication: for each `content_hash`, the
+/// highest-labelled copy (the best-ranked one on ties). Rows without a hash
+/// are always kept.
+fn dedup_by_content_hash(candidates: &[CandidateRow], labels: &[(f64, bool)]) -> Vec<bool> {
+    let mut best: BTreeMap<&str, usize> = BTreeMap::new();
+    for (idx, cand) in candidates.iter().enumerate() {
+        let Some(hash) = cand.content_hash.as_deref() else {
+            continue;
+        };
+        best.entry(hash)
+            .and_modify(|kept| {
+                if labels[idx].0 > labels[*kept].0 {
+                    *kept = idx;
+                }
+            })
+            .or_insert(idx);
+    }
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(idx, cand)| match cand.content_hash.as_deref() {
+            Some(hash) => best[hash] == idx,
+            None => true,
+        })
+        .collect()
+}
+
+/// A loader column missing from this database, and the value read in its
+/// place.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaWarning {
+    pub table: String,
+    pub column: String,
+    /// SQL expression substituted for the column
+    pub fallback: String,
+}
+
+/// Columns every query depends on. Without them there is nothing to train
+/// on, so a load fails up front with the full list.
+const REQUIRED_COLUMNS: &[(&str, &str)] = &[
+    ("session_scores", "session_key"),
+    ("session_scores", "score"),
+    ("session_scores", "created_at"),
+    ("session_memories", "session_key"),
+    ("session_memories", "memory_id"),
+    ("memories", "id"),
+    ("memories", "content"),
+    ("memories", "created_at"),
+    ("embeddings", "source_id"),
+    ("embeddings", "source_type"),
+    ("embeddings", "vector"),
+];
+
+/// Columns the loader reads but can do without, with the fallback used when
+/// a daemon schema (older or newer than ours) lacks them. Each substitution
+/// is reported as a [`SchemaWarning`].
+const DEGRADABLE_COLUMNS: &[(&str, &str, &str)] = &[
+    ("session_scores", "project", "NULL"),
+    ("session_scores", "harness", "NULL"),
+    // Without a confidence column every scored session passes the gate
+    ("session_scores", "confidence", "1.0"),
+    ("session_scores", "novel_context_count", "NULL"),
+    ("session_memories", "source", "'unknown'"),
+    ("session_memories", "effective_score", "NULL"),
+    ("session_memories", "was_injected", "0"),
+    ("session_memories", "relevance_score", "NULL"),
+    ("session_memories", "fts_hit_count", "0"),
+    ("session_memories", "rank", "NULL"),
+    ("session_memories", "entity_slot", "NULL"),
+    ("session_memories", "aspect_slot", "NULL"),
+    ("session_memories", "is_constraint", "0"),
+    ("session_memories", "structural_density", "NULL"),
+    ("memories", "importance", "NULL"),
+    ("memories", "access_count", "NULL"),
+    ("memories", "is_deleted", "0"),
+    ("memories", "project", "NULL"),
+    ("memories", "pinned", "0"),
+    ("memories", "tags", "NULL"),
+    ("embeddings", "dimensions", "NULL"),
+    ("embeddings", "created_at", "NULL"),
+];
+
+/// Columns added by later daemon migrations. Their absence is expected on
+/// older databases and is not worth a warning.
+const OPTIONAL_COLUMNS: &[(&str, &str)] = &[
+    // Quantized stores tag each vector's encoding; without the tag we rely
+    // on size-based inference.
+    ("embeddings", "vector_format"),
+    ("session_memories", "agent_feedback_count"),
+    ("memories", "content_hash"),
+    ("memory_history", "session_id"),
+];
+
+/// The daemon schema as seen by the loader, introspected once per
+/// connection. Queries select columns through [`LoaderSchema::col`] so a
+/// missing column reads as its fallback instead of failing the statement.
+#[derive(Clone)]
+struct LoaderSchema {
+    present: BTreeSet<(String, String)>,
+    warnings: Vec<SchemaWarning>,
+}
+
+impl LoaderSchema {
+    fn introspect(conn: &Connection) -> Result<Self, DataError> {
+        let mut present = BTreeSet::new();
+        for table in [
+            "session_scores",
+            "session_memories",
+            "memories",
+            "embeddings",
+            "memory_history",
+        ] {
+            for column in table_columns(conn, table)? {
+                present.insert((table.to_string(), column));
+            }
+        }
+        let has = |table: &str, column: &str| present.contains(&(table.into(), column.into()));
+
+        let missing: Vec<String> = REQUIRED_COLUMNS
+            .iter()
+            .filter(|(table, column)| !has(table, column))
+            .map(|(table, column)| format!("{table}.{column}"))
+            .collect();
+        if !missing.is_empty() {
+            return Err(DataError::Schema(format!(
+                "missing required columns: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let warnings = DEGRADABLE_COLUMNS
+            .iter()
+            .filter(|(table, column, _)| !has(table, column))
+            .map(|(table, column, fallback)| SchemaWarning {
+                table: table.to_string(),
+                column: column.to_string(),
+                fallback: fallback.to_string(),
+            })
+            .collect();
+        Ok(Self { present, warnings })
+    }
+
+    fn has(&self, table: &str, column: &str) -> bool {
+        self.present
+            .contains(&(table.to_string(), column.to_string()))
+    }
+
+    /// `alias.column`, or the column's fallback when this database lacks it.
+    fn col(&self, alias: &str, table: &str, column: &str) -> String {
+        if self.has(table, column) {
+            return format!("{alias}.{column}");
+        }
+        DEGRADABLE_COLUMNS
+            .iter()
+            .find(|(t, c, _)| *t == table && *c == column)
+            .map(|(_, _, fallback)| fallback.to_string())
+            .unwrap_or_else(|| {
+                debug_assert!(
+                    OPTIONAL_COLUMNS.contains(&(table, column)),
+                    "{table}.{column} has no fallback"
+                );
+                "NULL".to_string()
+            })
+    }
+
+    /// `session_memories.agent_relevance_score`, once any feedback was given
+    fn agent_feedback(&self) -> String {
+        if self.has("session_memories", "agent_feedback_count") {
+            "CASE WHEN sm.agent_feedback_count > 0 THEN sm.agent_relevance_score END".to_string()
+        } else {
+            "NULL".to_string()
+        }
+    }
+
+    /// `memory_history.session_id` exists, so deletions can be attributed to
+    /// the session they happened in
+    fn history_sessions(&self) -> bool {
+        self.has("memory_history", "session_id") && self.has("memory_history", "event")
+    }
+}
+
+/// Counters accumulated while building samples
+#[derive(Default)]
+struct LoadTelemetry {
+    hard_negatives: usize,
+    embedding_dims: BTreeMap<usize, usize>,
+    session_embeddings: usize,
+    feedback_labels: usize,
+    duplicates_removed: usize,
+    project_filtered: usize,
+    sanitized: SanitizeReport,
+}
+
+impl LoadTelemetry {
+    fn merge(&mut self, other: LoadTelemetry) {
+        self.hard_negatives += other.hard_negatives;
+        for (dim, count) in other.embedding_dims {
+            *self.embedding_dims.entry(dim).or_insert(0) += count;
+        }
+        self.session_embeddings += other.session_embeddings;
+        self.feedback_labels += other.feedback_labels;
+        self.duplicates_removed += other.duplicates_removed;
+        self.project_filtered += other.project_filtered;
+        self.sanitized.merge(other.sanitized);
+    }
+}
+
+/// Sessions fetched per round of batch queries. Bounds the `json_each`
+/// key list and the rows held ahead of sample construction.
+const SESSION_BATCH: usize = 256;
+
+/// Per-session rows for a batch of sessions, read with one set-based query
+/// each instead of one round trip per session.
+#[derive(Default)]
+struct SessionBatch {
+    /// Candidates per session key, in rank order
+    candidates: BTreeMap<String, Vec<CandidateRow>>,
+    /// Memories deleted during each session
+    deleted: BTreeMap<String, Vec<String>>,
+    /// Newest stored prompt embedding per session key
+    embeddings: BTreeMap<String, Vec<f64>>,
+    /// Previous session timestamp in the same project, keyed by
+    /// (session key, created_at)
+    previous: BTreeMap<(String, String), String>,
+}
+
+impl SessionBatch {
+    fn fetch(
+        conn: &Connection,
+        sessions: &[SessionRow],
+        config: &DataConfig,
+        schema: &LoaderSchema,
+    ) -> Result<Self, DataError> {
+        let mut batch = Self::default();
+        if sessions.is_empty() {
+            return Ok(batch);
+        }
+        let keys: Vec<String> = sessions.iter().map(|s| s.session_key.clone()).collect();
+        let keys = serde_json::to_string(&keys).unwrap_or_else(|_| "[]".to_string());
+
+        let sm = |column| schema.col("sm", "session_memories", column);
+        let m = |column| schema.col("m", "memories", column);
+        let e = |column| schema.col("e", "embeddings", column);
+        let mut candidates_stmt = conn.prepare_cached(&format!(
+            "SELECT sm.memory_id, {}, {},
+                    {}, {}, {},
+                    {}, m.created_at AS mem_created_at,
+                    {}, {}, {} AS mem_project,
+                    {}, m.content AS mem_content,
+                    e.vector AS embedding_blob, {} AS embedding_dims,
+                    {}, {}, {},
+                    {}, {}, {}, {}, {}, sm.session_key
+             FROM session_memories sm
+             JOIN memories m ON sm.memory_id = m.id
+             LEFT JOIN embeddings e
+               ON e.source_id = m.id AND e.source_type = 'memory'
+             WHERE sm.session_key IN (SELECT value FROM json_each(?1))
+             ORDER BY sm.session_key, {} ASC, sm.memory_id",
+            sm("effective_score"),
+            sm("was_injected"),
+            sm("relevance_score"),
+            sm("fts_hit_count"),
+            sm("source"),
+            m("importance"),
+            m("access_count"),
+            m("is_deleted"),
+            m("project"),
+            m("pinned"),
+            e("dimensions"),
+            sm("entity_slot"),
+            sm("aspect_slot"),
+            sm("is_constraint"),
+            sm("structural_density"),
+            e("vector_format"),
+            m("tags"),
+            schema.agent_feedback(),
+            m("content_hash"),
+            sm("rank"),
+        ))?;
+        let mut rows = candidates_stmt.query(rusqlite::params![&keys])?;
+        while let Some(row) = rows.next()? {
+            let key: String = row.get(23)?;
+            push_candidate_row(
+                batch.candidates.entry(key).or_default(),
+                read_candidate_row(row)?,
+            );
+        }
+
+        if schema.history_sessions() && config.feedback_mode != FeedbackMode::Ignore {
+            let mut deleted_stmt = conn.prepare_cached(
+                "SELECT DISTINCT session_id, memory_id FROM memory_history
+                 WHERE session_id IN (SELECT value FROM json_each(?1))
+                   AND event = 'deleted'",
+            )?;
+            let mut rows = deleted_stmt.query(rusqlite::params![&keys])?;
+            while let Some(row) = rows.next()? {
+                batch
+                    .deleted
+                    .entry(row.get(0)?)
+                    .or_default()
+                    .push(row.get(1)?);
+            }
+        }
+
+        // The newest row wins when a session was re-embedded
+        let mut embedding_stmt = conn.prepare_cached(&format!(
+            "SELECT source_id, vector, dims, format FROM (
+               SELECT e.source_id, e.vector, {} AS dims, {} AS format,
+                      ROW_NUMBER() OVER (
+                        PARTITION BY e.source_id ORDER BY {} DESC
+                      ) AS newest
+               FROM embeddings e
+               WHERE e.source_type = 'session'
+                 AND e.source_id IN (SELECT value FROM json_each(?1))
+             )
+             WHERE newest = 1",
+            e("dimensions"),
+            e("vector_format"),
+            e("created_at"),
+        ))?;
+        let mut rows = embedding_stmt.query(rusqlite::params![&keys])?;
+        while let Some(row) = rows.next()? {
+            let key: String = row.get(0)?;
+            let blob: Vec<u8> = row.get(1)?;
+            let dims = row
+                .get::<_, Option<i64>>(2)?
+                .map(|d| d as usize)
+                .unwrap_or(config.native_dim);
+            let format = row
+                .get::<_, Option<String>>(3)?
+                .as_deref()
+                .and_then(EmbeddingFormat::from_tag);
+            if let Some(embedding) =
+                decode_embedding(&blob, dims, format).filter(|emb| emb.len() == config.native_dim)
+            {
+                batch.embeddings.insert(key, embedding);
+            }
+        }
+
+        // Gap to the previous session in the same project. The window runs
+        // over distinct timestamps so sessions sharing one still look back
+        // to a strictly earlier session.
+        if schema.has("session_scores", "project") {
+            let mut gap_stmt = conn.prepare_cached(
+                "WITH stamps AS (
+                   SELECT project, created_at,
+                          LAG(created_at) OVER (
+                            PARTITION BY project ORDER BY created_at
+                          ) AS prev_created_at
+                   FROM (SELECT DISTINCT project, created_at
+                         FROM session_scores WHERE project IS NOT NULL)
+                 )
+                 SELECT ss.session_key, ss.created_at, st.prev_created_at
+                 FROM session_scores ss
+                 JOIN stamps st
+                   ON st.project = ss.project AND st.created_at = ss.created_at
+                 WHERE ss.session_key IN (SELECT value FROM json_each(?1))
+                   AND st.prev_created_at IS NOT NULL",
+            )?;
+            let mut rows = gap_stmt.query(rusqlite::params![&keys])?;
+            while let Some(row) = rows.next()? {
+                batch
+                    .previous
+                    .insert((row.get(0)?, row.get(1)?), row.get(2)?);
+            }
+        }
+        Ok(batch)
+    }
+}
+
+/// Build one session's sample from its prefetched batch rows. Only the
+/// hard-negative pool is still read per session.
+fn load_session_sample(
+    rows: &mut dyn RowSource,
+    session: &SessionRow,
+    batch: &SessionBatch,
+    config: &DataConfig,
+    telemetry: &mut LoadTelemetry,
+) -> Result<Option<TrainingSample>, DataError> {
+    let mut candidates = batch
+        .candidates
+        .get(&session.session_key)
+        .cloned()
+        .unwrap_or_default();
+
+    let before = candidates.len();
+    candidates.retain(|cand| config.project_filter.allows(cand.mem_project.as_deref()));
+    telemetry.project_filtered += before - candidates.len();
+
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+
+    // A memory the user deleted during the session is the strongest
+    // negative there is, whatever the agent said about it.
+    if let Some(deleted) = batch.deleted.get(&session.session_key) {
+        for cand in &mut candidates {
+            if deleted.contains(&cand.memory_id) {
+                cand.feedback = Some(-1.0);
+            }
+        }
+    }
+
+    if config.hard_negatives_per_session > 0 {
+        let mined = rows.hard_negatives(session, config.hard_negatives_per_session)?;
+        telemetry.hard_negatives += mined.len();
+        candidates.extend(mined);
+    }
+
+    let session_gap_days = batch
+        .previous
+        .get(&(session.session_key.clone(), session.created_at.clone()))
+        .map(|prev| days_between(prev, &session.created_at))
+        .unwrap_or(0.0);
+
+    // Build features, labels, embeddings
+    let resolved: Vec<(f64, bool)> = candidates
+        .iter()
+        .map(|cand| resolve_label(cand, session, config.feedback_mode))
+        .collect();
+    let (candidates, resolved): (Vec<CandidateRow>, Vec<(f64, bool)>) = if config.dedup_content_hash
+    {
+        let keep = dedup_by_content_hash(&candidates, &resolved);
+        telemetry.duplicates_removed += keep.iter().filter(|kept| !**kept).count();
+        candidates
+            .into_iter()
+            .zip(resolved)
+            .zip(keep)
+            .filter_map(|(pair, kept)| kept.then_some(pair))
+            .unzip()
+    } else {
+        (candidates, resolved)
+    };
+
+    // Prefer the session's own prompt embedding. The injected mean is only a
+    // fallback: it sits close to the positives by construction.
+    let query_embedding = match batch.embeddings.get(&session.session_key) {
+        Some(embedding) => {
+            telemetry.session_embeddings += 1;
+            embedding.clone()
+        }
+        None => compute_query_embedding(&candidates, config.native_dim),
+    };
+    let mut candidate_embeddings = Vec::with_capacity(candidates.len());
+    let mut candidate_texts = Vec::with_capacity(candidates.len());
+    let mut candidate_features = Vec::with_capacity(candidates.len());
+    let mut labels = Vec::with_capacity(candidates.len());
+
+    let overlaps = tag_overlaps(&candidates);
+
+    for ((cand, tag_overlap), (label, explicit)) in candidates.iter().zip(overlaps).zip(resolved) {
+        // Vectors are parsed at their stored width. Non-native widths keep
+        // the text alongside so a model without a matching adapter can
+        // still fall through to the text path.
+        let parsed = aggregate_candidate_embedding(
+            cand,
+            config.native_dim,
+            &query_embedding,
+            config.chunk_aggregation,
+        );
+        match parsed {
+            Some(emb) => {
+                *telemetry.embedding_dims.entry(emb.len()).or_insert(0) += 1;
+                candidate_texts.push(if emb.len() == config.native_dim {
+                    None
+                } else {
+                    Some(cand.mem_content.clone())
+                });
+                candidate_embeddings.push(emb);
+            }
+            None => {
+                candidate_embeddings.push(Vec::new());
+                candidate_texts.push(Some(cand.mem_content.clone()));
+            }
+        }
+        let features = build_features(cand, session, session_gap_days, tag_overlap);
+        candidate_features.push(config.feature_schema.project(&features));
+        if explicit {
+            telemetry.feedback_labels += 1;
+        }
+        labels.push(label);
+    }
+
+    let project_slot = project_to_slot(session.project.as_deref(), config.project_slots);
+
+    let mut sample = TrainingSample {
+        session_id: session.session_key.clone(),
+        query_embedding,
+        candidate_embeddings,
+        candidate_texts,
+        candidate_features,
+        project_slot,
+        labels,
+        weight: 1.0,
+    };
+    if !sanitize_sample(
+        &mut sample,
+        config.max_corrupt_fraction,
+        &mut telemetry.sanitized,
+    ) {
+        return Ok(None);
+    }
+    Ok(Some(sample))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::FEATURE_DIM;
+    use rusqlite::Connection;
+
+    fn create_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "
+            CREATE TABLE session_scores (
+                id TEXT PRIMARY KEY, session_key TEXT NOT NULL, project TEXT,
+                harness TEXT, score REAL NOT NULL, memories_recalled INTEGER,
+                memories_used INTEGER, novel_context_count INTEGER,
+                reasoning TEXT, created_at TEXT NOT NULL, confidence REAL,
+                continuity_reasoning TEXT
+            );
+            CREATE TABLE memories (
+                id TEXT PRIMARY KEY, type TEXT NOT NULL DEFAULT 'fact',
+                category TEXT, content TEXT NOT NULL, confidence REAL DEFAULT 1.0,
+                importance REAL DEFAULT 0.5, source_id TEXT, source_type TEXT,
+                tags TEXT, who TEXT, why TEXT, project TEXT,
+                created_at TEXT NOT NULL, updated_at TEXT NOT NULL,
+                updated_by TEXT NOT NULL DEFAULT 'system', last_accessed TEXT,
+                access_count INTEGER DEFAULT 0, vector_clock TEXT NOT NULL DEFAULT '{}',
+                version INTEGER DEFAULT 1, manual_override INTEGER DEFAULT 0,
+                pinned INTEGER DEFAULT 0, is_deleted INTEGER DEFAULT 0,
+                deleted_at TEXT, content_hash TEXT
+            );
+            CREATE TABLE session_memories (
+                id TEXT PRIMARY KEY, session_key TEXT NOT NULL,
+                memory_id TEXT NOT NULL, source TEXT NOT NULL,
+                effective_score REAL, predictor_score REAL,
+                final_score REAL NOT NULL, rank INTEGER NOT NULL,
+                was_injected INTEGER NOT NULL, relevance_score REAL,
+                fts_hit_count INTEGER NOT NULL DEFAULT 0,
+                agent_preference TEXT, created_at TEXT NOT NULL,
+                entity_slot INTEGER, aspect_slot INTEGER,
+                is_constraint INTEGER NOT NULL DEFAULT 0,
+                structural_density INTEGER,
+                UNIQUE(session_key, memory_id)
+            );
+            CREATE TABLE embeddings (
+                id TEXT PRIMARY KEY, content_hash TEXT NOT NULL UNIQUE,
+                vector BLOB NOT NULL, dimensions INTEGER NOT NULL,
+                source_type TEXT NOT NULL, source_id TEXT NOT NULL,
+                chunk_text TEXT NOT NULL, created_at TEXT NOT NULL
+            );
+        ",
+        )
+        .unwrap();
+        conn
+    }
+
+    /// Copy of an in-memory test database on disk, under a name unique to
+    /// this process and test so concurrent tests never share a file.
+    /// Removed on drop.
+    struct TempDb(std::path::PathBuf);
+
+    impl TempDb {
+        fn copy_of(conn: &Connection) -> Self {
+            use std::sync::atomic::{AtomicUsize, Ordering};
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let name = format!(
+                "predictor_test_{}_{}.db",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            );
+            let db = TempDb(std::env::temp_dir().join(name));
+            let _ = std::fs::remove_file(db.path());
+            conn.execute(&format!("VACUUM INTO '{}'", db.path().display()), [])
+                .unwrap();
+            db
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// Run the file-based loader against a copy of `conn`.
+    fn load_from_conn(
+        conn: &Connection,
+        limit: usize,
+        config: &DataConfig,
+    ) -> Result<LoadResult, DataError> {
+        let db = TempDb::copy_of(conn);
+        load_training_samples(db.path(), limit, config)
+    }
+
+    fn make_f32_blob(values: &[f32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn parse_embedding_blob_valid() {
+        let dims = 4;
+        let blob = make_f32_blob(&[1.0, 2.0, 3.0, 4.0]);
+        let result = decode_embedding(&blob, dims, None).unwrap();
+        assert_eq!(result.len(), 4);
+        assert!((result[0] - 1.0).abs() < 1e-6);
+        assert!((result[3] - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn parse_embedding_blob_wrong_size() {
+        let blob = make_f32_blob(&[1.0, 2.0]);
+        assert!(decode_embedding(&blob, 3, None).is_none());
+    }
+
+    #[test]
     fn parse_embedding_blob_empty() {
-        assert!(parse_embedding_blob(&[], 1).is_none());
-        assert!(parse_embedding_blob(&[], 0).is_some()); // 0 dims, 0 bytes = valid
+        assert!(decode_embedding(&[], 1, None).is_none());
+        assert!(decode_embedding(&[], 0, None).is_some()); // 0 dims, 0 bytes = valid
     }
 
     #[test]
-    fn build_features_produces_17_dims() {
+    fn build_features_produces_21_dims() {
         let row = CandidateRow {
             memory_id: "m1".into(),
             effective_score: 0.8,
@@ -613,6 +2281,11 @@ mod tests {
             mem_content: "test content".into(),
             embedding_blob: None,
             embedding_dims: None,
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            mem_tags: None,
+            feedback: None,
+            content_hash: None,
             entity_slot: Some(64),
             aspect_slot: Some(32),
             is_constraint: false,
@@ -626,8 +2299,8 @@ mod tests {
             novel_context_count: Some(3),
             created_at: "2026-02-20T14:30:00Z".into(),
         };
-        let features = build_features(&row, &session, 24.0);
-        assert_eq!(features.len(), 17);
+        let features = build_features(&row, &session, 24.0, 0.5);
+        assert_eq!(features.len(), 21);
         // [0] = ln(age_days + 1) > 0
         assert!(features[0] > 0.0);
         // [1] = importance = 0.6
@@ -649,10 +2322,59 @@ mod tests {
         assert!((features[15] - (6.0_f64).ln()).abs() < 1e-9);
         // [16] = 0 (not a traversal candidate)
         assert!((features[16] - 0.0).abs() < 1e-9);
+        // [17] = 0 (not pinned)
+        assert!((features[17] - 0.0).abs() < 1e-9);
+        // [18] = 1 (memory and session share "proj")
+        assert!((features[18] - 1.0).abs() < 1e-9);
+        // [19] = ln(len("test content") + 1)
+        assert!((features[19] - (13.0_f64).ln()).abs() < 1e-9);
+        // [20] = tag overlap passed through
+        assert!((features[20] - 0.5).abs() < 1e-9);
     }
 
     #[test]
-    fn build_features_clamps_negative_session_gap() {
+    fn tag_overlap_counts_tags_shared_with_other_candidates() {
+        assert_eq!(
+            parse_tags(Some(r#"["Rust", "cli", "rust"]"#)),
+            vec!["cli".to_string(), "rust".to_string()]
+        );
+        assert_eq!(parse_tags(Some("a, b,,c")).len(), 3);
+        assert!(parse_tags(None).is_empty());
+
+        let mut rows = Vec::new();
+        for tags in [Some("rust,cli"), Some(r#"["rust"]"#), None] {
+            rows.push(CandidateRow {
+                memory_id: "m".into(),
+                effective_score: 0.0,
+                was_injected: false,
+                relevance_score: None,
+                fts_hit_count: 0,
+                source: "recall".into(),
+                importance: 0.5,
+                mem_created_at: "2026-01-01T00:00:00Z".into(),
+                access_count: 0,
+                is_deleted: false,
+                mem_project: None,
+                pinned: false,
+                mem_content: String::new(),
+                mem_tags: tags.map(str::to_string),
+                feedback: None,
+                content_hash: None,
+                embedding_blob: None,
+                embedding_dims: None,
+                embedding_format: None,
+                extra_chunks: Vec::new(),
+                entity_slot: None,
+                aspect_slot: None,
+                is_constraint: false,
+                structural_density: None,
+            });
+        }
+        assert_eq!(tag_overlaps(&rows), vec![0.5, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn build_features_clamps_negative_session_gap() {
         let row = CandidateRow {
             memory_id: "m1".into(),
             effective_score: 0.8,
@@ -669,6 +2391,11 @@ mod tests {
             mem_content: "test content".into(),
             embedding_blob: None,
             embedding_dims: None,
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            mem_tags: None,
+            feedback: None,
+            content_hash: None,
             entity_slot: Some(64),
             aspect_slot: Some(32),
             is_constraint: false,
@@ -683,7 +2410,7 @@ mod tests {
             created_at: "2026-02-20T14:30:00Z".into(),
         };
 
-        let features = build_features(&row, &session, -4.0);
+        let features = build_features(&row, &session, -4.0, 0.0);
         assert!((features[9] - 0.0).abs() < 1e-9);
         assert!((features[16] - 1.0).abs() < 1e-9);
     }
@@ -706,6 +2433,11 @@ mod tests {
             mem_content: "deleted".into(),
             embedding_blob: None,
             embedding_dims: None,
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            mem_tags: None,
+            feedback: None,
+            content_hash: None,
             entity_slot: None,
             aspect_slot: None,
             is_constraint: false,
@@ -740,6 +2472,11 @@ mod tests {
             mem_content: "important".into(),
             embedding_blob: None,
             embedding_dims: None,
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            mem_tags: None,
+            feedback: None,
+            content_hash: None,
             entity_slot: None,
             aspect_slot: None,
             is_constraint: false,
@@ -776,6 +2513,11 @@ mod tests {
             mem_content: "test".into(),
             embedding_blob: None,
             embedding_dims: None,
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            mem_tags: None,
+            feedback: None,
+            content_hash: None,
             entity_slot: None,
             aspect_slot: None,
             is_constraint: false,
@@ -812,6 +2554,11 @@ mod tests {
             mem_content: "test".into(),
             embedding_blob: None,
             embedding_dims: None,
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            mem_tags: None,
+            feedback: None,
+            content_hash: None,
             entity_slot: None,
             aspect_slot: None,
             is_constraint: false,
@@ -846,6 +2593,11 @@ mod tests {
             mem_content: "test".into(),
             embedding_blob: None,
             embedding_dims: None,
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            mem_tags: None,
+            feedback: None,
+            content_hash: None,
             entity_slot: None,
             aspect_slot: None,
             is_constraint: false,
@@ -876,6 +2628,34 @@ mod tests {
         assert_eq!(project_to_slot(Some(""), 32), 0);
     }
 
+    #[test]
+    fn project_slot_stats_count_shared_slots() {
+        let session = |project: Option<&str>| SessionRow {
+            session_key: "s".into(),
+            project: project.map(String::from),
+            score: 0.5,
+            confidence: Some(0.9),
+            novel_context_count: None,
+            created_at: "2026-02-10T00:00:00Z".into(),
+        };
+        let sessions = vec![
+            session(Some("a")),
+            session(Some("b")),
+            session(Some("c")),
+            session(Some("a")),
+            session(None),
+        ];
+        // One slot: every project collides
+        let stats = project_slot_stats(&sessions, 1);
+        assert_eq!(stats.projects, 3);
+        assert_eq!(stats.slots_used, 1);
+        assert_eq!(stats.colliding_projects, 3);
+
+        let stats = project_slot_stats(&sessions, 4096);
+        assert_eq!(stats.slots_used, 3);
+        assert_eq!(stats.colliding_projects, 0);
+    }
+
     #[test]
     fn compute_query_embedding_mean_of_two() {
         let dims = 3;
@@ -898,6 +2678,11 @@ mod tests {
                 mem_content: "a".into(),
                 embedding_blob: Some(blob1),
                 embedding_dims: Some(dims as i64),
+                embedding_format: None,
+                extra_chunks: Vec::new(),
+                mem_tags: None,
+                feedback: None,
+                content_hash: None,
                 entity_slot: None,
                 aspect_slot: None,
                 is_constraint: false,
@@ -919,6 +2704,11 @@ mod tests {
                 mem_content: "b".into(),
                 embedding_blob: Some(blob2),
                 embedding_dims: Some(dims as i64),
+                embedding_format: None,
+                extra_chunks: Vec::new(),
+                mem_tags: None,
+                feedback: None,
+                content_hash: None,
                 entity_slot: None,
                 aspect_slot: None,
                 is_constraint: false,
@@ -950,6 +2740,11 @@ mod tests {
             mem_content: "x".into(),
             embedding_blob: None,
             embedding_dims: None,
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            mem_tags: None,
+            feedback: None,
+            content_hash: None,
             entity_slot: None,
             aspect_slot: None,
             is_constraint: false,
@@ -1038,17 +2833,11 @@ mod tests {
         )
         .unwrap();
 
-        // Write to temp file using VACUUM INTO
-        let tmp = std::env::temp_dir().join("predictor_test_data.db");
-        conn.execute(&format!("VACUUM INTO '{}'", tmp.display()), [])
-            .unwrap();
-
         let config = DataConfig {
-            min_scorer_confidence: 0.6,
-            loss_temperature: 0.5,
             native_dim: 4,
+            ..DataConfig::default()
         };
-        let result = load_training_samples(&tmp, 100, &config).unwrap();
+        let result = load_from_conn(&conn, 100, &config).unwrap();
 
         assert_eq!(
             result.sessions_skipped, 1,
@@ -1072,9 +2861,9 @@ mod tests {
         assert!(sample.candidate_embeddings[1].is_empty());
         assert!(sample.candidate_texts[1].is_some());
 
-        // Feature dims = 12
-        assert_eq!(sample.candidate_features[0].len(), 17);
-        assert_eq!(sample.candidate_features[1].len(), 17);
+        // Full feature vector under the default schema
+        assert_eq!(sample.candidate_features[0].len(), FEATURE_DIM);
+        assert_eq!(sample.candidate_features[1].len(), FEATURE_DIM);
 
         // Labels in reasonable range
         for label in &sample.labels {
@@ -1087,7 +2876,730 @@ mod tests {
         // Query embedding should be non-zero (one injected candidate with embedding)
         assert!(sample.query_embedding.iter().any(|v| *v != 0.0));
 
-        // Clean up
-        let _ = std::fs::remove_file(&tmp);
+        let stats = result.feature_stats.expect("feature stats");
+        assert_eq!(stats.dim(), FEATURE_DIM);
+        assert!(stats.std.iter().all(|sd| *sd > 0.0));
+    }
+
+    #[test]
+    fn load_training_samples_resumes_after_cursor() {
+        let conn = create_test_db();
+        for (id, key, created) in [
+            ("ss1", "session-old", "2026-02-18T09:00:00Z"),
+            ("ss2", "session-new", "2026-02-20T09:00:00Z"),
+        ] {
+            conn.execute(
+                "INSERT INTO session_scores (id, session_key, project, score, confidence, created_at)
+                 VALUES (?1, ?2, 'proj-a', 0.8, 0.9, ?3)",
+                rusqlite::params![id, key, created],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+                 VALUES (?1, ?2, 'mem1', 'recall', 0.5, 1, 1, ?3)",
+                rusqlite::params![format!("sm-{id}"), key, created],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO memories (id, content, created_at, updated_at)
+             VALUES ('mem1', 'User prefers dark mode', '2026-01-10T08:00:00Z', '2026-01-10T08:00:00Z')",
+            [],
+        )
+        .unwrap();
+
+        let db = TempDb::copy_of(&conn);
+
+        let full = load_training_samples(db.path(), 100, &DataConfig::default()).unwrap();
+        assert_eq!(full.samples.len(), 2);
+        let cursor = full.cursor.expect("cursor");
+        assert_eq!(cursor.session_key, "session-new");
+
+        let since_old = DataConfig {
+            since: Some(TrainingCursor {
+                created_at: "2026-02-18T09:00:00Z".into(),
+                session_key: "session-old".into(),
+            }),
+            ..DataConfig::default()
+        };
+        let incremental = load_training_samples(db.path(), 100, &since_old).unwrap();
+        assert_eq!(incremental.samples.len(), 1);
+        assert_eq!(incremental.samples[0].session_id, "session-new");
+
+        let caught_up = DataConfig {
+            since: Some(cursor),
+            ..DataConfig::default()
+        };
+        let none = load_training_samples(db.path(), 100, &caught_up).unwrap();
+        assert!(none.samples.is_empty());
+        assert!(none.cursor.is_none());
+    }
+
+    #[test]
+    fn load_training_samples_mines_hard_negatives() {
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO session_scores (id, session_key, project, score, confidence, created_at)
+             VALUES ('ss1', 'session-a', 'proj-a', 0.8, 0.9, '2026-02-20T09:00:00Z')",
+            [],
+        )
+        .unwrap();
+        for (id, project, created, deleted) in [
+            ("recalled", "proj-a", "2026-01-01T00:00:00Z", 0),
+            ("neg1", "proj-a", "2026-01-02T00:00:00Z", 0),
+            ("neg2", "proj-a", "2026-01-03T00:00:00Z", 0),
+            ("other-project", "proj-b", "2026-01-03T00:00:00Z", 0),
+            ("future", "proj-a", "2026-03-01T00:00:00Z", 0),
+            ("deleted", "proj-a", "2026-01-03T00:00:00Z", 1),
+        ] {
+            conn.execute(
+                "INSERT INTO memories (id, content, importance, project, created_at, updated_at, is_deleted)
+                 VALUES (?1, ?1, 0.9, ?2, ?3, ?3, ?4)",
+                rusqlite::params![id, project, created, deleted],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+             VALUES ('sm1', 'session-a', 'recalled', 'recall', 0.5, 1, 1, '2026-02-20T09:00:00Z')",
+            [],
+        )
+        .unwrap();
+
+        let config = DataConfig {
+            hard_negatives_per_session: 5,
+            ..DataConfig::default()
+        };
+        let result = load_from_conn(&conn, 100, &config).unwrap();
+
+        assert_eq!(result.hard_negatives, 2, "only neg1 and neg2 qualify");
+        let sample = &result.samples[0];
+        assert_eq!(sample.labels.len(), 3);
+        assert!(sample.labels[1..].iter().all(|label| *label == 0.0));
+        let texts: Vec<_> = sample.candidate_texts[1..]
+            .iter()
+            .map(|t| t.as_deref().unwrap())
+            .collect();
+        assert!(texts.contains(&"neg1") && texts.contains(&"neg2"));
+    }
+
+    #[test]
+    fn load_training_samples_prefers_session_prompt_embedding() {
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO memories (id, content, created_at, updated_at)
+             VALUES ('mem1', 'fact', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO embeddings (id, content_hash, vector, dimensions, source_type, source_id, chunk_text, created_at)
+             VALUES ('e1', 'h1', ?1, 4, 'memory', 'mem1', 'fact', '2026-01-01T00:00:00Z')",
+            rusqlite::params![make_f32_blob(&[1.0, 1.0, 1.0, 1.0])],
+        )
+        .unwrap();
+        for key in ["with-prompt", "without-prompt"] {
+            conn.execute(
+                "INSERT INTO session_scores (id, session_key, score, confidence, created_at)
+                 VALUES (?1, ?1, 0.8, 0.9, '2026-02-20T09:00:00Z')",
+                rusqlite::params![key],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+                 VALUES (?1, ?1, 'mem1', 'recall', 0.5, 1, 1, '2026-02-20T09:00:00Z')",
+                rusqlite::params![key],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO embeddings (id, content_hash, vector, dimensions, source_type, source_id, chunk_text, created_at)
+             VALUES ('e2', 'h2', ?1, 4, 'session', 'with-prompt', 'prompt', '2026-02-20T09:00:00Z')",
+            rusqlite::params![make_f32_blob(&[0.0, 2.0, 0.0, 2.0])],
+        )
+        .unwrap();
+
+        let config = DataConfig {
+            native_dim: 4,
+            ..DataConfig::default()
+        };
+        let result = load_from_conn(&conn, 100, &config).unwrap();
+
+        assert_eq!(result.session_embeddings, 1);
+        let query = |key: &str| {
+            result
+                .samples
+                .iter()
+                .find(|s| s.session_id == key)
+                .map(|s| s.query_embedding.clone())
+                .unwrap()
+        };
+        assert_eq!(query("with-prompt"), vec![0.0, 2.0, 0.0, 2.0]);
+        assert_eq!(query("without-prompt"), vec![1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn chunked_memories_aggregate_into_one_candidate() {
+        let conn = create_test_db();
+        conn.execute_batch(
+            "INSERT INTO memories (id, content, created_at, updated_at)
+             VALUES ('mem1', 'long fact', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z');
+             INSERT INTO session_scores (id, session_key, score, confidence, created_at)
+             VALUES ('ss1', 'session-a', 0.8, 0.9, '2026-02-20T09:00:00Z');
+             INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+             VALUES ('sm1', 'session-a', 'mem1', 'recall', 0.5, 1, 1, '2026-02-20T09:00:00Z');",
+        )
+        .unwrap();
+        let vectors: [(&str, &str, [f32; 2]); 3] = [
+            ("e1", "memory", [1.0, 0.0]),
+            ("e2", "memory", [0.0, 1.0]),
+            ("e3", "session", [0.1, 0.9]),
+        ];
+        for (id, source_type, vector) in vectors {
+            let source_id = if source_type == "memory" {
+                "mem1"
+            } else {
+                "session-a"
+            };
+            conn.execute(
+                "INSERT INTO embeddings (id, content_hash, vector, dimensions, source_type, source_id, chunk_text, created_at)
+                 VALUES (?1, ?1, ?2, 2, ?3, ?4, 'chunk', '2026-01-01T00:00:00Z')",
+                rusqlite::params![id, make_f32_blob(&vector), source_type, source_id],
+            )
+            .unwrap();
+        }
+
+        let db = TempDb::copy_of(&conn);
+        let load = |chunk_aggregation| {
+            let config = DataConfig {
+                native_dim: 2,
+                chunk_aggregation,
+                ..DataConfig::default()
+            };
+            load_training_samples(db.path(), 10, &config).unwrap()
+        };
+        let mean = load(ChunkAggregation::Mean);
+        let max_sim = load(ChunkAggregation::MaxSim);
+
+        assert_eq!(mean.samples[0].candidate_embeddings, vec![vec![0.5, 0.5]]);
+        assert_eq!(
+            max_sim.samples[0].candidate_embeddings,
+            vec![vec![0.0, 1.0]]
+        );
+    }
+
+    #[test]
+    fn explicit_feedback_overrides_heuristic_labels() {
+        let conn = create_test_db();
+        conn.execute_batch(
+            "ALTER TABLE session_memories ADD COLUMN agent_relevance_score REAL;
+             ALTER TABLE session_memories ADD COLUMN agent_feedback_count INTEGER DEFAULT 0;
+             CREATE TABLE memory_history (
+                 id TEXT PRIMARY KEY, memory_id TEXT NOT NULL, event TEXT NOT NULL,
+                 session_id TEXT, created_at TEXT NOT NULL
+             );
+             INSERT INTO session_scores (id, session_key, score, confidence, created_at)
+             VALUES ('ss1', 'session-a', 0.8, 0.9, '2026-02-20T09:00:00Z');",
+        )
+        .unwrap();
+        for (rank, id, agent_score, feedback_count) in [
+            (1, "liked", 0.9, 1),
+            (2, "disliked", -0.8, 2),
+            (3, "deleted", 0.7, 1),
+            (4, "no-feedback", 0.0, 0),
+        ] {
+            conn.execute(
+                "INSERT INTO memories (id, content, created_at, updated_at)
+                 VALUES (?1, ?1, '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+                rusqlite::params![id],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank,
+                     was_injected, created_at, agent_relevance_score, agent_feedback_count)
+                 VALUES (?1, 'session-a', ?1, 'recall', 0.5, ?2, 0, '2026-02-20T09:00:00Z', ?3, ?4)",
+                rusqlite::params![id, rank, agent_score, feedback_count],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO memory_history (id, memory_id, event, session_id, created_at)
+             VALUES ('h1', 'deleted', 'deleted', 'session-a', '2026-02-20T09:05:00Z')",
+            [],
+        )
+        .unwrap();
+
+        let db = TempDb::copy_of(&conn);
+
+        let result = load_training_samples(db.path(), 100, &DataConfig::default()).unwrap();
+        assert_eq!(result.feedback_labels, 3);
+        let labels = &result.samples[0].labels;
+        assert!((labels[0] - 0.9).abs() < 1e-9);
+        assert!((labels[1] - (-0.4)).abs() < 1e-9);
+        assert!(
+            (labels[2] - (-0.5)).abs() < 1e-9,
+            "deletion beats agent score"
+        );
+        assert!((labels[3] - 0.0).abs() < 1e-9, "heuristic label kept");
+
+        let ignore = DataConfig {
+            feedback_mode: FeedbackMode::Ignore,
+            ..DataConfig::default()
+        };
+        let result = load_training_samples(db.path(), 100, &ignore).unwrap();
+        assert_eq!(result.feedback_labels, 0);
+        assert!(result.samples[0].labels.iter().all(|label| *label == 0.0));
+    }
+
+    #[test]
+    fn duplicate_content_hashes_keep_the_highest_label() {
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO session_scores (id, session_key, score, confidence, created_at)
+             VALUES ('ss1', 'session-a', 0.8, 0.9, '2026-02-20T09:00:00Z')",
+            [],
+        )
+        .unwrap();
+        // copy-b is injected, so it outranks copy-a despite the worse rank
+        for (rank, id, hash, injected) in [
+            (1, "copy-a", Some("same"), 0),
+            (2, "copy-b", Some("same"), 1),
+            (3, "unique", Some("other"), 0),
+            (4, "unhashed", None, 0),
+        ] {
+            conn.execute(
+                "INSERT INTO memories (id, content, content_hash, created_at, updated_at)
+                 VALUES (?1, ?1, ?2, '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+                rusqlite::params![id, hash],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+                 VALUES (?1, 'session-a', ?1, 'recall', 0.5, ?2, ?3, '2026-02-20T09:00:00Z')",
+                rusqlite::params![id, rank, injected],
+            )
+            .unwrap();
+        }
+
+        let result = load_from_conn(&conn, 100, &DataConfig::default()).unwrap();
+
+        assert_eq!(result.duplicates_removed, 1);
+        let texts: Vec<_> = result.samples[0]
+            .candidate_texts
+            .iter()
+            .map(|t| t.as_deref().unwrap())
+            .collect();
+        assert_eq!(texts, vec!["copy-b", "unique", "unhashed"]);
+    }
+
+    #[test]
+    fn harness_filter_limits_sessions() {
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO memories (id, content, created_at, updated_at)
+             VALUES ('mem1', 'fact', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+        for (key, harness, confidence) in [
+            ("cc-1", "claude-code", 0.9),
+            ("cc-low", "claude-code", 0.1),
+            ("exp-1", "experimental", 0.9),
+            ("exp-low", "experimental", 0.1),
+        ] {
+            conn.execute(
+                "INSERT INTO session_scores (id, session_key, harness, score, confidence, created_at)
+                 VALUES (?1, ?1, ?2, 0.8, ?3, '2026-02-20T09:00:00Z')",
+                rusqlite::params![key, harness, confidence],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+                 VALUES (?1, ?1, 'mem1', 'recall', 0.5, 1, 1, '2026-02-20T09:00:00Z')",
+                rusqlite::params![key],
+            )
+            .unwrap();
+        }
+
+        let db = TempDb::copy_of(&conn);
+
+        let all = load_training_samples(db.path(), 100, &DataConfig::default()).unwrap();
+        assert_eq!(all.samples.len(), 2);
+        assert_eq!(all.sessions_skipped, 2);
+
+        let config = DataConfig {
+            harness_filter: vec!["claude-code".to_string()],
+            ..DataConfig::default()
+        };
+        let filtered = load_training_samples(db.path(), 100, &config).unwrap();
+        assert_eq!(filtered.samples.len(), 1);
+        assert_eq!(filtered.samples[0].session_id, "cc-1");
+        assert_eq!(filtered.sessions_skipped, 1);
+    }
+
+    #[test]
+    fn project_filter_isolates_sessions_and_memories() {
+        let conn = create_test_db();
+        for (id, project) in [
+            ("mem-a", Some("proj-a")),
+            ("mem-b", Some("proj-b")),
+            ("mem-global", None),
+        ] {
+            conn.execute(
+                "INSERT INTO memories (id, content, project, created_at, updated_at)
+                 VALUES (?1, ?1, ?2, '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+                rusqlite::params![id, project],
+            )
+            .unwrap();
+        }
+        for (key, project) in [("session-a", "proj-a"), ("session-b", "proj-b")] {
+            conn.execute(
+                "INSERT INTO session_scores (id, session_key, project, score, confidence, created_at)
+                 VALUES (?1, ?1, ?2, 0.8, 0.9, '2026-02-20T09:00:00Z')",
+                rusqlite::params![key, project],
+            )
+            .unwrap();
+            for (rank, mem) in ["mem-a", "mem-b", "mem-global"].iter().enumerate() {
+                conn.execute(
+                    "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+                     VALUES (?1, ?2, ?3, 'recall', 0.5, ?4, 1, '2026-02-20T09:00:00Z')",
+                    rusqlite::params![format!("{key}-{mem}"), key, mem, rank as i64],
+                )
+                .unwrap();
+            }
+        }
+
+        let db = TempDb::copy_of(&conn);
+
+        let include = DataConfig {
+            project_filter: ProjectFilter {
+                include: vec!["proj-a".to_string()],
+                exclude: Vec::new(),
+            },
+            ..DataConfig::default()
+        };
+        let result = load_training_samples(db.path(), 100, &include).unwrap();
+        assert_eq!(result.samples.len(), 1);
+        assert_eq!(result.samples[0].session_id, "session-a");
+        assert_eq!(result.samples[0].labels.len(), 1, "only mem-a survives");
+        assert_eq!(result.project_filtered, 2);
+
+        let exclude = DataConfig {
+            project_filter: ProjectFilter {
+                include: Vec::new(),
+                exclude: vec!["proj-a".to_string()],
+            },
+            ..DataConfig::default()
+        };
+        let result = load_training_samples(db.path(), 100, &exclude).unwrap();
+        assert_eq!(result.samples.len(), 1);
+        assert_eq!(result.samples[0].session_id, "session-b");
+        assert_eq!(result.samples[0].labels.len(), 2, "mem-b and mem-global");
+    }
+
+    #[test]
+    fn session_weight_halves_every_half_life() {
+        let session = |created_at: &str| SessionRow {
+            session_key: "s".into(),
+            project: None,
+            score: 0.8,
+            confidence: Some(0.9),
+            novel_context_count: None,
+            created_at: created_at.into(),
+        };
+        let newest = TrainingCursor {
+            created_at: "2026-03-21T00:00:00Z".into(),
+            session_key: "newest".into(),
+        };
+        let config = DataConfig {
+            label_half_life_days: Some(10.0),
+            ..DataConfig::default()
+        };
+        let weight = |ts: &str| session_weight(&session(ts), Some(&newest), &config);
+        assert!((weight("2026-03-21T00:00:00Z") - 1.0).abs() < 1e-9);
+        assert!((weight("2026-03-11T00:00:00Z") - 0.5).abs() < 1e-9);
+        assert!((weight("2026-03-01T00:00:00Z") - 0.25).abs() < 1e-9);
+
+        let no_decay = DataConfig::default();
+        let old = session("2025-01-01T00:00:00Z");
+        assert_eq!(session_weight(&old, Some(&newest), &no_decay), 1.0);
+    }
+
+    #[test]
+    fn older_schema_degrades_with_warnings() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "
+            CREATE TABLE session_scores (
+                id TEXT PRIMARY KEY, session_key TEXT NOT NULL, project TEXT,
+                score REAL NOT NULL, created_at TEXT NOT NULL
+            );
+            CREATE TABLE memories (
+                id TEXT PRIMARY KEY, content TEXT NOT NULL, importance REAL,
+                project TEXT, created_at TEXT NOT NULL, is_deleted INTEGER DEFAULT 0
+            );
+            CREATE TABLE session_memories (
+                id TEXT PRIMARY KEY, session_key TEXT NOT NULL,
+                memory_id TEXT NOT NULL, source TEXT NOT NULL,
+                rank INTEGER NOT NULL, was_injected INTEGER NOT NULL
+            );
+            CREATE TABLE embeddings (
+                id TEXT PRIMARY KEY, vector BLOB NOT NULL, dimensions INTEGER,
+                source_type TEXT NOT NULL, source_id TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+            INSERT INTO session_scores VALUES
+                ('s1', 'sess-1', 'proj', 0.8, '2026-02-10T09:00:00Z');
+            INSERT INTO memories VALUES
+                ('mem1', 'fact', 0.9, 'proj', '2026-01-01T00:00:00Z', 0);
+            INSERT INTO session_memories VALUES ('sm1', 'sess-1', 'mem1', 'recall', 1, 1);
+        ",
+        )
+        .unwrap();
+
+        let schema = LoaderSchema::introspect(&conn).unwrap();
+        let missing: Vec<String> = schema
+            .warnings
+            .iter()
+            .map(|w| format!("{}.{}", w.table, w.column))
+            .collect();
+        assert!(missing.contains(&"session_scores.confidence".to_string()));
+        assert!(missing.contains(&"session_memories.fts_hit_count".to_string()));
+        assert!(missing.contains(&"memories.tags".to_string()));
+        assert!(!missing.contains(&"memories.importance".to_string()));
+        // Later-migration columns are expected to be absent on old databases
+        assert!(!missing.contains(&"memories.content_hash".to_string()));
+
+        let result = load_from_conn(&conn, 10, &DataConfig::default()).unwrap();
+        assert_eq!(result.samples.len(), 1);
+        assert_eq!(result.samples[0].labels.len(), 1);
+        assert_eq!(result.schema_warnings, schema.warnings);
+    }
+
+    #[test]
+    fn missing_required_columns_fail_with_schema_error() {
+        let conn = create_test_db();
+        conn.execute_batch("ALTER TABLE session_scores DROP COLUMN score")
+            .unwrap();
+        match LoaderSchema::introspect(&conn) {
+            Err(DataError::Schema(msg)) => assert!(msg.contains("session_scores.score")),
+            Err(e) => panic!("unexpected error: {e:?}"),
+            Ok(_) => panic!("expected a schema error"),
+        }
+    }
+
+    #[test]
+    fn batched_gaps_look_back_to_a_strictly_earlier_session() {
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO memories (id, content, created_at, updated_at)
+             VALUES ('mem1', 'fact', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+        let sessions = [
+            ("s1", "proj", "2026-02-10T00:00:00Z"),
+            ("s2", "proj", "2026-02-12T00:00:00Z"),
+            ("s3", "proj", "2026-02-12T00:00:00Z"),
+            ("s4", "proj", "2026-02-15T00:00:00Z"),
+            ("s5", "other", "2026-02-14T00:00:00Z"),
+        ];
+        for (key, project, created_at) in sessions {
+            conn.execute(
+                "INSERT INTO session_scores (id, session_key, project, score, confidence, created_at)
+                 VALUES (?1, ?1, ?2, 0.8, 0.9, ?3)",
+                rusqlite::params![key, project, created_at],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+                 VALUES (?1, ?1, 'mem1', 'recall', 0.5, 1, 1, ?2)",
+                rusqlite::params![key, created_at],
+            )
+            .unwrap();
+        }
+
+        let schema = LoaderSchema::introspect(&conn).unwrap();
+        let rows = query_sessions(&conn, 10, &DataConfig::default(), &schema).unwrap();
+        let batch = SessionBatch::fetch(&conn, &rows, &DataConfig::default(), &schema).unwrap();
+        let gap = |key: &str| {
+            let session = rows.iter().find(|s| s.session_key == key).unwrap();
+            batch
+                .previous
+                .get(&(session.session_key.clone(), session.created_at.clone()))
+                .map(|prev| days_between(prev, &session.created_at))
+        };
+        assert_eq!(gap("s1"), None);
+        assert_eq!(gap("s2"), Some(2.0));
+        assert_eq!(gap("s3"), Some(2.0));
+        assert_eq!(gap("s4"), Some(3.0));
+        assert_eq!(gap("s5"), None);
+        assert_eq!(batch.candidates.len(), 5);
+    }
+
+    #[test]
+    fn parallel_loading_matches_sequential_order() {
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO memories (id, content, created_at, updated_at)
+             VALUES ('mem1', 'fact', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+        for i in 0..7 {
+            let key = format!("session-{i}");
+            conn.execute(
+                "INSERT INTO session_scores (id, session_key, score, confidence, created_at)
+                 VALUES (?1, ?1, ?2, 0.9, ?3)",
+                rusqlite::params![key, 0.1 * i as f64, format!("2026-02-1{i}T09:00:00Z")],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+                 VALUES (?1, ?1, 'mem1', 'recall', 0.5, 1, 1, '2026-02-10T09:00:00Z')",
+                rusqlite::params![key],
+            )
+            .unwrap();
+        }
+
+        let db = TempDb::copy_of(&conn);
+
+        let load = |workers: usize| {
+            let config = DataConfig {
+                load_workers: workers,
+                ..DataConfig::default()
+            };
+            load_training_samples(db.path(), 100, &config).unwrap()
+        };
+        let sequential = load(1);
+        let parallel = load(3);
+
+        let summary = |result: &LoadResult| {
+            result
+                .samples
+                .iter()
+                .map(|s| (s.session_id.clone(), s.labels.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sequential.samples.len(), 7);
+        assert_eq!(summary(&sequential), summary(&parallel));
+        assert_eq!(sequential.feature_stats, parallel.feature_stats);
+    }
+
+    #[test]
+    fn parse_candidate_embedding_uses_stored_dimensions() {
+        let mut row = CandidateRow {
+            memory_id: "m1".into(),
+            effective_score: 0.0,
+            was_injected: true,
+            relevance_score: None,
+            fts_hit_count: 0,
+            source: "recall".into(),
+            importance: 0.5,
+            mem_created_at: "2026-01-01T00:00:00Z".into(),
+            access_count: 0,
+            is_deleted: false,
+            mem_project: None,
+            pinned: false,
+            mem_content: "legacy vector".into(),
+            embedding_blob: Some(make_f32_blob(&[1.0, 2.0])),
+            embedding_dims: Some(2),
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            mem_tags: None,
+            feedback: None,
+            content_hash: None,
+            entity_slot: None,
+            aspect_slot: None,
+            is_constraint: false,
+            structural_density: None,
+        };
+        assert_eq!(parse_candidate_embedding(&row, 4).map(|v| v.len()), Some(2));
+        // Legacy vectors never feed the native-width query embedding
+        assert_eq!(
+            compute_query_embedding(std::slice::from_ref(&row), 4),
+            vec![0.0; 4]
+        );
+
+        row.embedding_dims = None;
+        assert!(parse_candidate_embedding(&row, 3).is_none());
+    }
+
+    #[test]
+    fn load_training_samples_retries_while_database_is_locked() {
+        let db = TempDb::copy_of(&create_test_db());
+
+        let writer = Connection::open(db.path()).unwrap();
+        writer.execute_batch("BEGIN EXCLUSIVE").unwrap();
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(120));
+            writer.execute_batch("COMMIT").unwrap();
+        });
+
+        let config = DataConfig {
+            busy_timeout_ms: 10,
+            lock_retries: 5,
+            ..DataConfig::default()
+        };
+        let result = load_training_samples(db.path(), 10, &config).unwrap();
+        release.join().unwrap();
+
+        assert!(result.lock_retries >= 1, "should have retried under lock");
+        assert!(result.lock_wait_ms > 0);
+        assert_eq!(result.journal_mode, "delete");
+    }
+
+    #[test]
+    fn sample_stream_chunks_and_rewinds() {
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO memories (id, content, created_at, updated_at)
+             VALUES ('mem1', 'User prefers dark mode', '2026-01-10T08:00:00Z', '2026-01-10T08:00:00Z')",
+            [],
+        )
+        .unwrap();
+        for i in 0..5 {
+            let key = format!("session-{i}");
+            let created = format!("2026-02-2{i}T09:00:00Z");
+            conn.execute(
+                "INSERT INTO session_scores (id, session_key, score, confidence, created_at)
+                 VALUES (?1, ?1, 0.8, 0.9, ?2)",
+                rusqlite::params![key, created],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_memories (id, session_key, memory_id, source, final_score, rank, was_injected, created_at)
+                 VALUES (?1, ?2, 'mem1', 'recall', 0.5, 1, 1, ?3)",
+                rusqlite::params![format!("sm-{i}"), key, created],
+            )
+            .unwrap();
+        }
+
+        let db = TempDb::copy_of(&conn);
+
+        let config = DataConfig {
+            native_dim: 4,
+            ..DataConfig::default()
+        };
+        // A 1-byte budget means one sample per chunk
+        let mut stream = SampleStream::open(db.path(), 100, &config, 1).unwrap();
+        let holdout = stream.take_holdout(1).unwrap();
+        assert_eq!(holdout.len(), 1);
+        assert!(stream.feature_stats().unwrap().is_some());
+
+        let mut first_pass = 0;
+        while let Some(chunk) = stream.next_chunk().unwrap() {
+            assert_eq!(chunk.len(), 1);
+            first_pass += 1;
+        }
+        assert_eq!(first_pass, 4);
+
+        stream.rewind();
+        let mut second_pass = 0;
+        while stream.next_chunk().unwrap().is_some() {
+            second_pass += 1;
+        }
+        assert_eq!(second_pass, 4);
+        assert_eq!(stream.summary().embedding_dims.values().sum::<usize>(), 0);
     }
 }
@@ -0,0 +1,676 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{
+    hard_negative_pool_size, pick_hard_negatives, push_candidate_row, CandidateRow, DataConfig,
+    DataError, FeedbackMode, ProjectFilter, RowSource, SchemaWarning, SessionBatch, SessionRow,
+    TrainingCursor, HARD_NEGATIVE_SOURCE,
+};
+use crate::embedding::{decode_embedding, EmbeddingFormat};
+use crate::transport::MAX_FRAME_BYTES;
+
+fn default_timeout_ms() -> u64 {
+    30_000
+}
+
+/// Daemon endpoint for `train_from_db` and `evaluate`, given as the
+/// `daemon` param in place of `db_path` when the database file is out of
+/// reach (a container, another host). The daemon serves the rows the SQLite
+/// loader would select, so labels and features come out the same:
+///
+/// - `POST /api/predictor/training-data/sessions`: qualifying sessions
+/// - `POST /api/predictor/training-data/batch`: candidates, deletions,
+///   prompt embeddings and project gaps for a list of session keys
+/// - `POST /api/predictor/training-data/hard-negatives`: the unrecalled
+///   memory pool for one session
+///
+/// Vectors travel as float arrays.
+#[derive(Clone, Deserialize)]
+pub struct DaemonSource {
+    /// Base URL, e.g. `http://127.0.0.1:3850`
+    pub url: String,
+    /// Sent as `Authorization: Bearer <token>`. Daemons with auth enabled
+    /// require the admin permission for these routes.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Connect and per-read timeout for each request
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+// The token stays out of logs and error messages.
+impl std::fmt::Debug for DaemonSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DaemonSource")
+            .field("url", &self.url)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("timeout_ms", &self.timeout_ms)
+            .finish()
+    }
+}
+
+impl DaemonSource {
+    pub(super) fn client(&self) -> Result<DaemonClient, DataError> {
+        let (host, port, base_path) = parse_http_url(&self.url)?;
+        Ok(DaemonClient {
+            host,
+            port,
+            base_path,
+            token: self.token.clone(),
+            timeout: Duration::from_millis(self.timeout_ms.max(1)),
+        })
+    }
+}
+
+/// `(host, port, base path)` of a plain `http://` URL. TLS is left to a
+/// local proxy; the predictor speaks HTTP/1.1 over a bare socket.
+fn parse_http_url(url: &str) -> Result<(String, u16, String), DataError> {
+    let rest = match url.split_once("://") {
+        Some(("http", rest)) => rest,
+        Some((scheme, _)) => {
+            return Err(DataError::Remote(format!(
+                "unsupported daemon URL scheme {scheme:?}; use http://"
+            )))
+        }
+        None => url,
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, ""),
+    };
+    let (host, port) = if let Some(v6) = authority.strip_prefix('[') {
+        let (host, after) = v6
+            .split_once(']')
+            .ok_or_else(|| DataError::Remote(format!("invalid daemon URL: {url}")))?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    if host.is_empty() {
+        return Err(DataError::Remote(format!("invalid daemon URL: {url}")));
+    }
+    let port = match port {
+        Some(port) => port
+            .parse()
+            .map_err(|_| DataError::Remote(format!("invalid port in daemon URL: {url}")))?,
+        None => 80,
+    };
+    Ok((
+        host.to_string(),
+        port,
+        path.trim_end_matches('/').to_string(),
+    ))
+}
+
+/// One connection per request, closed by the daemon after its response.
+pub(super) struct DaemonClient {
+    host: String,
+    port: u16,
+    base_path: String,
+    token: Option<String>,
+    timeout: Duration,
+}
+
+/// Sessions endpoint response
+#[derive(Deserialize)]
+pub(super) struct SessionListing {
+    pub sessions: Vec<SessionRow>,
+    #[serde(default)]
+    pub sessions_skipped: usize,
+    /// Columns the daemon's database lacks, as the daemon reports them
+    #[serde(default)]
+    pub schema_warnings: Vec<SchemaWarning>,
+    #[serde(default = "unknown_journal_mode")]
+    pub journal_mode: String,
+}
+
+fn unknown_journal_mode() -> String {
+    "unknown".to_string()
+}
+
+#[derive(Serialize)]
+struct SessionsRequest<'a> {
+    limit: usize,
+    min_confidence: f64,
+    /// Incremental loads: only sessions after this cursor, oldest first
+    since: Option<&'a TrainingCursor>,
+    harness_filter: &'a [String],
+    project_filter: &'a ProjectFilter,
+}
+
+#[derive(Serialize)]
+struct BatchRequest<'a> {
+    session_keys: Vec<&'a str>,
+    /// Memories deleted during each session are only needed for feedback
+    include_deleted: bool,
+}
+
+#[derive(Serialize)]
+struct HardNegativesRequest<'a> {
+    session_key: &'a str,
+    project: Option<&'a str>,
+    created_at: &'a str,
+    limit: usize,
+}
+
+/// A candidate row as the daemon sends it. Absent fields take the same
+/// fallbacks the SQLite loader uses for missing columns.
+#[derive(Deserialize)]
+struct WireCandidate {
+    memory_id: String,
+    #[serde(default)]
+    effective_score: Option<f64>,
+    #[serde(default)]
+    was_injected: bool,
+    #[serde(default)]
+    relevance_score: Option<f64>,
+    #[serde(default)]
+    fts_hit_count: i64,
+    #[serde(default = "unknown_source")]
+    source: String,
+    #[serde(default)]
+    importance: Option<f64>,
+    created_at: String,
+    #[serde(default)]
+    access_count: Option<i64>,
+    #[serde(default)]
+    is_deleted: bool,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    pinned: bool,
+    content: String,
+    #[serde(default)]
+    tags: Option<String>,
+    /// Agent relevance score, only once feedback was given
+    #[serde(default)]
+    feedback: Option<f64>,
+    #[serde(default)]
+    content_hash: Option<String>,
+    /// One vector per embedding chunk, in chunk order
+    #[serde(default)]
+    embeddings: Vec<Vec<f32>>,
+    #[serde(default)]
+    entity_slot: Option<i64>,
+    #[serde(default)]
+    aspect_slot: Option<i64>,
+    #[serde(default)]
+    is_constraint: bool,
+    #[serde(default)]
+    structural_density: Option<i64>,
+}
+
+fn unknown_source() -> String {
+    "unknown".to_string()
+}
+
+fn f32_blob(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+impl WireCandidate {
+    /// Loader rows for this candidate: one per embedding chunk, folded back
+    /// together by [`push_candidate_row`].
+    fn into_rows(self) -> Vec<CandidateRow> {
+        let row = CandidateRow {
+            memory_id: self.memory_id,
+            effective_score: self.effective_score.unwrap_or(0.0),
+            was_injected: self.was_injected,
+            relevance_score: self.relevance_score,
+            fts_hit_count: self.fts_hit_count,
+            source: self.source,
+            importance: self.importance.unwrap_or(0.5),
+            mem_created_at: self.created_at,
+            access_count: self.access_count.unwrap_or(0),
+            is_deleted: self.is_deleted,
+            mem_project: self.project,
+            pinned: self.pinned,
+            mem_content: self.content,
+            mem_tags: self.tags,
+            feedback: self.feedback,
+            content_hash: self.content_hash,
+            embedding_blob: None,
+            embedding_dims: None,
+            embedding_format: None,
+            extra_chunks: Vec::new(),
+            entity_slot: self.entity_slot,
+            aspect_slot: self.aspect_slot,
+            is_constraint: self.is_constraint,
+            structural_density: self.structural_density,
+        };
+        if self.embeddings.is_empty() {
+            return vec![row];
+        }
+        self.embeddings
+            .iter()
+            .map(|vector| CandidateRow {
+                embedding_blob: Some(f32_blob(vector)),
+                embedding_dims: Some(vector.len() as i64),
+                embedding_format: Some(EmbeddingFormat::F32),
+                ..row.clone()
+            })
+            .collect()
+    }
+}
+
+fn candidate_rows(wire: Vec<WireCandidate>) -> Vec<CandidateRow> {
+    let mut rows = Vec::new();
+    for candidate in wire {
+        for row in candidate.into_rows() {
+            push_candidate_row(&mut rows, row);
+        }
+    }
+    rows
+}
+
+#[derive(Deserialize)]
+struct WireGap {
+    session_key: String,
+    created_at: String,
+    previous_created_at: String,
+}
+
+/// Batch endpoint response
+#[derive(Deserialize)]
+struct WireBatch {
+    /// Candidates per session key, in rank order
+    #[serde(default)]
+    candidates: BTreeMap<String, Vec<WireCandidate>>,
+    #[serde(default)]
+    deleted: BTreeMap<String, Vec<String>>,
+    /// Newest prompt embedding per session key
+    #[serde(default)]
+    session_embeddings: BTreeMap<String, Vec<f32>>,
+    /// Previous session in the same project, where there is one
+    #[serde(default)]
+    previous: Vec<WireGap>,
+}
+
+#[derive(Deserialize)]
+struct WireHardNegatives {
+    candidates: Vec<WireCandidate>,
+}
+
+impl DaemonClient {
+    pub(super) fn sessions(
+        &mut self,
+        limit: usize,
+        config: &DataConfig,
+    ) -> Result<SessionListing, DataError> {
+        self.post(
+            "/api/predictor/training-data/sessions",
+            &SessionsRequest {
+                limit,
+                min_confidence: config.min_scorer_confidence,
+                since: config.since.as_ref(),
+                harness_filter: &config.harness_filter,
+                project_filter: &config.project_filter,
+            },
+        )
+    }
+
+    fn post<Req: Serialize, Resp: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &Req,
+    ) -> Result<Resp, DataError> {
+        let body = serde_json::to_vec(body).map_err(|e| DataError::Remote(e.to_string()))?;
+        let target = format!("{}{path}", self.base_path);
+        let remote = |what: &str, err: std::io::Error| {
+            DataError::Remote(format!("{what} {}:{}{target}: {err}", self.host, self.port))
+        };
+
+        let mut stream = self.connect().map_err(|e| remote("connect", e))?;
+        let mut head = format!(
+            "POST {target} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\n\
+             Accept: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.host,
+            self.port,
+            body.len()
+        );
+        if let Some(token) = &self.token {
+            head.push_str(&format!("Authorization: Bearer {token}\r\n"));
+        }
+        head.push_str("\r\n");
+        stream
+            .write_all(head.as_bytes())
+            .and_then(|()| stream.write_all(&body))
+            .map_err(|e| remote("send", e))?;
+
+        let mut raw = Vec::new();
+        stream
+            .take(MAX_FRAME_BYTES as u64 + 1)
+            .read_to_end(&mut raw)
+            .map_err(|e| remote("read", e))?;
+        if raw.len() > MAX_FRAME_BYTES {
+            return Err(DataError::Remote(format!(
+                "{target}: response exceeds {MAX_FRAME_BYTES} bytes"
+            )));
+        }
+        let (status, payload) = parse_response(&raw)
+            .ok_or_else(|| DataError::Remote(format!("{target}: malformed HTTP response")))?;
+        match status {
+            200 => serde_json::from_slice(&payload)
+                .map_err(|e| DataError::Remote(format!("{target}: invalid response: {e}"))),
+            401 | 403 => Err(DataError::Remote(format!(
+                "{target}: daemon rejected the token (HTTP {status})"
+            ))),
+            _ => {
+                let text = String::from_utf8_lossy(&payload);
+                let text: String = text.chars().take(200).collect();
+                Err(DataError::Remote(format!(
+                    "{target}: HTTP {status}: {text}"
+                )))
+            }
+        }
+    }
+
+    fn connect(&self) -> std::io::Result<TcpStream> {
+        let mut last_err = None;
+        for addr in (self.host.as_str(), self.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(self.timeout))?;
+                    stream.set_write_timeout(Some(self.timeout))?;
+                    return Ok(stream);
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "host did not resolve")
+        }))
+    }
+}
+
+/// Status code and body of a complete HTTP/1.1 response, undoing chunked
+/// transfer encoding when the daemon streams its reply.
+fn parse_response(raw: &[u8]) -> Option<(u16, Vec<u8>)> {
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&raw[..split]).ok()?;
+    let body = &raw[split + 4..];
+    let mut lines = head.split("\r\n");
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.to_ascii_lowercase().contains("chunked")
+        })
+    });
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        body.to_vec()
+    };
+    Some((status, body))
+}
+
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size_line = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size_hex = size_line.split(';').next()?.trim();
+        let size = usize::from_str_radix(size_hex, 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+impl RowSource for DaemonClient {
+    fn fetch_batch(
+        &mut self,
+        sessions: &[SessionRow],
+        config: &DataConfig,
+    ) -> Result<SessionBatch, DataError> {
+        if sessions.is_empty() {
+            return Ok(SessionBatch::default());
+        }
+        let wire: WireBatch = self.post(
+            "/api/predictor/training-data/batch",
+            &BatchRequest {
+                session_keys: sessions.iter().map(|s| s.session_key.as_str()).collect(),
+                include_deleted: config.feedback_mode != FeedbackMode::Ignore,
+            },
+        )?;
+        let mut batch = SessionBatch {
+            candidates: wire
+                .candidates
+                .into_iter()
+                .map(|(key, candidates)| (key, candidate_rows(candidates)))
+                .collect(),
+            deleted: wire.deleted,
+            ..SessionBatch::default()
+        };
+        for (key, vector) in wire.session_embeddings {
+            if let Some(embedding) =
+                decode_embedding(&f32_blob(&vector), vector.len(), Some(EmbeddingFormat::F32))
+                    .filter(|emb| emb.len() == config.native_dim)
+            {
+                batch.embeddings.insert(key, embedding);
+            }
+        }
+        for gap in wire.previous {
+            batch
+                .previous
+                .insert((gap.session_key, gap.created_at), gap.previous_created_at);
+        }
+        Ok(batch)
+    }
+
+    fn hard_negatives(
+        &mut self,
+        session: &SessionRow,
+        count: usize,
+    ) -> Result<Vec<CandidateRow>, DataError> {
+        let wire: WireHardNegatives = self.post(
+            "/api/predictor/training-data/hard-negatives",
+            &HardNegativesRequest {
+                session_key: &session.session_key,
+                project: session.project.as_deref(),
+                created_at: &session.created_at,
+                limit: hard_negative_pool_size(count),
+            },
+        )?;
+        // Mined rows never reached the session, whatever the daemon sent
+        let pool = candidate_rows(wire.candidates)
+            .into_iter()
+            .map(|row| CandidateRow {
+                effective_score: 0.0,
+                was_injected: false,
+                relevance_score: None,
+                fts_hit_count: 0,
+                source: HARD_NEGATIVE_SOURCE.to_string(),
+                feedback: None,
+                entity_slot: None,
+                aspect_slot: None,
+                is_constraint: false,
+                structural_density: None,
+                ..row
+            })
+            .collect();
+        Ok(pick_hard_negatives(pool, session, count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::load_daemon_samples;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn parses_http_urls() {
+        assert_eq!(
+            parse_http_url("http://127.0.0.1:3850").unwrap(),
+            ("127.0.0.1".to_string(), 3850, String::new())
+        );
+        assert_eq!(
+            parse_http_url("http://signet.local/daemon/").unwrap(),
+            ("signet.local".to_string(), 80, "/daemon".to_string())
+        );
+        assert_eq!(
+            parse_http_url("http://[::1]:3850/x").unwrap(),
+            ("::1".to_string(), 3850, "/x".to_string())
+        );
+        assert!(parse_http_url("https://example.com").is_err());
+        assert!(parse_http_url("http://host:port").is_err());
+    }
+
+    #[test]
+    fn parses_chunked_responses() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n3\r\n:1}\r\n0\r\n\r\n";
+        let (status, body) = parse_response(raw).unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, b"{\"a\":1}");
+
+        let raw = b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 2\r\n\r\nno";
+        assert_eq!(parse_response(raw).unwrap(), (401, b"no".to_vec()));
+    }
+
+    #[test]
+    fn token_is_redacted_from_debug_output() {
+        let source = DaemonSource {
+            url: "http://localhost:3850".into(),
+            token: Some("secret".into()),
+            timeout_ms: 1000,
+        };
+        assert!(!format!("{source:?}").contains("secret"));
+    }
+
+    fn candidate(id: &str, injected: bool, relevance: f64, dims: usize) -> Value {
+        json!({
+            "memory_id": id,
+            "effective_score": 0.5,
+            "was_injected": injected,
+            "relevance_score": relevance,
+            "source": "recall",
+            "importance": 0.7,
+            "created_at": "2026-01-01T00:00:00Z",
+            "content": format!("content of {id}"),
+            "embeddings": [vec![0.25f32; dims]],
+        })
+    }
+
+    /// Serve canned responses for the training-data endpoints on a local
+    /// port, recording each request's authorization header.
+    fn serve(
+        responses: Vec<(&'static str, Value)>,
+    ) -> (String, std::thread::JoinHandle<Vec<Option<String>>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut seen = Vec::new();
+            for _ in 0..responses.len() {
+                let request = server.recv().unwrap();
+                let auth = request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv("Authorization"))
+                    .map(|h| h.value.to_string());
+                let path = request.url().to_string();
+                let body = responses
+                    .iter()
+                    .find(|(p, _)| *p == path)
+                    .map(|(_, body)| body.to_string())
+                    .unwrap_or_default();
+                seen.push(auth);
+                let _ = request.respond(tiny_http::Response::from_string(body));
+            }
+            seen
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn loads_samples_from_daemon_endpoints() {
+        let dims = 4;
+        let (url, handle) = serve(vec![
+            (
+                "/api/predictor/training-data/sessions",
+                json!({
+                    "sessions": [{
+                        "session_key": "sess-1",
+                        "project": "proj",
+                        "score": 0.8,
+                        "confidence": 0.9,
+                        "novel_context_count": 1,
+                        "created_at": "2026-02-10T09:00:00Z",
+                    }],
+                    "sessions_skipped": 2,
+                    "journal_mode": "wal",
+                }),
+            ),
+            (
+                "/api/predictor/training-data/batch",
+                json!({
+                    "candidates": {
+                        "sess-1": [
+                            candidate("mem1", true, 0.9, dims),
+                            candidate("mem2", false, 0.1, dims),
+                        ],
+                    },
+                    "session_embeddings": { "sess-1": vec![1.0f32; dims] },
+                }),
+            ),
+        ]);
+        let source = DaemonSource {
+            url,
+            token: Some("tok".into()),
+            timeout_ms: 5_000,
+        };
+        let config = DataConfig {
+            native_dim: dims,
+            load_workers: 1,
+            ..DataConfig::default()
+        };
+        let result = load_daemon_samples(&source, 10, &config).unwrap();
+        let seen = handle.join().unwrap();
+
+        assert!(seen
+            .iter()
+            .all(|auth| auth.as_deref() == Some("Bearer tok")));
+        assert_eq!(result.samples.len(), 1);
+        assert_eq!(result.sessions_skipped, 2);
+        assert_eq!(result.journal_mode, "wal");
+        assert_eq!(result.session_embeddings, 1);
+        let sample = &result.samples[0];
+        assert_eq!(sample.labels.len(), 2);
+        assert!(sample.labels[0] > sample.labels[1]);
+        assert_eq!(sample.candidate_embeddings[0], vec![0.25; dims]);
+        assert_eq!(result.cursor.unwrap().session_key, "sess-1".to_string());
+    }
+
+    #[test]
+    fn rejected_token_is_reported() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let handle = std::thread::spawn(move || {
+            let request = server.recv().unwrap();
+            let _ = request.respond(tiny_http::Response::from_string("").with_status_code(401));
+        });
+        let source = DaemonSource {
+            url,
+            token: None,
+            timeout_ms: 5_000,
+        };
+        let err = load_daemon_samples(&source, 10, &DataConfig::default());
+        handle.join().unwrap();
+        match err {
+            Err(DataError::Remote(msg)) => assert!(msg.contains("rejected the token")),
+            Err(e) => panic!("unexpected error: {e:?}"),
+            Ok(_) => panic!("expected an auth error"),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+/// On-disk encoding of an embedding vector blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingFormat {
+    /// Little-endian f32, `dims * 4` bytes
+    F32,
+    /// Little-endian IEEE 754 half precision, `dims * 2` bytes
+    F16,
+    /// Signed bytes, `dims` bytes, optionally followed by a little-endian
+    /// f32 scale. Without a scale, values are mapped to [-1, 1] by 1/127.
+    Int8,
+}
+
+impl EmbeddingFormat {
+    /// Parse the format tag stored alongside a vector. Unknown tags return
+    /// `None` so callers fall back to size-based inference.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag.trim().to_ascii_lowercase().as_str() {
+            "f32" | "float32" => Some(Self::F32),
+            "f16" | "float16" | "half" => Some(Self::F16),
+            "int8" | "i8" | "q8" => Some(Self::Int8),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from blob size alone. f32 wins ties so legacy
+    /// blobs keep decoding exactly as before.
+    pub fn infer(blob_len: usize, dims: usize) -> Option<Self> {
+        if blob_len == dims * 4 {
+            Some(Self::F32)
+        } else if blob_len == dims * 2 {
+            Some(Self::F16)
+        } else if blob_len == dims || blob_len == dims + 4 {
+            Some(Self::Int8)
+        } else {
+            None
+        }
+    }
+}
+
+/// Decode `blob` as a `dims`-wide vector. An explicit `format` is trusted;
+/// otherwise it is inferred from the blob size. Returns `None` when the blob
+/// does not match the expected size for the format.
+pub fn decode_embedding(
+    blob: &[u8],
+    dims: usize,
+    format: Option<EmbeddingFormat>,
+) -> Option<Vec<f64>> {
+    let format = format.or_else(|| EmbeddingFormat::infer(blob.len(), dims))?;
+    match format {
+        EmbeddingFormat::F32 => {
+            if blob.len() != dims * 4 {
+                return None;
+            }
+            Some(
+                blob.chunks_exact(4)
+                    .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f64)
+                    .collect(),
+            )
+        }
+        EmbeddingFormat::F16 => {
+            if blob.len() != dims * 2 {
+                return None;
+            }
+            Some(
+                blob.chunks_exact(2)
+                    .map(|c| f16_to_f32(u16::from_le_bytes([c[0], c[1]])) as f64)
+                    .collect(),
+            )
+        }
+        EmbeddingFormat::Int8 => {
+            let scale = if blob.len() == dims {
+                1.0 / 127.0
+            } else if blob.len() == dims + 4 {
+                let s = &blob[dims..];
+                f32::from_le_bytes([s[0], s[1], s[2], s[3]]) as f64
+            } else {
+                return None;
+            };
+            Some(
+                blob[..dims]
+                    .iter()
+                    .map(|b| *b as i8 as f64 * scale)
+                    .collect(),
+            )
+        }
+    }
+}
+
+pub(crate) fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits >> 15) & 0x1) as u32;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x3ff) as u32;
+
+    let out = match (exponent, mantissa) {
+        (0, 0) => sign << 31,
+        (0, _) => {
+            // Subnormal: renormalize into an f32 normal
+            let mut e = 127 - 15 + 1;
+            let mut m = mantissa;
+            while m & 0x400 == 0 {
+                m <<= 1;
+                e -= 1;
+            }
+            (sign << 31) | ((e as u32) << 23) | ((m & 0x3ff) << 13)
+        }
+        (0x1f, 0) => (sign << 31) | 0x7f80_0000,
+        (0x1f, _) => (sign << 31) | 0x7fc0_0000 | (mantissa << 13),
+        _ => (sign << 31) | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(out)
+}
+
+/// Round to the nearest half-precision value (ties to even). Out-of-range
+/// values saturate to infinity.
+pub(crate) fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+    let exp = exponent - 127 + 15;
+    if exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if exp <= 0 {
+        // Subnormal half: shift the full significand into place
+        if exp < -10 {
+            return sign;
+        }
+        let m = mantissa | 0x80_0000;
+        let shift = (14 - exp) as u32;
+        let half = m >> shift;
+        let round_bit = 1_u32 << (shift - 1);
+        let round_up = m & round_bit != 0 && (m & (round_bit - 1) != 0 || half & 1 != 0);
+        return sign | (half + round_up as u32) as u16;
+    }
+    let half = ((exp as u32) << 10) | (mantissa >> 13);
+    let rest = mantissa & 0x1fff;
+    let round_up = rest > 0x1000 || (rest == 0x1000 && half & 1 != 0);
+    // A carry out of the mantissa correctly bumps the exponent
+    sign | (half + round_up as u32) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_f16_values() {
+        // 1.0, -2.0, 0.5, 0.0 in half precision
+        let halves: [u16; 4] = [0x3c00, 0xc000, 0x3800, 0x0000];
+        let blob: Vec<u8> = halves.iter().flat_map(|h| h.to_le_bytes()).collect();
+        let out = decode_embedding(&blob, 4, None).expect("f16");
+        assert_eq!(out, vec![1.0, -2.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn decodes_f16_subnormal() {
+        // Smallest positive subnormal half = 2^-24
+        let out = decode_embedding(&1_u16.to_le_bytes(), 1, Some(EmbeddingFormat::F16)).unwrap();
+        assert!((out[0] - 2f64.powi(-24)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn f16_encoding_roundtrips_representable_values() {
+        for value in [1.0_f32, -2.0, 0.5, 65504.0, 2f32.powi(-24), 0.0] {
+            assert_eq!(f16_to_f32(f32_to_f16(value)), value);
+        }
+        assert_eq!(f32_to_f16(1.0e6), 0x7c00);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        // 1 + 2^-11 is halfway between 1 and the next half; ties go to even
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+    }
+
+    #[test]
+    fn decodes_int8_with_and_without_scale() {
+        let plain = [127_u8, 0, (-127_i8) as u8];
+        let out = decode_embedding(&plain, 3, Some(EmbeddingFormat::Int8)).unwrap();
+        assert!((out[0] - 1.0).abs() < 1e-9);
+        assert!((out[2] + 1.0).abs() < 1e-9);
+
+        let mut scaled = vec![10_u8, (-20_i8) as u8];
+        scaled.extend_from_slice(&0.5_f32.to_le_bytes());
+        let out = decode_embedding(&scaled, 2, None).unwrap();
+        assert_eq!(out, vec![5.0, -10.0]);
+    }
+
+    #[test]
+    fn explicit_format_must_match_size() {
+        let blob = [0_u8; 8];
+        assert!(decode_embedding(&blob, 2, Some(EmbeddingFormat::F16)).is_none());
+        assert!(decode_embedding(&blob, 2, Some(EmbeddingFormat::F32)).is_some());
+    }
+
+    #[test]
+    fn format_tags_parse() {
+        assert_eq!(EmbeddingFormat::from_tag("F16"), Some(EmbeddingFormat::F16));
+        assert_eq!(
+            EmbeddingFormat::from_tag("int8"),
+            Some(EmbeddingFormat::Int8)
+        );
+        assert_eq!(EmbeddingFormat::from_tag("bf16"), None);
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::checkpoint::{self, CheckpointError, CheckpointMetadata, LoadedCheckpoint};
+use crate::model::ScorerConfig;
+
+/// Files written by [`export_safetensors`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportResult {
+    pub tensors_path: String,
+    pub config_path: String,
+    pub tensor_count: usize,
+}
+
+/// Config sidecar: everything needed to interpret the tensors.
+#[derive(Debug, Serialize)]
+struct ExportConfig<'a> {
+    checkpoint_version: u32,
+    config: &'a ScorerConfig,
+    metadata: Option<&'a CheckpointMetadata>,
+}
+
+/// The sidecar sits beside the tensors as `<stem>.config.json`.
+fn config_path(out: &Path) -> PathBuf {
+    let stem = out
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "model".to_string());
+    out.with_file_name(format!("{stem}.config.json"))
+}
+
+/// Write the checkpoint at `path` to `out` as safetensors: one F64 tensor
+/// per parameter, named as in `inspect`, plus `feature_stats.mean` and
+/// `feature_stats.std` when present. Parameters whose stored length does not
+/// match their config shape are refused rather than exported misshapen.
+pub fn export_safetensors(path: &Path, out: &Path) -> Result<ExportResult, CheckpointError> {
+    let loaded = checkpoint::load(path)?;
+    let tensors = named_tensors(&loaded)?;
+
+    let mut header = serde_json::Map::new();
+    let mut offset = 0_usize;
+    for (name, shape, values) in &tensors {
+        let end = offset + values.len() * 8;
+        header.insert(
+            name.clone(),
+            json!({ "dtype": "F64", "shape": shape, "data_offsets": [offset, end] }),
+        );
+        offset = end;
+    }
+    let mut metadata = BTreeMap::new();
+    metadata.insert("format", "signet-predictor".to_string());
+    metadata.insert("checkpoint_version", loaded.version.to_string());
+    header.insert("__metadata__".to_string(), json!(metadata));
+
+    // The spec allows trailing spaces; padding keeps the data 8-byte aligned.
+    let mut header_bytes = serde_json::to_vec(&Value::Object(header))?;
+    while header_bytes.len() % 8 != 0 {
+        header_bytes.push(b' ');
+    }
+
+    let mut file = File::create(out)?;
+    file.write_all(&(header_bytes.len() as u64).to_le_bytes())?;
+    file.write_all(&header_bytes)?;
+    for (_, _, values) in &tensors {
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        file.write_all(&bytes)?;
+    }
+
+    let sidecar = config_path(out);
+    let config = ExportConfig {
+        checkpoint_version: loaded.version,
+        config: &loaded.config,
+        metadata: loaded.metadata.as_ref(),
+    };
+    std::fs::write(&sidecar, serde_json::to_vec_pretty(&config)?)?;
+
+    Ok(ExportResult {
+        tensors_path: out.display().to_string(),
+        config_path: sidecar.display().to_string(),
+        tensor_count: tensors.len(),
+    })
+}
+
+type NamedTensor<'a> = (String, Vec<usize>, &'a [f64]);
+
+fn named_tensors(loaded: &LoadedCheckpoint) -> Result<Vec<NamedTensor<'_>>, CheckpointError> {
+    let mut tensors = Vec::new();
+    for (shape, values) in checkpoint::param_shapes(loaded)
+        .into_iter()
+        .zip(&loaded.params)
+    {
+        if shape.rows * shape.cols != values.len() {
+            return Err(CheckpointError::InvalidFormat(format!(
+                "parameter {} holds {} values but its shape is {}x{}",
+                shape.name,
+                values.len(),
+                shape.rows,
+                shape.cols
+            )));
+        }
+        tensors.push((shape.name, vec![shape.rows, shape.cols], values.as_slice()));
+    }
+    if let Some(ref stats) = loaded.feature_stats {
+        tensors.push((
+            "feature_stats.mean".to_string(),
+            vec![stats.mean.len()],
+            stats.mean.as_slice(),
+        ));
+        tensors.push((
+            "feature_stats.std".to_string(),
+            vec![stats.std.len()],
+            stats.std.as_slice(),
+        ));
+    }
+    Ok(tensors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        autograd::{Rng, Tape},
+        model::CrossAttentionScorer,
+    };
+
+    #[test]
+    fn writes_a_readable_safetensors_file() {
+        let config = ScorerConfig {
+            native_dim: 4,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 3,
+            hash_buckets: 16,
+            project_slots: 2,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(43);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, config);
+        let dir = std::env::temp_dir().join("predictor_test_export");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let ckpt = dir.join("model.sgpt");
+        let out = dir.join("model.safetensors");
+        checkpoint::save(&ckpt, &model, &tape, 0, None, None, None).unwrap();
+
+        let result = export_safetensors(&ckpt, &out).unwrap();
+        assert_eq!(result.tensor_count, model.param_indices().len());
+        let bytes = std::fs::read(&out).unwrap();
+        let sidecar: Value =
+            serde_json::from_slice(&std::fs::read(&result.config_path).unwrap()).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let header_len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+        assert_eq!(header_len % 8, 0);
+        let header: Value = serde_json::from_slice(&bytes[8..8 + header_len]).unwrap();
+        let q = &header["q_proj"];
+        assert_eq!(q["dtype"], "F64");
+        assert_eq!(q["shape"], json!([4, 4]));
+        let start = q["data_offsets"][0].as_u64().unwrap() as usize;
+        let data = &bytes[8 + header_len + start..];
+        let first = f64::from_le_bytes(data[..8].try_into().unwrap());
+        assert_eq!(first, tape.params()[model.param_indices()[1]].data[0]);
+        assert_eq!(sidecar["config"]["hash_buckets"], 16);
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::FEATURE_DIM;
+
+/// Bumped whenever a feature is added or its definition changes.
+/// v2 added pinned, project_match, content_length and tag_overlap.
+pub const FEATURE_SCHEMA_VERSION: u32 = 2;
+
+/// Names of the full candidate feature vector, in the order
+/// `build_features` emits them (see `protocol::FEATURE_DIM`).
+pub const FEATURE_NAMES: [&str; FEATURE_DIM] = [
+    "recency",
+    "importance",
+    "access_count",
+    "time_of_day_sin",
+    "time_of_day_cos",
+    "day_of_week_sin",
+    "day_of_week_cos",
+    "month_sin",
+    "month_cos",
+    "session_gap",
+    "has_embedding",
+    "superseded",
+    "entity_slot",
+    "aspect_slot",
+    "is_constraint",
+    "structural_density",
+    "ka_traversal",
+    "pinned",
+    "project_match",
+    "content_length",
+    "tag_overlap",
+];
+
+/// Width of the v1 vector, before pinned through tag_overlap were appended.
+pub const V1_FEATURE_DIM: usize = 17;
+
+/// The subset of features a model was trained on. Recorded in the
+/// checkpoint config so a model is never fed a differently shaped vector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureSchema {
+    pub version: u32,
+    /// Enabled feature names, in vector order
+    pub features: Vec<String>,
+}
+
+impl Default for FeatureSchema {
+    fn default() -> Self {
+        Self {
+            version: FEATURE_SCHEMA_VERSION,
+            features: FEATURE_NAMES.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+impl FeatureSchema {
+    /// Every feature except `disabled`. Unknown names are an error so a typo
+    /// does not silently train on the full set.
+    pub fn without(disabled: &[String]) -> Result<Self, String> {
+        if let Some(unknown) = disabled
+            .iter()
+            .find(|name| !FEATURE_NAMES.contains(&name.as_str()))
+        {
+            return Err(format!("unknown feature: {unknown}"));
+        }
+        let mut schema = Self::default();
+        schema.features.retain(|name| !disabled.contains(name));
+        Ok(schema)
+    }
+
+    /// The v1 feature set, for models saved before schemas were recorded.
+    pub fn v1() -> Self {
+        Self {
+            version: 1,
+            features: FEATURE_NAMES[..V1_FEATURE_DIM]
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.features.iter().any(|f| f == name)
+    }
+
+    /// Check the schema can be produced by this build: a version no newer
+    /// than ours and known, unique names in canonical order.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version > FEATURE_SCHEMA_VERSION {
+            return Err(format!(
+                "feature schema v{} is newer than supported v{}",
+                self.version, FEATURE_SCHEMA_VERSION
+            ));
+        }
+        let mut last = None;
+        for name in &self.features {
+            let idx = FEATURE_NAMES
+                .iter()
+                .position(|known| known == name)
+                .ok_or_else(|| format!("unknown feature: {name}"))?;
+            if last.is_some_and(|prev| idx <= prev) {
+                return Err(format!("feature {name} is duplicated or out of order"));
+            }
+            last = Some(idx);
+        }
+        Ok(())
+    }
+
+    /// Select the enabled features from a full `FEATURE_DIM` vector.
+    pub fn project(&self, full: &[f64]) -> Vec<f64> {
+        if self.dim() == full.len() {
+            return full.to_vec();
+        }
+        FEATURE_NAMES
+            .iter()
+            .zip(full)
+            .filter(|(name, _)| self.is_enabled(name))
+            .map(|(_, value)| *value)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_schema_covers_every_feature() {
+        let schema = FeatureSchema::default();
+        assert_eq!(schema.dim(), FEATURE_DIM);
+        assert!(schema.validate().is_ok());
+    }
+
+    #[test]
+    fn disabled_features_are_dropped_from_projection() {
+        let schema =
+            FeatureSchema::without(&["importance".to_string(), "ka_traversal".to_string()])
+                .unwrap();
+        assert_eq!(schema.dim(), FEATURE_DIM - 2);
+        assert!(schema.validate().is_ok());
+
+        let full: Vec<f64> = (0..FEATURE_DIM).map(|i| i as f64).collect();
+        let projected = schema.project(&full);
+        assert_eq!(projected.len(), FEATURE_DIM - 2);
+        assert_eq!(projected[0], 0.0);
+        assert_eq!(projected[1], 2.0);
+        assert_eq!(*projected.last().unwrap(), (FEATURE_DIM - 1) as f64);
+    }
+
+    #[test]
+    fn rejects_unknown_or_reordered_features() {
+        assert!(FeatureSchema::without(&["bogus".to_string()]).is_err());
+
+        let mut schema = FeatureSchema::default();
+        schema.features.swap(0, 1);
+        assert!(schema.validate().is_err());
+
+        let future = FeatureSchema {
+            version: FEATURE_SCHEMA_VERSION + 1,
+            ..FeatureSchema::default()
+        };
+        assert!(future.validate().is_err());
+    }
+}
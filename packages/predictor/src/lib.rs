@@ -1,7 +1,18 @@
 pub mod autograd;
+pub mod cache;
 pub mod checkpoint;
 pub mod data;
+pub mod embedding;
+pub mod export;
+pub mod features;
+pub mod logging;
+pub mod metrics;
 pub mod model;
+pub mod online;
 pub mod protocol;
+pub mod sanitize;
 pub mod tokenizer;
 pub mod training;
+pub mod transport;
+pub mod validation;
+pub mod watch;
@@ -0,0 +1,165 @@
+use std::{
+    cell::Cell,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{Map, Value};
+
+/// Severity, most severe first so `level <= threshold` means "emit".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw.to_ascii_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "warn" | "warning" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            other => Err(format!(
+                "unknown log level: {other} (expected error, warn, info or debug)"
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+        }
+    }
+}
+
+struct Logger {
+    threshold: Level,
+    sink: Mutex<Box<dyn Write + Send>>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+thread_local! {
+    /// Request being handled on this thread, stamped on every record
+    static CURRENT_REQUEST: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Send records at or above `threshold` to `file`, or stderr when `None`.
+/// Only the first call takes effect; until then records go to stderr at
+/// info.
+pub fn init(threshold: Level, file: Option<&Path>) -> io::Result<()> {
+    let sink: Box<dyn Write + Send> = match file {
+        Some(path) => Box::new(OpenOptions::new().create(true).append(true).open(path)?),
+        None => Box::new(io::stderr()),
+    };
+    let _ = LOGGER.set(Logger {
+        threshold,
+        sink: Mutex::new(sink),
+    });
+    Ok(())
+}
+
+fn logger() -> &'static Logger {
+    LOGGER.get_or_init(|| Logger {
+        threshold: Level::Info,
+        sink: Mutex::new(Box::new(io::stderr())),
+    })
+}
+
+pub fn enabled(level: Level) -> bool {
+    level <= logger().threshold
+}
+
+/// Tag records from this thread with a request ID until cleared.
+pub fn set_request(request: Option<u64>) {
+    CURRENT_REQUEST.with(|current| current.set(request));
+}
+
+/// One JSON object per line: timestamp, level, message, the current
+/// request if any, then `fields`.
+pub fn format_record(
+    ts_ms: u64,
+    level: Level,
+    message: &str,
+    request: Option<u64>,
+    fields: &[(&str, Value)],
+) -> String {
+    let mut record = Map::new();
+    record.insert("ts_ms".to_string(), ts_ms.into());
+    record.insert("level".to_string(), level.as_str().into());
+    record.insert("msg".to_string(), message.into());
+    if let Some(request) = request {
+        record.insert("request".to_string(), request.into());
+    }
+    for (key, value) in fields {
+        record.insert((*key).to_string(), value.clone());
+    }
+    Value::Object(record).to_string()
+}
+
+pub fn log(level: Level, message: &str, fields: &[(&str, Value)]) {
+    if !enabled(level) {
+        return;
+    }
+    let ts_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let request = CURRENT_REQUEST.with(Cell::get);
+    let line = format_record(ts_ms, level, message, request, fields);
+    if let Ok(mut sink) = logger().sink.lock() {
+        let _ = writeln!(sink, "{line}");
+        let _ = sink.flush();
+    }
+}
+
+pub fn error(message: &str, fields: &[(&str, Value)]) {
+    log(Level::Error, message, fields);
+}
+
+pub fn warn(message: &str, fields: &[(&str, Value)]) {
+    log(Level::Warn, message, fields);
+}
+
+pub fn info(message: &str, fields: &[(&str, Value)]) {
+    log(Level::Info, message, fields);
+}
+
+pub fn debug(message: &str, fields: &[(&str, Value)]) {
+    log(Level::Debug, message, fields);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_single_json_lines() {
+        let line = format_record(
+            1_700_000_000_000,
+            Level::Warn,
+            "checkpoint save failed",
+            Some(7),
+            &[("path", "/tmp/a\nb".into()), ("duration_ms", 12.into())],
+        );
+        assert!(!line.contains('\n'));
+        let parsed: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["level"], "warn");
+        assert_eq!(parsed["request"], 7);
+        assert_eq!(parsed["path"], "/tmp/a\nb");
+        assert_eq!(parsed["duration_ms"], 12);
+
+        assert!(Level::Error < Level::Debug);
+        assert_eq!(Level::parse("WARNING"), Ok(Level::Warn));
+        assert!(Level::parse("loud").is_err());
+    }
+}
@@ -1,18 +1,62 @@
-use std::io::{self, BufRead, Write};
+use std::{
+    collections::BTreeMap,
+    io::{self, Read},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc, Mutex, OnceLock,
+    },
+};
 
 use predictor::{
     autograd::{Rng, Tape},
+    cache::{self, EncodingCache, TtlCache},
     checkpoint,
-    data::{self, DataConfig, TrainingSample},
-    model::{CandidateInput, CrossAttentionScorer, ScorerConfig},
+    data::{self, DaemonSource, DataConfig, DataSource, TrainingCursor, TrainingSample},
+    embedding::decode_embedding,
+    export,
+    features::FeatureSchema,
+    logging::{self, Level},
+    metrics::{self, Metrics},
+    model::{self, CandidateInput, CrossAttentionScorer, ScorerConfig},
+    online::{self, FeedbackBuffer},
     protocol::{
-        JsonRpcRequest, JsonRpcResponse, SaveCheckpointParams, SaveCheckpointResult, ScoreParams,
-        ScoreResult, ScoredMemory, StatusResult, TrainFromDbParams, TrainFromDbResult, TrainParams,
-        TrainResult,
+        BenchmarkParams, BenchmarkResult, CancelResult, CheckpointVersions, CloneModelParams,
+        CloneModelResult, ConfigResult, CreateModelParams, CreateModelResult,
+        DiffCheckpointsParams, EvaluateParams, EvaluateResult, ExportSafetensorsParams,
+        InspectCheckpointParams, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse,
+        ListCheckpointsParams, ListCheckpointsResult, LoadCheckpointParams, LoadCheckpointResult,
+        MetricsResult, PingResult, RecordFeedbackParams, RecordFeedbackResult,
+        RestoreCheckpointParams, RestoreCheckpointResult, SaveCheckpointParams,
+        SaveCheckpointResult, ScoreCacheMode, ScoreParams, ScoreResult, ScoredMemory,
+        SetConfigParams, ShutdownParams, ShutdownResult, StatusResult, TrainFromDbParams,
+        TrainFromDbResult, TrainParams, TrainResult, WatchTrainedParams, FEATURE_DIM, METHODS,
+        PREDICTOR_VERSION,
     },
-    training::{self, train_batch, train_epochs, Adam},
+    sanitize::{sanitize_sample, sanitize_vector, SanitizeReport, DEFAULT_MAX_CORRUPT_FRACTION},
+    training::{
+        self, train_batch, train_epochs, train_epochs_streaming, Adam, AugmentedSource,
+        CancelToken, Hyperparameters, TrainLimits,
+    },
+    transport::{self, Framing, Protocol},
+    validation::{self, FieldError, RequestLimits, Validate},
+    watch::{DbFingerprint, QuietHours, Watcher},
 };
 
+/// Seed for freshly initialised weights
+const INIT_SEED: u64 = 0x51_9e7;
+
+/// Samples held back from training to check score stability
+const CANARY_SAMPLES: usize = 10;
+
+/// Upper bound on jittered copies per sample, to keep memory in check
+const MAX_AUGMENTATION_FACTOR: usize = 8;
+
+/// Training data for one `train_from_db` call
+enum TrainSet {
+    Memory(Vec<TrainingSample>),
+    Stream(Box<data::SampleStream>),
+}
+
 struct PredictorService {
     tape: Tape,
     model: CrossAttentionScorer,
@@ -21,79 +65,401 @@ struct PredictorService {
     train_steps: u64,
     training_pairs: usize,
     last_trained: Option<String>,
+    cursor: Option<TrainingCursor>,
+    /// Weights came from a checkpoint rather than a fresh initialisation
+    restored: bool,
+    /// Storage flags for checkpoints saved after training
+    checkpoint_flags: u32,
+    /// `--checkpoint`, where `shutdown` saves by default
+    checkpoint_path: Option<String>,
+    /// `train_steps` as of the last checkpoint written or loaded
+    saved_steps: u64,
+    saved_at: std::time::Instant,
+    /// When `train` updates are saved to `checkpoint_path`
+    autosave: checkpoint::AutosavePolicy,
+    /// `record_feedback` events awaiting an online update
+    feedback: FeedbackBuffer,
+    /// `shutdown` was answered; the main loop exits
+    shutting_down: bool,
+    /// Set by the stdin reader when a `cancel` request arrives
+    cancel: CancelToken,
+    /// A `train_from_db` call is running
+    training_active: Arc<AtomicBool>,
+    /// `restored`, readable from the request readers for `ping`
+    checkpoint_loaded: Arc<AtomicBool>,
+    /// Shared with the scoring worker, which records `score` calls
+    metrics: Arc<Mutex<Metrics>>,
+    /// Runtime-tunable defaults, saved in checkpoint metadata
+    hyperparameters: Hyperparameters,
+    /// `set_config` changed something since the last checkpoint
+    config_unsaved: bool,
+    /// Size caps checked before `train` and `record_feedback` run
+    limits: RequestLimits,
+    /// `optimizer` skips the encoder weights
+    encoder_frozen: bool,
+    /// Entries in each scoring snapshot's encoding cache; 0 turns it off
+    encoding_cache: usize,
+    /// How long `score` results are reused; zero turns the cache off
+    score_cache_ttl: std::time::Duration,
 }
 
-impl PredictorService {
-    fn new(native_dim: usize) -> Self {
-        let mut tape = Tape::new();
-        let mut rng = Rng::new(0x51_9e7);
-        let mut config = ScorerConfig::default();
-        config.native_dim = native_dim;
-        let model = CrossAttentionScorer::new(&mut tape, &mut rng, config);
-        let optimizer = Adam::new(&tape, 1e-3);
-        Self {
-            tape,
-            model,
-            optimizer,
-            model_version: 1,
-            train_steps: 0,
-            training_pairs: 0,
-            last_trained: None,
+/// Model used when a request has no `model` field
+const DEFAULT_MODEL: &str = "default";
+
+/// `--encoding-cache` default: candidate encodings kept per model
+const DEFAULT_ENCODING_CACHE: usize = 8192;
+
+/// Distinct `score` requests remembered per model while the score cache
+/// is on. A session re-asks the same few, so this stays small.
+const SCORE_CACHE_ENTRIES: usize = 256;
+
+/// The default model plus any made with `create_model`. Each has its own
+/// weights, optimizer, lineage and checkpoint; cancellation, limits and
+/// metrics are shared.
+struct Models {
+    default: PredictorService,
+    named: BTreeMap<String, PredictorService>,
+}
+
+impl Models {
+    fn get(&self, name: &str) -> Option<&PredictorService> {
+        if name == DEFAULT_MODEL {
+            Some(&self.default)
+        } else {
+            self.named.get(name)
         }
     }
 
-    fn status(&self) -> StatusResult {
-        let config = self.model.config();
-        StatusResult {
-            trained: self.train_steps > 0,
-            training_pairs: self.training_pairs,
-            model_version: self.model_version,
-            last_trained: self.last_trained.clone(),
-            native_dimensions: config.native_dim,
-            feature_dimensions: config.extra_features,
+    fn get_mut(&mut self, name: &str) -> Option<&mut PredictorService> {
+        if name == DEFAULT_MODEL {
+            Some(&mut self.default)
+        } else {
+            self.named.get_mut(name)
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        std::iter::once(DEFAULT_MODEL.to_string())
+            .chain(self.named.keys().cloned())
+            .collect()
+    }
+
+    /// A name `create_model` or `clone_model` may use.
+    fn check_new_name(&self, name: &str) -> Result<(), String> {
+        let valid = !name.is_empty()
+            && name.len() <= 64
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(format!(
+                "invalid model name {name:?}: use up to 64 letters, digits, '-', '_' or '.'"
+            ));
+        }
+        if self.get(name).is_some() {
+            return Err(format!("model {name} already exists"));
+        }
+        Ok(())
+    }
+
+    fn create(&mut self, params: CreateModelParams) -> Result<CreateModelResult, String> {
+        let CreateModelParams {
+            name,
+            checkpoint_path,
+        } = params;
+        self.check_new_name(&name)?;
+
+        let mut service = self.default.sibling();
+        let mut restored = false;
+        if let Some(ref path) = checkpoint_path {
+            let p = std::path::Path::new(path);
+            if p.exists() {
+                let loaded =
+                    checkpoint::load(p).map_err(|e| format!("checkpoint load error: {e:?}"))?;
+                service
+                    .apply_loaded(&loaded)
+                    .map_err(|e| format!("checkpoint apply error: {e:?}"))?;
+                restored = true;
+            }
+        }
+        service.checkpoint_path = checkpoint_path.clone();
+        self.named.insert(name.clone(), service);
+        logging::info(
+            "model created",
+            &[
+                ("model", name.as_str().into()),
+                ("restored", restored.into()),
+            ],
+        );
+        Ok(CreateModelResult {
+            name,
+            checkpoint_path,
+            restored,
+        })
+    }
+
+    /// Copy `source`'s weights and lineage into a new model with a fresh
+    /// optimizer. The training cursor is not copied, so the clone's first
+    /// `train_from_db` covers the full history.
+    fn clone_model(&mut self, params: CloneModelParams) -> Result<CloneModelResult, String> {
+        let CloneModelParams {
+            source,
+            name,
+            checkpoint_path,
+            freeze_encoder,
+        } = params;
+        let source = source.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        self.check_new_name(&name)?;
+        let from = self
+            .get(&source)
+            .ok_or_else(|| format!("unknown model: {source}"))?;
+
+        let mut service = from.sibling();
+        service.tape = from.tape.snapshot();
+        service.model = from.model.clone();
+        service.optimizer = Adam::new(&service.tape, service.hyperparameters.learning_rate);
+        service.model_version = from.model_version;
+        service.train_steps = from.train_steps;
+        service.training_pairs = from.training_pairs;
+        service.last_trained = from.last_trained.clone();
+        service.saved_steps = from.train_steps;
+        // Learned weights fix the project slot count, as for a restore
+        service.restored = true;
+        service.encoder_frozen = freeze_encoder || from.encoder_frozen;
+        if service.encoder_frozen {
+            service
+                .optimizer
+                .freeze(&service.model.encoder_param_indices());
+        }
+        if let Some(ref path) = checkpoint_path {
+            service
+                .save_to(std::path::Path::new(path), service.checkpoint_flags)
+                .map_err(|e| format!("checkpoint save error: {e:?}"))?;
+        }
+        service.checkpoint_path = checkpoint_path.clone();
+
+        let result = CloneModelResult {
+            name: name.clone(),
+            source,
+            checkpoint_path,
+            encoder_frozen: service.encoder_frozen,
+            source_steps: service.train_steps,
+        };
+        self.named.insert(name, service);
+        logging::info(
+            "model cloned",
+            &[
+                ("model", result.name.as_str().into()),
+                ("source", result.source.as_str().into()),
+                ("encoder_frozen", result.encoder_frozen.into()),
+            ],
+        );
+        Ok(result)
+    }
+
+    fn status(&self, name: &str) -> Option<StatusResult> {
+        let mut status = self.get(name)?.status();
+        status.model = name.to_string();
+        status.models = self.names();
+        Some(status)
+    }
+
+    /// Save every model with unsaved changes; `params.path` applies to the
+    /// default model only.
+    fn shutdown(&mut self, params: ShutdownParams) -> Result<ShutdownResult, String> {
+        let mut models = BTreeMap::new();
+        for (name, service) in &mut self.named {
+            models.insert(name.clone(), service.shutdown(ShutdownParams::default())?);
+        }
+        let mut result = self.default.shutdown(params)?;
+        result.models = models;
+        Ok(result)
+    }
+
+    /// Route one request to the model it names. Returns that model's name
+    /// once the request has been handled.
+    fn dispatch(&mut self, out: &mut Output, mut req: JsonRpcRequest) -> Option<String> {
+        let name = match validation::take_model(&mut req.params) {
+            Ok(name) => name.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            Err(err) => {
+                write_field_error(out, req.id, &err);
+                return None;
+            }
+        };
+        match req.method.as_str() {
+            "create_model" => {
+                let mut created = None;
+                handle_rpc(out, req.id, req.params, |p| {
+                    let result = self.create(p)?;
+                    created = Some(result.name.clone());
+                    Ok(result)
+                });
+                return created;
+            }
+            "clone_model" => {
+                let mut created = None;
+                handle_rpc(out, req.id, req.params, |p| {
+                    let result = self.clone_model(p)?;
+                    created = Some(result.name.clone());
+                    Ok(result)
+                });
+                return created;
+            }
+            "shutdown" => {
+                // Takes no required params, so a bare request is fine
+                let params = if req.params.is_null() {
+                    serde_json::json!({})
+                } else {
+                    req.params
+                };
+                handle_rpc(out, req.id, params, |p| self.shutdown(p));
+                return None;
+            }
+            "status" => {
+                if let Some(status) = self.status(&name) {
+                    write_response(out, &JsonRpcResponse::success(req.id, status));
+                    return Some(name);
+                }
+            }
+            _ => {
+                if let Some(service) = self.get_mut(&name) {
+                    dispatch(service, out, req);
+                    return Some(name);
+                }
+            }
         }
+        write_response(out, &unknown_model(req.id, &name));
+        None
+    }
+}
+
+fn unknown_model(id: serde_json::Value, name: &str) -> JsonRpcResponse<serde_json::Value> {
+    JsonRpcResponse::failure_with_data(
+        id,
+        -32000,
+        format!("unknown model: {name}"),
+        serde_json::json!({ "path": "model" }),
+    )
+}
+
+/// The source named by a request's `db_path` and `daemon` params; exactly
+/// one must be given.
+fn data_source<'a>(
+    db_path: Option<&'a str>,
+    daemon: Option<&'a DaemonSource>,
+) -> Result<DataSource<'a>, String> {
+    match (db_path, daemon) {
+        (Some(db_path), None) => Ok(DataSource::Sqlite(std::path::Path::new(db_path))),
+        (None, Some(daemon)) => Ok(DataSource::Daemon(daemon)),
+        (Some(_), Some(_)) => Err("db_path and daemon are mutually exclusive".to_string()),
+        (None, None) => Err("db_path or daemon is required".to_string()),
     }
+}
+
+/// Weights `score` runs against. The main loop publishes a fresh copy after
+/// each call that changes parameters, so scoring never sees a half-applied
+/// update and never waits for training.
+struct ScoringSnapshot {
+    tape: Tape,
+    model: CrossAttentionScorer,
+    /// Encodings from these weights. A new snapshot is published whenever
+    /// they change, a `model_version` bump or a load, so it starts empty.
+    encodings: EncodingCache,
+    /// Recent results, keyed by request hash; emptied the same way
+    results: TtlCache<ScoreResult>,
+}
 
+impl ScoringSnapshot {
     fn score(&mut self, params: ScoreParams) -> Result<ScoreResult, String> {
+        if !self.results.is_enabled() || params.cache == ScoreCacheMode::Bypass {
+            return self.compute(params);
+        }
+        let key = cache::score_request_hash(&params);
+        let now = std::time::Instant::now();
+        if params.cache.reads() {
+            let max_age = params
+                .cache_max_age_ms
+                .map(std::time::Duration::from_millis);
+            if let Some(hit) = self.results.get(key, now, max_age) {
+                return Ok(ScoreResult {
+                    cached: true,
+                    ..hit.clone()
+                });
+            }
+        }
+        let result = self.compute(params)?;
+        self.results.insert(key, now, result.clone());
+        Ok(result)
+    }
+
+    fn compute(&mut self, params: ScoreParams) -> Result<ScoreResult, String> {
         let ScoreParams {
             context_embedding,
             candidate_ids,
             candidate_embeddings,
+            candidate_embedding_blobs,
+            embedding_format,
             candidate_texts,
             candidate_features,
             project_slot,
+            top_k,
+            ..
         } = params;
 
-        if !candidate_embeddings.is_empty() && candidate_ids.len() != candidate_embeddings.len() {
-            return Err("candidate_ids and candidate_embeddings length mismatch".to_string());
-        }
-        if !candidate_texts.is_empty() && candidate_ids.len() != candidate_texts.len() {
-            return Err("candidate_ids and candidate_texts length mismatch".to_string());
-        }
-
         let cfg = self.model.config();
-        let embeddings = if candidate_embeddings.is_empty() {
+        let mut embeddings = if candidate_embeddings.is_empty() {
             vec![Vec::new(); candidate_ids.len()]
         } else {
             candidate_embeddings
         };
+        for (embedding, blob) in embeddings.iter_mut().zip(&candidate_embedding_blobs) {
+            if embedding.is_empty() {
+                if let Some(decoded) = blob
+                    .as_deref()
+                    .and_then(|b| decode_embedding(b, cfg.native_dim, embedding_format))
+                {
+                    *embedding = decoded;
+                }
+            }
+        }
         let texts = if candidate_texts.is_empty() {
             vec![None; candidate_ids.len()]
         } else {
             candidate_texts
         };
 
-        let features = if candidate_features.is_empty() {
+        let mut features = if candidate_features.is_empty() {
             vec![vec![0.0; cfg.extra_features]; candidate_ids.len()]
-        } else if candidate_features.len() == candidate_ids.len() {
-            candidate_features
         } else {
-            return Err("candidate_ids and candidate_features length mismatch".to_string());
+            cfg.project_features(candidate_features)
         };
         if features.iter().any(|f| f.len() != cfg.extra_features) {
             return Err("candidate_features row has invalid dimension".to_string());
         }
 
+        // NaN/Inf would spread through attention to every candidate's score.
+        // A corrupt candidate vector is dropped so the candidate falls back
+        // to its text; a corrupt context leaves nothing to score against.
+        let mut context_embedding = context_embedding;
+        let mut sanitized = SanitizeReport::default();
+        if !sanitize_vector(
+            &mut context_embedding,
+            DEFAULT_MAX_CORRUPT_FRACTION,
+            &mut sanitized,
+        ) {
+            return Err("context_embedding has too many NaN/Inf values".to_string());
+        }
+        for embedding in &mut embeddings {
+            if !sanitize_vector(embedding, DEFAULT_MAX_CORRUPT_FRACTION, &mut sanitized) {
+                embedding.clear();
+            }
+        }
+        for row in &mut features {
+            if !sanitize_vector(row, DEFAULT_MAX_CORRUPT_FRACTION, &mut sanitized) {
+                row.fill(0.0);
+            }
+        }
+
         let candidates = candidate_ids
             .iter()
             .zip(embeddings.iter())
@@ -101,7 +467,7 @@ impl PredictorService {
             .zip(features.iter())
             .map(|(((id, embedding), text), feature)| CandidateInput {
                 id,
-                embedding: if embedding.len() == cfg.native_dim {
+                embedding: if self.model.accepts_embedding_dim(embedding.len()) {
                     Some(embedding.as_slice())
                 } else {
                     None
@@ -111,12 +477,23 @@ impl PredictorService {
             })
             .collect::<Vec<_>>();
 
-        let scored = self.model.score(
-            &mut self.tape,
-            &context_embedding,
-            &candidates,
-            project_slot,
-        )?;
+        let scored = match top_k {
+            Some(top_k) => self.model.score_top_k(
+                &mut self.tape,
+                &context_embedding,
+                &candidates,
+                project_slot,
+                top_k,
+                &mut self.encodings,
+            )?,
+            None => self.model.score_cached(
+                &mut self.tape,
+                &context_embedding,
+                &candidates,
+                project_slot,
+                &mut self.encodings,
+            )?,
+        };
 
         Ok(ScoreResult {
             scores: scored
@@ -126,8 +503,359 @@ impl PredictorService {
                     score: entry.score,
                 })
                 .collect(),
+            sanitized,
+            cached: false,
+        })
+    }
+
+    /// Time `score` on synthetic candidates against a copy of the weights.
+    /// Both caches are off, so every iteration does the full work. Runs on
+    /// the scoring worker, so `score` calls wait for it but training and
+    /// `shutdown` do not.
+    fn benchmark(&self, params: BenchmarkParams) -> Result<BenchmarkResult, String> {
+        let native_dim = self.model.config().native_dim;
+        let embedding_dim = match params.text_tokens {
+            Some(_) => None,
+            None => Some(params.embedding_dim.unwrap_or(native_dim)),
+        };
+        if let Some(dim) = embedding_dim.filter(|&dim| !self.model.accepts_embedding_dim(dim)) {
+            return Err(format!(
+                "embedding_dim {dim} is neither the native dimension nor an adapter's"
+            ));
+        }
+
+        let mut rng = Rng::new(params.seed);
+        let vector = |rng: &mut Rng, len: usize| -> Vec<f64> {
+            (0..len).map(|_| rng.gauss(0.0, 1.0)).collect()
+        };
+        let context_embedding = vector(&mut rng, native_dim);
+        let candidate_embeddings = match embedding_dim {
+            Some(dim) => (0..params.candidates)
+                .map(|_| vector(&mut rng, dim))
+                .collect(),
+            None => Vec::new(),
+        };
+        let candidate_texts = match params.text_tokens {
+            Some(tokens) => (0..params.candidates)
+                .map(|_| {
+                    let words = (0..tokens)
+                        .map(|_| format!("w{}", rng.next_u64() % 10_000))
+                        .collect::<Vec<_>>();
+                    Some(words.join(" "))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let request = ScoreParams {
+            context_embedding,
+            candidate_ids: (0..params.candidates)
+                .map(|i| format!("bench-{i}"))
+                .collect(),
+            candidate_embeddings,
+            candidate_embedding_blobs: Vec::new(),
+            embedding_format: None,
+            candidate_texts,
+            candidate_features: Vec::new(),
+            project_slot: 0,
+            top_k: params.top_k,
+            cache: ScoreCacheMode::Bypass,
+            cache_max_age_ms: None,
+        };
+
+        let mut snapshot = ScoringSnapshot {
+            tape: self.tape.snapshot(),
+            model: self.model.clone(),
+            encodings: EncodingCache::new(0),
+            results: TtlCache::new(std::time::Duration::ZERO, 0),
+        };
+        let mut latencies_ms = Vec::with_capacity(params.iterations);
+        for _ in 0..params.iterations {
+            let request = request.clone();
+            let start = std::time::Instant::now();
+            snapshot.score(request)?;
+            latencies_ms.push(start.elapsed().as_secs_f64() * 1000.0);
+        }
+
+        let total_ms: f64 = latencies_ms.iter().sum();
+        latencies_ms.sort_by(f64::total_cmp);
+        let per_sec = |count: usize| {
+            if total_ms > 0.0 {
+                count as f64 * 1000.0 / total_ms
+            } else {
+                0.0
+            }
+        };
+        Ok(BenchmarkResult {
+            candidates: params.candidates,
+            iterations: params.iterations,
+            embedding_dim,
+            text_tokens: params.text_tokens,
+            total_ms,
+            inferences_per_sec: per_sec(params.candidates * params.iterations),
+            calls_per_sec: per_sec(params.iterations),
+            p50_ms: metrics::percentile(&latencies_ms, 0.50),
+            p95_ms: metrics::percentile(&latencies_ms, 0.95),
+            peak_rss_bytes: metrics::peak_rss_bytes(),
         })
     }
+}
+
+impl PredictorService {
+    fn new(
+        native_dim: usize,
+        adapter_dims: Vec<usize>,
+        feature_schema: FeatureSchema,
+        max_tokens: Option<usize>,
+        bigram_buckets: usize,
+    ) -> Self {
+        let config = ScorerConfig {
+            native_dim,
+            adapter_dims,
+            extra_features: feature_schema.dim(),
+            feature_schema: Some(feature_schema),
+            max_tokens,
+            bigram_buckets,
+            ..ScorerConfig::default()
+        };
+        Self::with_config(config)
+    }
+
+    fn with_config(config: ScorerConfig) -> Self {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(INIT_SEED);
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, config);
+        let hyperparameters = Hyperparameters::default();
+        let optimizer = Adam::new(&tape, hyperparameters.learning_rate);
+        Self {
+            tape,
+            model,
+            optimizer,
+            model_version: 1,
+            train_steps: 0,
+            training_pairs: 0,
+            last_trained: None,
+            cursor: None,
+            restored: false,
+            checkpoint_flags: 0,
+            checkpoint_path: None,
+            saved_steps: 0,
+            saved_at: std::time::Instant::now(),
+            autosave: checkpoint::AutosavePolicy::default(),
+            feedback: FeedbackBuffer::new(online::DEFAULT_MIN_UPDATE_INTERVAL),
+            shutting_down: false,
+            cancel: CancelToken::default(),
+            training_active: Arc::new(AtomicBool::new(false)),
+            checkpoint_loaded: Arc::new(AtomicBool::new(false)),
+            metrics: Arc::new(Mutex::new(Metrics::new())),
+            hyperparameters,
+            config_unsaved: false,
+            limits: RequestLimits::default(),
+            encoder_frozen: false,
+            encoding_cache: DEFAULT_ENCODING_CACHE,
+            score_cache_ttl: std::time::Duration::ZERO,
+        }
+    }
+
+    /// A fresh model with this one's architecture and runtime settings,
+    /// sharing cancellation and metrics. Lineage and checkpoint path start
+    /// empty.
+    fn sibling(&self) -> Self {
+        let mut sibling = Self::with_config(self.model.config().clone());
+        sibling.optimizer.set_lr(self.hyperparameters.learning_rate);
+        sibling.hyperparameters = self.hyperparameters;
+        sibling.checkpoint_flags = self.checkpoint_flags;
+        sibling.autosave = self.autosave;
+        sibling.feedback = FeedbackBuffer::new(self.feedback.min_interval());
+        sibling.cancel = self.cancel.clone();
+        sibling.training_active = Arc::clone(&self.training_active);
+        sibling.metrics = Arc::clone(&self.metrics);
+        sibling.limits = self.limits;
+        sibling.encoding_cache = self.encoding_cache;
+        sibling.score_cache_ttl = self.score_cache_ttl;
+        sibling
+    }
+
+    /// Adopt a loaded checkpoint's config, weights, optimizer state and
+    /// lineage. The model is rebuilt from the saved config, which wins over
+    /// the startup flags.
+    fn apply_loaded(
+        &mut self,
+        loaded: &checkpoint::LoadedCheckpoint,
+    ) -> Result<(), checkpoint::CheckpointError> {
+        let (tape, model) = checkpoint::rebuild_from_checkpoint(loaded, &mut Rng::new(INIT_SEED))?;
+        if model.config() != self.model.config() {
+            logging::warn(
+                "checkpoint config differs from startup flags; using the checkpoint's",
+                &[],
+            );
+        }
+        self.tape = tape;
+        self.model = model;
+        match loaded.metadata {
+            Some(ref metadata) => {
+                self.model_version = metadata.model_version;
+                self.train_steps = metadata.train_steps;
+                self.training_pairs = metadata.training_pairs;
+                self.last_trained = metadata.last_trained.clone();
+                self.encoder_frozen = metadata.encoder_frozen;
+                if let Some(saved) = metadata.hyperparameters {
+                    match saved.validate() {
+                        Ok(()) => self.hyperparameters = saved,
+                        Err(e) => logging::warn(
+                            "checkpoint hyperparameters ignored",
+                            &[("error", e.into())],
+                        ),
+                    }
+                }
+            }
+            None => self.model_version = loaded.version as u64,
+        }
+        self.config_unsaved = false;
+        self.cursor = loaded.cursor.clone();
+        self.restored = true;
+        self.checkpoint_loaded.store(true, Ordering::SeqCst);
+        self.saved_steps = self.train_steps;
+        self.saved_at = std::time::Instant::now();
+        self.optimizer = Adam::new(&self.tape, self.hyperparameters.learning_rate);
+        if let Some(ref state) = loaded.optimizer {
+            let indices = self.model.param_indices();
+            if let Err(e) = self.optimizer.restore_state(state, &indices) {
+                logging::warn("optimizer state ignored", &[("error", e.into())]);
+            }
+        }
+        if self.encoder_frozen {
+            self.optimizer.freeze(&self.model.encoder_param_indices());
+        }
+        Ok(())
+    }
+
+    fn metadata(&self) -> checkpoint::CheckpointMetadata {
+        checkpoint::CheckpointMetadata {
+            model_version: self.model_version,
+            train_steps: self.train_steps,
+            training_pairs: self.training_pairs,
+            last_trained: self.last_trained.clone(),
+            hyperparameters: Some(self.hyperparameters),
+            encoder_frozen: self.encoder_frozen,
+        }
+    }
+
+    fn get_config(&self) -> ConfigResult {
+        ConfigResult {
+            config: self.hyperparameters,
+            unsaved: self.config_unsaved,
+        }
+    }
+
+    /// Merge `params` over the current config and apply it if valid. A new
+    /// learning rate takes effect on the next optimizer step.
+    fn set_config(&mut self, params: SetConfigParams) -> Result<ConfigResult, String> {
+        let current = self.hyperparameters;
+        let next = Hyperparameters {
+            learning_rate: params.learning_rate.unwrap_or(current.learning_rate),
+            temperature: params.temperature.unwrap_or(current.temperature),
+            min_confidence: params.min_confidence.unwrap_or(current.min_confidence),
+        };
+        next.validate()?;
+        if next != current {
+            self.optimizer.set_lr(next.learning_rate);
+            self.hyperparameters = next;
+            self.config_unsaved = true;
+            logging::info(
+                "config updated",
+                &[
+                    ("learning_rate", next.learning_rate.into()),
+                    ("temperature", next.temperature.into()),
+                    ("min_confidence", next.min_confidence.into()),
+                ],
+            );
+        }
+        Ok(self.get_config())
+    }
+
+    /// Resize the project embedding table. Slots index learned weights, so
+    /// only a model that has never been trained or restored can change them.
+    fn set_project_slots(&mut self, slots: usize) -> Result<(), String> {
+        let current = self.model.config().project_slots;
+        if slots == current {
+            return Ok(());
+        }
+        if slots == 0 {
+            return Err("project_slots must be > 0".to_string());
+        }
+        if self.train_steps > 0 || self.restored {
+            return Err(format!(
+                "model was trained with {current} project slots; cannot switch to {slots}"
+            ));
+        }
+        let config = ScorerConfig {
+            project_slots: slots,
+            ..self.model.config().clone()
+        };
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(INIT_SEED);
+        self.model = CrossAttentionScorer::new(&mut tape, &mut rng, config);
+        self.optimizer = Adam::new(&tape, self.hyperparameters.learning_rate);
+        self.tape = tape;
+        Ok(())
+    }
+
+    fn status(&self) -> StatusResult {
+        let config = self.model.config();
+        StatusResult {
+            trained: self.train_steps > 0,
+            training_pairs: self.training_pairs,
+            model_version: self.model_version,
+            last_trained: self.last_trained.clone(),
+            native_dimensions: config.native_dim,
+            // The width callers send: a model with a schema narrows full
+            // rows itself, so v1 and reduced models take them too
+            feature_dimensions: if config.feature_schema.is_some() {
+                FEATURE_DIM
+            } else {
+                config.extra_features
+            },
+            feature_names: config
+                .feature_schema
+                .as_ref()
+                .map(|schema| schema.features.clone())
+                .unwrap_or_default(),
+            version: PREDICTOR_VERSION,
+            methods: METHODS,
+            loss_modes: training::LOSS_MODES,
+            checkpoint_versions: CheckpointVersions {
+                min_read: checkpoint::MIN_VERSION,
+                max_read: checkpoint::VERSION,
+                write: checkpoint::VERSION,
+            },
+            model: DEFAULT_MODEL.to_string(),
+            encoder_frozen: self.encoder_frozen,
+            models: vec![DEFAULT_MODEL.to_string()],
+        }
+    }
+
+    fn metrics(&self) -> MetricsResult {
+        let parameter_bytes = self
+            .model
+            .param_indices()
+            .iter()
+            .map(|&idx| self.tape.params()[idx].data.len() * std::mem::size_of::<f64>())
+            .sum();
+        match self.metrics.lock() {
+            Ok(metrics) => metrics.report(parameter_bytes),
+            Err(poisoned) => poisoned.into_inner().report(parameter_bytes),
+        }
+    }
+
+    /// Current weights for the scoring worker
+    fn snapshot(&self) -> ScoringSnapshot {
+        ScoringSnapshot {
+            tape: self.tape.snapshot(),
+            model: self.model.clone(),
+            encodings: EncodingCache::new(self.encoding_cache),
+            results: TtlCache::new(self.score_cache_ttl, SCORE_CACHE_ENTRIES),
+        }
+    }
 
     fn train(&mut self, params: TrainParams) -> Result<TrainResult, String> {
         let TrainParams {
@@ -139,23 +867,29 @@ impl PredictorService {
             temperature,
         } = params;
 
-        if candidate_embeddings.len() != labels.len() {
-            return Err("candidate_embeddings and labels length mismatch".to_string());
-        }
+        let temperature = temperature.unwrap_or(self.hyperparameters.temperature);
         if !temperature.is_finite() || temperature <= 0.0 {
             return Err("temperature must be > 0".to_string());
         }
 
         let label_count = labels.len();
-        let sample = TrainingSample {
+        let mut sample = TrainingSample {
             session_id: "rpc-train".to_string(),
             query_embedding: context_embedding,
             candidate_embeddings,
             candidate_texts: vec![],
-            candidate_features,
+            candidate_features: self.model.config().project_features(candidate_features),
             project_slot,
             labels,
+            weight: 1.0,
         };
+        if !sanitize_sample(
+            &mut sample,
+            DEFAULT_MAX_CORRUPT_FRACTION,
+            &mut SanitizeReport::default(),
+        ) {
+            return Err("training sample has too many NaN/Inf values".to_string());
+        }
         let stats = train_batch(
             &mut self.tape,
             &self.model,
@@ -175,61 +909,354 @@ impl PredictorService {
         Ok(TrainResult {
             loss: stats.loss,
             step: self.train_steps,
+            autosaved: self.maybe_autosave(),
+        })
+    }
+
+    /// Buffer a user's reaction to an injected memory, and apply buffered
+    /// feedback once the rate limit allows.
+    fn record_feedback(
+        &mut self,
+        params: RecordFeedbackParams,
+    ) -> Result<RecordFeedbackResult, String> {
+        let RecordFeedbackParams {
+            memory_id,
+            signal,
+            context_embedding,
+            candidate_ids,
+            candidate_embeddings,
+            candidate_features,
+            project_slot,
+        } = params;
+
+        let target = signal.label()?;
+        let position = candidate_ids
+            .iter()
+            .position(|id| *id == memory_id)
+            .ok_or_else(|| "memory_id is not among candidate_ids".to_string())?;
+        // A listwise loss over one candidate has nothing to rank against
+        if candidate_ids.len() < 2 {
+            return Err("feedback needs at least one other injected candidate".to_string());
+        }
+
+        // Only the memory acted on moves; the rest stay neutral
+        let mut labels = vec![0.5; candidate_ids.len()];
+        labels[position] = target;
+        let mut sample = TrainingSample {
+            session_id: "rpc-feedback".to_string(),
+            query_embedding: context_embedding,
+            candidate_embeddings,
+            candidate_texts: vec![],
+            candidate_features: self.model.config().project_features(candidate_features),
+            project_slot,
+            labels,
+            weight: online::FEEDBACK_WEIGHT,
+        };
+        // Checked now rather than when the buffered update is applied
+        let extra_features = self.model.config().extra_features;
+        if sample
+            .candidate_features
+            .iter()
+            .any(|row| row.len() != extra_features)
+        {
+            return Err("candidate_features row has invalid dimension".to_string());
+        }
+        if !sanitize_sample(
+            &mut sample,
+            DEFAULT_MAX_CORRUPT_FRACTION,
+            &mut SanitizeReport::default(),
+        ) {
+            return Err("feedback sample has too many NaN/Inf values".to_string());
+        }
+        self.feedback.push(memory_id, sample);
+
+        let mut update = None;
+        if let Some(samples) = self.feedback.take_due(std::time::Instant::now()) {
+            let applied = online::apply_update(
+                &mut self.tape,
+                &self.model,
+                &mut self.optimizer,
+                &samples,
+                online::FEEDBACK_TEMPERATURE,
+            )
+            .map_err(|err| format!("feedback update error: {err:?}"))?;
+            if applied.rolled_back {
+                logging::warn("feedback update rolled back: non-finite result", &[]);
+            }
+            if applied.steps > 0 {
+                self.train_steps += applied.steps;
+                self.training_pairs += samples.len();
+                self.model_version += 1;
+                self.last_trained = Some(format_timestamp());
+            }
+            update = Some(applied);
+        }
+
+        Ok(RecordFeedbackResult {
+            buffered: self.feedback.len(),
+            updated: update.is_some_and(|u| !u.rolled_back),
+            rolled_back: update.is_some_and(|u| u.rolled_back),
+            loss: update.map(|u| u.loss),
+            step: self.train_steps,
+            dropped: self.feedback.dropped,
+            autosaved: self.maybe_autosave(),
         })
     }
 
+    /// Save to the configured checkpoint if the autosave policy says so.
+    /// Failures are logged rather than failing the update that triggered them.
+    fn maybe_autosave(&mut self) -> bool {
+        let Some(path) = self.checkpoint_path.clone() else {
+            return false;
+        };
+        let unsaved = self.train_steps.saturating_sub(self.saved_steps);
+        if !self.autosave.due(unsaved, self.saved_at.elapsed()) {
+            return false;
+        }
+        match self.save_to(std::path::Path::new(&path), self.checkpoint_flags) {
+            Ok(()) => true,
+            Err(e) => {
+                logging::error("autosave failed", &[("error", format!("{e:?}").into())]);
+                false
+            }
+        }
+    }
+
+    /// Run [`Self::run_train_from_db`] with `training_active` set, so a
+    /// `cancel` request knows there is something to stop.
     fn train_from_db(&mut self, params: TrainFromDbParams) -> Result<TrainFromDbResult, String> {
-        if !params.temperature.is_finite() || params.temperature <= 0.0 {
+        self.cancel.reset();
+        self.training_active.store(true, Ordering::SeqCst);
+        let result = self.run_train_from_db(params);
+        self.training_active.store(false, Ordering::SeqCst);
+        result
+    }
+
+    fn run_train_from_db(
+        &mut self,
+        params: TrainFromDbParams,
+    ) -> Result<TrainFromDbResult, String> {
+        let temperature = params
+            .temperature
+            .unwrap_or(self.hyperparameters.temperature);
+        if !temperature.is_finite() || temperature <= 0.0 {
             return Err("temperature must be > 0".to_string());
         }
+        let min_confidence = params
+            .min_confidence
+            .unwrap_or(self.hyperparameters.min_confidence);
+
+        if let Some(slots) = params.project_slots {
+            self.set_project_slots(slots)?;
+        }
 
         let start = std::time::Instant::now();
+        let limits = TrainLimits {
+            deadline: params
+                .max_duration_ms
+                .map(|ms| start + std::time::Duration::from_millis(ms)),
+            cancel: self.cancel.clone(),
+        };
 
-        let db_path = std::path::Path::new(&params.db_path);
+        let source = data_source(params.db_path.as_deref(), params.daemon.as_ref())?;
+        let since = if params.full_retrain {
+            None
+        } else {
+            self.cursor.clone()
+        };
+        let incremental = since.is_some();
         let config = DataConfig {
-            min_scorer_confidence: params.min_confidence,
-            loss_temperature: params.temperature,
+            min_scorer_confidence: min_confidence,
+            loss_temperature: temperature,
             native_dim: self.model.config().native_dim,
+            since,
+            hard_negatives_per_session: params.hard_negatives,
+            feedback_mode: params.feedback,
+            harness_filter: params.harness_filter.clone(),
+            project_filter: params.project_filter.clone(),
+            label_half_life_days: params.label_half_life_days,
+            load_workers: params
+                .load_workers
+                .unwrap_or(DataConfig::default().load_workers),
+            project_slots: self.model.config().project_slots,
+            chunk_aggregation: params.chunk_aggregation,
+            max_corrupt_fraction: params
+                .max_corrupt_fraction
+                .unwrap_or(DEFAULT_MAX_CORRUPT_FRACTION),
+            feature_schema: self
+                .model
+                .config()
+                .feature_schema
+                .clone()
+                .unwrap_or_default(),
+            ..DataConfig::default()
         };
 
-        let load_result = data::load_training_samples(db_path, params.limit, &config)
-            .map_err(|e| format!("data load error: {e:?}"))?;
-
-        if load_result.samples.is_empty() {
-            return Ok(TrainFromDbResult {
+        let streaming = params.max_memory_mb.is_some();
+        let (summary, feature_stats, canary_samples, mut train_set) = match params.max_memory_mb {
+            Some(mb) => {
+                let max_chunk_bytes = (mb as usize).saturating_mul(1024 * 1024);
+                let mut stream = source
+                    .stream(params.limit, &config, max_chunk_bytes)
+                    .map_err(|e| format!("data load error: {e:?}"))?;
+                let canary = stream
+                    .take_holdout(CANARY_SAMPLES)
+                    .map_err(|e| format!("data load error: {e:?}"))?;
+                // The extra pass is only worth it while stats are unset
+                let feature_stats = if self.model.feature_stats().is_none() && !canary.is_empty() {
+                    stream
+                        .feature_stats()
+                        .map_err(|e| format!("data load error: {e:?}"))?
+                } else {
+                    None
+                };
+                (
+                    stream.summary(),
+                    feature_stats,
+                    canary,
+                    TrainSet::Stream(Box::new(stream)),
+                )
+            }
+            None => {
+                let load_result = source
+                    .load(params.limit, &config)
+                    .map_err(|e| format!("data load error: {e:?}"))?;
+                let summary = load_result.summary();
+                let samples = load_result.samples;
+                // Split into canary and training sets
+                let (canary, rest) = if samples.len() <= CANARY_SAMPLES {
+                    (samples.clone(), samples)
+                } else {
+                    let (canary, rest) = samples.split_at(CANARY_SAMPLES);
+                    (canary.to_vec(), rest.to_vec())
+                };
+                (
+                    summary,
+                    load_result.feature_stats,
+                    canary,
+                    TrainSet::Memory(rest),
+                )
+            }
+        };
+
+        let load_ms = start.elapsed().as_millis() as u64;
+
+        // Sessions that produced no samples are still consumed so the next
+        // incremental run does not revisit them.
+        let next_cursor = summary.cursor.clone().or_else(|| self.cursor.clone());
+
+        if canary_samples.is_empty() {
+            self.cursor = next_cursor;
+            return Ok(TrainFromDbResult {
                 loss: 0.0,
                 step: self.train_steps,
                 samples_used: 0,
-                samples_skipped: load_result.sessions_skipped,
+                samples_skipped: summary.sessions_skipped,
                 duration_ms: start.elapsed().as_millis() as u64,
+                load_ms,
+                train_ms: 0,
                 canary_score_variance: 0.0,
                 canary_topk_stability: 1.0,
                 checkpoint_saved: false,
+                epochs_completed: 0,
+                budget_exhausted: false,
+                cancelled: false,
+                incremental,
+                cursor: self.cursor.clone(),
+                hard_negatives: 0,
+                embedding_dimensions: summary.embedding_dims,
+                session_embeddings: summary.session_embeddings,
+                feedback_labels: summary.feedback_labels,
+                duplicates_removed: summary.duplicates_removed,
+                project_filtered: summary.project_filtered,
+                augmented_samples: 0,
+                schema_warnings: summary.schema_warnings,
+                project_slots: summary.project_slots,
+                sanitized: summary.sanitized,
+                journal_mode: summary.journal_mode,
+                lock_retries: summary.lock_retries,
+                lock_wait_ms: summary.lock_wait_ms,
+                streaming,
             });
         }
 
-        // Split into canary and training sets
-        let total = load_result.samples.len();
-        let (canary_samples, train_samples) = if total <= 10 {
-            (load_result.samples.clone(), load_result.samples)
-        } else {
-            let (canary, rest) = load_result.samples.split_at(10);
-            (canary.to_vec(), rest.to_vec())
-        };
+        // Standardization is frozen once learned: the gate weights are fit to
+        // the first stats, so later runs keep them instead of shifting inputs.
+        if self.model.feature_stats().is_none() {
+            if let Err(e) = self.model.set_feature_stats(feature_stats) {
+                logging::warn("feature stats rejected", &[("error", e.into())]);
+            }
+        }
+
+        // Document frequencies keep accumulating so IDF tracks new content
+        match train_set {
+            TrainSet::Memory(ref samples) => {
+                for sample in samples {
+                    self.model.observe_texts(sample.texts());
+                }
+            }
+            TrainSet::Stream(ref mut stream) => {
+                let model = &mut self.model;
+                stream
+                    .scan(|sample| model.observe_texts(sample.texts()))
+                    .map_err(|e| format!("data load error: {e:?}"))?;
+            }
+        }
 
         // Record pre-training top-5
         let pre_top5 = training::record_top5(&mut self.tape, &self.model, &canary_samples);
 
         // Train
-        let stats = train_epochs(
-            &mut self.tape,
-            &self.model,
-            &train_samples,
-            &mut self.optimizer,
-            params.epochs,
-            params.temperature,
-        )
-        .map_err(|e| format!("training error: {e:?}"))?;
+        let train_start = std::time::Instant::now();
+        // Jitter is seeded per step so repeated calls see different copies
+        let factor = params.augmentation_factor.min(MAX_AUGMENTATION_FACTOR);
+        let augment_rng = Rng::new(0xa06_5eed ^ (self.train_steps + 1));
+        let (stats, trained_count) = match train_set {
+            TrainSet::Memory(ref train_samples) => {
+                let augmented;
+                let train_samples = if factor > 0 {
+                    let mut rng = augment_rng;
+                    augmented = training::augment_samples(train_samples, factor, &mut rng);
+                    &augmented
+                } else {
+                    train_samples
+                };
+                let stats = train_epochs(
+                    &mut self.tape,
+                    &self.model,
+                    train_samples,
+                    &mut self.optimizer,
+                    params.epochs,
+                    temperature,
+                    &limits,
+                )
+                .map_err(|e| format!("training error: {e:?}"))?;
+                (stats, train_samples.len() / (factor + 1))
+            }
+            TrainSet::Stream(ref mut stream) => {
+                let mut source = AugmentedSource::new(stream.as_mut(), factor, augment_rng);
+                let stats = train_epochs_streaming(
+                    &mut self.tape,
+                    &self.model,
+                    &mut source,
+                    &mut self.optimizer,
+                    params.epochs,
+                    temperature,
+                    &limits,
+                )
+                .map_err(|e| format!("training error: {e:?}"))?;
+                let trained = stats.samples / (factor + 1);
+                (stats, trained)
+            }
+        };
+        let augmented_samples = trained_count * factor;
+        let train_ms = train_start.elapsed().as_millis() as u64;
+
+        // Stream telemetry is only complete once a full pass has been read
+        let summary = match train_set {
+            TrainSet::Memory(_) => summary,
+            TrainSet::Stream(ref stream) => stream.summary(),
+        };
 
         // Evaluate canary
         let canary =
@@ -239,14 +1266,44 @@ impl PredictorService {
         let valid =
             stats.loss.is_finite() && canary.score_variance > 0.0 && canary.topk_stability >= 0.6;
 
+        // A cancelled run has not seen every session up to the new cursor
+        if stats.steps > 0 && !stats.cancelled {
+            self.cursor = next_cursor;
+        }
+
+        // Update service state first so the checkpoint records it
+        self.train_steps += stats.steps;
+        self.training_pairs += trained_count;
+        if stats.steps > 0 {
+            self.model_version += 1;
+            self.last_trained = Some(format_timestamp());
+        }
+
         // Auto-save checkpoint if valid
         let checkpoint_saved = if valid {
             if let Some(ref ckpt_path) = params.checkpoint_path {
                 let path = std::path::Path::new(ckpt_path);
-                match checkpoint::save(path, &self.model, &self.tape, 0) {
-                    Ok(()) => true,
+                match self.save_to(path, self.checkpoint_flags) {
+                    Ok(()) => {
+                        let keep = params
+                            .keep_checkpoints
+                            .unwrap_or(checkpoint::DEFAULT_KEEP_CHECKPOINTS);
+                        if keep > 0 {
+                            // Saved only after the canary checks passed
+                            if let Err(e) = checkpoint::snapshot(path, keep, true) {
+                                logging::warn(
+                                    "checkpoint snapshot failed",
+                                    &[("error", format!("{e:?}").into())],
+                                );
+                            }
+                        }
+                        true
+                    }
                     Err(e) => {
-                        eprintln!("[predictor] checkpoint save failed: {e:?}");
+                        logging::error(
+                            "checkpoint save failed",
+                            &[("error", format!("{e:?}").into())],
+                        );
                         false
                     }
                 }
@@ -257,138 +1314,1153 @@ impl PredictorService {
             false
         };
 
-        // Update service state
-        let trained_count = train_samples.len();
-        self.train_steps += stats.steps;
-        self.training_pairs += trained_count;
-        if stats.steps > 0 {
-            self.model_version += 1;
-            self.last_trained = Some(format_timestamp());
-        }
-
         Ok(TrainFromDbResult {
             loss: stats.loss,
             step: self.train_steps,
             samples_used: trained_count,
-            samples_skipped: load_result.sessions_skipped,
+            samples_skipped: summary.sessions_skipped,
             duration_ms: start.elapsed().as_millis() as u64,
+            load_ms,
+            train_ms,
             canary_score_variance: canary.score_variance,
             canary_topk_stability: canary.topk_stability,
             checkpoint_saved,
+            epochs_completed: stats.epochs_completed,
+            budget_exhausted: stats.budget_exhausted,
+            cancelled: stats.cancelled,
+            incremental,
+            cursor: self.cursor.clone(),
+            hard_negatives: summary.hard_negatives,
+            embedding_dimensions: summary.embedding_dims,
+            session_embeddings: summary.session_embeddings,
+            feedback_labels: summary.feedback_labels,
+            duplicates_removed: summary.duplicates_removed,
+            project_filtered: summary.project_filtered,
+            augmented_samples,
+            schema_warnings: summary.schema_warnings,
+            project_slots: summary.project_slots,
+            sanitized: summary.sanitized,
+            journal_mode: summary.journal_mode,
+            lock_retries: summary.lock_retries,
+            lock_wait_ms: summary.lock_wait_ms,
+            streaming,
         })
     }
 
+    /// Write the full training state to `path`.
+    fn save_to(
+        &mut self,
+        path: &std::path::Path,
+        flags: u32,
+    ) -> Result<(), checkpoint::CheckpointError> {
+        checkpoint::save(
+            path,
+            &self.model,
+            &self.tape,
+            flags,
+            self.cursor.as_ref(),
+            Some(&self.optimizer.state(&self.model.param_indices())),
+            Some(&self.metadata()),
+        )?;
+        self.saved_steps = self.train_steps;
+        self.saved_at = std::time::Instant::now();
+        self.config_unsaved = false;
+        Ok(())
+    }
+
     fn save_checkpoint(
-        &self,
+        &mut self,
         params: SaveCheckpointParams,
     ) -> Result<SaveCheckpointResult, String> {
-        let path = std::path::Path::new(&params.path);
-        checkpoint::save(path, &self.model, &self.tape, params.flags)
+        self.save_to(std::path::Path::new(&params.path), params.flags)
             .map_err(|e| format!("checkpoint save error: {e:?}"))?;
         Ok(SaveCheckpointResult { saved: true })
     }
+
+    /// Save a final checkpoint if any training or config change is not yet
+    /// on disk. The caller exits once this has been answered.
+    fn shutdown(&mut self, params: ShutdownParams) -> Result<ShutdownResult, String> {
+        let path = params.path.or_else(|| self.checkpoint_path.clone());
+        let unsaved_steps = self.train_steps.saturating_sub(self.saved_steps);
+        let (saved, error) = match path {
+            Some(ref path) if unsaved_steps > 0 || self.config_unsaved => {
+                match self.save_to(std::path::Path::new(path), self.checkpoint_flags) {
+                    Ok(()) => (true, None),
+                    Err(e) => (false, Some(format!("{e:?}"))),
+                }
+            }
+            _ => (false, None),
+        };
+        if let Some(ref error) = error {
+            logging::error(
+                "final checkpoint save failed",
+                &[("error", error.clone().into())],
+            );
+        }
+        self.shutting_down = true;
+        Ok(ShutdownResult {
+            saved,
+            path,
+            unsaved_steps,
+            error,
+            models: BTreeMap::new(),
+        })
+    }
+
+    fn evaluate(&mut self, params: EvaluateParams) -> Result<EvaluateResult, String> {
+        let start = std::time::Instant::now();
+        let config = DataConfig {
+            min_scorer_confidence: params
+                .min_confidence
+                .unwrap_or(self.hyperparameters.min_confidence),
+            loss_temperature: params
+                .temperature
+                .unwrap_or(self.hyperparameters.temperature),
+            native_dim: self.model.config().native_dim,
+            hard_negatives_per_session: params.hard_negatives,
+            feedback_mode: params.feedback,
+            harness_filter: params.harness_filter,
+            project_filter: params.project_filter,
+            load_workers: params
+                .load_workers
+                .unwrap_or(DataConfig::default().load_workers),
+            project_slots: self.model.config().project_slots,
+            chunk_aggregation: params.chunk_aggregation,
+            max_corrupt_fraction: params
+                .max_corrupt_fraction
+                .unwrap_or(DEFAULT_MAX_CORRUPT_FRACTION),
+            feature_schema: self
+                .model
+                .config()
+                .feature_schema
+                .clone()
+                .unwrap_or_default(),
+            ..DataConfig::default()
+        };
+        let load_result = data_source(params.db_path.as_deref(), params.daemon.as_ref())?
+            .load(params.limit, &config)
+            .map_err(|e| format!("data load error: {e:?}"))?;
+        let load_ms = start.elapsed().as_millis() as u64;
+        let summary = load_result.summary();
+        let metrics = training::evaluate_samples(&mut self.tape, &self.model, &load_result.samples);
+        Ok(EvaluateResult {
+            metrics,
+            model_version: self.model_version,
+            samples_loaded: load_result.samples.len(),
+            samples_skipped: summary.sessions_skipped,
+            load_ms,
+            duration_ms: start.elapsed().as_millis() as u64,
+            schema_warnings: summary.schema_warnings,
+            sanitized: summary.sanitized,
+        })
+    }
+
+    fn load_checkpoint(
+        &mut self,
+        params: LoadCheckpointParams,
+    ) -> Result<LoadCheckpointResult, String> {
+        let loaded = checkpoint::load(std::path::Path::new(&params.path))
+            .map_err(|e| format!("checkpoint load error: {e:?}"))?;
+        self.apply_loaded(&loaded)
+            .map_err(|e| format!("checkpoint apply error: {e:?}"))?;
+        logging::info(
+            "hot-loaded checkpoint",
+            &[
+                ("version", loaded.version.into()),
+                ("path", params.path.clone().into()),
+            ],
+        );
+        Ok(LoadCheckpointResult {
+            loaded: true,
+            model_version: self.model_version,
+            file_version: loaded.version,
+        })
+    }
+
+    fn list_checkpoints(
+        &self,
+        params: ListCheckpointsParams,
+    ) -> Result<ListCheckpointsResult, String> {
+        let checkpoints = checkpoint::list_snapshots(std::path::Path::new(&params.path))
+            .map_err(|e| format!("checkpoint list error: {e:?}"))?;
+        Ok(ListCheckpointsResult { checkpoints })
+    }
+
+    fn inspect_checkpoint(
+        &self,
+        params: InspectCheckpointParams,
+    ) -> Result<checkpoint::CheckpointSummary, String> {
+        checkpoint::inspect(std::path::Path::new(&params.path))
+            .map_err(|e| format!("checkpoint inspect error: {e:?}"))
+    }
+
+    fn diff_checkpoints(
+        &self,
+        params: DiffCheckpointsParams,
+    ) -> Result<checkpoint::CheckpointDiff, String> {
+        checkpoint::diff(
+            std::path::Path::new(&params.before),
+            std::path::Path::new(&params.after),
+        )
+        .map_err(|e| format!("checkpoint diff error: {e:?}"))
+    }
+
+    fn export_safetensors(
+        &self,
+        params: ExportSafetensorsParams,
+    ) -> Result<export::ExportResult, String> {
+        export::export_safetensors(
+            std::path::Path::new(&params.path),
+            std::path::Path::new(&params.out),
+        )
+        .map_err(|e| format!("safetensors export error: {e:?}"))
+    }
+
+    fn restore_checkpoint(
+        &mut self,
+        params: RestoreCheckpointParams,
+    ) -> Result<RestoreCheckpointResult, String> {
+        let loaded = checkpoint::restore_snapshot(
+            std::path::Path::new(&params.path),
+            std::path::Path::new(&params.snapshot),
+        )
+        .map_err(|e| format!("checkpoint restore error: {e:?}"))?;
+        self.apply_loaded(&loaded)
+            .map_err(|e| format!("checkpoint apply error: {e:?}"))?;
+        Ok(RestoreCheckpointResult {
+            restored: true,
+            model_version: self.model_version,
+        })
+    }
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
+    if let Some(path) = find_arg(&args, "--inspect") {
+        print_and_exit("inspect", checkpoint::inspect(std::path::Path::new(&path)));
+    }
+    if let Some(i) = args.iter().position(|a| a == "--diff") {
+        let (Some(before), Some(after)) = (args.get(i + 1), args.get(i + 2)) else {
+            eprintln!("[predictor] --diff needs two checkpoint paths");
+            std::process::exit(2);
+        };
+        print_and_exit(
+            "diff",
+            checkpoint::diff(std::path::Path::new(before), std::path::Path::new(after)),
+        );
+    }
+    if let Some(i) = args.iter().position(|a| a == "--export-safetensors") {
+        let (Some(path), Some(out)) = (args.get(i + 1), args.get(i + 2)) else {
+            eprintln!("[predictor] --export-safetensors needs a checkpoint and an output path");
+            std::process::exit(2);
+        };
+        print_and_exit(
+            "export",
+            export::export_safetensors(std::path::Path::new(path), std::path::Path::new(out)),
+        );
+    }
+    let log_level = match find_prefixed_arg(&args, "--log-level") {
+        Some(raw) => Level::parse(&raw).unwrap_or_else(|e| {
+            eprintln!("[predictor] {e}");
+            std::process::exit(2);
+        }),
+        None => Level::Info,
+    };
+    let log_file = find_prefixed_arg(&args, "--log-file");
+    if let Err(e) = logging::init(log_level, log_file.as_deref().map(std::path::Path::new)) {
+        eprintln!("[predictor] cannot open --log-file: {e}");
+        std::process::exit(2);
+    }
+
     let checkpoint_path = find_arg(&args, "--checkpoint");
     let native_dim = parse_usize_arg(&args, "--native-dim").unwrap_or(768);
+    let adapter_dims = parse_usize_list_arg(&args, "--adapter-dims");
+    // Callers keep sending full-width rows and the model drops disabled
+    // features by name, so status still reports FEATURE_DIM to the daemon
+    let disabled_features: Vec<String> = find_arg(&args, "--disable-features")
+        .map(|raw| {
+            raw.split(',')
+                .map(|part| part.trim().to_string())
+                .filter(|part| !part.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let feature_schema = match FeatureSchema::without(&disabled_features) {
+        Ok(schema) => schema,
+        Err(e) => {
+            eprintln!("[predictor] invalid --disable-features: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    let checkpoint_flags = match find_arg(&args, "--checkpoint-storage").as_deref() {
+        None | Some("f64") => 0,
+        Some("f32") => checkpoint::FLAG_F32_PARAMS,
+        Some("f16") => checkpoint::FLAG_F16_PARAMS,
+        Some(other) => {
+            eprintln!(
+                "[predictor] invalid --checkpoint-storage: {other} (expected f64, f32 or f16)"
+            );
+            std::process::exit(2);
+        }
+    };
+
+    let framing = match find_prefixed_arg(&args, "--framing") {
+        Some(raw) => match Framing::parse(&raw) {
+            Ok(framing) => framing,
+            Err(e) => {
+                eprintln!("[predictor] {e}");
+                std::process::exit(2);
+            }
+        },
+        None => Framing::Lines,
+    };
+    let protocol = match find_prefixed_arg(&args, "--protocol") {
+        Some(raw) => match Protocol::parse(&raw) {
+            Ok(protocol) => protocol,
+            Err(e) => {
+                eprintln!("[predictor] {e}");
+                std::process::exit(2);
+            }
+        },
+        None => Protocol::Json,
+    };
+    // Binary bodies may contain newline bytes, so they always carry a length
+    let framing = if protocol == Protocol::MsgPack {
+        Framing::ContentLength
+    } else {
+        framing
+    };
+    if framing != Framing::Lines || protocol != Protocol::Json {
+        logging::info(
+            "transport",
+            &[
+                ("framing", framing.as_str().into()),
+                ("protocol", protocol.as_str().into()),
+            ],
+        );
+    }
+
+    let listen = find_prefixed_arg(&args, "--listen").map(|spec| {
+        transport::parse_listen(&spec).unwrap_or_else(|e| {
+            eprintln!("[predictor] {e}");
+            std::process::exit(2);
+        })
+    });
+    // HTTP mode refuses to start unauthenticated
+    let token = match (&listen, find_arg(&args, "--token-file")) {
+        (None, _) => String::new(),
+        (Some(_), Some(path)) => {
+            transport::read_token(std::path::Path::new(&path)).unwrap_or_else(|e| {
+                eprintln!("[predictor] {e}");
+                std::process::exit(2);
+            })
+        }
+        (Some(_), None) => {
+            eprintln!("[predictor] --listen needs --token-file");
+            std::process::exit(2);
+        }
+    };
+
+    let watch = find_arg(&args, "--watch").map(|db_path| WatchConfig {
+        db_path,
+        poll: std::time::Duration::from_secs(
+            parse_usize_arg(&args, "--watch-poll-secs").unwrap_or(10) as u64,
+        ),
+        debounce: std::time::Duration::from_secs(
+            parse_usize_arg(&args, "--watch-debounce-secs").unwrap_or(60) as u64,
+        ),
+        quiet: find_arg(&args, "--quiet-hours").map(|raw| {
+            QuietHours::parse(&raw).unwrap_or_else(|e| {
+                eprintln!("[predictor] {e}");
+                std::process::exit(2);
+            })
+        }),
+    });
+
+    let checkpoint_flags = if args.iter().any(|a| a == "--compress-checkpoints") {
+        checkpoint_flags | checkpoint::FLAG_ZSTD_PAYLOAD
+    } else {
+        checkpoint_flags
+    };
+
+    // 0 lifts the cap entirely
+    let max_tokens = match parse_usize_arg(&args, "--max-candidate-tokens") {
+        Some(0) => None,
+        Some(n) => Some(n),
+        None => Some(model::DEFAULT_MAX_TOKENS),
+    };
+
+    let bigram_buckets = parse_usize_arg(&args, "--bigram-buckets").unwrap_or(0);
 
-    let mut service = PredictorService::new(native_dim);
+    let mut service = PredictorService::new(
+        native_dim,
+        adapter_dims,
+        feature_schema,
+        max_tokens,
+        bigram_buckets,
+    );
+    service.checkpoint_flags = checkpoint_flags;
+    service.checkpoint_path = checkpoint_path.clone();
+    service.autosave = checkpoint::AutosavePolicy {
+        every_steps: parse_usize_arg(&args, "--autosave-steps").map(|n| n as u64),
+        every: parse_usize_arg(&args, "--autosave-minutes")
+            .map(|m| std::time::Duration::from_secs(m as u64 * 60)),
+    };
+    if let Some(secs) = parse_usize_arg(&args, "--feedback-interval-secs") {
+        service.feedback = FeedbackBuffer::new(std::time::Duration::from_secs(secs as u64));
+    }
+    let defaults = RequestLimits::default();
+    service.limits = RequestLimits {
+        max_candidates: parse_usize_arg(&args, "--max-candidates")
+            .unwrap_or(defaults.max_candidates),
+        max_top_k_candidates: parse_usize_arg(&args, "--max-top-k-candidates")
+            .unwrap_or(defaults.max_top_k_candidates),
+        max_embedding_dim: parse_usize_arg(&args, "--max-embedding-dim")
+            .unwrap_or(defaults.max_embedding_dim),
+    };
+    if let Some(raw) = find_arg(&args, "--encoding-cache") {
+        match raw.parse::<usize>() {
+            Ok(entries) => service.encoding_cache = entries,
+            Err(_) => {
+                eprintln!("[predictor] --encoding-cache needs an entry count, got {raw}");
+                std::process::exit(2);
+            }
+        }
+    }
+    if let Some(ms) = parse_usize_arg(&args, "--score-cache-ttl-ms") {
+        service.score_cache_ttl = std::time::Duration::from_millis(ms as u64);
+    }
+    if service.autosave.is_enabled() && checkpoint_path.is_none() {
+        logging::warn("autosave needs --checkpoint; disabled", &[]);
+    }
 
     if let Some(ref path) = checkpoint_path {
         let p = std::path::Path::new(path);
         if p.exists() {
             match checkpoint::load(p) {
-                Ok(loaded) => {
-                    match checkpoint::apply_checkpoint(&loaded, &service.model, &mut service.tape) {
-                        Ok(()) => {
-                            service.model_version = loaded.version as u64;
-                            eprintln!("[predictor] loaded checkpoint v{}", loaded.version);
-                        }
-                        Err(e) => eprintln!("[predictor] checkpoint apply failed: {e:?}"),
-                    }
+                Ok(loaded) => match service.apply_loaded(&loaded) {
+                    Ok(()) => logging::info(
+                        "loaded checkpoint",
+                        &[
+                            ("version", loaded.version.into()),
+                            ("path", path.as_str().into()),
+                        ],
+                    ),
+                    Err(e) => logging::error(
+                        "checkpoint apply failed",
+                        &[("error", format!("{e:?}").into())],
+                    ),
+                },
+                Err(e) => logging::error(
+                    "checkpoint load failed",
+                    &[("error", format!("{e:?}").into())],
+                ),
+            }
+        }
+    }
+
+    // Requests are read on their own thread so `cancel` and `ping` are
+    // answered while a long call such as `train_from_db` holds the main loop,
+    // and `score` goes to a worker of its own so it keeps flowing meanwhile.
+    let (main_tx, incoming) = mpsc::channel();
+    let (scoring_tx, scoring) = mpsc::channel();
+    let routes = Routes {
+        main: main_tx,
+        scoring: scoring_tx,
+    };
+    let control = Control {
+        token: service.cancel.clone(),
+        training_active: Arc::clone(&service.training_active),
+        checkpoint_loaded: Arc::clone(&service.checkpoint_loaded),
+        metrics: Arc::clone(&service.metrics),
+    };
+    let _ = SHUTDOWN.set(Shutdown {
+        main: routes.main.clone(),
+        control: control.clone(),
+    });
+    // SIGTERM/SIGINT stop any training and take the same path as `shutdown`
+    {
+        let installed = ctrlc::set_handler(|| request_shutdown(ShutdownReason::Signal));
+        if let Err(e) = installed {
+            logging::warn(
+                "signal handler not installed",
+                &[("error", e.to_string().into())],
+            );
+        }
+    }
+
+    let published = Arc::new(Mutex::new(BTreeMap::from([(
+        DEFAULT_MODEL.to_string(),
+        service.snapshot(),
+    )])));
+    let metrics = Arc::clone(&service.metrics);
+    let limits = service.limits;
+    let scorer = {
+        let published = Arc::clone(&published);
+        let metrics = Arc::clone(&metrics);
+        std::thread::spawn(move || {
+            run_scorer(scoring, &published, &metrics, limits, framing, protocol)
+        })
+    };
+
+    if let Some(config) = watch {
+        let main = routes.main.clone();
+        // Over HTTP there is no stream to notify on; the log still has it
+        let notify = listen.is_none().then(|| Output::stdout(framing, protocol));
+        let checkpoint_path = checkpoint_path.clone();
+        std::thread::spawn(move || run_watch(config, checkpoint_path, &main, notify, protocol));
+    }
+
+    if let Some(addr) = listen {
+        std::thread::spawn(move || serve_http(&addr, token, protocol, routes, control));
+    } else {
+        std::thread::spawn(move || read_requests(routes, scorer, framing, protocol, &control));
+    }
+
+    let mut stdout = Output::stdout(framing, protocol);
+    let mut models = Models {
+        default: service,
+        named: BTreeMap::new(),
+    };
+
+    for incoming in incoming {
+        let changes_weights = PARAMETER_METHODS.contains(&incoming.req.method.as_str());
+        let reason = incoming.shutdown.unwrap_or(ShutdownReason::Requested);
+        let mut handled = None;
+        answer(incoming, &mut stdout, protocol, &metrics, |out, req| {
+            handled = models.dispatch(out, req);
+        });
+        if models.default.shutting_down {
+            logging::info(
+                "shutting down",
+                &[
+                    ("reason", reason.as_str().into()),
+                    ("exit_code", reason.exit_code().into()),
+                ],
+            );
+            // Readers may still be blocked on input, so don't wait for them
+            std::process::exit(reason.exit_code());
+        }
+        let Some(name) = handled.filter(|_| changes_weights) else {
+            continue;
+        };
+        if let (Some(service), Ok(mut pending)) = (models.get(&name), published.lock()) {
+            pending.insert(name, service.snapshot());
+        }
+    }
+}
+
+/// Methods after which the model's scoring snapshot is republished.
+const PARAMETER_METHODS: &[&str] = &[
+    "create_model",
+    "clone_model",
+    "train",
+    "record_feedback",
+    "train_from_db",
+    "load_checkpoint",
+    "restore_checkpoint",
+];
+
+/// Answer `score` and `benchmark` requests against the latest published
+/// snapshot of the model each names, picking up new ones between requests.
+fn run_scorer(
+    incoming: mpsc::Receiver<Incoming>,
+    published: &Mutex<BTreeMap<String, ScoringSnapshot>>,
+    metrics: &Mutex<Metrics>,
+    limits: RequestLimits,
+    framing: Framing,
+    protocol: Protocol,
+) {
+    let mut current = BTreeMap::new();
+    let mut stdout = Output::stdout(framing, protocol);
+    for incoming in incoming {
+        if let Ok(mut pending) = published.lock() {
+            current.append(&mut pending);
+        }
+        answer(incoming, &mut stdout, protocol, metrics, |out, mut req| {
+            let name = match validation::take_model(&mut req.params) {
+                Ok(name) => name.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+                Err(err) => return write_field_error(out, req.id, &err),
+            };
+            match current.get_mut(&name) {
+                Some(snapshot) if req.method == "benchmark" => {
+                    handle_checked_rpc(out, req.id, req.params, &limits, |p| snapshot.benchmark(p))
+                }
+                Some(snapshot) => {
+                    handle_checked_rpc(out, req.id, req.params, &limits, |p| snapshot.score(p))
                 }
-                Err(e) => eprintln!("[predictor] checkpoint load failed: {e:?}"),
+                None => write_response(out, &unknown_model(req.id, &name)),
+            }
+        });
+        let mut encodings = (0, 0, 0);
+        let mut results = (0, 0, 0);
+        for snapshot in current.values_mut() {
+            let (hits, misses) = snapshot.encodings.take_counts();
+            encodings = (
+                encodings.0 + hits,
+                encodings.1 + misses,
+                encodings.2 + snapshot.encodings.len(),
+            );
+            let (hits, misses) = snapshot.results.take_counts();
+            results = (
+                results.0 + hits,
+                results.1 + misses,
+                results.2 + snapshot.results.len(),
+            );
+        }
+        if let Ok(mut metrics) = metrics.lock() {
+            metrics.record_encodings(encodings.0, encodings.1, encodings.2);
+            metrics.record_scores(results.0, results.1, results.2);
+        }
+    }
+}
+
+/// `--watch` settings.
+struct WatchConfig {
+    db_path: String,
+    poll: std::time::Duration,
+    debounce: std::time::Duration,
+    quiet: Option<QuietHours>,
+}
+
+/// Poll the database and queue an incremental `train_from_db` once a change
+/// has settled, outside quiet hours. Each run is reported with a
+/// `watch_trained` notification on `notify`.
+fn run_watch(
+    config: WatchConfig,
+    checkpoint_path: Option<String>,
+    main: &mpsc::Sender<Incoming>,
+    mut notify: Option<Output>,
+    protocol: Protocol,
+) {
+    let db_path = std::path::Path::new(&config.db_path);
+    let mut watcher = Watcher::new(config.debounce);
+    let mut runs = 0_u64;
+    logging::info(
+        "watching database",
+        &[("db_path", config.db_path.as_str().into())],
+    );
+    loop {
+        std::thread::sleep(config.poll);
+        let now = std::time::Instant::now();
+        match DbFingerprint::read(db_path) {
+            Ok(fingerprint) => watcher.observe(fingerprint, now),
+            Err(e) => {
+                logging::warn(
+                    "watched database unreadable",
+                    &[("error", e.to_string().into())],
+                );
+                continue;
+            }
+        }
+        if !watcher.is_due(now) || config.quiet.is_some_and(|q| q.contains(utc_hour())) {
+            continue;
+        }
+        watcher.triggered();
+        runs += 1;
+
+        let (reply, response) = mpsc::channel();
+        let req = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(format!("watch-{runs}")),
+            method: "train_from_db".to_string(),
+            params: serde_json::json!({
+                "db_path": config.db_path,
+                "checkpoint_path": checkpoint_path,
+            }),
+        };
+        if main.send(Incoming::new(req, Some(reply))).is_err() {
+            return;
+        }
+        let Ok(body) = response.recv() else {
+            return;
+        };
+        let decoded: serde_json::Value = protocol.decode(&body).unwrap_or_default();
+        let params = WatchTrainedParams {
+            db_path: config.db_path.clone(),
+            result: decoded.get("result").cloned(),
+            error: decoded.get("error").cloned(),
+            duration_ms: now.elapsed().as_millis() as u64,
+        };
+        logging::info(
+            "watch retrain finished",
+            &[
+                ("ok", params.error.is_none().into()),
+                ("duration_ms", params.duration_ms.into()),
+            ],
+        );
+        if let Some(ref mut out) = notify {
+            let notification = JsonRpcNotification::new("watch_trained", params);
+            if let Ok(body) = out.protocol.encode(&notification) {
+                out.send(&body);
+            }
+        }
+    }
+}
+
+fn utc_hour() -> u32 {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    ((secs % 86400) / 3600) as u32
+}
+
+/// Run `handle` for one request, sending its response wherever the request
+/// came from, then log and count it.
+fn answer(
+    incoming: Incoming,
+    stdout: &mut Output,
+    protocol: Protocol,
+    metrics: &Mutex<Metrics>,
+    handle: impl FnOnce(&mut Output, JsonRpcRequest),
+) {
+    let request = NEXT_REQUEST.fetch_add(1, Ordering::Relaxed);
+    let method = incoming.req.method.clone();
+    let start = std::time::Instant::now();
+    logging::set_request(Some(request));
+
+    let error = match incoming.reply {
+        Some(reply) => {
+            let mut out = Output::buffer(protocol);
+            handle(&mut out, incoming.req);
+            let error = out.error.take();
+            let _ = reply.send(out.into_body());
+            error
+        }
+        None => {
+            handle(stdout, incoming.req);
+            stdout.error.take()
+        }
+    };
+    let elapsed = start.elapsed();
+    if let Ok(mut metrics) = metrics.lock() {
+        metrics.record(
+            &method,
+            elapsed,
+            error
+                .as_ref()
+                .map(|(code, message)| (*code, message.as_str())),
+        );
+    }
+
+    let mut fields = vec![
+        ("method", method.into()),
+        ("duration_ms", (elapsed.as_secs_f64() * 1000.0).into()),
+    ];
+    match error {
+        Some((code, _)) => {
+            fields.push(("error_code", code.into()));
+            logging::warn("rpc failed", &fields);
+        }
+        None => logging::debug("rpc", &fields),
+    }
+    logging::set_request(None);
+}
+
+/// Sequence number for log correlation, separate from the caller's JSON-RPC id
+static NEXT_REQUEST: AtomicU64 = AtomicU64::new(1);
+
+/// Run one request against the service and write its response.
+fn dispatch(service: &mut PredictorService, out: &mut Output, req: JsonRpcRequest) {
+    match req.method.as_str() {
+        "metrics" => {
+            let response = JsonRpcResponse::success(req.id, service.metrics());
+            write_response(out, &response);
+        }
+        "get_config" => {
+            let response = JsonRpcResponse::success(req.id, service.get_config());
+            write_response(out, &response);
+        }
+        "set_config" => {
+            handle_rpc(out, req.id, req.params, |p| service.set_config(p));
+        }
+        "train" => {
+            let limits = service.limits;
+            handle_checked_rpc(out, req.id, req.params, &limits, |p| service.train(p));
+        }
+        "train_from_db" => {
+            let limits = service.limits;
+            handle_checked_rpc(out, req.id, req.params, &limits, |p| {
+                service.train_from_db(p)
+            });
+        }
+        "record_feedback" => {
+            let limits = service.limits;
+            handle_checked_rpc(out, req.id, req.params, &limits, |p| {
+                service.record_feedback(p)
+            });
+        }
+        "save_checkpoint" => {
+            handle_rpc(out, req.id, req.params, |p| service.save_checkpoint(p));
+        }
+        "evaluate" => {
+            let limits = service.limits;
+            handle_checked_rpc(out, req.id, req.params, &limits, |p| service.evaluate(p));
+        }
+        "load_checkpoint" => {
+            handle_rpc(out, req.id, req.params, |p| service.load_checkpoint(p));
+        }
+        "list_checkpoints" => {
+            handle_rpc(out, req.id, req.params, |p| service.list_checkpoints(p));
+        }
+        "inspect_checkpoint" => {
+            handle_rpc(out, req.id, req.params, |p| service.inspect_checkpoint(p));
+        }
+        "diff_checkpoints" => {
+            handle_rpc(out, req.id, req.params, |p| service.diff_checkpoints(p));
+        }
+        "export_safetensors" => {
+            handle_rpc(out, req.id, req.params, |p| service.export_safetensors(p));
+        }
+        "restore_checkpoint" => {
+            handle_rpc(out, req.id, req.params, |p| service.restore_checkpoint(p));
+        }
+        _ => {
+            let response =
+                JsonRpcResponse::<serde_json::Value>::failure(req.id, -32601, "method not found");
+            write_response(out, &response);
+        }
+    }
+}
+
+/// A request for the main loop. `reply` carries the encoded response back
+/// to an HTTP handler; stdin requests are answered on stdout.
+struct Incoming {
+    req: JsonRpcRequest,
+    reply: Option<mpsc::Sender<Vec<u8>>>,
+    /// Set on the internal `shutdown`; a client's is `Requested`
+    shutdown: Option<ShutdownReason>,
+}
+
+impl Incoming {
+    fn new(req: JsonRpcRequest, reply: Option<mpsc::Sender<Vec<u8>>>) -> Self {
+        Self {
+            req,
+            reply,
+            shutdown: None,
+        }
+    }
+
+    /// Internal `shutdown` on a signal or a closed stdio stream. Its
+    /// response is dropped; nobody is waiting for it.
+    fn shutdown(reason: ShutdownReason) -> Self {
+        let (reply, _) = mpsc::channel();
+        Self {
+            req: JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::Value::Null,
+                method: "shutdown".to_string(),
+                params: serde_json::json!({}),
+            },
+            reply: Some(reply),
+            shutdown: Some(reason),
+        }
+    }
+}
+
+/// Distinct from usage errors (2) and failed one-shot commands (1), so a
+/// supervisor can tell the daemon went away from a crash.
+const EXIT_PARENT_GONE: i32 = 3;
+
+/// Why the process is exiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShutdownReason {
+    /// A `shutdown` RPC
+    Requested,
+    /// SIGTERM or SIGINT
+    Signal,
+    /// End of stdin
+    InputClosed,
+    /// A write to stdout hit a broken pipe. Rust ignores SIGPIPE, so this
+    /// is where a reader going away shows up.
+    OutputClosed,
+}
+
+impl ShutdownReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Requested => "requested",
+            Self::Signal => "signal",
+            Self::InputClosed => "stdin_closed",
+            Self::OutputClosed => "stdout_closed",
+        }
+    }
+
+    fn exit_code(self) -> i32 {
+        match self {
+            Self::Requested | Self::Signal => 0,
+            Self::InputClosed | Self::OutputClosed => EXIT_PARENT_GONE,
+        }
+    }
+}
+
+/// How threads without a route of their own, a signal handler or a
+/// failed stdout write, reach the main loop.
+struct Shutdown {
+    main: mpsc::Sender<Incoming>,
+    control: Control,
+}
+
+static SHUTDOWN: OnceLock<Shutdown> = OnceLock::new();
+
+/// Stop any training and queue the final save. Only the first request
+/// counts; the main loop exits after answering it.
+fn request_shutdown(reason: ShutdownReason) {
+    static REQUESTED: AtomicBool = AtomicBool::new(false);
+    if REQUESTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Some(shutdown) = SHUTDOWN.get() {
+        shutdown.control.cancel();
+        let _ = shutdown.main.send(Incoming::shutdown(reason));
+    }
+}
+
+/// Methods answered by the scoring worker rather than the main loop.
+const SCORING_METHODS: &[&str] = &["score", "benchmark"];
+
+/// Where readers send accepted requests.
+struct Routes {
+    main: mpsc::Sender<Incoming>,
+    scoring: mpsc::Sender<Incoming>,
+}
+
+impl Routes {
+    /// False once the receiving loop has gone.
+    fn send(&self, incoming: Incoming) -> bool {
+        let route = if SCORING_METHODS.contains(&incoming.req.method.as_str()) {
+            &self.scoring
+        } else {
+            &self.main
+        };
+        route.send(incoming).is_ok()
+    }
+}
+
+/// What request readers answer without going through the main loop, which
+/// may be busy training.
+#[derive(Clone)]
+struct Control {
+    token: CancelToken,
+    training_active: Arc<AtomicBool>,
+    checkpoint_loaded: Arc<AtomicBool>,
+    metrics: Arc<Mutex<Metrics>>,
+}
+
+impl Control {
+    /// Only a run in flight is cancelled, so a stray `cancel` cannot abort
+    /// the next `train_from_db`.
+    fn cancel(&self) -> CancelResult {
+        let cancelled = self.training_active.load(Ordering::SeqCst);
+        if cancelled {
+            self.token.cancel();
+        }
+        CancelResult { cancelled }
+    }
+
+    fn ping(&self) -> PingResult {
+        let (uptime, last_activity_ms) = match self.metrics.lock() {
+            Ok(metrics) => (metrics.uptime(), metrics.last_activity_ms()),
+            Err(poisoned) => {
+                let metrics = poisoned.into_inner();
+                (metrics.uptime(), metrics.last_activity_ms())
             }
+        };
+        PingResult {
+            uptime_ms: uptime.as_millis() as u64,
+            checkpoint_loaded: self.checkpoint_loaded.load(Ordering::SeqCst),
+            training: self.training_active.load(Ordering::SeqCst),
+            last_activity_ms,
         }
     }
+}
 
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
+/// Decode and validate one request body. Errors, `cancel` and `ping` are
+/// answered on `out` directly; anything else is returned for the main loop.
+fn accept(
+    raw: &[u8],
+    protocol: Protocol,
+    control: &Control,
+    out: &mut Output,
+) -> Option<JsonRpcRequest> {
+    let req = match protocol.decode::<JsonRpcRequest>(raw) {
+        Ok(req) => req,
+        Err(err) => {
+            let what = match protocol {
+                Protocol::Json => "JSON",
+                Protocol::MsgPack => "MessagePack",
+            };
+            let response = JsonRpcResponse::<serde_json::Value>::failure(
+                serde_json::Value::Null,
+                -32700,
+                format!("invalid {what}: {err}"),
+            );
+            write_response(out, &response);
+            return None;
+        }
+    };
+
+    if req.jsonrpc != "2.0" {
+        let response =
+            JsonRpcResponse::<serde_json::Value>::failure(req.id, -32600, "jsonrpc must be '2.0'");
+        write_response(out, &response);
+        return None;
+    }
 
-    for line in stdin.lock().lines() {
-        let raw = match line {
-            Ok(raw) => raw,
+    if req.method == "cancel" {
+        let response = JsonRpcResponse::success(req.id, control.cancel());
+        write_response(out, &response);
+        return None;
+    }
+    if req.method == "ping" {
+        let response = JsonRpcResponse::success(req.id, control.ping());
+        write_response(out, &response);
+        return None;
+    }
+    // Training in flight stops early so the final save isn't held up
+    if req.method == "shutdown" {
+        control.cancel();
+    }
+
+    Some(req)
+}
+
+/// Read requests from stdin, forwarding them to the main loop. Closing
+/// stdin shuts down like `shutdown` once queued scores are answered, so
+/// the daemon exiting doesn't lose unsaved training; the process then
+/// exits with [`EXIT_PARENT_GONE`].
+fn read_requests(
+    requests: Routes,
+    scorer: std::thread::JoinHandle<()>,
+    framing: Framing,
+    protocol: Protocol,
+    control: &Control,
+) {
+    let mut stdin = io::stdin().lock();
+    let mut out = Output::stdout(framing, protocol);
+
+    loop {
+        let raw = match transport::read_frame(&mut stdin, framing) {
+            Ok(Some(raw)) => raw,
+            Ok(None) => break,
             Err(err) => {
                 let fallback = JsonRpcResponse::<serde_json::Value>::failure(
                     serde_json::Value::Null,
                     -32603,
                     format!("stdin read error: {err}"),
                 );
-                write_response(&mut stdout, &fallback);
+                write_response(&mut out, &fallback);
                 continue;
             }
         };
 
-        if raw.trim().is_empty() {
+        if raw.iter().all(u8::is_ascii_whitespace) {
             continue;
         }
 
-        let req = match serde_json::from_str::<JsonRpcRequest>(&raw) {
-            Ok(req) => req,
-            Err(err) => {
-                let response = JsonRpcResponse::<serde_json::Value>::failure(
-                    serde_json::Value::Null,
-                    -32700,
-                    format!("invalid JSON: {err}"),
-                );
-                write_response(&mut stdout, &response);
-                continue;
-            }
+        let Some(req) = accept(&raw, protocol, control, &mut out) else {
+            continue;
         };
 
-        if req.jsonrpc != "2.0" {
-            let response = JsonRpcResponse::<serde_json::Value>::failure(
-                req.id,
-                -32600,
-                "jsonrpc must be '2.0'",
+        if !requests.send(Incoming::new(req, None)) {
+            return;
+        }
+    }
+
+    let Routes { main, scoring } = requests;
+    drop(scoring);
+    let _ = scorer.join();
+    drop(main);
+    request_shutdown(ShutdownReason::InputClosed);
+}
+
+/// Serve the RPCs over HTTP: one request per `POST /rpc` body, authorised
+/// with `Authorization: Bearer <token>`. Each connection gets a thread so a
+/// `cancel` is answered while training holds the main loop.
+fn serve_http(addr: &str, token: String, protocol: Protocol, requests: Routes, control: Control) {
+    let requests = Arc::new(requests);
+    let server = match tiny_http::Server::http(addr) {
+        Ok(server) => server,
+        Err(e) => {
+            logging::error(
+                "cannot listen",
+                &[("addr", addr.into()), ("error", e.to_string().into())],
             );
-            write_response(&mut stdout, &response);
-            continue;
+            std::process::exit(2);
         }
+    };
+    logging::info("listening", &[("url", format!("http://{addr}").into())]);
+    let token = Arc::new(token);
+    for request in server.incoming_requests() {
+        let requests = Arc::clone(&requests);
+        let control = control.clone();
+        let token = Arc::clone(&token);
+        std::thread::spawn(move || answer_http(request, &token, protocol, &requests, &control));
+    }
+}
 
-        match req.method.as_str() {
-            "status" => {
-                let response = JsonRpcResponse::success(req.id, service.status());
-                write_response(&mut stdout, &response);
-            }
-            "score" => {
-                handle_rpc(&mut stdout, req.id, req.params, |p| service.score(p));
-            }
-            "train" => {
-                handle_rpc(&mut stdout, req.id, req.params, |p| service.train(p));
-            }
-            "train_from_db" => {
-                handle_rpc(&mut stdout, req.id, req.params, |p| {
-                    service.train_from_db(p)
-                });
-            }
-            "save_checkpoint" => {
-                handle_rpc(&mut stdout, req.id, req.params, |p| {
-                    service.save_checkpoint(p)
-                });
-            }
-            _ => {
-                let response = JsonRpcResponse::<serde_json::Value>::failure(
-                    req.id,
-                    -32601,
-                    "method not found",
-                );
-                write_response(&mut stdout, &response);
+fn answer_http(
+    mut request: tiny_http::Request,
+    token: &str,
+    protocol: Protocol,
+    requests: &Routes,
+    control: &Control,
+) {
+    let status =
+        |code: u16, text: &str| tiny_http::Response::from_string(text).with_status_code(code);
+    let authorized = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .is_some_and(|h| transport::bearer_matches(h.value.as_str(), token));
+    if !authorized {
+        let challenge = tiny_http::Header::from_bytes(&b"WWW-Authenticate"[..], &b"Bearer"[..])
+            .expect("static header");
+        let _ = request.respond(status(401, "unauthorized").with_header(challenge));
+        return;
+    }
+    if *request.method() != tiny_http::Method::Post || request.url() != "/rpc" {
+        let _ = request.respond(status(404, "not found"));
+        return;
+    }
+
+    let mut body = Vec::new();
+    let limit = transport::MAX_FRAME_BYTES as u64 + 1;
+    if request
+        .as_reader()
+        .take(limit)
+        .read_to_end(&mut body)
+        .is_err()
+    {
+        let _ = request.respond(status(400, "unreadable body"));
+        return;
+    }
+    if body.len() > transport::MAX_FRAME_BYTES {
+        let _ = request.respond(status(413, "body too large"));
+        return;
+    }
+
+    let mut out = Output::buffer(protocol);
+    let payload = match accept(&body, protocol, control, &mut out) {
+        Some(req) => {
+            let (reply, response) = mpsc::channel();
+            let sent = requests.send(Incoming::new(req, Some(reply)));
+            match sent.then(|| response.recv().ok()).flatten() {
+                Some(payload) => payload,
+                None => {
+                    let _ = request.respond(status(503, "predictor shutting down"));
+                    return;
+                }
             }
         }
-    }
+        None => out.into_body(),
+    };
+    let content_type =
+        tiny_http::Header::from_bytes(&b"Content-Type"[..], protocol.content_type().as_bytes())
+            .expect("static header");
+    let _ = request.respond(tiny_http::Response::from_data(payload).with_header(content_type));
 }
 
 fn parse_usize_arg(args: &[String], flag: &str) -> Option<usize> {
@@ -398,8 +2470,78 @@ fn parse_usize_arg(args: &[String], flag: &str) -> Option<usize> {
         .filter(|value| *value > 0)
 }
 
+/// Comma-separated dimensions, e.g. `--adapter-dims 384,1024`.
+fn parse_usize_list_arg(args: &[String], flag: &str) -> Vec<usize> {
+    find_arg(args, flag)
+        .map(|raw| {
+            raw.split(',')
+                .filter_map(|part| part.trim().parse::<usize>().ok())
+                .filter(|value| *value > 0)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Where a response goes once encoded.
+enum Sink {
+    /// Framed on stdout. Each message is written under the stdout lock so
+    /// frames from the main loop and the stdin reader never interleave.
+    Stdout(io::Stdout, Framing),
+    /// Held for an HTTP handler
+    Buffer(Vec<u8>),
+}
+
+struct Output {
+    sink: Sink,
+    protocol: Protocol,
+    /// JSON-RPC error code and message of the last response, for request
+    /// logging and metrics
+    error: Option<(i32, String)>,
+}
+
+impl Output {
+    fn stdout(framing: Framing, protocol: Protocol) -> Self {
+        Self {
+            sink: Sink::Stdout(io::stdout(), framing),
+            protocol,
+            error: None,
+        }
+    }
+
+    fn buffer(protocol: Protocol) -> Self {
+        Self {
+            sink: Sink::Buffer(Vec::new()),
+            protocol,
+            error: None,
+        }
+    }
+
+    fn send(&mut self, payload: &[u8]) {
+        match self.sink {
+            Sink::Stdout(ref stdout, framing) => {
+                let written = transport::write_frame(&mut stdout.lock(), framing, payload);
+                if written.is_err_and(|e| e.kind() == io::ErrorKind::BrokenPipe) {
+                    request_shutdown(ShutdownReason::OutputClosed);
+                }
+            }
+            Sink::Buffer(ref mut buffer) => {
+                buffer.clear();
+                buffer.extend_from_slice(payload);
+            }
+        }
+    }
+
+    /// The buffered response; empty for stdout.
+    fn into_body(self) -> Vec<u8> {
+        match self.sink {
+            Sink::Stdout(..) => Vec::new(),
+            Sink::Buffer(buffer) => buffer,
+        }
+    }
+}
+
 fn handle_rpc<P, R, F>(
-    stdout: &mut io::Stdout,
+    out: &mut Output,
     id: serde_json::Value,
     params: serde_json::Value,
     handler: F,
@@ -408,40 +2550,91 @@ fn handle_rpc<P, R, F>(
     R: serde::Serialize,
     F: FnOnce(P) -> Result<R, String>,
 {
-    match serde_json::from_value::<P>(params) {
-        Ok(parsed) => match handler(parsed) {
-            Ok(result) => {
-                let response = JsonRpcResponse::success(id, result);
-                write_response(stdout, &response);
-            }
-            Err(message) => {
-                let response = JsonRpcResponse::<serde_json::Value>::failure(id, -32000, message);
-                write_response(stdout, &response);
-            }
-        },
+    match validation::parse_params::<P>(params) {
+        Ok(parsed) => respond(out, id, handler(parsed)),
+        Err(err) => write_field_error(out, id, &err),
+    }
+}
+
+/// As [`handle_rpc`], checking params against `limits` before the handler
+/// runs.
+fn handle_checked_rpc<P, R, F>(
+    out: &mut Output,
+    id: serde_json::Value,
+    params: serde_json::Value,
+    limits: &RequestLimits,
+    handler: F,
+) where
+    P: serde::de::DeserializeOwned + Validate,
+    R: serde::Serialize,
+    F: FnOnce(P) -> Result<R, String>,
+{
+    match validation::parse_params::<P>(params).and_then(|p| p.validate(limits).map(|()| p)) {
+        Ok(parsed) => respond(out, id, handler(parsed)),
+        Err(err) => write_field_error(out, id, &err),
+    }
+}
+
+fn respond<R: serde::Serialize>(
+    out: &mut Output,
+    id: serde_json::Value,
+    result: Result<R, String>,
+) {
+    match result {
+        Ok(result) => {
+            let response = JsonRpcResponse::success(id, result);
+            write_response(out, &response);
+        }
+        Err(message) => {
+            let response = JsonRpcResponse::<serde_json::Value>::failure(id, -32000, message);
+            write_response(out, &response);
+        }
+    }
+}
+
+fn write_field_error(out: &mut Output, id: serde_json::Value, err: &FieldError) {
+    let response = JsonRpcResponse::<serde_json::Value>::failure_with_data(
+        id,
+        err.code,
+        err.message.clone(),
+        err.data(),
+    );
+    write_response(out, &response);
+}
+
+fn write_response<T: serde::Serialize>(out: &mut Output, response: &JsonRpcResponse<T>) {
+    out.error = response.error.as_ref().map(|e| (e.code, e.message.clone()));
+    match out.protocol.encode(response) {
+        Ok(body) => out.send(&body),
         Err(err) => {
-            let response = JsonRpcResponse::<serde_json::Value>::failure(
-                id,
-                -32602,
-                format!("invalid params: {err}"),
+            let fallback = JsonRpcResponse::<serde_json::Value>::failure(
+                serde_json::Value::Null,
+                -32603,
+                format!("response serialization error: {err}"),
             );
-            write_response(stdout, &response);
+            if let Ok(body) = out.protocol.encode(&fallback) {
+                out.send(&body);
+            }
         }
     }
 }
 
-fn write_response<T: serde::Serialize>(stdout: &mut io::Stdout, response: &JsonRpcResponse<T>) {
-    match serde_json::to_string(response) {
-        Ok(json) => {
-            let _ = writeln!(stdout, "{json}");
-            let _ = stdout.flush();
-        }
-        Err(err) => {
-            let fallback = format!(
-                "{{\"jsonrpc\":\"2.0\",\"id\":null,\"error\":{{\"code\":-32603,\"message\":\"response serialization error: {err}\"}}}}"
+/// Standalone modes: print the result as JSON and exit without serving.
+fn print_and_exit<T: serde::Serialize>(
+    what: &str,
+    result: Result<T, checkpoint::CheckpointError>,
+) -> ! {
+    match result {
+        Ok(value) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&value).unwrap_or_default()
             );
-            let _ = writeln!(stdout, "{fallback}");
-            let _ = stdout.flush();
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("[predictor] checkpoint {what} failed: {e:?}");
+            std::process::exit(1);
         }
     }
 }
@@ -452,6 +2645,14 @@ fn find_arg(args: &[String], flag: &str) -> Option<String> {
         .and_then(|i| args.get(i + 1).cloned())
 }
 
+/// `--flag=value` or `--flag value`.
+fn find_prefixed_arg(args: &[String], flag: &str) -> Option<String> {
+    let prefix = format!("{flag}=");
+    args.iter()
+        .find_map(|a| a.strip_prefix(&prefix).map(str::to_string))
+        .or_else(|| find_arg(args, flag))
+}
+
 fn format_timestamp() -> String {
     let secs = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -0,0 +1,238 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::protocol::{CacheMetrics, CallMetrics, LastError, MetricsResult};
+
+/// Latencies kept per method for percentiles. Older calls fall out, so the
+/// figures track recent behaviour rather than the whole process lifetime.
+pub const LATENCY_WINDOW: usize = 1024;
+
+#[derive(Debug, Default)]
+struct MethodStats {
+    count: u64,
+    errors: u64,
+    latencies_ms: VecDeque<f64>,
+}
+
+impl MethodStats {
+    fn record(&mut self, duration_ms: f64, failed: bool) {
+        self.count += 1;
+        if failed {
+            self.errors += 1;
+        }
+        if self.latencies_ms.len() >= LATENCY_WINDOW {
+            self.latencies_ms.pop_front();
+        }
+        self.latencies_ms.push_back(duration_ms);
+    }
+
+    fn summary(&self) -> CallMetrics {
+        let mut sorted: Vec<f64> = self.latencies_ms.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        CallMetrics {
+            count: self.count,
+            errors: self.errors,
+            p50_ms: percentile(&sorted, 0.50),
+            p95_ms: percentile(&sorted, 0.95),
+            p99_ms: percentile(&sorted, 0.99),
+        }
+    }
+}
+
+fn add_lookups(cache: &mut CacheMetrics, hits: u64, misses: u64, entries: usize) {
+    cache.hits += hits;
+    cache.misses += misses;
+    cache.entries = entries;
+    let lookups = cache.hits + cache.misses;
+    cache.hit_rate = if lookups == 0 {
+        0.0
+    } else {
+        cache.hits as f64 / lookups as f64
+    };
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Peak resident set size from `VmHWM` in `/proc/self/status`. `None`
+/// off Linux.
+pub fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_hwm(&status)
+}
+
+fn parse_vm_hwm(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Nearest-rank percentile of ascending `sorted`, 0 when empty.
+pub fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Call counters and latencies for every RPC handled since startup.
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    requests: u64,
+    methods: BTreeMap<String, MethodStats>,
+    last_error: Option<LastError>,
+    /// Unix time in milliseconds when the last request finished
+    last_activity_ms: Option<u64>,
+    encoding_cache: CacheMetrics,
+    score_cache: CacheMetrics,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            requests: 0,
+            methods: BTreeMap::new(),
+            last_error: None,
+            last_activity_ms: None,
+            encoding_cache: CacheMetrics::default(),
+            score_cache: CacheMetrics::default(),
+        }
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn last_activity_ms(&self) -> Option<u64> {
+        self.last_activity_ms
+    }
+
+    /// Count one finished request. `error` is its JSON-RPC code and message.
+    pub fn record(&mut self, method: &str, duration: Duration, error: Option<(i32, &str)>) {
+        let now_ms = unix_ms();
+        self.requests += 1;
+        self.last_activity_ms = Some(now_ms);
+        self.methods
+            .entry(method.to_string())
+            .or_default()
+            .record(duration.as_secs_f64() * 1000.0, error.is_some());
+        if let Some((code, message)) = error {
+            self.last_error = Some(LastError {
+                method: method.to_string(),
+                code,
+                message: message.to_string(),
+                at_ms: now_ms,
+            });
+        }
+    }
+
+    /// Add a `score` call's encoding cache lookups. `entries` is the total
+    /// now held across models.
+    pub fn record_encodings(&mut self, hits: u64, misses: u64, entries: usize) {
+        add_lookups(&mut self.encoding_cache, hits, misses, entries);
+    }
+
+    /// As [`record_encodings`](Self::record_encodings), for whole results.
+    pub fn record_scores(&mut self, hits: u64, misses: u64, entries: usize) {
+        add_lookups(&mut self.score_cache, hits, misses, entries);
+    }
+
+    /// Current figures. `parameter_bytes` comes from the caller, which owns
+    /// the weights.
+    pub fn report(&self, parameter_bytes: usize) -> MetricsResult {
+        let call = |method: &str| {
+            self.methods
+                .get(method)
+                .map(MethodStats::summary)
+                .unwrap_or_default()
+        };
+        MetricsResult {
+            uptime_ms: self.uptime().as_millis() as u64,
+            requests: self.requests,
+            score: call("score"),
+            train: call("train"),
+            methods: self
+                .methods
+                .iter()
+                .map(|(name, stats)| (name.clone(), stats.summary()))
+                .collect(),
+            last_error: self.last_error.clone(),
+            parameter_bytes,
+            encoding_cache: self.encoding_cache.clone(),
+            score_cache: self.score_cache.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_cover_the_recent_window() {
+        let mut metrics = Metrics::new();
+        for ms in 1..=100 {
+            metrics.record("score", Duration::from_millis(ms), None);
+        }
+        metrics.record(
+            "train",
+            Duration::from_millis(5),
+            Some((-32000, "empty batch")),
+        );
+
+        let report = metrics.report(64);
+        assert_eq!(report.requests, 101);
+        assert_eq!(report.score.count, 100);
+        assert_eq!(report.score.p50_ms, 50.0);
+        assert_eq!(report.score.p95_ms, 95.0);
+        assert_eq!(report.score.p99_ms, 99.0);
+        assert_eq!(report.train.errors, 1);
+        assert_eq!(report.last_error.as_ref().unwrap().method, "train");
+        assert_eq!(report.methods["score"].count, 100);
+        assert_eq!(report.parameter_bytes, 64);
+        assert!(metrics.last_activity_ms().is_some());
+        assert_eq!(report.encoding_cache.hit_rate, 0.0);
+
+        metrics.record_encodings(1, 3, 4);
+        metrics.record_encodings(2, 0, 4);
+        let cache = metrics.report(64).encoding_cache;
+        assert_eq!((cache.hits, cache.misses, cache.entries), (3, 3, 4));
+        assert_eq!(cache.hit_rate, 0.5);
+
+        for _ in 0..LATENCY_WINDOW {
+            metrics.record("score", Duration::from_millis(2), None);
+        }
+        let report = metrics.report(64);
+        assert_eq!(report.score.count, 100 + LATENCY_WINDOW as u64);
+        assert_eq!(report.score.p99_ms, 2.0, "old latencies age out");
+        assert_eq!(percentile(&[], 0.5), 0.0);
+    }
+
+    #[test]
+    fn peak_rss_comes_from_vm_hwm() {
+        let status = "Name:\tsignet-predictor\nVmPeak:\t  90000 kB\nVmHWM:\t   2048 kB\n";
+        assert_eq!(parse_vm_hwm(status), Some(2048 * 1024));
+        assert_eq!(parse_vm_hwm("Name:\tx\n"), None);
+    }
+}
@@ -1,12 +1,16 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
     autograd::{Act, Param, Rng, Tape},
+    cache::{content_hash, EncodingCache},
+    features::FeatureSchema,
     protocol::FEATURE_DIM,
-    tokenizer::HashTrickTokenizer,
+    tokenizer::{HashTrickTokenizer, TokenizerDescriptor},
 };
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ScorerConfig {
     pub native_dim: usize,
     pub internal_dim: usize,
@@ -14,8 +18,32 @@ pub struct ScorerConfig {
     pub extra_features: usize,
     pub hash_buckets: usize,
     pub project_slots: usize,
+    /// Extra candidate embedding widths (e.g. vectors left over from a
+    /// previous embedding provider), each with its own down-projection into
+    /// the shared internal space.
+    #[serde(default)]
+    pub adapter_dims: Vec<usize>,
+    /// Named features behind `extra_features`. `None` for checkpoints that
+    /// predate schemas, whose features are only checked by width.
+    #[serde(default)]
+    pub feature_schema: Option<FeatureSchema>,
+    /// Tokens kept per text-only candidate, first N after stopwords.
+    /// `None` (and checkpoints from before the cap) keep them all.
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+    /// Tokenization the hash table was trained with. `None` for checkpoints
+    /// that predate descriptors, which used the legacy ASCII split.
+    #[serde(default)]
+    pub tokenizer: Option<TokenizerDescriptor>,
+    /// Extra hash table rows for adjacent-token bigrams, after the unigram
+    /// rows so the two never collide. 0 disables bigrams.
+    #[serde(default)]
+    pub bigram_buckets: usize,
 }
 
+/// Enough for a paragraph; beyond it the mean pool mostly adds noise.
+pub const DEFAULT_MAX_TOKENS: usize = 128;
+
 impl Default for ScorerConfig {
     fn default() -> Self {
         Self {
@@ -25,10 +53,166 @@ impl Default for ScorerConfig {
             extra_features: FEATURE_DIM,
             hash_buckets: 16_384,
             project_slots: 32,
+            adapter_dims: Vec::new(),
+            feature_schema: Some(FeatureSchema::default()),
+            max_tokens: Some(DEFAULT_MAX_TOKENS),
+            tokenizer: Some(TokenizerDescriptor::default()),
+            bigram_buckets: 0,
+        }
+    }
+}
+
+impl ScorerConfig {
+    /// Rows of the hash embedding table: unigram then bigram buckets.
+    pub fn token_rows(&self) -> usize {
+        self.hash_buckets + self.bigram_buckets
+    }
+
+    /// Narrow candidate feature rows to this model's schema. Callers always
+    /// send full `FEATURE_DIM` rows; rows of any other width pass through
+    /// for the caller's own dimension check.
+    pub fn project_features(&self, rows: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+        match self.feature_schema {
+            Some(ref schema) if schema.dim() != FEATURE_DIM => rows
+                .into_iter()
+                .map(|row| {
+                    if row.len() == FEATURE_DIM {
+                        schema.project(&row)
+                    } else {
+                        row
+                    }
+                })
+                .collect(),
+            _ => rows,
         }
     }
 }
 
+/// Per-feature standardization learned from the training data. Applied to
+/// every candidate feature row before it reaches the gate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureStats {
+    pub mean: Vec<f64>,
+    pub std: Vec<f64>,
+}
+
+impl FeatureStats {
+    /// Features with (near) zero spread keep their raw scale instead of
+    /// being divided by a tiny number.
+    const MIN_STD: f64 = 1e-6;
+
+    pub fn from_rows<'a>(rows: impl IntoIterator<Item = &'a [f64]>, dim: usize) -> Option<Self> {
+        let mut builder = FeatureStatsBuilder::new(dim);
+        for row in rows {
+            builder.add(row);
+        }
+        builder.finish()
+    }
+
+    pub fn dim(&self) -> usize {
+        self.mean.len()
+    }
+
+    pub fn apply(&self, features: &[f64]) -> Vec<f64> {
+        features
+            .iter()
+            .zip(self.mean.iter().zip(&self.std))
+            .map(|(value, (mean, std))| (value - mean) / std)
+            .collect()
+    }
+}
+
+/// Running sums for [`FeatureStats`], so stats can be gathered over data
+/// that never sits in memory all at once.
+#[derive(Debug, Clone)]
+pub struct FeatureStatsBuilder {
+    count: usize,
+    sum: Vec<f64>,
+    sum_sq: Vec<f64>,
+}
+
+impl FeatureStatsBuilder {
+    pub fn new(dim: usize) -> Self {
+        Self {
+            count: 0,
+            sum: vec![0.0; dim],
+            sum_sq: vec![0.0; dim],
+        }
+    }
+
+    /// Rows of the wrong width are ignored.
+    pub fn add(&mut self, row: &[f64]) {
+        if row.len() != self.sum.len() {
+            return;
+        }
+        for (i, value) in row.iter().enumerate() {
+            self.sum[i] += value;
+            self.sum_sq[i] += value * value;
+        }
+        self.count += 1;
+    }
+
+    pub fn finish(&self) -> Option<FeatureStats> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let n = self.count as f64;
+        let mean: Vec<f64> = self.sum.iter().map(|s| s / n).collect();
+        let std = self
+            .sum_sq
+            .iter()
+            .zip(&mean)
+            .map(|(sq, m)| {
+                let sd = (sq / n - m * m).max(0.0).sqrt();
+                if sd < FeatureStats::MIN_STD {
+                    1.0
+                } else {
+                    sd
+                }
+            })
+            .collect();
+        Some(FeatureStats { mean, std })
+    }
+}
+
+/// Per-bucket document frequency of hash tokens seen in training texts,
+/// used to weight token embeddings by IDF instead of a plain mean.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenStats {
+    pub documents: u64,
+    /// Texts containing each bucket at least once
+    pub df: Vec<u64>,
+}
+
+impl TokenStats {
+    pub fn new(buckets: usize) -> Self {
+        Self {
+            documents: 0,
+            df: vec![0; buckets],
+        }
+    }
+
+    /// Count one document's tokens; repeats within it count once.
+    pub fn observe(&mut self, buckets: &[usize]) {
+        let mut seen = buckets.to_vec();
+        seen.sort_unstable();
+        seen.dedup();
+        for bucket in seen {
+            if let Some(count) = self.df.get_mut(bucket) {
+                *count += 1;
+            }
+        }
+        self.documents += 1;
+    }
+
+    /// Smoothed IDF, always positive so no token is dropped outright.
+    pub fn idf(&self, bucket: usize) -> f64 {
+        let df = self.df.get(bucket).copied().unwrap_or(0);
+        ((1 + self.documents) as f64 / (1 + df) as f64).ln() + 1.0
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CandidateInput<'a> {
     pub id: &'a str,
@@ -37,6 +221,36 @@ pub struct CandidateInput<'a> {
     pub features: &'a [f64],
 }
 
+/// Candidate kept by the top-k prefilter. Orders by similarity; on a tie
+/// the earlier candidate ranks higher.
+#[derive(Debug, Clone, Copy)]
+struct Ranked {
+    similarity: f64,
+    index: usize,
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.similarity
+            .total_cmp(&other.similarity)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ScoredCandidate {
     pub id: String,
@@ -54,7 +268,11 @@ pub struct CrossAttentionScorer {
     gate_proj: usize,
     hash_embeddings: usize,
     project_embeddings: usize,
+    /// `(embedding_dim, param_idx)` down-projections for `adapter_dims`
+    adapters: Vec<(usize, usize)>,
     tokenizer: HashTrickTokenizer,
+    feature_stats: Option<FeatureStats>,
+    token_stats: Option<TokenStats>,
 }
 
 impl CrossAttentionScorer {
@@ -88,7 +306,7 @@ impl CrossAttentionScorer {
         ));
         let hash_embeddings = tape.add_param(Param::matrix(
             rng,
-            config.hash_buckets,
+            config.token_rows(),
             config.internal_dim,
             h_std,
         ));
@@ -98,11 +316,31 @@ impl CrossAttentionScorer {
             config.internal_dim,
             h_std,
         ));
-        // Gate input = value projection + 17 structured/behavioral features
+        // Gate input = value projection + structured/behavioral features
         // + project embedding + bias.
         let gate_width = config.value_dim + config.extra_features + config.internal_dim + 1;
         let gate_proj = tape.add_param(Param::matrix(rng, 1, gate_width, h_std));
 
+        // Adapters come after the core parameters so checkpoints written
+        // without them keep their parameter order.
+        let mut adapters: Vec<(usize, usize)> = Vec::new();
+        for &dim in &config.adapter_dims {
+            if dim == 0 || dim == config.native_dim || adapters.iter().any(|(d, _)| *d == dim) {
+                continue;
+            }
+            let std = (1.0 / dim as f64).sqrt();
+            let param = tape.add_param(Param::matrix(rng, config.internal_dim, dim, std));
+            adapters.push((dim, param));
+        }
+
+        let descriptor = config
+            .tokenizer
+            .clone()
+            .unwrap_or_else(TokenizerDescriptor::legacy);
+        let tokenizer = HashTrickTokenizer::new(config.hash_buckets)
+            .with_max_tokens(config.max_tokens)
+            .with_descriptor(descriptor)
+            .with_bigrams(config.bigram_buckets);
         Self {
             config,
             down_proj,
@@ -112,16 +350,70 @@ impl CrossAttentionScorer {
             gate_proj,
             hash_embeddings,
             project_embeddings,
-            tokenizer: HashTrickTokenizer::new(config.hash_buckets),
+            adapters,
+            tokenizer,
+            feature_stats: None,
+            token_stats: None,
         }
     }
 
-    pub fn config(&self) -> ScorerConfig {
-        self.config
+    pub fn config(&self) -> &ScorerConfig {
+        &self.config
+    }
+
+    /// Whether a candidate embedding of this width can be encoded directly.
+    pub fn accepts_embedding_dim(&self, dim: usize) -> bool {
+        dim == self.config.native_dim || self.adapters.iter().any(|(d, _)| *d == dim)
+    }
+
+    pub fn feature_stats(&self) -> Option<&FeatureStats> {
+        self.feature_stats.as_ref()
+    }
+
+    pub fn token_stats(&self) -> Option<&TokenStats> {
+        self.token_stats.as_ref()
+    }
+
+    pub fn set_token_stats(&mut self, stats: Option<TokenStats>) -> Result<(), String> {
+        if let Some(ref stats) = stats {
+            if stats.df.len() != self.config.token_rows() {
+                return Err(format!(
+                    "token stats bucket mismatch: expected {}, got {}",
+                    self.config.token_rows(),
+                    stats.df.len()
+                ));
+            }
+        }
+        self.token_stats = stats;
+        Ok(())
+    }
+
+    /// Add training texts to the document frequencies behind IDF weighting.
+    pub fn observe_texts<'a>(&mut self, texts: impl IntoIterator<Item = &'a str>) {
+        let stats = self
+            .token_stats
+            .get_or_insert_with(|| TokenStats::new(self.config.token_rows()));
+        for text in texts {
+            stats.observe(&self.tokenizer.token_indices(text));
+        }
     }
 
-    pub fn param_indices(&self) -> [usize; 7] {
-        [
+    pub fn set_feature_stats(&mut self, stats: Option<FeatureStats>) -> Result<(), String> {
+        if let Some(ref stats) = stats {
+            if stats.dim() != self.config.extra_features || stats.std.len() != stats.dim() {
+                return Err(format!(
+                    "feature stats dim mismatch: expected {}, got {}",
+                    self.config.extra_features,
+                    stats.dim()
+                ));
+            }
+        }
+        self.feature_stats = stats;
+        Ok(())
+    }
+
+    pub fn param_indices(&self) -> Vec<usize> {
+        let mut indices = vec![
             self.down_proj,
             self.q_proj,
             self.k_proj,
@@ -129,7 +421,41 @@ impl CrossAttentionScorer {
             self.gate_proj,
             self.hash_embeddings,
             self.project_embeddings,
+        ];
+        indices.extend(self.adapters.iter().map(|(_, param)| *param));
+        indices
+    }
+
+    /// Parameters that map embeddings and text into the internal space:
+    /// the down projection, hashed token embeddings and adapters. A model
+    /// cloned for a new agent can keep these fixed and retrain only the
+    /// attention and project weights.
+    pub fn encoder_param_indices(&self) -> Vec<usize> {
+        let mut indices = vec![self.down_proj, self.hash_embeddings];
+        indices.extend(self.adapters.iter().map(|(_, param)| *param));
+        indices
+    }
+
+    /// Names of the parameters, in [`param_indices`](Self::param_indices) order.
+    pub fn param_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = [
+            "down_proj",
+            "q_proj",
+            "k_proj",
+            "v_proj",
+            "gate_proj",
+            "hash_embeddings",
+            "project_embeddings",
         ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+        names.extend(
+            self.adapters
+                .iter()
+                .map(|(dim, _)| format!("adapter_{dim}")),
+        );
+        names
     }
 
     fn encode_candidate(
@@ -138,9 +464,17 @@ impl CrossAttentionScorer {
         candidate: &CandidateInput<'_>,
     ) -> Result<Act, String> {
         if let Some(embedding) = candidate.embedding {
-            if embedding.len() == self.config.native_dim {
+            let projection = if embedding.len() == self.config.native_dim {
+                Some(self.down_proj)
+            } else {
+                self.adapters
+                    .iter()
+                    .find(|(dim, _)| *dim == embedding.len())
+                    .map(|(_, param)| *param)
+            };
+            if let Some(projection) = projection {
                 let embedding_act = tape.constant(embedding.to_vec());
-                let down = tape.matvec(self.down_proj, embedding_act);
+                let down = tape.matvec(projection, embedding_act);
                 return Ok(tape.layer_norm(down));
             }
         }
@@ -151,10 +485,16 @@ impl CrossAttentionScorer {
                 return Ok(tape.constant(vec![0.0; self.config.internal_dim]));
             }
             let token_embeds = token_ids
-                .into_iter()
-                .map(|idx| tape.embed_row(self.hash_embeddings, idx))
+                .iter()
+                .map(|&idx| tape.embed_row(self.hash_embeddings, idx))
                 .collect::<Vec<_>>();
-            let pooled = tape.mean_pool(&token_embeds);
+            let pooled = match self.token_stats {
+                Some(ref stats) if stats.documents > 0 => {
+                    let weights: Vec<f64> = token_ids.iter().map(|&idx| stats.idf(idx)).collect();
+                    tape.weighted_pool(&token_embeds, &weights)
+                }
+                _ => tape.mean_pool(&token_embeds),
+            };
             return Ok(tape.layer_norm(pooled));
         }
 
@@ -164,12 +504,43 @@ impl CrossAttentionScorer {
         ))
     }
 
+    /// As [`encode_candidate`](Self::encode_candidate), reusing `cache`. A
+    /// hit goes on the tape as a constant, so only scoring may pass one.
+    fn encode_cached(
+        &self,
+        tape: &mut Tape,
+        candidate: &CandidateInput<'_>,
+        cache: &mut EncodingCache,
+    ) -> Result<Act, String> {
+        let embedding = candidate
+            .embedding
+            .filter(|embedding| self.accepts_embedding_dim(embedding.len()));
+        let content = content_hash(embedding, candidate.text);
+        if let Some(encoded) = cache.get(candidate.id, content) {
+            return Ok(tape.constant(encoded.to_vec()));
+        }
+        let encoded = self.encode_candidate(tape, candidate)?;
+        cache.insert(candidate.id, content, tape.value(encoded).to_vec());
+        Ok(encoded)
+    }
+
     pub fn forward_logits(
         &self,
         tape: &mut Tape,
         query_embedding: &[f64],
         candidates: &[CandidateInput<'_>],
         project_slot: usize,
+    ) -> Result<Act, String> {
+        self.forward_logits_with(tape, query_embedding, candidates, project_slot, None)
+    }
+
+    fn forward_logits_with(
+        &self,
+        tape: &mut Tape,
+        query_embedding: &[f64],
+        candidates: &[CandidateInput<'_>],
+        project_slot: usize,
+        mut cache: Option<&mut EncodingCache>,
     ) -> Result<Act, String> {
         if query_embedding.len() != self.config.native_dim {
             return Err(format!(
@@ -203,7 +574,10 @@ impl CrossAttentionScorer {
                 ));
             }
 
-            let encoded = self.encode_candidate(tape, candidate)?;
+            let encoded = match cache {
+                Some(ref mut cache) => self.encode_cached(tape, candidate, cache)?,
+                None => self.encode_candidate(tape, candidate)?,
+            };
             let k = tape.matvec(self.k_proj, encoded);
             let v = tape.matvec(self.v_proj, encoded);
 
@@ -211,7 +585,11 @@ impl CrossAttentionScorer {
             let scaled_similarity =
                 tape.scale(similarity, 1.0 / (self.config.internal_dim as f64).sqrt());
 
-            let feature_act = tape.constant(candidate.features.to_vec());
+            let features = match self.feature_stats {
+                Some(ref stats) => stats.apply(candidate.features),
+                None => candidate.features.to_vec(),
+            };
+            let feature_act = tape.constant(features);
             let bias = tape.constant(vec![1.0]);
             let gate_input = tape.feature_concat(&[v, feature_act, project_embedding, bias]);
             let gate_logit = tape.matvec(self.gate_proj, gate_input);
@@ -229,9 +607,108 @@ impl CrossAttentionScorer {
         candidates: &[CandidateInput<'_>],
         project_slot: usize,
     ) -> Result<Vec<ScoredCandidate>, String> {
+        self.score_with(tape, query_embedding, candidates, project_slot, None)
+    }
+
+    /// As [`score`](Self::score), taking candidate encodings from `cache`
+    /// and adding the ones it lacks. The cache must have been filled from
+    /// these same weights.
+    pub fn score_cached(
+        &self,
+        tape: &mut Tape,
+        query_embedding: &[f64],
+        candidates: &[CandidateInput<'_>],
+        project_slot: usize,
+        cache: &mut EncodingCache,
+    ) -> Result<Vec<ScoredCandidate>, String> {
+        self.score_with(tape, query_embedding, candidates, project_slot, Some(cache))
+    }
+
+    /// As [`score_cached`](Self::score_cached), but only the `top_k`
+    /// candidates with the highest query-key similarity get the gate and
+    /// softmax, so scores sum to 1 over those survivors. The rest are
+    /// encoded one at a time and dropped.
+    pub fn score_top_k(
+        &self,
+        tape: &mut Tape,
+        query_embedding: &[f64],
+        candidates: &[CandidateInput<'_>],
+        project_slot: usize,
+        top_k: usize,
+        cache: &mut EncodingCache,
+    ) -> Result<Vec<ScoredCandidate>, String> {
+        if top_k >= candidates.len() {
+            return self.score_cached(tape, query_embedding, candidates, project_slot, cache);
+        }
+        let kept = self
+            .prefilter(tape, query_embedding, candidates, top_k, cache)?
+            .into_iter()
+            .map(|index| candidates[index].clone())
+            .collect::<Vec<_>>();
+        self.score_cached(tape, query_embedding, &kept, project_slot, cache)
+    }
+
+    /// Indices, in input order, of the `top_k` candidates by scaled q·k.
+    /// The tape is rewound after each candidate, so it never holds more
+    /// than one.
+    fn prefilter(
+        &self,
+        tape: &mut Tape,
+        query_embedding: &[f64],
+        candidates: &[CandidateInput<'_>],
+        top_k: usize,
+        cache: &mut EncodingCache,
+    ) -> Result<Vec<usize>, String> {
+        if query_embedding.len() != self.config.native_dim {
+            return Err(format!(
+                "query embedding dim mismatch: expected {}, got {}",
+                self.config.native_dim,
+                query_embedding.len()
+            ));
+        }
+
         tape.reset();
+        let query = tape.constant(query_embedding.to_vec());
+        let query_down = tape.matvec(self.down_proj, query);
+        let query_norm = tape.layer_norm(query_down);
+        let q = tape.matvec(self.q_proj, query_norm);
+        let mark = tape.mark();
+        let scale = 1.0 / (self.config.internal_dim as f64).sqrt();
 
-        let logits = self.forward_logits(tape, query_embedding, candidates, project_slot)?;
+        let mut heap = BinaryHeap::with_capacity(top_k + 1);
+        for (index, candidate) in candidates.iter().enumerate() {
+            let encoded = self.encode_cached(tape, candidate, cache)?;
+            let k = tape.matvec(self.k_proj, encoded);
+            let similarity = tape.dot(q, k);
+            let similarity = tape.scalar(similarity) * scale;
+            tape.truncate(mark);
+
+            heap.push(Reverse(Ranked { similarity, index }));
+            if heap.len() > top_k {
+                heap.pop();
+            }
+        }
+
+        let mut kept = heap
+            .into_iter()
+            .map(|Reverse(ranked)| ranked.index)
+            .collect::<Vec<_>>();
+        kept.sort_unstable();
+        Ok(kept)
+    }
+
+    fn score_with(
+        &self,
+        tape: &mut Tape,
+        query_embedding: &[f64],
+        candidates: &[CandidateInput<'_>],
+        project_slot: usize,
+        cache: Option<&mut EncodingCache>,
+    ) -> Result<Vec<ScoredCandidate>, String> {
+        tape.reset();
+
+        let logits =
+            self.forward_logits_with(tape, query_embedding, candidates, project_slot, cache)?;
         let probs = tape.softmax(logits);
 
         let prob_values = tape.value(probs).to_vec();
@@ -262,6 +739,29 @@ mod tests {
     use super::*;
     use crate::autograd::{Rng, Tape};
 
+    #[test]
+    fn project_features_narrows_full_rows_to_the_schema() {
+        let schema = FeatureSchema::without(&["recency".to_string()]).unwrap();
+        let cfg = ScorerConfig {
+            extra_features: schema.dim(),
+            feature_schema: Some(schema),
+            ..ScorerConfig::default()
+        };
+        let full: Vec<f64> = (0..FEATURE_DIM).map(|i| i as f64).collect();
+        let rows = cfg.project_features(vec![full, vec![7.0; 3]]);
+        assert_eq!(rows[0].len(), FEATURE_DIM - 1);
+        assert_eq!(rows[0][0], 1.0, "recency is dropped");
+        assert_eq!(rows[1], vec![7.0; 3], "other widths are left alone");
+
+        let v1 = ScorerConfig {
+            extra_features: FEATURE_DIM,
+            feature_schema: None,
+            ..ScorerConfig::default()
+        };
+        let full = vec![0.5; FEATURE_DIM];
+        assert_eq!(v1.project_features(vec![full.clone()]), vec![full]);
+    }
+
     #[test]
     fn score_returns_distribution_over_candidates() {
         let mut tape = Tape::new();
@@ -273,6 +773,11 @@ mod tests {
             extra_features: 3,
             hash_buckets: 128,
             project_slots: 4,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
         };
         let scorer = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
 
@@ -321,6 +826,11 @@ mod tests {
             extra_features: 3,
             hash_buckets: 64,
             project_slots: 4,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
         };
         let scorer = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
         let query = vec![0.2; 8];
@@ -339,4 +849,228 @@ mod tests {
         assert_eq!(scores.len(), 1);
         assert!((scores[0].score - 1.0).abs() < 1e-8);
     }
+
+    #[test]
+    fn cached_encodings_score_the_same() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(11);
+        let cfg = ScorerConfig {
+            native_dim: 8,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 1,
+            hash_buckets: 64,
+            project_slots: 2,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let scorer = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+        let embedding = vec![0.3; 8];
+        let features = vec![0.5];
+        let candidates = vec![
+            CandidateInput {
+                id: "emb",
+                embedding: Some(&embedding),
+                text: None,
+                features: &features,
+            },
+            CandidateInput {
+                id: "txt",
+                embedding: None,
+                text: Some("prefers tabs over spaces"),
+                features: &features,
+            },
+        ];
+
+        let plain = scorer
+            .score(&mut tape, &[0.1; 8], &candidates, 0)
+            .expect("score");
+        let mut cache = EncodingCache::new(16);
+        for _ in 0..2 {
+            let cached = scorer
+                .score_cached(&mut tape, &[0.1; 8], &candidates, 0, &mut cache)
+                .expect("score");
+            for (a, b) in plain.iter().zip(&cached) {
+                assert_eq!(a.id, b.id);
+                assert!((a.logit - b.logit).abs() < 1e-12);
+            }
+        }
+        assert_eq!(cache.take_counts(), (2, 2));
+    }
+
+    #[test]
+    fn top_k_keeps_the_most_similar() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(13);
+        let cfg = ScorerConfig {
+            native_dim: 8,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 1,
+            hash_buckets: 16,
+            project_slots: 2,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let scorer = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+        let query = [0.1, -0.4, 0.3, 0.2, -0.1, 0.5, 0.0, 0.7];
+        let embeddings = (0..20)
+            .map(|i| (0..8).map(|j| ((i * 8 + j) as f64 * 0.37).sin()).collect())
+            .collect::<Vec<Vec<f64>>>();
+        let ids = (0..20).map(|i| format!("m{i}")).collect::<Vec<_>>();
+        let features = vec![0.0];
+        let candidates = ids
+            .iter()
+            .zip(&embeddings)
+            .map(|(id, embedding)| CandidateInput {
+                id,
+                embedding: Some(embedding),
+                text: None,
+                features: &features,
+            })
+            .collect::<Vec<_>>();
+
+        // Reference: q·k of every candidate, from the full forward pass
+        let mut cache = EncodingCache::new(0);
+        let mut similarities = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| {
+                tape.reset();
+                let q_in = tape.constant(query.to_vec());
+                let down = tape.matvec(scorer.down_proj, q_in);
+                let norm = tape.layer_norm(down);
+                let q = tape.matvec(scorer.q_proj, norm);
+                let encoded = scorer.encode_candidate(&mut tape, candidate).unwrap();
+                let k = tape.matvec(scorer.k_proj, encoded);
+                let dot = tape.dot(q, k);
+                (tape.scalar(dot), index)
+            })
+            .collect::<Vec<_>>();
+        similarities.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let mut expected = similarities[..5]
+            .iter()
+            .map(|(_, index)| ids[*index].clone())
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        let scores = scorer
+            .score_top_k(&mut tape, &query, &candidates, 0, 5, &mut cache)
+            .expect("score");
+        let mut kept = scores.iter().map(|s| s.id.clone()).collect::<Vec<_>>();
+        kept.sort();
+        assert_eq!(kept, expected);
+        let total: f64 = scores.iter().map(|s| s.score).sum();
+        assert!((total - 1.0).abs() < 1e-8);
+
+        let all = scorer
+            .score_top_k(&mut tape, &query, &candidates, 0, 50, &mut cache)
+            .expect("score");
+        assert_eq!(all.len(), 20);
+    }
+
+    #[test]
+    fn feature_stats_standardize_rows() {
+        let rows = [vec![1.0, 5.0], vec![3.0, 5.0]];
+        let stats = FeatureStats::from_rows(rows.iter().map(Vec::as_slice), 2).expect("stats");
+        assert!((stats.mean[0] - 2.0).abs() < 1e-9);
+        assert!((stats.std[0] - 1.0).abs() < 1e-9);
+        // constant column keeps unit scale
+        assert!((stats.std[1] - 1.0).abs() < 1e-9);
+
+        let out = stats.apply(&[3.0, 5.0]);
+        assert!((out[0] - 1.0).abs() < 1e-9);
+        assert!(out[1].abs() < 1e-9);
+    }
+
+    #[test]
+    fn idf_favours_rare_buckets() {
+        let mut stats = TokenStats::new(8);
+        stats.observe(&[1, 2, 2]);
+        stats.observe(&[1, 3]);
+        stats.observe(&[1]);
+        assert_eq!(stats.documents, 3);
+        assert_eq!(stats.df[1], 3);
+        assert_eq!(stats.df[2], 1);
+        assert!(stats.idf(2) > stats.idf(1));
+        assert!(stats.idf(1) > 0.0);
+        assert!(stats.idf(7) > stats.idf(2));
+    }
+
+    #[test]
+    fn set_feature_stats_rejects_wrong_dim() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(3);
+        let cfg = ScorerConfig {
+            native_dim: 4,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 3,
+            hash_buckets: 16,
+            project_slots: 2,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let mut scorer = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+        let stats = FeatureStats {
+            mean: vec![0.0; 2],
+            std: vec![1.0; 2],
+        };
+        assert!(scorer.set_feature_stats(Some(stats)).is_err());
+        assert!(scorer.feature_stats().is_none());
+    }
+
+    #[test]
+    fn adapter_encodes_non_native_embedding() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(5);
+        let cfg = ScorerConfig {
+            native_dim: 8,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 1,
+            hash_buckets: 16,
+            project_slots: 2,
+            adapter_dims: vec![3],
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let scorer = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+        assert_eq!(scorer.param_indices().len(), 8);
+        assert!(scorer.accepts_embedding_dim(3));
+        assert!(!scorer.accepts_embedding_dim(5));
+
+        let legacy = vec![0.3, -0.1, 0.7];
+        let odd = vec![0.1; 5];
+        let features = vec![0.0];
+        let candidates = vec![
+            CandidateInput {
+                id: "legacy",
+                embedding: Some(&legacy),
+                text: None,
+                features: &features,
+            },
+            CandidateInput {
+                id: "unknown-dim",
+                embedding: Some(&odd),
+                text: Some("falls back to text"),
+                features: &features,
+            },
+        ];
+        let scores = scorer
+            .score(&mut tape, &[0.2; 8], &candidates, 0)
+            .expect("score");
+        assert_eq!(scores.len(), 2);
+    }
 }
@@ -0,0 +1,207 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    autograd::Tape,
+    data::TrainingSample,
+    model::CrossAttentionScorer,
+    training::{train_batch, Adam, TrainingError},
+};
+
+/// Step size of a feedback update relative to a batch-training session.
+/// A single click should nudge the model, not swing it.
+pub const FEEDBACK_WEIGHT: f64 = 0.25;
+
+/// Listwise temperature for feedback updates, as for `train`
+pub const FEEDBACK_TEMPERATURE: f64 = 0.5;
+
+/// Events held between updates; the oldest are dropped past this.
+pub const MAX_BUFFERED_EVENTS: usize = 256;
+
+/// Shortest gap between two online updates unless configured otherwise
+pub const DEFAULT_MIN_UPDATE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What the user did with an injected memory.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackSignal {
+    Pin,
+    Delete,
+    /// Explicit usefulness rating in `[0, 1]`
+    Rating(f64),
+}
+
+impl FeedbackSignal {
+    /// Target label for the memory the signal is about.
+    pub fn label(self) -> Result<f64, String> {
+        match self {
+            Self::Pin => Ok(1.0),
+            Self::Delete => Ok(0.0),
+            Self::Rating(value) if (0.0..=1.0).contains(&value) => Ok(value),
+            Self::Rating(value) => Err(format!("rating must be within [0, 1], got {value}")),
+        }
+    }
+}
+
+/// Feedback samples waiting for the next update, at most one per memory so
+/// a user toggling a pin doesn't count twice.
+#[derive(Debug)]
+pub struct FeedbackBuffer {
+    events: VecDeque<(String, TrainingSample)>,
+    min_interval: Duration,
+    last_update: Option<Instant>,
+    /// Events evicted unapplied because the buffer was full
+    pub dropped: usize,
+}
+
+impl FeedbackBuffer {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            events: VecDeque::new(),
+            min_interval,
+            last_update: None,
+            dropped: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Queue a sample, replacing any earlier one for the same memory.
+    pub fn push(&mut self, memory_id: String, sample: TrainingSample) {
+        self.events.retain(|(id, _)| *id != memory_id);
+        if self.events.len() >= MAX_BUFFERED_EVENTS {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back((memory_id, sample));
+    }
+
+    /// Drain the buffer if an update is allowed at `now`.
+    pub fn take_due(&mut self, now: Instant) -> Option<Vec<TrainingSample>> {
+        if self.events.is_empty() {
+            return None;
+        }
+        if self
+            .last_update
+            .is_some_and(|last| now.duration_since(last) < self.min_interval)
+        {
+            return None;
+        }
+        self.last_update = Some(now);
+        Some(self.events.drain(..).map(|(_, sample)| sample).collect())
+    }
+}
+
+/// Outcome of one online update.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OnlineUpdate {
+    pub loss: f64,
+    pub steps: u64,
+    /// The update produced non-finite values and was undone
+    pub rolled_back: bool,
+}
+
+/// Train on buffered feedback, undoing the step if it leaves a non-finite
+/// loss or parameter. Weights and optimizer moments are restored together
+/// so a rejected update leaves no trace.
+pub fn apply_update(
+    tape: &mut Tape,
+    model: &CrossAttentionScorer,
+    optimizer: &mut Adam,
+    samples: &[TrainingSample],
+    temperature: f64,
+) -> Result<OnlineUpdate, TrainingError> {
+    let indices = model.param_indices();
+    let backup: Vec<Vec<f64>> = indices
+        .iter()
+        .map(|&idx| tape.params()[idx].data.clone())
+        .collect();
+    let optimizer_backup = optimizer.clone();
+
+    let stats = train_batch(tape, model, samples, optimizer, temperature)?;
+    let finite = stats.loss.is_finite()
+        && indices
+            .iter()
+            .all(|&idx| tape.params()[idx].data.iter().all(|v| v.is_finite()));
+    if finite {
+        return Ok(OnlineUpdate {
+            loss: stats.loss,
+            steps: stats.steps,
+            rolled_back: false,
+        });
+    }
+
+    for (&idx, data) in indices.iter().zip(backup) {
+        tape.params_mut()[idx].data = data;
+    }
+    *optimizer = optimizer_backup;
+    Ok(OnlineUpdate {
+        loss: stats.loss,
+        steps: 0,
+        rolled_back: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(label: f64) -> TrainingSample {
+        TrainingSample {
+            session_id: "feedback".to_string(),
+            query_embedding: vec![0.1; 4],
+            candidate_embeddings: vec![vec![0.2; 4], vec![0.5; 4]],
+            candidate_texts: vec![],
+            candidate_features: vec![],
+            project_slot: 0,
+            labels: vec![label, 0.5],
+            weight: FEEDBACK_WEIGHT,
+        }
+    }
+
+    #[test]
+    fn buffer_dedups_by_memory_and_rate_limits_updates() {
+        let mut buffer = FeedbackBuffer::new(Duration::from_secs(30));
+        buffer.push("m1".to_string(), sample(1.0));
+        buffer.push("m1".to_string(), sample(0.0));
+        buffer.push("m2".to_string(), sample(1.0));
+        assert_eq!(buffer.len(), 2);
+
+        let start = Instant::now();
+        let drained = buffer.take_due(start).expect("first update runs");
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained[0].labels[0], 0.0, "latest signal wins");
+
+        buffer.push("m3".to_string(), sample(1.0));
+        assert!(buffer.take_due(start + Duration::from_secs(10)).is_none());
+        assert!(buffer.take_due(start + Duration::from_secs(30)).is_some());
+    }
+
+    #[test]
+    fn signals_map_to_labels() {
+        assert_eq!(FeedbackSignal::Pin.label(), Ok(1.0));
+        assert_eq!(FeedbackSignal::Delete.label(), Ok(0.0));
+        assert_eq!(FeedbackSignal::Rating(0.7).label(), Ok(0.7));
+        assert!(FeedbackSignal::Rating(1.5).label().is_err());
+        assert!(FeedbackSignal::Rating(f64::NAN).label().is_err());
+
+        let parsed: FeedbackSignal = serde_json::from_str(r#"{"rating":0.25}"#).unwrap();
+        assert_eq!(parsed, FeedbackSignal::Rating(0.25));
+        let parsed: FeedbackSignal = serde_json::from_str(r#""pin""#).unwrap();
+        assert_eq!(parsed, FeedbackSignal::Pin);
+    }
+}
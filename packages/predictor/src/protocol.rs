@@ -1,6 +1,20 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::{
+    checkpoint::SnapshotInfo,
+    data::{
+        ChunkAggregation, DaemonSource, FeedbackMode, ProjectFilter, ProjectSlotStats,
+        SchemaWarning, TrainingCursor,
+    },
+    embedding::EmbeddingFormat,
+    online::FeedbackSignal,
+    sanitize::SanitizeReport,
+    training::{EvalMetrics, Hyperparameters},
+};
+
 /// Feature vector layout per candidate:
 /// [0]  log(age_days)
 /// [1]  importance
@@ -19,7 +33,11 @@ use serde_json::Value;
 /// [14] is_constraint
 /// [15] log(structural_density + 1)
 /// [16] is_ka_traversal
-pub const FEATURE_DIM: usize = 17;
+/// [17] is_pinned
+/// [18] project_match
+/// [19] log(content_chars + 1)
+/// [20] tag_overlap (share of tags seen on other session candidates)
+pub const FEATURE_DIM: usize = 21;
 
 #[derive(Debug, Deserialize)]
 pub struct JsonRpcRequest {
@@ -43,10 +61,18 @@ where
     pub error: Option<JsonRpcError>,
 }
 
+/// Params failed to decode or validate; `data.path` names the field
+pub const INVALID_PARAMS: i32 = -32602;
+
+/// A request exceeded a configured size limit; `data.path` names the field
+pub const LIMIT_EXCEEDED: i32 = -32001;
+
 #[derive(Debug, Serialize)]
 pub struct JsonRpcError {
     pub code: i32,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
 }
 
 impl<T> JsonRpcResponse<T>
@@ -70,34 +96,113 @@ where
             error: Some(JsonRpcError {
                 code,
                 message: message.into(),
+                data: None,
             }),
         }
     }
+
+    pub fn failure_with_data(
+        id: Value,
+        code: i32,
+        message: impl Into<String>,
+        data: Value,
+    ) -> Self {
+        let mut response = Self::failure(id, code, message);
+        if let Some(ref mut error) = response.error {
+            error.data = Some(data);
+        }
+        response
+    }
 }
 
-#[derive(Debug, Deserialize)]
+/// A message sent unprompted. It has no id and expects no reply.
+#[derive(Debug, Serialize)]
+pub struct JsonRpcNotification<T> {
+    pub jsonrpc: &'static str,
+    pub method: &'static str,
+    pub params: T,
+}
+
+impl<T: Serialize> JsonRpcNotification<T> {
+    pub fn new(method: &'static str, params: T) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            method,
+            params,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct ScoreParams {
     pub context_embedding: Vec<f64>,
     pub candidate_ids: Vec<String>,
     #[serde(default)]
     pub candidate_embeddings: Vec<Vec<f64>>,
+    /// Raw vector blobs as stored in the embeddings table (f32, f16 or
+    /// int8). Used for candidates whose `candidate_embeddings` row is empty.
+    #[serde(default)]
+    pub candidate_embedding_blobs: Vec<Option<Vec<u8>>>,
+    /// Encoding of `candidate_embedding_blobs`; inferred from size if absent
+    #[serde(default)]
+    pub embedding_format: Option<EmbeddingFormat>,
     #[serde(default)]
     pub candidate_texts: Vec<Option<String>>,
     #[serde(default)]
     pub candidate_features: Vec<Vec<f64>>,
     #[serde(default)]
     pub project_slot: usize,
+    /// Return only the best `top_k`. Candidates are ranked by query-key
+    /// similarity first and only the survivors are fully scored, so their
+    /// scores sum to 1 among themselves. Such requests are capped by
+    /// `--max-top-k-candidates` instead of `--max-candidates`.
+    #[serde(default)]
+    pub top_k: Option<usize>,
+    #[serde(default)]
+    pub cache: ScoreCacheMode,
+    /// Accept a cached result only if it is at most this old. The server
+    /// TTL still applies.
+    #[serde(default)]
+    pub cache_max_age_ms: Option<u64>,
 }
 
-#[derive(Debug, Serialize)]
+/// How a `score` call uses the result cache, which is off unless the
+/// predictor runs with `--score-cache-ttl-ms`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreCacheMode {
+    /// Answer from a fresh entry if there is one, else score and store
+    #[default]
+    Use,
+    /// Score again and replace the entry
+    Refresh,
+    /// Neither read nor store
+    Bypass,
+}
+
+impl ScoreCacheMode {
+    pub fn reads(self) -> bool {
+        self == Self::Use
+    }
+
+    pub fn stores(self) -> bool {
+        self != Self::Bypass
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ScoredMemory {
     pub id: String,
     pub score: f64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ScoreResult {
     pub scores: Vec<ScoredMemory>,
+    /// NaN/Inf inputs zeroed, and vectors dropped as corrupt
+    pub sanitized: SanitizeReport,
+    /// Answered from the score cache
+    pub cached: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -109,18 +214,86 @@ pub struct TrainParams {
     pub labels: Vec<f64>,
     #[serde(default)]
     pub project_slot: usize,
-    #[serde(default = "default_temperature")]
-    pub temperature: f64,
+    /// Defaults to the `set_config` temperature
+    #[serde(default)]
+    pub temperature: Option<f64>,
+}
+
+/// A user action on one injected memory, with the injection it came from.
+#[derive(Debug, Deserialize)]
+pub struct RecordFeedbackParams {
+    pub memory_id: String,
+    pub signal: FeedbackSignal,
+    /// Context the memory was injected for
+    pub context_embedding: Vec<f64>,
+    /// Everything injected alongside it, including `memory_id`. The other
+    /// candidates are what the signal is ranked against.
+    pub candidate_ids: Vec<String>,
+    pub candidate_embeddings: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub candidate_features: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub project_slot: usize,
 }
 
-const fn default_temperature() -> f64 {
-    0.5
+#[derive(Debug, Serialize)]
+pub struct RecordFeedbackResult {
+    /// Events still waiting for an update
+    pub buffered: usize,
+    /// An online update ran on this call
+    pub updated: bool,
+    /// The update was undone by its safeguards
+    pub rolled_back: bool,
+    pub loss: Option<f64>,
+    pub step: u64,
+    /// Events evicted unapplied since startup
+    pub dropped: usize,
+    pub autosaved: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct TrainResult {
     pub loss: f64,
     pub step: u64,
+    /// The update triggered an autosave of the live checkpoint
+    pub autosaved: bool,
+}
+
+/// Semantic version of this build, reported so callers can feature-detect.
+pub const PREDICTOR_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// RPC methods this build answers. Keep in step with the dispatch in main.
+pub const METHODS: &[&str] = &[
+    "status",
+    "score",
+    "train",
+    "train_from_db",
+    "record_feedback",
+    "cancel",
+    "shutdown",
+    "evaluate",
+    "save_checkpoint",
+    "load_checkpoint",
+    "list_checkpoints",
+    "restore_checkpoint",
+    "inspect_checkpoint",
+    "diff_checkpoints",
+    "export_safetensors",
+    "metrics",
+    "ping",
+    "create_model",
+    "clone_model",
+    "get_config",
+    "set_config",
+    "benchmark",
+];
+
+/// Checkpoint file versions this build reads and writes.
+#[derive(Debug, Serialize)]
+pub struct CheckpointVersions {
+    pub min_read: u32,
+    pub max_read: u32,
+    pub write: u32,
 }
 
 #[derive(Debug, Serialize)]
@@ -130,7 +303,22 @@ pub struct StatusResult {
     pub model_version: u64,
     pub last_trained: Option<String>,
     pub native_dimensions: usize,
+    /// Width of the `candidate_features` rows `score` accepts. Always
+    /// `FEATURE_DIM` for models with a feature schema, whatever subset
+    /// they were trained on.
     pub feature_dimensions: usize,
+    /// Enabled feature names in vector order (empty for legacy models)
+    pub feature_names: Vec<String>,
+    pub version: &'static str,
+    pub methods: &'static [&'static str],
+    pub loss_modes: &'static [&'static str],
+    pub checkpoint_versions: CheckpointVersions,
+    /// Model this status describes
+    pub model: String,
+    /// Encoder weights are held fixed during training
+    pub encoder_frozen: bool,
+    /// Every model in the process, the default first
+    pub models: Vec<String>,
 }
 
 fn default_limit() -> usize {
@@ -142,20 +330,73 @@ fn default_epochs() -> usize {
 
 #[derive(Debug, Deserialize)]
 pub struct TrainFromDbParams {
-    pub db_path: String,
+    /// The daemon database, opened read-only. Required unless `daemon` is set.
+    #[serde(default)]
+    pub db_path: Option<String>,
+    /// Read sessions through the daemon's HTTP API instead of `db_path`
+    #[serde(default)]
+    pub daemon: Option<DaemonSource>,
     pub checkpoint_path: Option<String>,
     #[serde(default = "default_limit")]
     pub limit: usize,
     #[serde(default = "default_epochs")]
     pub epochs: usize,
-    #[serde(default = "default_temperature")]
-    pub temperature: f64,
-    #[serde(default = "default_min_confidence")]
-    pub min_confidence: f64,
-}
-
-fn default_min_confidence() -> f64 {
-    0.6
+    /// Defaults to the `set_config` value, as does `min_confidence`
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    /// Training budget, counted from the start of the call. Only training
+    /// checks it: loading always runs to completion and at least one epoch
+    /// runs, after which training stops at the next epoch boundary that
+    /// would exceed the budget.
+    #[serde(default)]
+    pub max_duration_ms: Option<u64>,
+    /// Ignore the incremental cursor and retrain on the most recent `limit`
+    /// sessions.
+    #[serde(default)]
+    pub full_retrain: bool,
+    /// Label-0 candidates mined per session from unrecalled memories
+    #[serde(default)]
+    pub hard_negatives: usize,
+    /// Stream samples from the database in chunks of roughly this size
+    /// instead of loading them all up front.
+    #[serde(default)]
+    pub max_memory_mb: Option<u64>,
+    /// Precedence of explicit feedback over heuristic labels
+    #[serde(default)]
+    pub feedback: FeedbackMode,
+    /// Only train on sessions from these harnesses, e.g. `["claude-code"]`
+    #[serde(default)]
+    pub harness_filter: Vec<String>,
+    /// Project isolation: `{ "include": [...], "exclude": [...] }`
+    #[serde(default)]
+    pub project_filter: ProjectFilter,
+    /// Age decay of session weight, in days (None disables decay)
+    #[serde(default)]
+    pub label_half_life_days: Option<f64>,
+    /// Jittered copies trained per sample (0 disables augmentation)
+    #[serde(default)]
+    pub augmentation_factor: usize,
+    /// Parallel read-only connections for loading (default: up to 4,
+    /// at most 8)
+    #[serde(default)]
+    pub load_workers: Option<usize>,
+    /// Hashed project slots, at most 4096. Only an untrained model can be
+    /// resized; a trained one rejects a count other than its own.
+    #[serde(default)]
+    pub project_slots: Option<usize>,
+    /// Combination of a chunked memory's vectors: "mean" or "max_sim"
+    #[serde(default)]
+    pub chunk_aggregation: ChunkAggregation,
+    /// Share of NaN/Inf values past which a vector is dropped, in [0, 1]
+    /// (default 5%). A corrupt query embedding drops the whole sample.
+    #[serde(default)]
+    pub max_corrupt_fraction: Option<f64>,
+    /// Timestamped snapshots kept beside `checkpoint_path` (default 5,
+    /// 0 disables snapshots)
+    #[serde(default)]
+    pub keep_checkpoints: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
@@ -165,9 +406,86 @@ pub struct TrainFromDbResult {
     pub samples_used: usize,
     pub samples_skipped: usize,
     pub duration_ms: u64,
+    /// Time spent reading sessions before training. In streaming mode most
+    /// reads happen during training and count towards `train_ms` instead.
+    pub load_ms: u64,
+    pub train_ms: u64,
     pub canary_score_variance: f64,
     pub canary_topk_stability: f64,
     pub checkpoint_saved: bool,
+    pub epochs_completed: usize,
+    pub budget_exhausted: bool,
+    /// Stopped early by a `cancel` request; the cursor is left unchanged
+    pub cancelled: bool,
+    pub incremental: bool,
+    pub cursor: Option<TrainingCursor>,
+    pub hard_negatives: usize,
+    /// Candidate vectors seen per embedding width
+    pub embedding_dimensions: BTreeMap<usize, usize>,
+    /// Samples queried with the session's own prompt embedding
+    pub session_embeddings: usize,
+    /// Candidate labels taken from explicit feedback
+    pub feedback_labels: usize,
+    /// Candidates dropped as `content_hash` duplicates
+    pub duplicates_removed: usize,
+    /// Candidates dropped by the project filter
+    pub project_filtered: usize,
+    /// Jittered copies trained on in the last epoch
+    pub augmented_samples: usize,
+    /// Columns the database lacks; the loader read fallbacks instead
+    pub schema_warnings: Vec<SchemaWarning>,
+    /// Spread of the loaded projects over the hashed slots
+    pub project_slots: ProjectSlotStats,
+    /// Non-finite inputs zeroed, and samples or vectors dropped as corrupt
+    pub sanitized: SanitizeReport,
+    pub journal_mode: String,
+    pub lock_retries: u32,
+    pub lock_wait_ms: u64,
+    pub streaming: bool,
+}
+
+/// Score the most recent sessions with the current model, without training
+/// and without moving the incremental cursor.
+#[derive(Debug, Deserialize)]
+pub struct EvaluateParams {
+    #[serde(default)]
+    pub db_path: Option<String>,
+    #[serde(default)]
+    pub daemon: Option<DaemonSource>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    /// Defaults to the `set_config` value, as does `temperature`
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(default)]
+    pub hard_negatives: usize,
+    #[serde(default)]
+    pub feedback: FeedbackMode,
+    #[serde(default)]
+    pub harness_filter: Vec<String>,
+    #[serde(default)]
+    pub project_filter: ProjectFilter,
+    #[serde(default)]
+    pub chunk_aggregation: ChunkAggregation,
+    /// As for `train_from_db`
+    #[serde(default)]
+    pub max_corrupt_fraction: Option<f64>,
+    #[serde(default)]
+    pub load_workers: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EvaluateResult {
+    pub metrics: EvalMetrics,
+    pub model_version: u64,
+    pub samples_loaded: usize,
+    pub samples_skipped: usize,
+    pub load_ms: u64,
+    pub duration_ms: u64,
+    pub schema_warnings: Vec<SchemaWarning>,
+    pub sanitized: SanitizeReport,
 }
 
 #[derive(Debug, Deserialize)]
@@ -182,6 +500,282 @@ pub struct SaveCheckpointResult {
     pub saved: bool,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct ShutdownParams {
+    /// Where to save; defaults to the `--checkpoint` path
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShutdownResult {
+    pub saved: bool,
+    pub path: Option<String>,
+    /// Training steps not yet in any checkpoint when shutdown began
+    pub unsaved_steps: u64,
+    /// Why the final save failed, if it did
+    pub error: Option<String>,
+    /// Final saves of models made with `create_model`, by name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, ShutdownResult>,
+}
+
+/// A named model alongside the default. Requests pick it with a `model`
+/// field in their params.
+#[derive(Debug, Deserialize)]
+pub struct CreateModelParams {
+    pub name: String,
+    /// Its own checkpoint, loaded if it exists and saved to by autosave and
+    /// `shutdown`
+    #[serde(default)]
+    pub checkpoint_path: Option<String>,
+}
+
+/// Warm-start a named model from an existing one's weights.
+#[derive(Debug, Deserialize)]
+pub struct CloneModelParams {
+    /// Model to copy; defaults to the default model
+    #[serde(default)]
+    pub source: Option<String>,
+    pub name: String,
+    /// Written straight away, then used as by `create_model`
+    #[serde(default)]
+    pub checkpoint_path: Option<String>,
+    /// Train only the attention and project weights of the clone, keeping
+    /// the encoder the source learned
+    #[serde(default)]
+    pub freeze_encoder: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CloneModelResult {
+    pub name: String,
+    pub source: String,
+    pub checkpoint_path: Option<String>,
+    pub encoder_frozen: bool,
+    /// Training steps behind the copied weights
+    pub source_steps: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateModelResult {
+    pub name: String,
+    pub checkpoint_path: Option<String>,
+    /// Weights came from `checkpoint_path` rather than a fresh initialisation
+    pub restored: bool,
+}
+
+/// Params of the `watch_trained` notification, sent after each retrain
+/// that `--watch` starts. Exactly one of `result` and `error` is set, as in
+/// the `train_from_db` response.
+#[derive(Debug, Serialize)]
+pub struct WatchTrainedParams {
+    pub db_path: String,
+    pub result: Option<Value>,
+    pub error: Option<Value>,
+    pub duration_ms: u64,
+}
+
+/// Calls and latencies for one method. Percentiles cover recent calls only.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CallMetrics {
+    pub count: u64,
+    pub errors: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+/// Most recent request that returned a JSON-RPC error.
+#[derive(Debug, Clone, Serialize)]
+pub struct LastError {
+    pub method: String,
+    pub code: i32,
+    pub message: String,
+    /// Unix time in milliseconds
+    pub at_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricsResult {
+    pub uptime_ms: u64,
+    /// Requests answered since startup, all methods
+    pub requests: u64,
+    pub score: CallMetrics,
+    pub train: CallMetrics,
+    pub methods: BTreeMap<String, CallMetrics>,
+    pub last_error: Option<LastError>,
+    /// Model weights held in memory, excluding optimizer state and the
+    /// scoring snapshot copy
+    pub parameter_bytes: usize,
+    pub encoding_cache: CacheMetrics,
+    pub score_cache: CacheMetrics,
+}
+
+/// Lookups in one of the `score` path's caches since startup.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    /// `hits / (hits + misses)`, 0 before any lookup
+    pub hit_rate: f64,
+    /// Encodings held now, all models
+    pub entries: usize,
+}
+
+/// Fields to change; the rest keep their current values. The merged
+/// config is validated as a whole and applied only if it passes.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetConfigParams {
+    #[serde(default)]
+    pub learning_rate: Option<f64>,
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+}
+
+/// Config in effect after `get_config` or `set_config`.
+#[derive(Debug, Serialize)]
+pub struct ConfigResult {
+    #[serde(flatten)]
+    pub config: Hyperparameters,
+    /// Changes not yet written to a checkpoint. `shutdown` and autosave
+    /// write them along with the weights.
+    pub unsaved: bool,
+}
+
+/// Liveness for frequent polling. Answered by the request reader, so it
+/// returns promptly even while training holds the main loop.
+#[derive(Debug, Serialize)]
+pub struct PingResult {
+    pub uptime_ms: u64,
+    /// Weights came from a checkpoint rather than a fresh initialisation
+    pub checkpoint_loaded: bool,
+    /// A `train_from_db` call is running; other calls queue behind it
+    pub training: bool,
+    /// Unix time in milliseconds of the last answered request, excluding
+    /// `ping` and `cancel`
+    pub last_activity_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelResult {
+    /// A training run was in flight and will stop at the next sample
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoadCheckpointParams {
+    pub path: String,
+}
+
+/// The running model is only replaced once the file has loaded and fits.
+#[derive(Debug, Serialize)]
+pub struct LoadCheckpointResult {
+    pub loaded: bool,
+    pub model_version: u64,
+    /// On-disk checkpoint format version
+    pub file_version: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListCheckpointsParams {
+    /// Live checkpoint path; snapshots are looked up beside it
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListCheckpointsResult {
+    /// Newest first
+    pub checkpoints: Vec<SnapshotInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RestoreCheckpointParams {
+    pub path: String,
+    /// A `path` from `list_checkpoints`
+    pub snapshot: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreCheckpointResult {
+    pub restored: bool,
+    pub model_version: u64,
+}
+
+/// Answered with a `checkpoint::CheckpointSummary`; the file is only read,
+/// never applied to the running model.
+#[derive(Debug, Deserialize)]
+pub struct InspectCheckpointParams {
+    pub path: String,
+}
+
+/// Answered with an `export::ExportResult`.
+#[derive(Debug, Deserialize)]
+pub struct ExportSafetensorsParams {
+    /// Checkpoint to export
+    pub path: String,
+    /// Destination `.safetensors` file; the config sidecar goes beside it
+    pub out: String,
+}
+
+/// Synthetic `score` load. Candidates are random embeddings of
+/// `embedding_dim`, or random text of `text_tokens` words when set.
+#[derive(Debug, Deserialize)]
+pub struct BenchmarkParams {
+    #[serde(default = "default_benchmark_candidates")]
+    pub candidates: usize,
+    #[serde(default = "default_benchmark_iterations")]
+    pub iterations: usize,
+    /// Defaults to the model's native dimension
+    #[serde(default)]
+    pub embedding_dim: Option<usize>,
+    #[serde(default)]
+    pub text_tokens: Option<usize>,
+    /// Passed through to each `score` call
+    #[serde(default)]
+    pub top_k: Option<usize>,
+    #[serde(default = "default_benchmark_seed")]
+    pub seed: u64,
+}
+
+fn default_benchmark_candidates() -> usize {
+    100
+}
+fn default_benchmark_iterations() -> usize {
+    50
+}
+fn default_benchmark_seed() -> u64 {
+    42
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchmarkResult {
+    pub candidates: usize,
+    pub iterations: usize,
+    pub embedding_dim: Option<usize>,
+    pub text_tokens: Option<usize>,
+    /// Time spent in `score`; setup is excluded
+    pub total_ms: f64,
+    /// Candidates scored per second
+    pub inferences_per_sec: f64,
+    pub calls_per_sec: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    /// Peak resident set of the whole process since it started, where the
+    /// platform reports one
+    pub peak_rss_bytes: Option<u64>,
+}
+
+/// Answered with a `checkpoint::CheckpointDiff`.
+#[derive(Debug, Deserialize)]
+pub struct DiffCheckpointsParams {
+    pub before: String,
+    pub after: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,6 +790,24 @@ mod tests {
         let parsed: ScoreParams = serde_json::from_value(payload).expect("parse");
         assert!(parsed.candidate_features.is_empty());
         assert!(parsed.candidate_texts.is_empty());
+        assert!(parsed.candidate_embedding_blobs.is_empty());
         assert_eq!(parsed.project_slot, 0);
     }
+
+    #[test]
+    fn parses_score_params_with_quantized_blobs() {
+        let payload = serde_json::json!({
+            "context_embedding": [0.1, 0.2],
+            "candidate_ids": ["m1", "m2"],
+            "candidate_embedding_blobs": [[0, 60, 0, 56], null],
+            "embedding_format": "f16"
+        });
+        let parsed: ScoreParams = serde_json::from_value(payload).expect("parse");
+        assert_eq!(parsed.embedding_format, Some(EmbeddingFormat::F16));
+        assert_eq!(
+            parsed.candidate_embedding_blobs[0],
+            Some(vec![0, 60, 0, 56])
+        );
+        assert!(parsed.candidate_embedding_blobs[1].is_none());
+    }
 }
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::TrainingSample;
+
+/// Share of non-finite values above which an input is rejected outright
+/// rather than patched. A few bad values are usually one broken column; a
+/// vector that is mostly NaN is a corrupt blob.
+pub const DEFAULT_MAX_CORRUPT_FRACTION: f64 = 0.05;
+
+/// Non-finite inputs found and handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SanitizeReport {
+    /// NaN/Inf values replaced with 0
+    pub replaced_values: usize,
+    /// Samples and candidate vectors dropped for exceeding the corruption
+    /// threshold
+    pub rejected: usize,
+}
+
+impl SanitizeReport {
+    pub fn merge(&mut self, other: SanitizeReport) {
+        self.replaced_values += other.replaced_values;
+        self.rejected += other.rejected;
+    }
+}
+
+/// Replace non-finite values with 0, returning how many were replaced.
+pub fn sanitize_values(values: &mut [f64]) -> usize {
+    let mut replaced = 0;
+    for value in values.iter_mut().filter(|v| !v.is_finite()) {
+        *value = 0.0;
+        replaced += 1;
+    }
+    replaced
+}
+
+fn non_finite(values: &[f64]) -> usize {
+    values.iter().filter(|v| !v.is_finite()).count()
+}
+
+/// Whether `bad` of `total` values is past `max_fraction`.
+pub fn exceeds_threshold(bad: usize, total: usize, max_fraction: f64) -> bool {
+    total > 0 && bad as f64 / total as f64 > max_fraction
+}
+
+/// Sanitize a vector in place unless it is too corrupt to trust, in which
+/// case it is left untouched and `false` is returned.
+pub fn sanitize_vector(values: &mut [f64], max_fraction: f64, report: &mut SanitizeReport) -> bool {
+    let bad = non_finite(values);
+    if exceeds_threshold(bad, values.len(), max_fraction) {
+        report.rejected += 1;
+        return false;
+    }
+    report.replaced_values += sanitize_values(values);
+    true
+}
+
+/// Sanitize every numeric input of a sample, each vector judged on its own
+/// as `score` does. A corrupt query embedding or any non-finite label drops
+/// the sample, since neither has a sensible stand-in. A corrupt candidate
+/// embedding is cleared so the candidate falls back to its text, and a
+/// corrupt feature row is zeroed. Returns `false` when the sample should be
+/// dropped.
+pub fn sanitize_sample(
+    sample: &mut TrainingSample,
+    max_fraction: f64,
+    report: &mut SanitizeReport,
+) -> bool {
+    if non_finite(&sample.labels) > 0 {
+        report.rejected += 1;
+        return false;
+    }
+    if !sanitize_vector(&mut sample.query_embedding, max_fraction, report) {
+        return false;
+    }
+    for embedding in &mut sample.candidate_embeddings {
+        if !sanitize_vector(embedding, max_fraction, report) {
+            embedding.clear();
+        }
+    }
+    for row in &mut sample.candidate_features {
+        if !sanitize_vector(row, max_fraction, report) {
+            row.fill(0.0);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(query: Vec<f64>, labels: Vec<f64>) -> TrainingSample {
+        TrainingSample {
+            session_id: "s".into(),
+            query_embedding: query,
+            candidate_embeddings: vec![vec![1.0; 20], vec![1.0; 20]],
+            candidate_texts: vec![None, None],
+            candidate_features: vec![vec![0.5; 4], vec![0.5; 4]],
+            project_slot: 0,
+            labels,
+            weight: 1.0,
+        }
+    }
+
+    #[test]
+    fn patches_a_few_bad_values_and_rejects_corrupt_samples() {
+        let mut report = SanitizeReport::default();
+        let mut query = vec![0.1; 20];
+        query[3] = f64::NAN;
+        let mut ok = sample(query, vec![1.0, 0.0]);
+        assert!(sanitize_sample(
+            &mut ok,
+            DEFAULT_MAX_CORRUPT_FRACTION,
+            &mut report
+        ));
+        assert_eq!(ok.query_embedding[3], 0.0);
+        assert_eq!(report.replaced_values, 1);
+
+        let mut corrupt = sample(vec![f64::INFINITY; 20], vec![1.0, 0.0]);
+        assert!(!sanitize_sample(
+            &mut corrupt,
+            DEFAULT_MAX_CORRUPT_FRACTION,
+            &mut report
+        ));
+
+        let mut bad_label = sample(vec![0.1; 20], vec![f64::NAN, 0.0]);
+        assert!(!sanitize_sample(
+            &mut bad_label,
+            DEFAULT_MAX_CORRUPT_FRACTION,
+            &mut report
+        ));
+        assert_eq!(report.rejected, 2);
+    }
+
+    #[test]
+    fn corrupt_candidate_vectors_are_dropped_without_the_sample() {
+        let mut report = SanitizeReport::default();
+        let mut s = sample(vec![0.1; 20], vec![1.0, 0.0]);
+        s.candidate_embeddings[0] = vec![f64::NAN; 20];
+        s.candidate_features[1] = vec![f64::INFINITY; 4];
+        assert!(sanitize_sample(
+            &mut s,
+            DEFAULT_MAX_CORRUPT_FRACTION,
+            &mut report
+        ));
+        assert!(s.candidate_embeddings[0].is_empty());
+        assert_eq!(s.candidate_embeddings[1], vec![1.0; 20]);
+        assert_eq!(s.candidate_features[1], vec![0.0; 4]);
+        assert_eq!(report.rejected, 2);
+        assert_eq!(report.replaced_values, 0);
+    }
+}
@@ -1,29 +1,147 @@
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Common English function words, sorted for binary search. They appear
+/// in nearly every memory, so their buckets carry no signal.
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "am", "an", "and", "any", "are", "as", "at", "be",
+    "been", "before", "being", "but", "by", "can", "could", "did", "do", "does", "for", "from",
+    "had", "has", "have", "he", "her", "his", "how", "i", "if", "in", "into", "is", "it", "its",
+    "just", "me", "my", "no", "not", "of", "on", "or", "our", "she", "so", "some", "than", "that",
+    "the", "their", "them", "then", "there", "these", "they", "this", "to", "too", "up", "us",
+    "was", "we", "were", "what", "when", "which", "who", "will", "with", "would", "you", "your",
+];
+
+pub fn is_stopword(token: &str) -> bool {
+    STOPWORDS.binary_search(&token).is_ok()
+}
+
+/// Bumped whenever `TokenizerDescriptor` gains a field or a mode changes.
+pub const TOKENIZER_VERSION: u32 = 1;
+
+/// How text becomes tokens. Recorded in the checkpoint config because the
+/// hash table is only meaningful for the tokenization it was trained on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenizerDescriptor {
+    pub version: u32,
+    /// NFKC, lowercase and Unicode word boundaries; `false` is the original
+    /// case-sensitive ASCII split
+    pub unicode: bool,
+    pub stopwords: bool,
+    /// Also emit the parts of `snake_case`, `camelCase` and dotted names
+    pub split_identifiers: bool,
+}
+
+impl Default for TokenizerDescriptor {
+    fn default() -> Self {
+        Self {
+            version: TOKENIZER_VERSION,
+            unicode: true,
+            stopwords: true,
+            split_identifiers: true,
+        }
+    }
+}
+
+impl TokenizerDescriptor {
+    /// The tokenizer of models saved before descriptors were recorded.
+    pub fn legacy() -> Self {
+        Self {
+            version: TOKENIZER_VERSION,
+            unicode: false,
+            stopwords: false,
+            split_identifiers: false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version > TOKENIZER_VERSION {
+            return Err(format!(
+                "tokenizer v{} is newer than supported v{}",
+                self.version, TOKENIZER_VERSION
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HashTrickTokenizer {
     buckets: usize,
+    /// Bigram buckets, indexed after the `buckets` unigram rows
+    bigram_buckets: usize,
+    max_tokens: Option<usize>,
+    descriptor: TokenizerDescriptor,
 }
 
 impl HashTrickTokenizer {
     pub fn new(buckets: usize) -> Self {
         assert!(buckets > 0, "buckets must be > 0");
-        Self { buckets }
+        Self {
+            buckets,
+            bigram_buckets: 0,
+            max_tokens: None,
+            descriptor: TokenizerDescriptor::default(),
+        }
+    }
+
+    pub fn with_descriptor(mut self, descriptor: TokenizerDescriptor) -> Self {
+        self.descriptor = descriptor;
+        self
+    }
+
+    pub fn descriptor(&self) -> &TokenizerDescriptor {
+        &self.descriptor
+    }
+
+    /// Keep only the first `max_tokens` tokens of each text.
+    pub fn with_max_tokens(mut self, max_tokens: Option<usize>) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Also hash each pair of adjacent tokens into `bigram_buckets` rows of
+    /// their own, so "dark mode" and "mode dark" differ.
+    pub fn with_bigrams(mut self, bigram_buckets: usize) -> Self {
+        self.bigram_buckets = bigram_buckets;
+        self
     }
 
     pub fn buckets(&self) -> usize {
         self.buckets
     }
 
+    /// Unigram plus bigram rows an embedding table needs.
+    pub fn rows(&self) -> usize {
+        self.buckets + self.bigram_buckets
+    }
+
+    /// Unigram indices in text order, then bigram indices. The token cap
+    /// applies to unigrams; bigrams are only formed within it.
     pub fn token_indices(&self, text: &str) -> Vec<usize> {
-        split_tokens(text)
+        let tokens: Vec<String> = split_tokens(text, &self.descriptor)
             .into_iter()
+            .filter(|token| !(self.descriptor.stopwords && is_stopword(token)))
+            .take(self.max_tokens.unwrap_or(usize::MAX))
+            .collect();
+        let mut indices: Vec<usize> = tokens
+            .iter()
             .map(|token| fnv1a_hash(token.as_bytes()) as usize % self.buckets)
-            .collect()
+            .collect();
+        if self.bigram_buckets > 0 {
+            indices.extend(tokens.windows(2).map(|pair| {
+                let joined = format!("{}\u{0}{}", pair[0], pair[1]);
+                self.buckets + fnv1a_hash(joined.as_bytes()) as usize % self.bigram_buckets
+            }));
+        }
+        indices
     }
 
     pub fn encode_mean(&self, text: &str, embedding_table: &[f64], dim: usize) -> Vec<f64> {
         assert_eq!(
             embedding_table.len(),
-            self.buckets * dim,
+            self.rows() * dim,
             "embedding table size mismatch"
         );
 
@@ -49,10 +167,60 @@ impl HashTrickTokenizer {
     }
 }
 
-fn split_tokens(text: &str) -> Vec<&str> {
-    text.split(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '-'))
-        .filter(|token| !token.is_empty())
-        .collect()
+/// NFKC-normalize, split on Unicode word boundaries (UAX #29), then
+/// lowercase. Full-width and compatibility forms fold to one token, and
+/// scripts without spaces still break into words or characters.
+///
+/// With `split_identifiers`, identifiers are emitted whole and then as
+/// their parts, so `CrossAttentionScorer` also yields `cross`, `attention`
+/// and `scorer`. Path separators already end a word; `_` and `.` do not,
+/// so they are split here along with case transitions.
+fn split_tokens(text: &str, descriptor: &TokenizerDescriptor) -> Vec<String> {
+    if !descriptor.unicode {
+        return text
+            .split(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '-'))
+            .filter(|token| !token.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    let normalized: String = text.nfkc().collect();
+    let mut tokens = Vec::new();
+    for word in normalized.unicode_words() {
+        tokens.push(word.to_lowercase());
+        if !descriptor.split_identifiers {
+            continue;
+        }
+        let parts = identifier_parts(word);
+        if parts.len() > 1 {
+            tokens.extend(parts.into_iter().map(|part| part.to_lowercase()));
+        }
+    }
+    tokens
+}
+
+/// Split an identifier on `_`, `.`, lower-to-upper transitions and the
+/// end of an acronym (`HTTPServer` -> `HTTP`, `Server`).
+fn identifier_parts(word: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    for piece in word.split(['_', '.']).filter(|p| !p.is_empty()) {
+        let chars: Vec<(usize, char)> = piece.char_indices().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (at, ch) = chars[i];
+            let prev = chars[i - 1].1;
+            let next_lower = chars.get(i + 1).is_some_and(|(_, c)| c.is_lowercase());
+            let boundary = ch.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next_lower));
+            if boundary {
+                parts.push(&piece[start..at]);
+                start = at;
+            }
+        }
+        parts.push(&piece[start..]);
+    }
+    parts
 }
 
 pub fn fnv1a_hash(bytes: &[u8]) -> u64 {
@@ -71,6 +239,10 @@ pub fn fnv1a_hash(bytes: &[u8]) -> u64 {
 mod tests {
     use super::*;
 
+    fn current() -> TokenizerDescriptor {
+        TokenizerDescriptor::default()
+    }
+
     #[test]
     fn token_indices_are_stable() {
         let tokenizer = HashTrickTokenizer::new(256);
@@ -80,6 +252,91 @@ mod tests {
         assert!(a.iter().all(|idx| *idx < 256));
     }
 
+    #[test]
+    fn tokens_are_normalized_and_unicode_aware() {
+        assert_eq!(split_tokens("Ｆｏｏ Bar", &current()), ["foo", "bar"]);
+        assert_eq!(
+            split_tokens("Grüße, Straße", &current()),
+            ["grüße", "straße"]
+        );
+        assert_eq!(split_tokens("Привет мир", &current()), ["привет", "мир"]);
+        assert_eq!(split_tokens("记忆库", &current()), ["记", "忆", "库"]);
+        assert_eq!(split_tokens("word, !!", &current()), ["word"]);
+    }
+
+    #[test]
+    fn identifiers_emit_whole_and_sub_tokens() {
+        assert_eq!(
+            split_tokens("session_memories", &current()),
+            ["session_memories", "session", "memories"]
+        );
+        assert_eq!(
+            split_tokens("CrossAttentionScorer", &current()),
+            ["crossattentionscorer", "cross", "attention", "scorer"]
+        );
+        assert_eq!(
+            split_tokens("HTTPServer", &current()),
+            ["httpserver", "http", "server"]
+        );
+        assert_eq!(
+            split_tokens("src/main.rs", &current()),
+            ["src", "main.rs", "main", "rs"]
+        );
+        assert_eq!(split_tokens("sqlite", &current()), ["sqlite"]);
+    }
+
+    #[test]
+    fn legacy_descriptor_keeps_the_ascii_split() {
+        let legacy = TokenizerDescriptor::legacy();
+        assert_eq!(
+            split_tokens("Foo snake_case x-y é", &legacy),
+            ["Foo", "snake_case", "x-y"]
+        );
+        let old = HashTrickTokenizer::new(64).with_descriptor(legacy);
+        assert_eq!(old.token_indices("the").len(), 1);
+
+        let future = TokenizerDescriptor {
+            version: TOKENIZER_VERSION + 1,
+            ..current()
+        };
+        assert!(future.validate().is_err());
+    }
+
+    #[test]
+    fn bigrams_use_their_own_rows_and_respect_order() {
+        let tokenizer = HashTrickTokenizer::new(64).with_bigrams(32);
+        assert_eq!(tokenizer.rows(), 96);
+        let forward = tokenizer.token_indices("dark mode");
+        let reverse = tokenizer.token_indices("mode dark");
+        assert_eq!(forward.len(), 3);
+        assert!(forward[..2].iter().all(|idx| *idx < 64));
+        assert!((64..96).contains(&forward[2]));
+
+        let mut unigrams = forward[..2].to_vec();
+        unigrams.reverse();
+        assert_eq!(reverse[..2], unigrams[..]);
+        assert_ne!(forward[2], reverse[2]);
+        assert_eq!(HashTrickTokenizer::new(64).token_indices("dark").len(), 1);
+    }
+
+    #[test]
+    fn stopwords_are_dropped_and_tokens_capped() {
+        let mut sorted = STOPWORDS.to_vec();
+        sorted.sort_unstable();
+        assert_eq!(sorted, STOPWORDS);
+
+        let tokenizer = HashTrickTokenizer::new(1024);
+        assert_eq!(
+            tokenizer.token_indices("the deploy of the api"),
+            tokenizer.token_indices("deploy api")
+        );
+        let capped = HashTrickTokenizer::new(1024).with_max_tokens(Some(2));
+        assert_eq!(
+            capped.token_indices("alpha beta gamma delta"),
+            tokenizer.token_indices("alpha beta")
+        );
+    }
+
     #[test]
     fn encode_mean_returns_zero_for_empty_text() {
         let tokenizer = HashTrickTokenizer::new(64);
@@ -1,5 +1,15 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    autograd::Tape,
+    autograd::{Rng, Tape},
     data::TrainingSample,
     model::{CandidateInput, CrossAttentionScorer},
 };
@@ -9,15 +19,94 @@ pub struct TrainingStats {
     pub loss: f64,
     pub steps: u64,
     pub samples: usize,
+    pub epochs_completed: usize,
+    pub budget_exhausted: bool,
+    /// Stopped early by a [`CancelToken`]
+    pub cancelled: bool,
+}
+
+/// Loss functions this build can train with.
+pub const LOSS_MODES: &[&str] = &["listwise"];
+
+/// Shared flag asking a running training loop to stop. Loops check it
+/// between samples, so cancellation takes effect within one step.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// When an epoch loop should stop besides running out of epochs.
+#[derive(Debug, Clone, Default)]
+pub struct TrainLimits {
+    /// Wall-clock budget, checked at epoch boundaries
+    pub deadline: Option<Instant>,
+    pub cancel: CancelToken,
 }
 
 #[derive(Debug)]
 pub enum TrainingError {
     InvalidSample(String),
     Model(String),
+    Source(String),
 }
 
-#[derive(Debug)]
+/// Hyperparameters the daemon can retune at runtime with `set_config`.
+/// Per-call `temperature` and `min_confidence` params still override them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Hyperparameters {
+    pub learning_rate: f64,
+    /// Listwise loss temperature when a call doesn't give one
+    pub temperature: f64,
+    /// Scorer confidence below which `train_from_db` and `evaluate` ignore
+    /// a session's heuristic labels
+    pub min_confidence: f64,
+}
+
+impl Default for Hyperparameters {
+    fn default() -> Self {
+        Self {
+            learning_rate: 1e-3,
+            temperature: 0.5,
+            min_confidence: 0.6,
+        }
+    }
+}
+
+impl Hyperparameters {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.learning_rate.is_finite() || self.learning_rate <= 0.0 || self.learning_rate > 1.0
+        {
+            return Err(format!(
+                "learning_rate must be within (0, 1], got {}",
+                self.learning_rate
+            ));
+        }
+        if !self.temperature.is_finite() || self.temperature <= 0.0 {
+            return Err(format!("temperature must be > 0, got {}", self.temperature));
+        }
+        if !(0.0..=1.0).contains(&self.min_confidence) {
+            return Err(format!(
+                "min_confidence must be within [0, 1], got {}",
+                self.min_confidence
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Adam {
     lr: f64,
     beta1: f64,
@@ -26,6 +115,8 @@ pub struct Adam {
     t: u64,
     m: Vec<Vec<f64>>,
     v: Vec<Vec<f64>>,
+    /// Tape indices `step` leaves untouched
+    frozen: Vec<bool>,
 }
 
 impl Adam {
@@ -48,13 +139,42 @@ impl Adam {
             t: 0,
             m,
             v,
+            frozen: Vec::new(),
         }
     }
 
-    pub fn step(&mut self, tape: &mut Tape) {
+    /// Stop updating the parameters at `indices`. Their gradients are still
+    /// computed; `step` just skips them.
+    pub fn freeze(&mut self, indices: &[usize]) {
+        self.frozen.resize(self.m.len(), false);
+        for &idx in indices {
+            if let Some(slot) = self.frozen.get_mut(idx) {
+                *slot = true;
+            }
+        }
+    }
+
+    pub fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    /// Change the step size. Moments are kept, so training continues
+    /// smoothly at the new rate.
+    pub fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    /// Apply one update with the learning rate scaled by `lr_scale`.
+    /// Adam normalizes gradient magnitude away, so per-sample weights have
+    /// to scale the step rather than the loss to have any effect.
+    pub fn step(&mut self, tape: &mut Tape, lr_scale: f64) {
+        let lr = self.lr * lr_scale;
         self.t += 1;
         let t = self.t as f64;
         for (param_idx, param) in tape.params_mut().iter_mut().enumerate() {
+            if self.frozen.get(param_idx).copied().unwrap_or(false) {
+                continue;
+            }
             for i in 0..param.data.len() {
                 let grad = param.grad[i];
                 self.m[param_idx][i] =
@@ -64,10 +184,55 @@ impl Adam {
 
                 let m_hat = self.m[param_idx][i] / (1.0 - self.beta1.powf(t));
                 let v_hat = self.v[param_idx][i] / (1.0 - self.beta2.powf(t));
-                param.data[i] -= self.lr * m_hat / (v_hat.sqrt() + self.eps);
+                param.data[i] -= lr * m_hat / (v_hat.sqrt() + self.eps);
             }
         }
     }
+
+    /// Moments and step count for `param_indices`, in that order.
+    pub fn state(&self, param_indices: &[usize]) -> AdamState {
+        AdamState {
+            step: self.t,
+            m: param_indices.iter().map(|&i| self.m[i].clone()).collect(),
+            v: param_indices.iter().map(|&i| self.v[i].clone()).collect(),
+        }
+    }
+
+    /// Restore state captured by [`Adam::state`] for the same parameters.
+    pub fn restore_state(
+        &mut self,
+        state: &AdamState,
+        param_indices: &[usize],
+    ) -> Result<(), String> {
+        if state.m.len() != param_indices.len() || state.v.len() != param_indices.len() {
+            return Err(format!(
+                "optimizer state covers {} parameters, model has {}",
+                state.m.len(),
+                param_indices.len()
+            ));
+        }
+        for (slot, &idx) in param_indices.iter().enumerate() {
+            let expected = self.m[idx].len();
+            if state.m[slot].len() != expected || state.v[slot].len() != expected {
+                return Err(format!("optimizer state {slot} size mismatch"));
+            }
+        }
+        for (slot, &idx) in param_indices.iter().enumerate() {
+            self.m[idx].copy_from_slice(&state.m[slot]);
+            self.v[idx].copy_from_slice(&state.v[slot]);
+        }
+        self.t = state.step;
+        Ok(())
+    }
+}
+
+/// Adam first and second moments for a model's parameters, persisted so a
+/// resumed run continues with the same effective step sizes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdamState {
+    pub step: u64,
+    pub m: Vec<Vec<f64>>,
+    pub v: Vec<Vec<f64>>,
 }
 
 // ---------------------------------------------------------------------------
@@ -76,7 +241,7 @@ impl Adam {
 
 fn build_candidates_for_sample<'a>(
     sample: &'a TrainingSample,
-    native_dim: usize,
+    model: &CrossAttentionScorer,
     feature_storage: &'a [Vec<f64>],
 ) -> Vec<CandidateInput<'a>> {
     let has_texts = !sample.candidate_texts.is_empty();
@@ -94,7 +259,7 @@ fn build_candidates_for_sample<'a>(
             };
             CandidateInput {
                 id: "",
-                embedding: if embedding.len() == native_dim {
+                embedding: if model.accepts_embedding_dim(embedding.len()) {
                     Some(embedding.as_slice())
                 } else {
                     None
@@ -116,11 +281,37 @@ pub fn train_batch(
     batch: &[TrainingSample],
     optimizer: &mut Adam,
     temperature: f64,
+) -> Result<TrainingStats, TrainingError> {
+    train_batch_cancellable(
+        tape,
+        model,
+        batch,
+        optimizer,
+        temperature,
+        &CancelToken::default(),
+    )
+}
+
+/// [`train_batch`] that stops before the next sample once `cancel` is set.
+pub fn train_batch_cancellable(
+    tape: &mut Tape,
+    model: &CrossAttentionScorer,
+    batch: &[TrainingSample],
+    optimizer: &mut Adam,
+    temperature: f64,
+    cancel: &CancelToken,
 ) -> Result<TrainingStats, TrainingError> {
     let mut total_loss = 0.0;
     let mut steps = 0;
+    let mut seen = 0;
+    let mut cancelled = false;
 
     for sample in batch {
+        if cancel.is_cancelled() {
+            cancelled = true;
+            break;
+        }
+        seen += 1;
         if sample.candidate_embeddings.len() != sample.labels.len() {
             return Err(TrainingError::InvalidSample(format!(
                 "sample {} has {} candidates but {} labels",
@@ -129,7 +320,7 @@ pub fn train_batch(
                 sample.labels.len()
             )));
         }
-        if sample.candidate_embeddings.is_empty() {
+        if sample.candidate_embeddings.is_empty() || sample.weight <= 0.0 {
             continue;
         }
 
@@ -167,7 +358,7 @@ pub fn train_batch(
             )));
         }
 
-        let candidates = build_candidates_for_sample(sample, cfg.native_dim, &feature_storage);
+        let candidates = build_candidates_for_sample(sample, model, &feature_storage);
 
         tape.reset();
         let logits = model
@@ -185,8 +376,10 @@ pub fn train_batch(
             continue;
         }
 
+        // Decayed sessions take smaller steps; the reported loss stays
+        // unweighted so it is comparable across runs.
         tape.backward(loss);
-        optimizer.step(tape);
+        optimizer.step(tape, sample.weight);
         total_loss += loss_value;
         steps += 1;
     }
@@ -200,7 +393,10 @@ pub fn train_batch(
     Ok(TrainingStats {
         loss: avg_loss,
         steps,
-        samples: batch.len(),
+        samples: seen,
+        epochs_completed: usize::from(!cancelled),
+        budget_exhausted: false,
+        cancelled,
     })
 }
 
@@ -208,6 +404,10 @@ pub fn train_batch(
 // Multi-epoch training
 // ---------------------------------------------------------------------------
 
+/// Run up to `epochs` passes over `samples`. When a deadline is given,
+/// training stops at the first epoch boundary where the next epoch (estimated
+/// from the previous one) would overrun it. The first epoch always runs so a
+/// budgeted call still learns something. Cancellation stops mid-epoch.
 pub fn train_epochs(
     tape: &mut Tape,
     model: &CrossAttentionScorer,
@@ -215,13 +415,35 @@ pub fn train_epochs(
     optimizer: &mut Adam,
     epochs: usize,
     temperature: f64,
+    limits: &TrainLimits,
 ) -> Result<TrainingStats, TrainingError> {
     let mut total_loss = 0.0;
     let mut total_steps = 0u64;
+    let mut epochs_completed = 0usize;
+    let mut budget_exhausted = false;
+    let mut cancelled = false;
+    let mut last_epoch = Duration::ZERO;
     for _epoch in 0..epochs {
-        let stats = train_batch(tape, model, samples, optimizer, temperature)?;
-        total_loss = stats.loss; // last epoch's loss (intentional)
+        if let Some(deadline) = limits.deadline {
+            if epochs_completed > 0 && Instant::now() + last_epoch > deadline {
+                budget_exhausted = true;
+                break;
+            }
+        }
+        let epoch_start = Instant::now();
+        let stats =
+            train_batch_cancellable(tape, model, samples, optimizer, temperature, &limits.cancel)?;
+        last_epoch = epoch_start.elapsed();
         total_steps += stats.steps;
+        if stats.cancelled {
+            cancelled = true;
+            if stats.steps > 0 {
+                total_loss = stats.loss;
+            }
+            break;
+        }
+        epochs_completed += 1;
+        total_loss = stats.loss; // last epoch's loss (intentional)
         if stats.loss < 1e-6 && stats.steps > 0 {
             break;
         }
@@ -230,9 +452,191 @@ pub fn train_epochs(
         loss: total_loss,
         steps: total_steps,
         samples: samples.len(),
+        epochs_completed,
+        budget_exhausted,
+        cancelled,
     })
 }
 
+// ---------------------------------------------------------------------------
+// Streaming training
+// ---------------------------------------------------------------------------
+
+/// Bounded-memory producer of training samples, re-readable per epoch.
+pub trait SampleSource {
+    /// Restart from the first training sample.
+    fn rewind(&mut self);
+    /// Next chunk, or `None` when the pass is complete.
+    fn next_chunk(&mut self) -> Result<Option<Vec<TrainingSample>>, String>;
+}
+
+/// Epoch loop of [`train_epochs`] over chunks pulled from `source`, so only
+/// one chunk is resident at a time. The reported loss is the step-weighted
+/// mean of the last epoch.
+pub fn train_epochs_streaming<S: SampleSource + ?Sized>(
+    tape: &mut Tape,
+    model: &CrossAttentionScorer,
+    source: &mut S,
+    optimizer: &mut Adam,
+    epochs: usize,
+    temperature: f64,
+    limits: &TrainLimits,
+) -> Result<TrainingStats, TrainingError> {
+    let mut last_loss = 0.0;
+    let mut total_steps = 0u64;
+    let mut samples = 0usize;
+    let mut epochs_completed = 0usize;
+    let mut budget_exhausted = false;
+    let mut cancelled = false;
+    let mut last_epoch = Duration::ZERO;
+    for _epoch in 0..epochs {
+        if let Some(deadline) = limits.deadline {
+            if epochs_completed > 0 && Instant::now() + last_epoch > deadline {
+                budget_exhausted = true;
+                break;
+            }
+        }
+        let epoch_start = Instant::now();
+        source.rewind();
+        let mut loss_sum = 0.0;
+        let mut epoch_steps = 0u64;
+        let mut epoch_samples = 0usize;
+        while let Some(chunk) = source.next_chunk().map_err(TrainingError::Source)? {
+            let stats = train_batch_cancellable(
+                tape,
+                model,
+                &chunk,
+                optimizer,
+                temperature,
+                &limits.cancel,
+            )?;
+            loss_sum += stats.loss * stats.steps as f64;
+            epoch_steps += stats.steps;
+            epoch_samples += stats.samples;
+            if stats.cancelled {
+                cancelled = true;
+                break;
+            }
+        }
+        last_epoch = epoch_start.elapsed();
+        total_steps += epoch_steps;
+        samples = samples.max(epoch_samples);
+        if !cancelled || epoch_steps > 0 {
+            last_loss = if epoch_steps == 0 {
+                0.0
+            } else {
+                loss_sum / epoch_steps as f64
+            };
+        }
+        if cancelled {
+            break;
+        }
+        epochs_completed += 1;
+        if last_loss < 1e-6 && epoch_steps > 0 {
+            break;
+        }
+    }
+    Ok(TrainingStats {
+        loss: last_loss,
+        steps: total_steps,
+        samples,
+        epochs_completed,
+        budget_exhausted,
+        cancelled,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Augmentation
+// ---------------------------------------------------------------------------
+
+/// Embedding noise std as a fraction of each vector's RMS, so the jitter is
+/// the same relative size whatever the provider's scale.
+const AUGMENT_NOISE_RATIO: f64 = 0.1;
+/// Probability of zeroing each candidate feature in a jittered copy
+const AUGMENT_FEATURE_DROPOUT: f64 = 0.1;
+
+fn jitter_embedding(values: &[f64], rng: &mut Rng) -> Vec<f64> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+    let rms = (values.iter().map(|v| v * v).sum::<f64>() / values.len() as f64).sqrt();
+    let std = rms * AUGMENT_NOISE_RATIO;
+    values.iter().map(|v| v + rng.gauss(0.0, std)).collect()
+}
+
+/// A jittered copy of `sample`: Gaussian noise on the query and candidate
+/// embeddings, and random feature dropout. Labels, texts and weight are kept.
+pub fn jitter_sample(sample: &TrainingSample, rng: &mut Rng) -> TrainingSample {
+    TrainingSample {
+        query_embedding: jitter_embedding(&sample.query_embedding, rng),
+        candidate_embeddings: sample
+            .candidate_embeddings
+            .iter()
+            .map(|embedding| jitter_embedding(embedding, rng))
+            .collect(),
+        candidate_features: sample
+            .candidate_features
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|value| {
+                        if rng.next_f64() < AUGMENT_FEATURE_DROPOUT {
+                            0.0
+                        } else {
+                            *value
+                        }
+                    })
+                    .collect()
+            })
+            .collect(),
+        ..sample.clone()
+    }
+}
+
+/// Each sample followed by `factor` jittered copies of it.
+pub fn augment_samples(
+    samples: &[TrainingSample],
+    factor: usize,
+    rng: &mut Rng,
+) -> Vec<TrainingSample> {
+    let mut out = Vec::with_capacity(samples.len() * (factor + 1));
+    for sample in samples {
+        out.push(sample.clone());
+        for _ in 0..factor {
+            out.push(jitter_sample(sample, rng));
+        }
+    }
+    out
+}
+
+/// Augments each chunk of `inner` as it is read, so copies never outlive
+/// the chunk they came from.
+pub struct AugmentedSource<'a, S: SampleSource + ?Sized> {
+    inner: &'a mut S,
+    factor: usize,
+    rng: Rng,
+}
+
+impl<'a, S: SampleSource + ?Sized> AugmentedSource<'a, S> {
+    pub fn new(inner: &'a mut S, factor: usize, rng: Rng) -> Self {
+        Self { inner, factor, rng }
+    }
+}
+
+impl<S: SampleSource + ?Sized> SampleSource for AugmentedSource<'_, S> {
+    fn rewind(&mut self) {
+        self.inner.rewind();
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Vec<TrainingSample>>, String> {
+        Ok(self
+            .inner
+            .next_chunk()?
+            .map(|chunk| augment_samples(&chunk, self.factor, &mut self.rng)))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Canary evaluation
 // ---------------------------------------------------------------------------
@@ -263,7 +667,7 @@ pub fn record_top5(
             sample.candidate_features.clone()
         };
 
-        let candidates = build_candidates_for_sample(sample, cfg.native_dim, &feature_storage);
+        let candidates = build_candidates_for_sample(sample, model, &feature_storage);
 
         tape.reset();
         match model.forward_logits(
@@ -316,7 +720,7 @@ pub fn evaluate_canary(
             sample.candidate_features.clone()
         };
 
-        let candidates = build_candidates_for_sample(sample, cfg.native_dim, &feature_storage);
+        let candidates = build_candidates_for_sample(sample, model, &feature_storage);
 
         tape.reset();
         if let Ok(logits) = model.forward_logits(
@@ -371,6 +775,136 @@ pub fn evaluate_canary(
     }
 }
 
+// ---------------------------------------------------------------------------
+// Offline evaluation
+// ---------------------------------------------------------------------------
+
+/// Calibration bins for [`EvalMetrics::ece`]
+const CALIBRATION_BINS: usize = 10;
+
+/// Ranking quality and calibration of the current model over labelled
+/// samples, computed without any parameter update. A candidate counts as
+/// relevant when its label is above 0.5; NDCG uses the labels as graded
+/// gains. Calibration compares `sigmoid(logit)` with relevance.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvalMetrics {
+    /// Samples scored; those without a relevant candidate are skipped
+    pub samples: usize,
+    pub candidates: usize,
+    pub mrr: f64,
+    pub ndcg_at_10: f64,
+    pub precision_at_5: f64,
+    pub recall_at_5: f64,
+    /// Mean squared error of `sigmoid(logit)` against relevance
+    pub brier: f64,
+    /// Expected calibration error over equal-width probability bins
+    pub ece: f64,
+    pub mean_predicted: f64,
+    pub positive_rate: f64,
+}
+
+fn dcg(gains: impl Iterator<Item = f64>) -> f64 {
+    gains
+        .enumerate()
+        .map(|(rank, gain)| gain / (rank as f64 + 2.0).log2())
+        .sum()
+}
+
+pub fn evaluate_samples(
+    tape: &mut Tape,
+    model: &CrossAttentionScorer,
+    samples: &[TrainingSample],
+) -> EvalMetrics {
+    let cfg = model.config();
+    let mut metrics = EvalMetrics::default();
+    // (count, summed prediction, summed relevance) per bin
+    let mut bins = [(0usize, 0.0f64, 0.0f64); CALIBRATION_BINS];
+    let (mut mrr, mut ndcg, mut precision, mut recall) = (0.0, 0.0, 0.0, 0.0);
+    let (mut brier, mut predicted, mut positives) = (0.0, 0.0, 0usize);
+
+    for sample in samples {
+        if sample.candidate_embeddings.is_empty() || sample.query_embedding.len() != cfg.native_dim
+        {
+            continue;
+        }
+        let relevant = sample.labels.iter().filter(|l| **l > 0.5).count();
+        if relevant == 0 {
+            continue;
+        }
+
+        let feature_storage = if sample.candidate_features.is_empty() {
+            vec![vec![0.0; cfg.extra_features]; sample.candidate_embeddings.len()]
+        } else {
+            sample.candidate_features.clone()
+        };
+        let candidates = build_candidates_for_sample(sample, model, &feature_storage);
+
+        tape.reset();
+        let Ok(logits) = model.forward_logits(
+            tape,
+            &sample.query_embedding,
+            &candidates,
+            sample.project_slot,
+        ) else {
+            continue;
+        };
+        let logits = tape.value(logits).to_vec();
+        let mut order: Vec<usize> = (0..logits.len()).collect();
+        order.sort_by(|a, b| {
+            logits[*b]
+                .partial_cmp(&logits[*a])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let is_relevant = |idx: usize| sample.labels[idx] > 0.5;
+        if let Some(rank) = order.iter().position(|&idx| is_relevant(idx)) {
+            mrr += 1.0 / (rank as f64 + 1.0);
+        }
+        let mut ideal = sample.labels.clone();
+        ideal.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+        let ideal_dcg = dcg(ideal.iter().take(10).copied());
+        if ideal_dcg > 0.0 {
+            ndcg += dcg(order.iter().take(10).map(|&idx| sample.labels[idx])) / ideal_dcg;
+        }
+        let hits = order
+            .iter()
+            .take(5)
+            .filter(|&&idx| is_relevant(idx))
+            .count();
+        precision += hits as f64 / 5.0_f64.min(order.len() as f64);
+        recall += hits as f64 / relevant as f64;
+
+        for (idx, logit) in logits.iter().enumerate() {
+            let p = 1.0 / (1.0 + (-logit).exp());
+            let y = if is_relevant(idx) { 1.0 } else { 0.0 };
+            brier += (p - y) * (p - y);
+            predicted += p;
+            positives += is_relevant(idx) as usize;
+            let bin = ((p * CALIBRATION_BINS as f64) as usize).min(CALIBRATION_BINS - 1);
+            bins[bin].0 += 1;
+            bins[bin].1 += p;
+            bins[bin].2 += y;
+        }
+        metrics.samples += 1;
+        metrics.candidates += logits.len();
+    }
+
+    if metrics.samples == 0 {
+        return metrics;
+    }
+    let n = metrics.samples as f64;
+    let c = metrics.candidates as f64;
+    metrics.mrr = mrr / n;
+    metrics.ndcg_at_10 = ndcg / n;
+    metrics.precision_at_5 = precision / n;
+    metrics.recall_at_5 = recall / n;
+    metrics.brier = brier / c;
+    metrics.mean_predicted = predicted / c;
+    metrics.positive_rate = positives as f64 / c;
+    metrics.ece = bins.iter().map(|(_, p, y)| (p - y).abs() / c).sum();
+    metrics
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -383,7 +917,10 @@ mod tests {
         model::{CrossAttentionScorer, ScorerConfig},
     };
 
-    use super::{train_batch, train_epochs, Adam};
+    use super::{
+        augment_samples, evaluate_samples, train_batch, train_batch_cancellable, train_epochs,
+        train_epochs_streaming, Adam, Hyperparameters, SampleSource, TrainLimits,
+    };
 
     fn make_sample(native_dim: usize, extra_features: usize) -> TrainingSample {
         TrainingSample {
@@ -394,6 +931,7 @@ mod tests {
             candidate_features: vec![vec![0.0; extra_features], vec![1.0; extra_features]],
             project_slot: 1,
             labels: vec![1.0, 0.0],
+            weight: 1.0,
         }
     }
 
@@ -408,6 +946,11 @@ mod tests {
             extra_features: 2,
             hash_buckets: 64,
             project_slots: 4,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
         };
         let model = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
         let mut optimizer = Adam::new(&tape, 1e-2);
@@ -421,6 +964,7 @@ mod tests {
             candidate_features: vec![vec![0.0, 1.0], vec![1.0, 0.0]],
             project_slot: 1,
             labels: vec![1.0, 0.0],
+            weight: 1.0,
         };
 
         let stats = train_batch(&mut tape, &model, &[sample], &mut optimizer, 0.5).expect("train");
@@ -431,6 +975,85 @@ mod tests {
         assert_ne!(before, after);
     }
 
+    #[test]
+    fn lower_sample_weight_moves_parameters_less() {
+        let displacement = |weight: f64| {
+            let mut tape = Tape::new();
+            let mut rng = Rng::new(19);
+            let cfg = ScorerConfig {
+                native_dim: 4,
+                internal_dim: 4,
+                value_dim: 2,
+                extra_features: 2,
+                hash_buckets: 64,
+                project_slots: 4,
+                adapter_dims: Vec::new(),
+                feature_schema: None,
+                max_tokens: None,
+                tokenizer: None,
+                bigram_buckets: 0,
+            };
+            let model = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+            let mut optimizer = Adam::new(&tape, 1e-2);
+            let before: Vec<Vec<f64>> = tape.params().iter().map(|p| p.data.clone()).collect();
+
+            let mut sample = make_sample(4, 2);
+            sample.weight = weight;
+            train_batch(&mut tape, &model, &[sample], &mut optimizer, 0.5).expect("train");
+
+            tape.params()
+                .iter()
+                .zip(&before)
+                .flat_map(|(p, b)| p.data.iter().zip(b).map(|(x, y)| (x - y).powi(2)))
+                .sum::<f64>()
+                .sqrt()
+        };
+
+        let light = displacement(0.1);
+        let full = displacement(1.0);
+        assert!(light > 0.0);
+        assert!(
+            light < full * 0.2,
+            "weight 0.1 moved {light}, weight 1.0 moved {full}"
+        );
+    }
+
+    #[test]
+    fn evaluate_reports_bounded_metrics_without_training() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(59);
+        let cfg = ScorerConfig {
+            native_dim: 4,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 2,
+            hash_buckets: 64,
+            project_slots: 4,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+        let before = tape.params()[0].data.clone();
+        let mut unlabelled = make_sample(4, 2);
+        unlabelled.labels = vec![0.0, 0.0];
+        let samples = vec![make_sample(4, 2), unlabelled];
+
+        let metrics = evaluate_samples(&mut tape, &model, &samples);
+        assert_eq!(metrics.samples, 1);
+        assert_eq!(metrics.candidates, 2);
+        assert!(metrics.mrr == 1.0 || metrics.mrr == 0.5);
+        assert_eq!(metrics.recall_at_5, 1.0);
+        assert_eq!(metrics.precision_at_5, 0.5);
+        assert_eq!(metrics.positive_rate, 0.5);
+        for value in [metrics.ndcg_at_10, metrics.brier, metrics.ece] {
+            assert!((0.0..=1.0).contains(&value));
+        }
+        assert_eq!(tape.params()[0].data, before);
+    }
+
     #[test]
     fn train_epochs_reduces_loss() {
         let mut tape = Tape::new();
@@ -442,6 +1065,11 @@ mod tests {
             extra_features: 3,
             hash_buckets: 64,
             project_slots: 4,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
         };
         let model = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
         let mut optimizer = Adam::new(&tape, 1e-2);
@@ -449,13 +1077,27 @@ mod tests {
         let sample = make_sample(4, 3);
 
         // Get initial loss
-        let stats_1 =
-            train_batch(&mut tape, &model, &[sample.clone()], &mut optimizer, 0.5).expect("train");
+        let stats_1 = train_batch(
+            &mut tape,
+            &model,
+            std::slice::from_ref(&sample),
+            &mut optimizer,
+            0.5,
+        )
+        .expect("train");
         let initial_loss = stats_1.loss;
 
         // Train for multiple epochs
-        let stats = train_epochs(&mut tape, &model, &[sample], &mut optimizer, 20, 0.5)
-            .expect("train_epochs");
+        let stats = train_epochs(
+            &mut tape,
+            &model,
+            &[sample],
+            &mut optimizer,
+            20,
+            0.5,
+            &TrainLimits::default(),
+        )
+        .expect("train_epochs");
 
         assert!(stats.steps > 1, "should have taken multiple steps");
         assert!(
@@ -465,4 +1107,222 @@ mod tests {
             initial_loss,
         );
     }
+
+    #[test]
+    fn train_epochs_stops_at_epoch_boundary_when_budget_spent() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(42);
+        let cfg = ScorerConfig {
+            native_dim: 4,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 3,
+            hash_buckets: 64,
+            project_slots: 4,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+        let mut optimizer = Adam::new(&tape, 1e-2);
+        let sample = make_sample(4, 3);
+
+        let deadline = std::time::Instant::now();
+        let stats = train_epochs(
+            &mut tape,
+            &model,
+            &[sample],
+            &mut optimizer,
+            20,
+            0.5,
+            &TrainLimits {
+                deadline: Some(deadline),
+                ..TrainLimits::default()
+            },
+        )
+        .expect("train_epochs");
+
+        assert_eq!(stats.epochs_completed, 1, "first epoch always runs");
+        assert_eq!(stats.steps, 1);
+        assert!(stats.budget_exhausted);
+    }
+
+    #[test]
+    fn hyperparameters_reject_out_of_range_values() {
+        assert!(Hyperparameters::default().validate().is_ok());
+        let with = |f: fn(&mut Hyperparameters)| {
+            let mut config = Hyperparameters::default();
+            f(&mut config);
+            config.validate()
+        };
+        assert!(with(|c| c.learning_rate = 0.0).is_err());
+        assert!(with(|c| c.learning_rate = f64::NAN).is_err());
+        assert!(with(|c| c.temperature = -1.0).is_err());
+        assert!(with(|c| c.min_confidence = 1.5).is_err());
+        assert!(with(|c| c.min_confidence = 1.0).is_ok());
+    }
+
+    #[test]
+    fn cancelled_training_stops_before_the_next_sample() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(42);
+        let cfg = ScorerConfig {
+            native_dim: 4,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 3,
+            hash_buckets: 64,
+            project_slots: 4,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+        let mut optimizer = Adam::new(&tape, 1e-2);
+        let samples = vec![make_sample(4, 3), make_sample(4, 3)];
+        let limits = TrainLimits::default();
+        limits.cancel.cancel();
+
+        let stats = train_epochs(&mut tape, &model, &samples, &mut optimizer, 5, 0.5, &limits)
+            .expect("train_epochs");
+
+        assert!(stats.cancelled);
+        assert_eq!(stats.steps, 0);
+        assert_eq!(stats.epochs_completed, 0);
+
+        limits.cancel.reset();
+        let stats = train_batch_cancellable(
+            &mut tape,
+            &model,
+            &samples,
+            &mut optimizer,
+            0.5,
+            &limits.cancel,
+        )
+        .expect("train_batch");
+        assert!(!stats.cancelled);
+        assert_eq!(stats.samples, 2);
+    }
+
+    #[test]
+    fn frozen_parameters_are_left_unchanged() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(42);
+        let cfg = ScorerConfig {
+            native_dim: 4,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 3,
+            hash_buckets: 64,
+            project_slots: 4,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+        let mut optimizer = Adam::new(&tape, 1e-2);
+        let encoder = model.encoder_param_indices();
+        optimizer.freeze(&encoder);
+        let before: Vec<Vec<f64>> = model
+            .param_indices()
+            .iter()
+            .map(|&idx| tape.params()[idx].data.clone())
+            .collect();
+
+        train_batch(&mut tape, &model, &[make_sample(4, 3)], &mut optimizer, 0.5)
+            .expect("train_batch");
+
+        for (&idx, before) in model.param_indices().iter().zip(&before) {
+            let changed = tape.params()[idx].data != *before;
+            assert_eq!(changed, !encoder.contains(&idx), "param {idx}");
+        }
+    }
+
+    struct VecSource {
+        chunks: Vec<Vec<TrainingSample>>,
+        next: usize,
+        rewinds: usize,
+    }
+
+    impl SampleSource for VecSource {
+        fn rewind(&mut self) {
+            self.next = 0;
+            self.rewinds += 1;
+        }
+
+        fn next_chunk(&mut self) -> Result<Option<Vec<TrainingSample>>, String> {
+            let chunk = self.chunks.get(self.next).cloned();
+            self.next += 1;
+            Ok(chunk)
+        }
+    }
+
+    #[test]
+    fn train_epochs_streaming_rereads_every_epoch() {
+        let mut tape = Tape::new();
+        let mut rng = Rng::new(42);
+        let cfg = ScorerConfig {
+            native_dim: 4,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 3,
+            hash_buckets: 64,
+            project_slots: 4,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let model = CrossAttentionScorer::new(&mut tape, &mut rng, cfg);
+        let mut optimizer = Adam::new(&tape, 1e-2);
+        let mut source = VecSource {
+            chunks: vec![vec![make_sample(4, 3)], vec![make_sample(4, 3)]],
+            next: 0,
+            rewinds: 0,
+        };
+
+        let stats = train_epochs_streaming(
+            &mut tape,
+            &model,
+            &mut source,
+            &mut optimizer,
+            3,
+            0.5,
+            &TrainLimits::default(),
+        )
+        .expect("train_epochs_streaming");
+
+        assert_eq!(source.rewinds, stats.epochs_completed);
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.steps, 2 * stats.epochs_completed as u64);
+        assert!(stats.loss.is_finite());
+    }
+
+    #[test]
+    fn augment_samples_jitters_copies_but_keeps_labels() {
+        let mut rng = Rng::new(3);
+        let original = make_sample(4, 3);
+        let out = augment_samples(std::slice::from_ref(&original), 2, &mut rng);
+
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0].query_embedding, original.query_embedding);
+        for copy in &out[1..] {
+            assert_eq!(copy.labels, original.labels);
+            assert_eq!(copy.candidate_embeddings.len(), 2);
+            assert_ne!(copy.query_embedding, original.query_embedding);
+            assert!(copy
+                .candidate_features
+                .iter()
+                .flatten()
+                .all(|v| *v == 0.0 || *v == 1.0));
+        }
+        assert!(augment_samples(&[original], 0, &mut rng).len() == 1);
+    }
 }
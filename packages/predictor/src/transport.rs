@@ -0,0 +1,285 @@
+use std::{
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Largest Content-Length accepted, so a corrupt header cannot make the
+/// reader allocate without bound.
+pub const MAX_FRAME_BYTES: usize = 256 * 1024 * 1024;
+
+/// How JSON-RPC messages are delimited on stdin/stdout. Chosen once at
+/// startup with `--framing` and used for both directions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Framing {
+    /// One JSON document per line
+    #[default]
+    Lines,
+    /// LSP-style `Content-Length: N` header block, a blank line, then an
+    /// N-byte body. Bodies may contain raw newlines.
+    ContentLength,
+}
+
+impl Framing {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw {
+            "lines" | "newline" => Ok(Self::Lines),
+            "content-length" => Ok(Self::ContentLength),
+            other => Err(format!("unknown framing: {other}")),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lines => "lines",
+            Self::ContentLength => "content-length",
+        }
+    }
+}
+
+/// How each message body is encoded. Method names and param shapes are the
+/// same either way; MessagePack just avoids printing and parsing floats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Protocol {
+    #[default]
+    Json,
+    /// Named-field MessagePack maps. Binary, so it needs length framing.
+    MsgPack,
+}
+
+impl Protocol {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw {
+            "json" => Ok(Self::Json),
+            "msgpack" | "messagepack" => Ok(Self::MsgPack),
+            other => Err(format!("unknown protocol: {other}")),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::MsgPack => "msgpack",
+        }
+    }
+
+    /// MIME type for HTTP bodies in this encoding.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MsgPack => "application/msgpack",
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, body: &[u8]) -> Result<T, String> {
+        match self {
+            Self::Json => serde_json::from_slice(body).map_err(|e| e.to_string()),
+            Self::MsgPack => rmp_serde::from_slice(body).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            Self::MsgPack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Read the next message body, or `None` at end of input. In line mode the
+/// trailing line break is stripped and blank lines come back empty for the
+/// caller to skip.
+pub fn read_frame<R: BufRead>(reader: &mut R, framing: Framing) -> io::Result<Option<Vec<u8>>> {
+    match framing {
+        Framing::Lines => {
+            let mut line = Vec::new();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok(None);
+            }
+            while matches!(line.last(), Some(b'\n' | b'\r')) {
+                line.pop();
+            }
+            Ok(Some(line))
+        }
+        Framing::ContentLength => read_content_length(reader),
+    }
+}
+
+fn read_content_length<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut length = None;
+    let mut saw_header = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            if saw_header {
+                return Err(invalid("end of input inside a frame header"));
+            }
+            return Ok(None);
+        }
+        let line = line.trim_end_matches(['\n', '\r']);
+        if line.is_empty() {
+            // Tolerate stray blank lines between frames
+            if !saw_header {
+                continue;
+            }
+            break;
+        }
+        saw_header = true;
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| invalid(format!("malformed frame header: {line}")))?;
+        // Other headers such as Content-Type are accepted and ignored
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            let value = value
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| invalid(format!("bad Content-Length: {}", value.trim())))?;
+            length = Some(value);
+        }
+    }
+    let length = length.ok_or_else(|| invalid("frame has no Content-Length header"))?;
+    if length > MAX_FRAME_BYTES {
+        return Err(invalid(format!(
+            "frame of {length} bytes exceeds the {MAX_FRAME_BYTES} byte limit"
+        )));
+    }
+    let mut body = vec![0_u8; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+/// Write one message and flush it.
+pub fn write_frame<W: Write>(writer: &mut W, framing: Framing, payload: &[u8]) -> io::Result<()> {
+    match framing {
+        Framing::Lines => {
+            writer.write_all(payload)?;
+            writer.write_all(b"\n")?;
+        }
+        Framing::ContentLength => {
+            write!(writer, "Content-Length: {}\r\n\r\n", payload.len())?;
+            writer.write_all(payload)?;
+        }
+    }
+    writer.flush()
+}
+
+/// Socket address from a `--listen` spec such as `http:127.0.0.1:7340`.
+pub fn parse_listen(spec: &str) -> Result<String, String> {
+    let addr = spec
+        .strip_prefix("http:")
+        .ok_or_else(|| format!("unsupported listen spec: {spec} (expected http:HOST:PORT)"))?;
+    let addr = addr.strip_prefix("//").unwrap_or(addr);
+    if addr
+        .rsplit_once(':')
+        .is_none_or(|(host, port)| host.is_empty() || port.parse::<u16>().is_err())
+    {
+        return Err(format!("listen address needs HOST:PORT: {addr}"));
+    }
+    Ok(addr.to_string())
+}
+
+/// Bearer token from a file, e.g. a mounted container secret. Surrounding
+/// whitespace is dropped; an empty token is refused so a missing secret
+/// doesn't leave the server open.
+pub fn read_token(path: &Path) -> Result<String, String> {
+    let raw = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read token file {}: {e}", path.display()))?;
+    let token = raw.trim();
+    if token.is_empty() {
+        return Err(format!("token file {} is empty", path.display()));
+    }
+    Ok(token.to_string())
+}
+
+/// Whether an `Authorization` header value carries `token`. The comparison
+/// doesn't stop at the first differing byte, so timing leaks nothing.
+pub fn bearer_matches(header: &str, token: &str) -> bool {
+    let Some((scheme, presented)) = header.trim().split_once(' ') else {
+        return false;
+    };
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return false;
+    }
+    let (presented, token) = (presented.trim().as_bytes(), token.as_bytes());
+    presented.len() == token.len()
+        && presented
+            .iter()
+            .zip(token)
+            .fold(0_u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_length_frames_round_trip_with_embedded_newlines() {
+        let payload = b"{\"text\":\"line one\nline two\"}";
+        let mut wire = Vec::new();
+        write_frame(&mut wire, Framing::ContentLength, payload).unwrap();
+        write_frame(&mut wire, Framing::ContentLength, b"{}").unwrap();
+
+        let mut reader = io::Cursor::new(wire);
+        let first = read_frame(&mut reader, Framing::ContentLength).unwrap();
+        assert_eq!(first.as_deref(), Some(&payload[..]));
+        let second = read_frame(&mut reader, Framing::ContentLength).unwrap();
+        assert_eq!(second.as_deref(), Some(&b"{}"[..]));
+        assert!(read_frame(&mut reader, Framing::ContentLength)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn rejects_frames_without_a_usable_length() {
+        let mut missing = io::Cursor::new(b"Content-Type: json\r\n\r\n{}".to_vec());
+        assert!(read_frame(&mut missing, Framing::ContentLength).is_err());
+
+        let mut huge = io::Cursor::new(
+            format!("Content-Length: {}\r\n\r\n", MAX_FRAME_BYTES + 1).into_bytes(),
+        );
+        assert!(read_frame(&mut huge, Framing::ContentLength).is_err());
+
+        let mut lines = io::Cursor::new(b"{\"a\":1}\r\n".to_vec());
+        let line = read_frame(&mut lines, Framing::Lines).unwrap();
+        assert_eq!(line.as_deref(), Some(&b"{\"a\":1}"[..]));
+    }
+
+    #[test]
+    fn http_listen_specs_and_bearer_tokens() {
+        assert_eq!(
+            parse_listen("http:127.0.0.1:7340").unwrap(),
+            "127.0.0.1:7340"
+        );
+        assert!(parse_listen("tcp:127.0.0.1:7340").is_err());
+        assert!(parse_listen("http:127.0.0.1").is_err());
+
+        assert!(bearer_matches("Bearer s3cret", "s3cret"));
+        assert!(bearer_matches("bearer  s3cret ", "s3cret"));
+        assert!(!bearer_matches("Bearer s3cres", "s3cret"));
+        assert!(!bearer_matches("Basic s3cret", "s3cret"));
+        assert!(!bearer_matches("s3cret", "s3cret"));
+    }
+
+    #[test]
+    fn msgpack_requests_decode_to_the_json_shapes() {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "score",
+            "params": { "context_embedding": [0.25, -1.5] },
+        });
+        let body = Protocol::MsgPack.encode(&request).unwrap();
+        assert!(body.len() < serde_json::to_vec(&request).unwrap().len());
+
+        let decoded: crate::protocol::JsonRpcRequest = Protocol::MsgPack.decode(&body).unwrap();
+        assert_eq!(decoded.method, "score");
+        assert_eq!(decoded.id, 7);
+        assert_eq!(decoded.params["context_embedding"][1], -1.5);
+    }
+}
@@ -0,0 +1,533 @@
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::data::MAX_LOAD_WORKERS;
+use crate::protocol::{
+    BenchmarkParams, EvaluateParams, RecordFeedbackParams, ScoreParams, TrainFromDbParams,
+    TrainParams, INVALID_PARAMS, LIMIT_EXCEEDED,
+};
+
+/// Most `benchmark` iterations per call; `score` waits on the scoring worker
+/// meanwhile.
+pub const MAX_BENCHMARK_ITERATIONS: usize = 10_000;
+
+/// Most words per synthetic `benchmark` text.
+pub const MAX_BENCHMARK_TEXT_TOKENS: usize = 4096;
+
+/// Most hashed project slots a model may be resized to. Each slot is a row
+/// of the project embedding table, saved with every checkpoint.
+pub const MAX_PROJECT_SLOTS: usize = 4096;
+
+/// Per-request size caps, set with `--max-candidates`,
+/// `--max-top-k-candidates` and `--max-embedding-dim`. They bound the work
+/// one call can ask for; the frame limit in `transport` bounds the bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimits {
+    pub max_candidates: usize,
+    /// Candidate cap for `score` with `top_k`, which only fully scores the
+    /// survivors of the q·k prefilter.
+    pub max_top_k_candidates: usize,
+    pub max_embedding_dim: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_candidates: 4096,
+            max_top_k_candidates: 65_536,
+            max_embedding_dim: 8192,
+        }
+    }
+}
+
+impl RequestLimits {
+    /// Candidate cap for a scoring call with the given `top_k`.
+    pub fn candidates_for(&self, top_k: Option<usize>) -> usize {
+        if top_k.is_some() {
+            self.max_top_k_candidates
+        } else {
+            self.max_candidates
+        }
+    }
+}
+
+/// A rejected request, with the JSON path of the offending value such as
+/// `candidate_embeddings[3]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub code: i32,
+    pub path: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn invalid(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    fn too_large(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code: LIMIT_EXCEEDED,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// `error.data` of the JSON-RPC response
+    pub fn data(&self) -> Value {
+        json!({ "path": self.path })
+    }
+}
+
+/// Deserialize params, reporting where in the document decoding failed.
+pub fn parse_params<P: DeserializeOwned>(params: Value) -> Result<P, FieldError> {
+    serde_path_to_error::deserialize(params).map_err(|err| {
+        let path = err.path().to_string();
+        FieldError::invalid(path, format!("invalid params: {}", err.into_inner()))
+    })
+}
+
+/// Remove and return the `model` selector from a params object, so the
+/// method's own params never see it.
+pub fn take_model(params: &mut Value) -> Result<Option<String>, FieldError> {
+    let Some(object) = params.as_object_mut() else {
+        return Ok(None);
+    };
+    match object.remove("model") {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(name)) => Ok(Some(name)),
+        Some(_) => Err(FieldError::invalid(
+            "model",
+            "invalid params: model must be a string",
+        )),
+    }
+}
+
+/// Shape and size checks that need no model state, run before a handler.
+pub trait Validate {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), FieldError>;
+}
+
+fn check_count(limit: usize, path: &str, count: usize) -> Result<(), FieldError> {
+    if count > limit {
+        return Err(FieldError::too_large(
+            path,
+            format!("{path} has {count} candidates; the limit is {limit}"),
+        ));
+    }
+    Ok(())
+}
+
+fn check_dim(limits: &RequestLimits, path: &str, dim: usize) -> Result<(), FieldError> {
+    if dim > limits.max_embedding_dim {
+        return Err(FieldError::too_large(
+            path,
+            format!(
+                "{path} has {dim} dimensions; the limit is {}",
+                limits.max_embedding_dim
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn check_rows(limits: &RequestLimits, path: &str, rows: &[Vec<f64>]) -> Result<(), FieldError> {
+    for (i, row) in rows.iter().enumerate() {
+        check_dim(limits, &format!("{path}[{i}]"), row.len())?;
+    }
+    Ok(())
+}
+
+/// `path` must have one entry per candidate, or be empty when `optional`.
+fn check_parallel(
+    path: &str,
+    len: usize,
+    expected: usize,
+    against: &str,
+    optional: bool,
+) -> Result<(), FieldError> {
+    if len == expected || (optional && len == 0) {
+        return Ok(());
+    }
+    Err(FieldError::invalid(
+        path,
+        format!("{path} has {len} entries but {against} has {expected}"),
+    ))
+}
+
+impl Validate for ScoreParams {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), FieldError> {
+        let count = self.candidate_ids.len();
+        check_dim(limits, "context_embedding", self.context_embedding.len())?;
+        check_count(limits.candidates_for(self.top_k), "candidate_ids", count)?;
+        for (path, len) in [
+            ("candidate_embeddings", self.candidate_embeddings.len()),
+            (
+                "candidate_embedding_blobs",
+                self.candidate_embedding_blobs.len(),
+            ),
+            ("candidate_texts", self.candidate_texts.len()),
+            ("candidate_features", self.candidate_features.len()),
+        ] {
+            check_parallel(path, len, count, "candidate_ids", true)?;
+        }
+        check_rows(limits, "candidate_embeddings", &self.candidate_embeddings)?;
+        if self.top_k == Some(0) {
+            return Err(FieldError::invalid("top_k", "top_k must be at least 1"));
+        }
+        // The widest blob encoding is f32
+        let max_blob = limits.max_embedding_dim * 4;
+        for (i, blob) in self.candidate_embedding_blobs.iter().enumerate() {
+            let len = blob.as_ref().map_or(0, Vec::len);
+            if len > max_blob {
+                return Err(FieldError::too_large(
+                    format!("candidate_embedding_blobs[{i}]"),
+                    format!("embedding blob of {len} bytes exceeds {max_blob}"),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for TrainParams {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), FieldError> {
+        let count = self.candidate_embeddings.len();
+        check_dim(limits, "context_embedding", self.context_embedding.len())?;
+        check_count(limits.max_candidates, "candidate_embeddings", count)?;
+        check_parallel(
+            "labels",
+            self.labels.len(),
+            count,
+            "candidate_embeddings",
+            false,
+        )?;
+        check_parallel(
+            "candidate_features",
+            self.candidate_features.len(),
+            count,
+            "candidate_embeddings",
+            true,
+        )?;
+        check_rows(limits, "candidate_embeddings", &self.candidate_embeddings)
+    }
+}
+
+impl Validate for RecordFeedbackParams {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), FieldError> {
+        let count = self.candidate_ids.len();
+        check_dim(limits, "context_embedding", self.context_embedding.len())?;
+        check_count(limits.max_candidates, "candidate_ids", count)?;
+        check_parallel(
+            "candidate_embeddings",
+            self.candidate_embeddings.len(),
+            count,
+            "candidate_ids",
+            false,
+        )?;
+        check_parallel(
+            "candidate_features",
+            self.candidate_features.len(),
+            count,
+            "candidate_ids",
+            true,
+        )?;
+        check_rows(limits, "candidate_embeddings", &self.candidate_embeddings)
+    }
+}
+
+fn check_load_workers(workers: Option<usize>) -> Result<(), FieldError> {
+    match workers {
+        Some(0) => Err(FieldError::invalid(
+            "load_workers",
+            "load_workers must be at least 1",
+        )),
+        Some(n) if n > MAX_LOAD_WORKERS => Err(FieldError::too_large(
+            "load_workers",
+            format!("load_workers is limited to {MAX_LOAD_WORKERS}"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// NaN would disable rejection and a negative value would reject every
+/// vector, so the fraction must be a real share.
+fn check_corrupt_fraction(fraction: Option<f64>) -> Result<(), FieldError> {
+    match fraction {
+        Some(f) if !(0.0..=1.0).contains(&f) => Err(FieldError::invalid(
+            "max_corrupt_fraction",
+            "max_corrupt_fraction must be between 0 and 1",
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl Validate for TrainFromDbParams {
+    fn validate(&self, _limits: &RequestLimits) -> Result<(), FieldError> {
+        check_load_workers(self.load_workers)?;
+        check_corrupt_fraction(self.max_corrupt_fraction)?;
+        match self.project_slots {
+            Some(0) => Err(FieldError::invalid(
+                "project_slots",
+                "project_slots must be at least 1",
+            )),
+            Some(n) if n > MAX_PROJECT_SLOTS => Err(FieldError::too_large(
+                "project_slots",
+                format!("project_slots is limited to {MAX_PROJECT_SLOTS}"),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Validate for EvaluateParams {
+    fn validate(&self, _limits: &RequestLimits) -> Result<(), FieldError> {
+        check_load_workers(self.load_workers)?;
+        check_corrupt_fraction(self.max_corrupt_fraction)
+    }
+}
+
+impl Validate for BenchmarkParams {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), FieldError> {
+        if self.candidates == 0 {
+            return Err(FieldError::invalid(
+                "candidates",
+                "candidates must be at least 1",
+            ));
+        }
+        check_count(
+            limits.candidates_for(self.top_k),
+            "candidates",
+            self.candidates,
+        )?;
+        if self.iterations == 0 || self.iterations > MAX_BENCHMARK_ITERATIONS {
+            return Err(FieldError::invalid(
+                "iterations",
+                format!("iterations must be between 1 and {MAX_BENCHMARK_ITERATIONS}"),
+            ));
+        }
+        if let Some(dim) = self.embedding_dim {
+            check_dim(limits, "embedding_dim", dim)?;
+        }
+        if self
+            .text_tokens
+            .is_some_and(|tokens| tokens > MAX_BENCHMARK_TEXT_TOKENS)
+        {
+            return Err(FieldError::too_large(
+                "text_tokens",
+                format!("text_tokens is limited to {MAX_BENCHMARK_TEXT_TOKENS}"),
+            ));
+        }
+        if self.top_k == Some(0) {
+            return Err(FieldError::invalid("top_k", "top_k must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_errors_point_at_the_offending_field() {
+        let params = json!({
+            "context_embedding": [0.1, 0.2],
+            "candidate_embeddings": [[0.1, 0.2], [0.3, "x"]],
+            "labels": [1.0, 0.0],
+        });
+        let err = parse_params::<TrainParams>(params).unwrap_err();
+        assert_eq!(err.code, INVALID_PARAMS);
+        assert_eq!(err.path, "candidate_embeddings[1][1]");
+        assert!(err.message.starts_with("invalid params: "));
+        assert_eq!(err.data()["path"], "candidate_embeddings[1][1]");
+    }
+
+    #[test]
+    fn model_selector_is_stripped_from_params() {
+        let mut params = json!({ "model": "agent-a", "path": "/tmp/x" });
+        assert_eq!(take_model(&mut params).unwrap().as_deref(), Some("agent-a"));
+        assert_eq!(params, json!({ "path": "/tmp/x" }));
+
+        assert_eq!(take_model(&mut json!({})).unwrap(), None);
+        assert_eq!(take_model(&mut Value::Null).unwrap(), None);
+        let err = take_model(&mut json!({ "model": 3 })).unwrap_err();
+        assert_eq!(err.path, "model");
+    }
+
+    #[test]
+    fn limits_and_mismatches_are_reported_with_paths() {
+        let limits = RequestLimits {
+            max_candidates: 2,
+            max_top_k_candidates: 2,
+            max_embedding_dim: 4,
+        };
+        let train = |embeddings: Value, labels: Value| {
+            parse_params::<TrainParams>(json!({
+                "context_embedding": [0.1, 0.2],
+                "candidate_embeddings": embeddings,
+                "labels": labels,
+            }))
+            .unwrap()
+            .validate(&limits)
+        };
+
+        assert!(train(json!([[0.1], [0.2]]), json!([1.0, 0.0])).is_ok());
+
+        let err = train(json!([[0.1], [0.2], [0.3]]), json!([1.0, 0.0, 0.0])).unwrap_err();
+        assert_eq!(err.code, LIMIT_EXCEEDED);
+        assert_eq!(err.path, "candidate_embeddings");
+
+        let err = train(json!([[0.1], [0.1, 0.2, 0.3, 0.4, 0.5]]), json!([1.0, 0.0])).unwrap_err();
+        assert_eq!(err.code, LIMIT_EXCEEDED);
+        assert_eq!(err.path, "candidate_embeddings[1]");
+
+        let err = train(json!([[0.1], [0.2]]), json!([1.0])).unwrap_err();
+        assert_eq!(err.code, INVALID_PARAMS);
+        assert_eq!(err.path, "labels");
+
+        let err = parse_params::<ScoreParams>(json!({
+            "context_embedding": [0.1],
+            "candidate_ids": ["a"],
+            "top_k": 0,
+        }))
+        .unwrap()
+        .validate(&limits)
+        .unwrap_err();
+        assert_eq!(err.path, "top_k");
+    }
+
+    #[test]
+    fn load_workers_must_be_between_one_and_the_cap() {
+        let limits = RequestLimits::default();
+        let train_from_db = |workers: Value| {
+            parse_params::<TrainFromDbParams>(json!({ "load_workers": workers }))
+                .unwrap()
+                .validate(&limits)
+        };
+        assert!(train_from_db(Value::Null).is_ok());
+        assert!(train_from_db(json!(MAX_LOAD_WORKERS)).is_ok());
+
+        let err = train_from_db(json!(0)).unwrap_err();
+        assert_eq!(err.code, INVALID_PARAMS);
+        assert_eq!(err.path, "load_workers");
+        let err = train_from_db(json!(MAX_LOAD_WORKERS + 1)).unwrap_err();
+        assert_eq!(err.code, LIMIT_EXCEEDED);
+        assert_eq!(err.path, "load_workers");
+
+        let err = parse_params::<EvaluateParams>(json!({ "load_workers": 0 }))
+            .unwrap()
+            .validate(&limits)
+            .unwrap_err();
+        assert_eq!(err.path, "load_workers");
+    }
+
+    #[test]
+    fn project_slots_are_bounded() {
+        let limits = RequestLimits::default();
+        let train_from_db = |slots: usize| {
+            parse_params::<TrainFromDbParams>(json!({ "project_slots": slots }))
+                .unwrap()
+                .validate(&limits)
+        };
+        assert!(train_from_db(64).is_ok());
+        assert_eq!(train_from_db(0).unwrap_err().code, INVALID_PARAMS);
+        let err = train_from_db(MAX_PROJECT_SLOTS + 1).unwrap_err();
+        assert_eq!(err.code, LIMIT_EXCEEDED);
+        assert_eq!(err.path, "project_slots");
+    }
+
+    #[test]
+    fn max_corrupt_fraction_must_be_a_share() {
+        let limits = RequestLimits::default();
+        let mut params =
+            parse_params::<EvaluateParams>(json!({ "max_corrupt_fraction": 0.5 })).unwrap();
+        assert!(params.validate(&limits).is_ok());
+
+        // NaN and infinities can't come through JSON, but the check must
+        // not rely on that
+        for bad in [-0.1, 1.5, f64::NAN, f64::INFINITY] {
+            params.max_corrupt_fraction = Some(bad);
+            let err = params.validate(&limits).unwrap_err();
+            assert_eq!(err.path, "max_corrupt_fraction", "{bad}");
+        }
+
+        let err = parse_params::<TrainFromDbParams>(json!({ "max_corrupt_fraction": -1.0 }))
+            .unwrap()
+            .validate(&limits)
+            .unwrap_err();
+        assert_eq!(err.code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn top_k_scores_candidate_sets_past_the_plain_cap() {
+        use crate::autograd::{Rng, Tape};
+        use crate::cache::EncodingCache;
+        use crate::model::{CandidateInput, CrossAttentionScorer, ScorerConfig};
+
+        let limits = RequestLimits::default();
+        let count = limits.max_candidates + 1000;
+        let ids = (0..count).map(|i| format!("m{i}")).collect::<Vec<_>>();
+        let embeddings = (0..count)
+            .map(|i| (0..8).map(|j| ((i * 8 + j) as f64 * 0.37).sin()).collect())
+            .collect::<Vec<Vec<f64>>>();
+        let request = |top_k: Option<usize>| {
+            parse_params::<ScoreParams>(json!({
+                "context_embedding": [0.1, -0.4, 0.3, 0.2, -0.1, 0.5, 0.0, 0.7],
+                "candidate_ids": ids,
+                "candidate_embeddings": embeddings,
+                "top_k": top_k,
+            }))
+            .unwrap()
+        };
+
+        let err = request(None).validate(&limits).unwrap_err();
+        assert_eq!(err.code, LIMIT_EXCEEDED);
+        let params = request(Some(10));
+        params
+            .validate(&limits)
+            .expect("top_k request within its cap");
+
+        let mut tape = Tape::new();
+        let cfg = ScorerConfig {
+            native_dim: 8,
+            internal_dim: 4,
+            value_dim: 2,
+            extra_features: 0,
+            hash_buckets: 16,
+            project_slots: 1,
+            adapter_dims: Vec::new(),
+            feature_schema: None,
+            max_tokens: None,
+            tokenizer: None,
+            bigram_buckets: 0,
+        };
+        let scorer = CrossAttentionScorer::new(&mut tape, &mut Rng::new(7), cfg);
+        let candidates = params
+            .candidate_ids
+            .iter()
+            .zip(&params.candidate_embeddings)
+            .map(|(id, embedding)| CandidateInput {
+                id,
+                embedding: Some(embedding),
+                text: None,
+                features: &[],
+            })
+            .collect::<Vec<_>>();
+        let scores = scorer
+            .score_top_k(
+                &mut tape,
+                &params.context_embedding,
+                &candidates,
+                0,
+                10,
+                &mut EncodingCache::new(0),
+            )
+            .expect("score");
+        assert_eq!(scores.len(), 10);
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Cheap stand-in for "the database has new rows": the main file's size
+/// and mtime plus those of its `-wal` file. The WAL size grows with its
+/// frame count, so an append shows up even before a checkpoint touches the
+/// main file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbFingerprint {
+    pub db_len: u64,
+    pub db_modified: Option<SystemTime>,
+    pub wal_len: u64,
+    pub wal_modified: Option<SystemTime>,
+}
+
+impl DbFingerprint {
+    pub fn read(db_path: &Path) -> io::Result<Self> {
+        let db = fs::metadata(db_path)?;
+        let mut wal_path = db_path.as_os_str().to_owned();
+        wal_path.push("-wal");
+        // No -wal file is normal outside WAL mode or after a clean close.
+        // Readers, ours included, create an empty one, which is no change.
+        let wal = fs::metadata(Path::new(&wal_path))
+            .ok()
+            .filter(|m| m.len() > 0);
+        Ok(Self {
+            db_len: db.len(),
+            db_modified: db.modified().ok(),
+            wal_len: wal.as_ref().map_or(0, fs::Metadata::len),
+            wal_modified: wal.and_then(|m| m.modified().ok()),
+        })
+    }
+}
+
+/// Hours of the day, UTC, during which watch mode holds retraining back,
+/// e.g. `22-7` for ten at night to seven in the morning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl QuietHours {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let (start, end) = raw
+            .split_once('-')
+            .ok_or_else(|| format!("quiet hours need START-END, got {raw}"))?;
+        let hour = |part: &str| {
+            part.trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|h| *h < 24)
+                .ok_or_else(|| format!("invalid hour in quiet hours: {part}"))
+        };
+        let quiet = Self {
+            start: hour(start)?,
+            end: hour(end)?,
+        };
+        if quiet.start == quiet.end {
+            return Err(format!("quiet hours {raw} are empty"));
+        }
+        Ok(quiet)
+    }
+
+    /// Whether `hour` falls in the window, which may wrap past midnight.
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+/// Debounces database changes: a retrain is due once the fingerprint has
+/// changed and then held still for `debounce`, so a burst of writes
+/// triggers one run rather than one per poll.
+#[derive(Debug)]
+pub struct Watcher {
+    debounce: Duration,
+    seen: Option<DbFingerprint>,
+    changed_at: Option<Instant>,
+}
+
+impl Watcher {
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            seen: None,
+            changed_at: None,
+        }
+    }
+
+    /// Record a poll. The first one only sets the baseline, so starting
+    /// the predictor doesn't retrain on its own.
+    pub fn observe(&mut self, fingerprint: DbFingerprint, now: Instant) {
+        match self.seen {
+            None => self.seen = Some(fingerprint),
+            Some(previous) if previous != fingerprint => {
+                self.seen = Some(fingerprint);
+                self.changed_at = Some(now);
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// A change is pending and has settled.
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.changed_at
+            .is_some_and(|changed| now.duration_since(changed) >= self.debounce)
+    }
+
+    /// Clear the pending change once a retrain has been started for it.
+    pub fn triggered(&mut self) {
+        self.changed_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(wal_len: u64) -> DbFingerprint {
+        DbFingerprint {
+            db_len: 4096,
+            db_modified: None,
+            wal_len,
+            wal_modified: None,
+        }
+    }
+
+    #[test]
+    fn retrains_once_writes_settle() {
+        let mut watcher = Watcher::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        watcher.observe(fingerprint(0), at(0));
+        assert!(!watcher.is_due(at(120)), "baseline alone is not a change");
+
+        watcher.observe(fingerprint(100), at(130));
+        watcher.observe(fingerprint(200), at(160));
+        assert!(
+            !watcher.is_due(at(200)),
+            "still within debounce of the last write"
+        );
+        watcher.observe(fingerprint(200), at(220));
+        assert!(watcher.is_due(at(220)));
+
+        watcher.triggered();
+        watcher.observe(fingerprint(200), at(300));
+        assert!(!watcher.is_due(at(400)));
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let night = QuietHours::parse("22-7").unwrap();
+        assert!(night.contains(23));
+        assert!(night.contains(0));
+        assert!(night.contains(6));
+        assert!(!night.contains(7));
+        assert!(!night.contains(12));
+
+        let lunch = QuietHours::parse("12-13").unwrap();
+        assert!(lunch.contains(12));
+        assert!(!lunch.contains(13));
+
+        assert!(QuietHours::parse("5-5").is_err());
+        assert!(QuietHours::parse("22-24").is_err());
+        assert!(QuietHours::parse("22").is_err());
+    }
+
+    #[test]
+    fn fingerprint_sees_wal_appends() {
+        let dir = std::env::temp_dir().join("predictor_test_watch");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let db = dir.join("memories.db");
+        fs::write(&db, b"main").unwrap();
+
+        let before = DbFingerprint::read(&db).unwrap();
+        assert_eq!(before.wal_len, 0);
+        fs::write(dir.join("memories.db-wal"), b"").unwrap();
+        assert_eq!(DbFingerprint::read(&db).unwrap(), before);
+        fs::write(dir.join("memories.db-wal"), b"frame").unwrap();
+        let after = DbFingerprint::read(&db).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(after.wal_len, 5);
+        assert_ne!(before, after);
+    }
+}
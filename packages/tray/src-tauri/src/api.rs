@@ -0,0 +1,482 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::proxy::{self, ProxyOptions};
+use crate::settings;
+use crate::tls::{self, TlsOptions};
+use crate::token;
+
+/// Set when the daemon last answered 401, cleared by the next success.
+/// Every request goes through `request`, so this is the one place a stale
+/// token is noticed.
+static AUTH_REJECTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the daemon is currently rejecting the tray's token.
+pub(crate) fn auth_rejected() -> bool {
+    AUTH_REJECTED.load(Ordering::Relaxed)
+}
+
+/// A daemon response: status code and raw body.
+pub(crate) struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    /// The body as JSON, or `Value::Null` when it isn't JSON.
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or_default()
+    }
+
+    /// The error for a non-2xx response.
+    pub fn error(&self) -> ApiError {
+        let data = self.json();
+        let detail = data["error"]
+            .as_str()
+            .or(data["message"].as_str())
+            .map(str::to_string);
+        ApiError {
+            status: self.status,
+            detail,
+            body: self.text(),
+        }
+    }
+
+    /// The response itself if it succeeded, else its error.
+    pub fn check(self) -> Result<Self, ApiError> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn into_text(self) -> Result<String, String> {
+        Ok(self.check()?.text())
+    }
+}
+
+/// What a failed status means for the caller.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorKind {
+    /// 401 or 403: the token was rejected or lacks the role
+    Unauthorized,
+    /// 404: a missing record, or a daemon too old to have the endpoint
+    NotFound,
+    /// 409
+    Conflict,
+    /// 429: rate limited, or a repair's cooldown or budget
+    RateLimited,
+    /// 5xx
+    Server,
+    Other,
+}
+
+/// A non-2xx daemon response. Shows the daemon's own `error` or `message`
+/// text when it gave one, else `HTTP <status>: <body>`.
+pub(crate) struct ApiError {
+    pub status: u16,
+    pub detail: Option<String>,
+    body: String,
+}
+
+impl ApiError {
+    pub fn kind(&self) -> ErrorKind {
+        match self.status {
+            401 | 403 => ErrorKind::Unauthorized,
+            404 => ErrorKind::NotFound,
+            409 => ErrorKind::Conflict,
+            429 => ErrorKind::RateLimited,
+            500..=599 => ErrorKind::Server,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.detail {
+            Some(detail) => f.write_str(detail),
+            None => write!(f, "HTTP {}: {}", self.status, self.body),
+        }
+    }
+}
+
+impl From<ApiError> for String {
+    fn from(err: ApiError) -> Self {
+        err.to_string()
+    }
+}
+
+/// GET a daemon endpoint and parse the JSON body.
+pub(crate) async fn get_json(path: &str, timeout: Duration) -> Result<Value, String> {
+    get_json_as(path, token::read().as_deref(), timeout).await
+}
+
+/// GET with an explicit bearer token instead of the stored one.
+pub(crate) async fn get_json_as(
+    path: &str,
+    bearer: Option<&str>,
+    timeout: Duration,
+) -> Result<Value, String> {
+    let text = request("GET", path, None, bearer, timeout)
+        .await?
+        .into_text()?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))
+}
+
+/// GET a daemon endpoint and return the raw response, leaving status and
+/// body handling to the caller.
+pub(crate) async fn get(path: &str, timeout: Duration) -> Result<Response, String> {
+    request("GET", path, None, token::read().as_deref(), timeout).await
+}
+
+/// POST a JSON body to a daemon endpoint and return the response text.
+pub(crate) async fn post_json(path: &str, body: &Value, timeout: Duration) -> Result<String, String> {
+    let bytes = serde_json::to_vec(body).map_err(|e| e.to_string())?;
+    request("POST", path, Some(bytes), token::read().as_deref(), timeout)
+        .await?
+        .into_text()
+}
+
+/// Send a JSON body with any method (e.g. `PATCH`, `DELETE`) and return
+/// the raw response, leaving status handling to the caller.
+pub(crate) async fn send_json(
+    method: &str,
+    path: &str,
+    body: &Value,
+    timeout: Duration,
+) -> Result<Response, String> {
+    let bytes = serde_json::to_vec(body).map_err(|e| e.to_string())?;
+    request(method, path, Some(bytes), token::read().as_deref(), timeout).await
+}
+
+/// POST a JSON body and return the raw response, leaving status handling
+/// to the caller. `Err` means the daemon could not be reached at all.
+pub(crate) async fn post(path: &str, body: &Value, timeout: Duration) -> Result<Response, String> {
+    let bytes = serde_json::to_vec(body).map_err(|e| e.to_string())?;
+    request("POST", path, Some(bytes), token::read().as_deref(), timeout).await
+}
+
+/// Send a request over the daemon's unix socket when it has one, or HTTP
+/// otherwise. A socket that refuses connections (stale file from a crashed
+/// daemon) falls through to HTTP.
+async fn request(
+    method: &str,
+    path: &str,
+    body: Option<Vec<u8>>,
+    bearer: Option<&str>,
+    timeout: Duration,
+) -> Result<Response, String> {
+    let res = send(method, path, body, bearer, timeout).await?;
+    // Only the stored token counts; a rejected candidate while pairing
+    // says nothing about it.
+    if bearer == token::read().as_deref() {
+        if res.status == 401 {
+            AUTH_REJECTED.store(true, Ordering::Relaxed);
+        } else if res.is_success() {
+            AUTH_REJECTED.store(false, Ordering::Relaxed);
+        }
+    }
+    Ok(res)
+}
+
+async fn send(
+    method: &str,
+    path: &str,
+    body: Option<Vec<u8>>,
+    bearer: Option<&str>,
+    timeout: Duration,
+) -> Result<Response, String> {
+    #[cfg(unix)]
+    if let Some(socket) = socket_path() {
+        match unix::request(&socket, method, path, body.as_deref(), bearer, timeout).await {
+            Err(unix::Error::Connect(_)) => {}
+            other => return other.map_err(|e| e.to_string()),
+        }
+    }
+    http_request(method, path, body, bearer, timeout).await
+}
+
+/// Idle pooled connections are dropped before the daemon's 5s keep-alive
+/// closes them from its end.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// The daemon client and the options it was built with. Shared so requests
+/// reuse pooled connections instead of connecting every time.
+static CLIENT: Mutex<Option<(TlsOptions, ProxyOptions, reqwest::Client)>> = Mutex::new(None);
+
+/// HTTP client for the daemon, with the configured proxy and CA or
+/// pinned certificate applied. The same client is returned until those
+/// settings change.
+pub(crate) fn client() -> Result<reqwest::Client, String> {
+    let tls_options = TlsOptions::load()?;
+    let proxy_options = ProxyOptions::load();
+    let mut cached = CLIENT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((tls, proxy, client)) = cached.as_ref() {
+        if *tls == tls_options && *proxy == proxy_options {
+            return Ok(client.clone());
+        }
+    }
+    let builder = reqwest::Client::builder().pool_idle_timeout(POOL_IDLE_TIMEOUT);
+    let builder = proxy::configure(builder, &proxy_options)?;
+    let client = tls::configure(builder, &tls_options)?
+        .build()
+        .map_err(|e| format!("{} {}", tls::ERROR_PREFIX, e))?;
+    *cached = Some((tls_options, proxy_options, client.clone()));
+    Ok(client)
+}
+
+/// Whether an error from this module came from TLS setup or verification.
+pub(crate) fn is_tls_error(err: &str) -> bool {
+    err.starts_with(tls::ERROR_PREFIX)
+}
+
+/// Describe a failed request, spelling out TLS failures.
+pub(crate) fn send_error(err: &reqwest::Error) -> String {
+    tls::describe(err).unwrap_or_else(|| format!("Failed to send: {}", err))
+}
+
+/// Whether a request failed because its connection was reset or closed
+/// under it, as when the daemon drops a pooled connection just as it is
+/// reused.
+fn connection_reset(err: &reqwest::Error) -> bool {
+    use std::io::ErrorKind::{BrokenPipe, ConnectionAborted, ConnectionReset, UnexpectedEof};
+
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(e) = source {
+        if let Some(io) = e.downcast_ref::<std::io::Error>() {
+            if matches!(
+                io.kind(),
+                BrokenPipe | ConnectionAborted | ConnectionReset | UnexpectedEof
+            ) {
+                return true;
+            }
+        }
+        source = e.source();
+    }
+    false
+}
+
+async fn http_request(
+    method: &str,
+    path: &str,
+    body: Option<Vec<u8>>,
+    bearer: Option<&str>,
+    timeout: Duration,
+) -> Result<Response, String> {
+    let client = client()?;
+    let url = format!("{}{}", settings::daemon_url(), path);
+    let mut req = match method {
+        "POST" => client.post(url),
+        "PATCH" => client.patch(url),
+        "DELETE" => client.delete(url),
+        _ => client.get(url),
+    }
+    .timeout(timeout);
+    if let Some(body) = body {
+        req = req.header("Content-Type", "application/json").body(body);
+    }
+    if let Some(bearer) = bearer {
+        req = req.bearer_auth(bearer);
+    }
+    // A reset connection is retried once. Not for POST, which the daemon
+    // may already have acted on.
+    let retry = if method == "POST" {
+        None
+    } else {
+        req.try_clone()
+    };
+    let res = match (req.send().await, retry) {
+        (Ok(res), _) => res,
+        (Err(e), Some(retry)) if connection_reset(&e) => {
+            retry.send().await.map_err(|e| send_error(&e))?
+        }
+        (Err(e), _) => return Err(send_error(&e)),
+    };
+    let status = res.status().as_u16();
+    let body = res
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read body: {}", e))?;
+    Ok(Response {
+        status,
+        body: body.to_vec(),
+    })
+}
+
+/// The daemon's unix socket, if it exposes one and the configured daemon
+/// is on this machine. `SIGNET_SOCKET` overrides the default
+/// `<data dir>/.daemon/signet.sock`.
+#[cfg(unix)]
+pub(crate) fn socket_path() -> Option<std::path::PathBuf> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::path::PathBuf;
+
+    if !settings::daemon_is_local() {
+        return None;
+    }
+    let path = match std::env::var_os("SIGNET_SOCKET") {
+        Some(p) if !p.is_empty() => PathBuf::from(p),
+        _ => settings::daemon_dir()?.join("signet.sock"),
+    };
+    let is_socket = std::fs::metadata(&path)
+        .map(|m| m.file_type().is_socket())
+        .unwrap_or(false);
+    is_socket.then_some(path)
+}
+
+/// Minimal HTTP/1.1 over a unix stream: one request per connection,
+/// `Connection: close`, body read to EOF.
+#[cfg(unix)]
+mod unix {
+    use std::fmt;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixStream;
+
+    use super::Response;
+
+    /// Responses larger than this are refused; nothing the tray reads
+    /// comes close.
+    const MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+    pub(super) enum Error {
+        /// Could not reach the socket at all; HTTP may still work
+        Connect(std::io::Error),
+        Other(String),
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Error::Connect(e) => write!(f, "Failed to connect to socket: {}", e),
+                Error::Other(msg) => f.write_str(msg),
+            }
+        }
+    }
+
+    pub(super) async fn request(
+        socket: &Path,
+        method: &str,
+        path: &str,
+        body: Option<&[u8]>,
+        bearer: Option<&str>,
+        timeout: Duration,
+    ) -> Result<Response, Error> {
+        let mut stream = tokio::time::timeout(timeout, UnixStream::connect(socket))
+            .await
+            .map_err(|_| Error::Other("Socket connect timed out".to_string()))?
+            .map_err(Error::Connect)?;
+
+        let exchange = async {
+            let body = body.unwrap_or_default();
+            let mut head = format!(
+                "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nAccept: application/json\r\nContent-Length: {}\r\n",
+                method,
+                path,
+                body.len()
+            );
+            if !body.is_empty() {
+                head.push_str("Content-Type: application/json\r\n");
+            }
+            if let Some(bearer) = bearer {
+                head.push_str(&format!("Authorization: Bearer {}\r\n", bearer));
+            }
+            head.push_str("\r\n");
+            stream.write_all(head.as_bytes()).await?;
+            stream.write_all(body).await?;
+            stream.flush().await?;
+
+            let mut raw = Vec::new();
+            (&mut stream)
+                .take(MAX_RESPONSE_BYTES as u64 + 1)
+                .read_to_end(&mut raw)
+                .await?;
+            Ok::<_, std::io::Error>(raw)
+        };
+        let raw = tokio::time::timeout(timeout, exchange)
+            .await
+            .map_err(|_| Error::Other("Socket request timed out".to_string()))?
+            .map_err(|e| Error::Other(format!("Socket I/O failed: {}", e)))?;
+        if raw.len() > MAX_RESPONSE_BYTES {
+            return Err(Error::Other("Response too large".to_string()));
+        }
+        parse_response(&raw).map_err(Error::Other)
+    }
+
+    fn parse_response(raw: &[u8]) -> Result<Response, String> {
+        let split = raw
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or("Malformed response: no header terminator")?;
+        let head = String::from_utf8_lossy(&raw[..split]);
+        let rest = &raw[split + 4..];
+
+        let mut lines = head.split("\r\n");
+        let status = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or("Malformed response: bad status line")?;
+
+        let mut chunked = false;
+        let mut length = None;
+        for line in lines {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.eq_ignore_ascii_case("chunked");
+            } else if name.eq_ignore_ascii_case("content-length") {
+                length = value.parse::<usize>().ok();
+            }
+        }
+
+        let body = if chunked {
+            dechunk(rest)?
+        } else {
+            match length {
+                Some(n) => rest.get(..n).ok_or("Truncated response body")?.to_vec(),
+                None => rest.to_vec(),
+            }
+        };
+        Ok(Response { status, body })
+    }
+
+    fn dechunk(mut data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut body = Vec::new();
+        loop {
+            let line_end = data
+                .windows(2)
+                .position(|w| w == b"\r\n")
+                .ok_or("Truncated chunk header")?;
+            let size_field = String::from_utf8_lossy(&data[..line_end]);
+            let size_hex = size_field.split(';').next().unwrap_or_default().trim();
+            let size = usize::from_str_radix(size_hex, 16)
+                .map_err(|_| format!("Bad chunk size: {}", size_hex))?;
+            data = &data[line_end + 2..];
+            if size == 0 {
+                return Ok(body);
+            }
+            let chunk = data.get(..size).ok_or("Truncated chunk")?;
+            body.extend_from_slice(chunk);
+            data = data.get(size + 2..).ok_or("Truncated chunk")?;
+        }
+    }
+}
@@ -1,35 +1,86 @@
-use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager, PhysicalSize, Size, WebviewWindowBuilder};
 
+use crate::api::{self, ErrorKind};
 use crate::daemon;
+use crate::export;
+use crate::i18n::{self, Locale};
+use crate::platform::autostart;
+use crate::profile::{self, Profile, Profiles};
+use crate::settings::{self, TraySettings};
+use crate::spool;
+use crate::stats::ProcessStats;
+use crate::tls;
+use crate::token::{self, TokenStatus};
 use crate::tray;
+use crate::update;
 
 const TRAY_ID: &str = "signet-tray";
-const DEFAULT_PORT: u16 = 3850;
-
-/// Get the configured daemon port, respecting SIGNET_PORT env var.
-pub(crate) fn daemon_port() -> u16 {
-    std::env::var("SIGNET_PORT")
-        .ok()
-        .and_then(|p| p.parse::<u16>().ok())
-        .unwrap_or(DEFAULT_PORT)
-}
 
-/// Get the daemon URL, respecting SIGNET_PORT env var.
+/// Get the daemon URL (see `settings::daemon_url` for where it comes from).
 pub(crate) fn daemon_url() -> String {
-    format!("http://localhost:{}", daemon_port())
+    settings::daemon_url()
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Clone, PartialEq)]
 #[allow(dead_code)]
 pub struct RecentMemory {
+    #[serde(default)]
+    pub id: String,
     pub content: String,
     pub created_at: String,
     pub who: String,
     pub importance: f64,
 }
 
-#[derive(Deserialize)]
+/// A daemon-wide pause of hook-driven memory capture.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct CapturePause {
+    /// RFC 3339 time the pause ends, or `None` until resumed
+    pub until: Option<String>,
+}
+
+/// The learned reranker, when the daemon has it enabled.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct PredictorStatus {
+    /// The sidecar process is running
+    pub alive: bool,
+    pub model_version: Option<u64>,
+    /// When the model was last trained, `None` if it never has been
+    pub last_trained: Option<String>,
+    /// Training pairs the model has learned from
+    pub training_pairs: Option<u64>,
+    /// Samples used by the most recent training run
+    pub last_run_samples: Option<u64>,
+}
+
+/// Jobs in one of the daemon's pipeline queues.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct QueueCounts {
+    pub pending: u64,
+    /// Failed permanently; the worker no longer retries these
+    pub dead: u64,
+}
+
+/// Pipeline queues by job type.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct JobQueues {
+    /// Memory extraction jobs
+    pub memory: QueueCounts,
+    /// Session summary jobs
+    pub summary: QueueCounts,
+}
+
+impl JobQueues {
+    pub fn dead(&self) -> u64 {
+        self.memory.dead + self.summary.dead
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
 #[serde(tag = "kind")]
 pub enum TrayState {
     #[serde(rename = "running")]
@@ -45,6 +96,14 @@ pub enum TrayState {
         queue_depth: Option<u64>,
         recent_memories: Option<Vec<RecentMemory>>,
         ingestion_rate: Option<f64>,
+        /// Pending and dead jobs per queue
+        job_queues: Option<JobQueues>,
+        /// Daemon process usage, for a local daemon
+        resources: Option<ProcessStats>,
+        /// Set while hook-driven capture is paused
+        capture_pause: Option<CapturePause>,
+        /// `None` when the predictor is disabled
+        predictor: Option<PredictorStatus>,
     },
     #[serde(rename = "stopped")]
     Stopped,
@@ -78,6 +137,37 @@ pub(crate) async fn restart_daemon_inner(
     daemon::start().map_err(|e| e.to_string())
 }
 
+/// Point the tray at a free port and start the daemon there, for when
+/// another program holds the configured one.
+pub(crate) async fn start_on_alternate_port_inner() -> Result<u16, String> {
+    if !crate::conflict::can_move_port() {
+        return Err("The daemon's port is set by its service definition".to_string());
+    }
+    let port = crate::conflict::free_port().ok_or("No free port found")?;
+    let mut prefs = TraySettings::load();
+    prefs.daemon_url = Some(format!("http://localhost:{}", port));
+    prefs.save()?;
+    daemon::start().map_err(|e| e.to_string())?;
+    Ok(port)
+}
+
+#[tauri::command]
+pub async fn start_on_alternate_port() -> Result<u16, String> {
+    start_on_alternate_port_inner().await
+}
+
+pub(crate) async fn install_service_inner(app: &AppHandle) -> Result<(), String> {
+    daemon::install_service().map_err(|e| e.to_string())?;
+    refresh_tray(app)
+}
+
+/// Install the daemon as a systemd user unit (Linux) or launchd agent
+/// (macOS) and hand management over to it.
+#[tauri::command]
+pub async fn install_service(app: AppHandle) -> Result<(), String> {
+    install_service_inner(&app).await
+}
+
 #[tauri::command]
 pub async fn start_daemon(app: AppHandle) -> Result<(), String> {
     start_daemon_inner(&app).await
@@ -93,6 +183,25 @@ pub async fn restart_daemon(app: AppHandle) -> Result<(), String> {
     restart_daemon_inner(&app).await
 }
 
+#[tauri::command]
+pub async fn open_logs(app: AppHandle) -> Result<(), String> {
+    crate::notify::open_logs(&app)
+}
+
+/// Write a diagnostics bundle for support requests and reveal it in the
+/// file manager. Returns the bundle's path.
+#[tauri::command]
+pub async fn create_diagnostics_bundle() -> Result<String, String> {
+    let path = crate::bundle::create().await?;
+    let _ = crate::bundle::reveal(&path);
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn open_data_dir(app: AppHandle) -> Result<(), String> {
+    crate::notify::open_data_dir(&app)
+}
+
 #[tauri::command]
 pub async fn get_daemon_pid() -> Result<Option<u32>, String> {
     daemon::read_pid().map_err(|e| e.to_string())
@@ -141,11 +250,46 @@ pub(crate) fn open_dashboard_inner(app: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// sessionStorage key holding the daemon URL the dashboard should use
+pub(crate) const DASHBOARD_URL_KEY: &str = "signet-daemon-url";
+
+/// Point the dashboard at the daemon the tray now uses, reloading it if the
+/// address changed. The init script reads the stored URL on every load.
+pub(crate) fn retarget_dashboard(app: &AppHandle) {
+    let Some(win) = app.get_webview_window("main") else {
+        return;
+    };
+    let key = serde_json::Value::from(DASHBOARD_URL_KEY);
+    let url = serde_json::Value::from(daemon_url());
+    let _ = win.eval(&format!(
+        "if (window.__SIGNET_DAEMON_URL__ !== {url}) {{ \
+         sessionStorage.setItem({key}, {url}); location.reload(); }}"
+    ));
+}
+
 #[tauri::command]
 pub async fn open_dashboard(app: AppHandle) -> Result<(), String> {
     open_dashboard_inner(&app)
 }
 
+/// Abbreviate a count for the menu bar title, e.g. 12,400 -> "12.4k" and
+/// 1,250,000 -> "1.3M". Counts under 1,000 are shown as is.
+fn format_compact(n: u64) -> String {
+    let (value, suffix) = match n {
+        0..=999 => return n.to_string(),
+        // Past 999,499 "k" would round up to "1000k"
+        1_000..=999_499 => (n as f64 / 1_000.0, "k"),
+        _ => (n as f64 / 1_000_000.0, "M"),
+    };
+    // One decimal below 100, none above; drop a trailing ".0"
+    let text = if value < 100.0 {
+        format!("{:.1}", value)
+    } else {
+        format!("{:.0}", value)
+    };
+    format!("{}{}", text.trim_end_matches(".0"), suffix)
+}
+
 /// Format a number with comma separators
 fn format_count(n: u64) -> String {
     let s = n.to_string();
@@ -159,16 +303,32 @@ fn format_count(n: u64) -> String {
     result.chars().rev().collect()
 }
 
-#[tauri::command]
-pub async fn update_tray(
-    app: AppHandle,
-    state: TrayState,
+/// Apply a tray state to the icon, menu, title and tooltip.
+/// The state last applied to the tray, so menus can be rebuilt in place
+/// after a toggle.
+static LAST_STATE: Mutex<Option<TrayState>> = Mutex::new(None);
+
+/// Rebuild the tray from the last applied state, e.g. after a menu toggle
+/// changed what the menu shows.
+pub(crate) fn refresh_tray(app: &AppHandle) -> Result<(), String> {
+    let state = LAST_STATE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or(TrayState::Stopped);
+    apply_tray_state(app, &state)
+}
+
+pub(crate) fn apply_tray_state(
+    app: &AppHandle,
+    state: &TrayState,
 ) -> Result<(), String> {
     let tray = app
         .tray_by_id(TRAY_ID)
         .ok_or("tray not found")?;
+    *LAST_STATE.lock().unwrap_or_else(|e| e.into_inner()) = Some(state.clone());
 
-    match &state {
+    match state {
         TrayState::Running {
             version,
             health_score,
@@ -181,12 +341,16 @@ pub async fn update_tray(
             queue_depth,
             recent_memories,
             ingestion_rate,
+            job_queues,
+            resources,
+            capture_pause,
+            predictor,
         } => {
             let empty_memories = Vec::new();
             let memories = recent_memories.as_deref().unwrap_or(&empty_memories);
 
             let menu = tray::build_running_menu(
-                &app,
+                app,
                 version,
                 *health_score,
                 health_status.as_deref(),
@@ -198,44 +362,75 @@ pub async fn update_tray(
                 *queue_depth,
                 memories,
                 *ingestion_rate,
+                job_queues.as_ref(),
+                resources.as_ref(),
+                capture_pause.as_ref(),
+                predictor.as_ref(),
             )
             .map_err(|e| e.to_string())?;
 
             tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
 
-            // Set menu bar title with memory count
-            if let Some(count) = memory_count {
-                let title = format_count(*count);
-                let _ = tray.set_title(Some(&title));
-            } else {
-                let _ = tray.set_title(Some("..."));
-            }
+            // Set menu bar title with the chosen memory count, badged when
+            // dead jobs pile up or capture is paused
+            let dead_jobs = job_queues.map(|q| q.dead());
+            let badge = match (tray::dead_jobs_over_threshold(dead_jobs), capture_pause) {
+                (true, _) => "⚠",
+                (false, Some(_)) => "⏸",
+                (false, None) => "",
+            };
+            let count = match settings::title_display() {
+                settings::TitleDisplay::Total => Some(*memory_count),
+                settings::TitleDisplay::Today => Some(*memories_today),
+                settings::TitleDisplay::Hidden => None,
+            };
+            let count = count.map(|count| match count {
+                Some(n) if settings::compact_title() => format_compact(n),
+                Some(n) => format_count(n),
+                None => "...".to_string(),
+            });
+            let title = match (badge, count) {
+                ("", Some(count)) => Some(count),
+                (badge, Some(count)) => Some(format!("{} {}", badge, count)),
+                ("", None) => None,
+                (badge, None) => Some(badge.to_string()),
+            };
+            let _ = tray.set_title(title.as_deref());
 
-            tray.set_tooltip(Some(&format!(
-                "Signet v{version} — Running"
-            )))
-            .map_err(|e| e.to_string())?;
-            let _ = tray.set_icon(Some(tray::icon_for_state("running")));
+            let running = i18n::tf("status_running", &[version]);
+            let tooltip = match capture_pause {
+                Some(pause) => format!("{} · {}", running, tray::capture_pause_label(pause)),
+                None => running,
+            };
+            tray.set_tooltip(Some(&tooltip)).map_err(|e| e.to_string())?;
+            // Paused wins, since capture being off matters more than a
+            // busy queue
+            let busy = queue_depth.is_some_and(|d| d >= tray::PROCESSING_QUEUE_DEPTH)
+                || backfill_progress().is_some();
+            let icon = match (capture_pause, busy) {
+                (Some(_), _) => "paused",
+                (None, true) => "processing",
+                (None, false) => "running",
+            };
+            tray::set_icon_state(app, icon);
         }
         TrayState::Stopped => {
-            let menu = tray::build_stopped_menu(&app)
+            let menu = tray::build_stopped_menu(app)
                 .map_err(|e| e.to_string())?;
             tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
             let _ = tray.set_title(None::<&str>);
-            tray.set_tooltip(Some("Signet — Stopped"))
+            tray.set_tooltip(Some(i18n::t("status_stopped")))
                 .map_err(|e| e.to_string())?;
-            let _ = tray.set_icon(Some(tray::icon_for_state("stopped")));
+            tray::set_icon_state(app, "stopped");
         }
         TrayState::Error { message } => {
-            let menu = tray::build_error_menu(&app, message)
+            let menu = tray::build_error_menu(app, message)
                 .map_err(|e| e.to_string())?;
             tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
             let _ = tray.set_title(Some("⚠"));
-            tray.set_tooltip(Some(&format!(
-                "Signet — Error: {message}"
-            )))
-            .map_err(|e| e.to_string())?;
-            let _ = tray.set_icon(Some(tray::icon_for_state("error")));
+            tray.set_tooltip(Some(&i18n::tf("status_error", &[message])))
+                .map_err(|e| e.to_string())?;
+            tray::set_icon_state(app, "error");
         }
     }
 
@@ -243,30 +438,86 @@ pub async fn update_tray(
 }
 
 #[tauri::command]
-pub async fn quick_capture(content: String) -> Result<(), String> {
-    let client = reqwest::Client::new();
-    let base = daemon_url();
+pub async fn update_tray(
+    app: AppHandle,
+    state: TrayState,
+) -> Result<(), String> {
+    apply_tray_state(&app, &state)
+}
+
+/// Whether a failed capture is worth keeping for later: the daemon was
+/// unreachable, overloaded, or rejected the token. Anything else is a
+/// problem with the capture itself.
+fn capture_retryable(res: &Result<api::Response, String>) -> bool {
+    match res {
+        Err(_) => true,
+        Ok(r) => r.status == 401 || r.status == 429 || r.status >= 500,
+    }
+}
+
+/// Save a memory, or queue it in the spool if the daemon can't take it
+/// right now. Returns "saved" or "queued".
+#[tauri::command]
+pub async fn quick_capture(content: String) -> Result<String, String> {
     let body = serde_json::json!({
         "content": content,
         "who": "tray-capture",
         "importance": 0.7
     });
 
-    let res = client
-        .post(format!("{}/api/memory/remember", base))
-        .json(&body)
-        .timeout(std::time::Duration::from_secs(5))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send: {}", e))?;
+    // Keep order: while older captures are waiting, queue behind them
+    if spool::len() > 0 {
+        spool::enqueue(&body)?;
+        return Ok("queued".to_string());
+    }
 
-    if !res.status().is_success() {
-        let status = res.status();
-        let text = res.text().await.unwrap_or_default();
-        return Err(format!("HTTP {}: {}", status, text));
+    let res = api::post(
+        "/api/memory/remember",
+        &body,
+        std::time::Duration::from_secs(5),
+    )
+    .await;
+    if capture_retryable(&res) {
+        spool::enqueue(&body)?;
+        return Ok("queued".to_string());
     }
 
-    Ok(())
+    res?.check()?;
+    Ok("saved".to_string())
+}
+
+/// Held while captures are off disk being delivered
+static SPOOL_FLUSH: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Wait for any flush to finish and keep new ones from starting while the
+/// guard is held.
+pub(crate) async fn hold_capture_spool() -> tokio::sync::MutexGuard<'static, ()> {
+    SPOOL_FLUSH.lock().await
+}
+
+/// Deliver queued captures in order, stopping at the first one the daemon
+/// can't take yet. Captures the daemon rejects outright are dropped so
+/// they don't block the queue. Returns how many were delivered.
+pub(crate) async fn flush_capture_spool() -> usize {
+    let _flushing = SPOOL_FLUSH.lock().await;
+    let queued = spool::take();
+    let mut delivered = 0;
+    for (i, body) in queued.iter().enumerate() {
+        let res = api::post(
+            "/api/memory/remember",
+            body,
+            std::time::Duration::from_secs(5),
+        )
+        .await;
+        if capture_retryable(&res) {
+            let _ = spool::restore(&queued[i..]);
+            break;
+        }
+        if res.is_ok_and(|r| r.is_success()) {
+            delivered += 1;
+        }
+    }
+    delivered
 }
 
 #[tauri::command]
@@ -274,29 +525,692 @@ pub async fn search_memories(
     query: String,
     limit: Option<u32>,
 ) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let base = daemon_url();
     let body = serde_json::json!({
         "query": query,
         "limit": limit.unwrap_or(10)
     });
 
-    let res = client
-        .post(format!("{}/api/memory/recall", base))
-        .json(&body)
-        .timeout(std::time::Duration::from_secs(10))
+    api::post_json(
+        "/api/memory/recall",
+        &body,
+        std::time::Duration::from_secs(10),
+    )
+    .await
+}
+
+/// Path for one memory, with the id percent-encoded.
+fn memory_path(id: &str) -> String {
+    let id: String = url::form_urlencoded::byte_serialize(id.as_bytes()).collect();
+    format!("/api/memory/{}", id)
+}
+
+/// Turn a memory API response into something worth showing in a window.
+/// The daemon's own `error` text is used where it has one.
+fn memory_result(res: api::Response) -> Result<serde_json::Value, String> {
+    let body = res.json();
+    let err = match res.check() {
+        Ok(_) => return Ok(body),
+        Err(err) => err,
+    };
+    Err(match err.kind() {
+        ErrorKind::Unauthorized => {
+            "The daemon rejected the tray's token. Re-pair from the tray menu.".to_string()
+        }
+        ErrorKind::NotFound => "Memory not found. It may have been deleted.".to_string(),
+        ErrorKind::Conflict if body["status"] == "version_conflict" => {
+            "This memory changed since it was loaded. Reload it and try again.".to_string()
+        }
+        _ => err.to_string(),
+    })
+}
+
+async fn send_memory(
+    method: &str,
+    id: &str,
+    body: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let res = api::send_json(
+        method,
+        &memory_path(id),
+        &body,
+        std::time::Duration::from_secs(30),
+    )
+    .await?;
+    memory_result(res)
+}
+
+/// Full memory record: content, tags, importance, project, pinned,
+/// version and timestamps.
+#[tauri::command]
+pub async fn get_memory(id: String) -> Result<serde_json::Value, String> {
+    let res = api::get(&memory_path(&id), std::time::Duration::from_secs(5)).await?;
+    memory_result(res)
+}
+
+/// Pin or unpin a memory.
+#[tauri::command]
+pub async fn pin_memory(id: String, pinned: bool) -> Result<serde_json::Value, String> {
+    let body = serde_json::json!({
+        "pinned": pinned,
+        "reason": if pinned { "pinned from tray" } else { "unpinned from tray" },
+    });
+    send_memory("PATCH", &id, body).await
+}
+
+/// Change a memory's content, tags or importance; fields left `None` are
+/// untouched. `if_version` guards against overwriting a change made since
+/// the memory was loaded.
+#[tauri::command]
+pub async fn update_memory(
+    id: String,
+    content: Option<String>,
+    tags: Option<Vec<String>>,
+    importance: Option<f64>,
+    if_version: Option<u64>,
+) -> Result<serde_json::Value, String> {
+    let mut body = serde_json::json!({ "reason": "edited from tray" });
+    if let Some(content) = content {
+        body["content"] = content.into();
+    }
+    if let Some(tags) = tags {
+        body["tags"] = tags.into();
+    }
+    if let Some(importance) = importance {
+        body["importance"] = importance.into();
+    }
+    if let Some(version) = if_version {
+        body["if_version"] = version.into();
+    }
+    send_memory("PATCH", &id, body).await
+}
+
+/// Soft-delete a memory.
+#[tauri::command]
+pub async fn delete_memory(id: String) -> Result<serde_json::Value, String> {
+    send_memory("DELETE", &id, serde_json::json!({ "reason": "deleted from tray" })).await
+}
+
+/// The daemon's capture pause, or `None` while capturing. A daemon too old
+/// to have the endpoint reports `None`.
+pub(crate) async fn fetch_capture_pause() -> Result<Option<CapturePause>, String> {
+    let data = api::get_json("/api/capture/pause", std::time::Duration::from_secs(3)).await?;
+    Ok(capture_pause_from(&data))
+}
+
+fn capture_pause_from(data: &serde_json::Value) -> Option<CapturePause> {
+    data["paused"].as_bool().unwrap_or(false).then(|| CapturePause {
+        until: data["until"].as_str().map(str::to_string),
+    })
+}
+
+/// Show a new pause state right away instead of waiting for the next poll.
+fn show_capture_pause(app: &AppHandle, pause: Option<CapturePause>) {
+    let mut last = LAST_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(TrayState::Running { capture_pause, .. }) = last.as_mut() {
+        *capture_pause = pause;
+    }
+    drop(last);
+    let _ = refresh_tray(app);
+}
+
+pub(crate) async fn pause_capture_inner(
+    app: &AppHandle,
+    minutes: Option<u32>,
+) -> Result<Option<CapturePause>, String> {
+    let body = match minutes {
+        Some(minutes) => serde_json::json!({ "minutes": minutes }),
+        None => serde_json::json!({}),
+    };
+    let res = api::post("/api/capture/pause", &body, std::time::Duration::from_secs(5))
+        .await?
+        .check()
+        .map_err(|err| match err.kind() {
+            ErrorKind::NotFound => {
+                "This daemon does not support pausing capture. Update it to use this.".to_string()
+            }
+            _ => err.to_string(),
+        })?;
+    let pause = capture_pause_from(&res.json());
+    show_capture_pause(app, pause.clone());
+    Ok(pause)
+}
+
+pub(crate) async fn resume_capture_inner(app: &AppHandle) -> Result<(), String> {
+    let res = api::send_json(
+        "DELETE",
+        "/api/capture/pause",
+        &serde_json::json!({}),
+        std::time::Duration::from_secs(5),
+    )
+    .await?;
+    res.check()?;
+    show_capture_pause(app, None);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_capture_pause() -> Result<Option<CapturePause>, String> {
+    fetch_capture_pause().await
+}
+
+/// Pause hook-driven capture for `minutes`, or until resumed with `None`.
+#[tauri::command]
+pub async fn pause_capture(
+    app: AppHandle,
+    minutes: Option<u32>,
+) -> Result<Option<CapturePause>, String> {
+    pause_capture_inner(&app, minutes).await
+}
+
+#[tauri::command]
+pub async fn resume_capture(app: AppHandle) -> Result<(), String> {
+    resume_capture_inner(&app).await
+}
+
+/// Set while a retrain started from the tray is running
+static PREDICTOR_TRAINING: AtomicBool = AtomicBool::new(false);
+
+/// A run fails its canary when scores on the held-out canary set come out
+/// flat or the top 5 reshuffles; this mirrors the predictor's threshold.
+const CANARY_MIN_TOPK_STABILITY: f64 = 0.6;
+
+/// Training reads the whole memory database, so allow it plenty of time
+const PREDICTOR_TRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30 * 60);
+
+pub(crate) fn predictor_training() -> bool {
+    PREDICTOR_TRAINING.load(Ordering::Relaxed)
+}
+
+/// The result of a finished training run.
+#[derive(Serialize)]
+pub struct TrainOutcome {
+    pub model_version: u64,
+    pub samples_used: u64,
+    pub loss: Option<f64>,
+    /// The loss is finite and the canary checks passed
+    pub canary_passed: bool,
+}
+
+fn train_outcome(data: &serde_json::Value) -> TrainOutcome {
+    let loss = data["loss"].as_f64().filter(|l| l.is_finite());
+    let variance = data["canary_score_variance"].as_f64().unwrap_or(0.0);
+    let stability = data["canary_topk_stability"].as_f64().unwrap_or(0.0);
+    TrainOutcome {
+        model_version: data["step"].as_u64().unwrap_or(0),
+        samples_used: data["samples_used"].as_u64().unwrap_or(0),
+        loss,
+        canary_passed: loss.is_some() && variance > 0.0 && stability >= CANARY_MIN_TOPK_STABILITY,
+    }
+}
+
+/// Train the predictor now and notify with the outcome. The menu shows
+/// "Training..." until the daemon answers.
+pub(crate) async fn retrain_predictor_inner(app: &AppHandle) -> Result<TrainOutcome, String> {
+    if PREDICTOR_TRAINING.swap(true, Ordering::Relaxed) {
+        return Err("Training is already running".to_string());
+    }
+    let _ = refresh_tray(app);
+    let result = async {
+        let res = api::post("/api/predictor/train", &serde_json::json!({}), PREDICTOR_TRAIN_TIMEOUT)
+            .await?
+            .check()?;
+        Ok::<_, String>(train_outcome(&res.json()))
+    }
+    .await;
+    PREDICTOR_TRAINING.store(false, Ordering::Relaxed);
+    let _ = refresh_tray(app);
+
+    match &result {
+        Ok(outcome) if outcome.canary_passed => crate::notify::predictor_trained(app, outcome),
+        Ok(_) => crate::notify::predictor_canary_failed(app),
+        Err(e) => crate::notify::predictor_train_failed(app, e),
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn retrain_predictor(app: AppHandle) -> Result<TrainOutcome, String> {
+    retrain_predictor_inner(&app).await
+}
+
+/// How far an embedding backfill has got: `done` of the `of` memories
+/// that were missing embeddings when it started.
+#[derive(Serialize, Clone, Copy)]
+pub struct BackfillProgress {
+    pub done: u64,
+    pub of: u64,
+}
+
+/// Set while a backfill started from the tray is running
+static BACKFILL: Mutex<Option<BackfillProgress>> = Mutex::new(None);
+
+/// How often the menu's backfill progress is refreshed
+const BACKFILL_PROGRESS_EVERY: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// A full sweep embeds every missing memory in one request
+const BACKFILL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2 * 60 * 60);
+
+pub(crate) fn backfill_progress() -> Option<BackfillProgress> {
+    *BACKFILL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_backfill(progress: Option<BackfillProgress>) {
+    *BACKFILL.lock().unwrap_or_else(|e| e.into_inner()) = progress;
+}
+
+/// Memories still missing embeddings.
+async fn embedding_gaps() -> Result<u64, String> {
+    let data = api::get_json("/api/repair/embedding-gaps", std::time::Duration::from_secs(5)).await?;
+    Ok(data["unembedded"].as_u64().unwrap_or(0))
+}
+
+/// Embed every memory that is missing an embedding, through the daemon's
+/// re-embed repair with `fullSweep`. The menu shows "X of Y" while it runs.
+/// Returns how many memories were embedded.
+pub(crate) async fn backfill_embeddings_inner(app: &AppHandle) -> Result<u64, String> {
+    if backfill_progress().is_some() {
+        return Err("A backfill is already running".to_string());
+    }
+    let missing = embedding_gaps().await?;
+    if missing == 0 {
+        crate::notify::embeddings_backfilled(app, 0, "Every memory already has an embedding.");
+        return Ok(0);
+    }
+    set_backfill(Some(BackfillProgress { done: 0, of: missing }));
+    let _ = refresh_tray(app);
+
+    let body = serde_json::json!({ "fullSweep": true });
+    let request = api::post("/api/repair/re-embed", &body, BACKFILL_TIMEOUT);
+    tokio::pin!(request);
+    let mut ticker = tokio::time::interval(BACKFILL_PROGRESS_EVERY);
+    let res = loop {
+        tokio::select! {
+            res = &mut request => break res,
+            _ = ticker.tick() => {
+                if let Ok(left) = embedding_gaps().await {
+                    set_backfill(Some(BackfillProgress {
+                        done: missing.saturating_sub(left),
+                        of: missing,
+                    }));
+                    let _ = refresh_tray(app);
+                }
+            }
+        }
+    };
+    set_backfill(None);
+    let _ = refresh_tray(app);
+
+    let result = res.and_then(|res| match res.check() {
+        Ok(res) => {
+            let data = res.json();
+            Ok((
+                data["affected"].as_u64().unwrap_or(0),
+                data["message"].as_str().unwrap_or_default().to_string(),
+            ))
+        }
+        Err(err) if err.kind() == ErrorKind::Unauthorized => {
+            Err("Backfilling needs a token with the admin role".to_string())
+        }
+        Err(err) => Err(err.to_string()),
+    });
+    match result {
+        Ok((written, message)) => {
+            crate::notify::embeddings_backfilled(app, written, &message);
+            Ok(written)
+        }
+        Err(e) => {
+            crate::notify::embedding_backfill_failed(app, &e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn backfill_embeddings(app: AppHandle) -> Result<u64, String> {
+    backfill_embeddings_inner(&app).await
+}
+
+#[tauri::command]
+pub async fn get_backfill_progress() -> Option<BackfillProgress> {
+    backfill_progress()
+}
+
+/// Pending and dead jobs per queue, from the pipeline snapshot.
+pub(crate) async fn fetch_job_queues() -> Result<JobQueues, String> {
+    let data = api::get_json("/api/pipeline/status", std::time::Duration::from_secs(5)).await?;
+    let counts = |queue: &serde_json::Value| QueueCounts {
+        pending: queue["pending"].as_u64().unwrap_or(0),
+        dead: queue["dead"].as_u64().unwrap_or(0),
+    };
+    Ok(JobQueues {
+        memory: counts(&data["queues"]["memory"]),
+        summary: counts(&data["queues"]["summary"]),
+    })
+}
+
+/// Re-read the queues so the menu reflects a retry or purge right away.
+async fn show_job_queues(app: &AppHandle) {
+    let Ok(queues) = fetch_job_queues().await else {
+        return;
+    };
+    let mut last = LAST_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(TrayState::Running { job_queues, .. }) = last.as_mut() {
+        *job_queues = Some(queues);
+    }
+    drop(last);
+    let _ = refresh_tray(app);
+}
+
+/// Run one of the daemon's dead-job repairs and return how many jobs it
+/// touched.
+async fn dead_job_repair(path: &str, body: &serde_json::Value) -> Result<u64, String> {
+    let res = api::post(path, body, std::time::Duration::from_secs(30)).await?;
+    let err = match res.check() {
+        Ok(res) => return Ok(res.json()["affected"].as_u64().unwrap_or(0)),
+        Err(err) => err,
+    };
+    Err(match err.kind() {
+        ErrorKind::Unauthorized => "Managing the queue needs a token with the admin role".to_string(),
+        ErrorKind::NotFound => {
+            "This daemon does not support this action. Update it to use this.".to_string()
+        }
+        // Repairs share a cooldown and hourly budget
+        ErrorKind::RateLimited if err.detail.is_some() => format!("Try again later: {}", err),
+        _ => err.to_string(),
+    })
+}
+
+/// Put every dead job back to pending so the worker retries it. Returns
+/// how many were requeued.
+pub(crate) async fn retry_dead_jobs_inner(app: &AppHandle) -> Result<u64, String> {
+    let dead = fetch_job_queues().await?.dead();
+    if dead == 0 {
+        return Ok(0);
+    }
+    let body = serde_json::json!({ "maxBatch": dead });
+    let result = dead_job_repair("/api/repair/requeue-dead", &body).await;
+    show_job_queues(app).await;
+    match result {
+        Ok(count) => {
+            crate::notify::dead_jobs_retried(app, count);
+            Ok(count)
+        }
+        Err(e) => {
+            crate::notify::queue_action_failed(app, &e);
+            Err(e)
+        }
+    }
+}
+
+/// Delete every dead job. Callers confirm with the user first; see
+/// `confirm_purge_dead_jobs`. Returns how many were deleted.
+pub(crate) async fn purge_dead_jobs_inner(app: &AppHandle) -> Result<u64, String> {
+    let result = dead_job_repair("/api/repair/purge-dead", &serde_json::json!({})).await;
+    show_job_queues(app).await;
+    match result {
+        Ok(count) => {
+            crate::notify::dead_jobs_purged(app, count);
+            Ok(count)
+        }
+        Err(e) => {
+            crate::notify::queue_action_failed(app, &e);
+            Err(e)
+        }
+    }
+}
+
+/// Ask before purging, since deleted jobs can't be retried later.
+pub(crate) async fn confirm_purge_dead_jobs(app: &AppHandle, dead: u64) -> bool {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let noun = if dead == 1 { "job" } else { "jobs" };
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(format!(
+            "Delete {} dead {}? They can't be retried afterwards. The memories \
+             they belong to are kept.",
+            dead, noun
+        ))
+        .title("Purge Dead Jobs")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Purge".to_string(),
+            "Cancel".to_string(),
+        ))
+        .show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+    rx.await.unwrap_or(false)
+}
+
+#[tauri::command]
+pub async fn get_job_queues() -> Result<JobQueues, String> {
+    fetch_job_queues().await
+}
+
+#[tauri::command]
+pub async fn retry_dead_jobs(app: AppHandle) -> Result<u64, String> {
+    retry_dead_jobs_inner(&app).await
+}
+
+/// Delete every dead job after asking the user to confirm. Returns `None`
+/// if they cancelled.
+#[tauri::command]
+pub async fn purge_dead_jobs(app: AppHandle) -> Result<Option<u64>, String> {
+    let dead = fetch_job_queues().await?.dead();
+    if dead == 0 {
+        return Ok(Some(0));
+    }
+    if !confirm_purge_dead_jobs(&app, dead).await {
+        return Ok(None);
+    }
+    purge_dead_jobs_inner(&app).await.map(Some)
+}
+
+#[tauri::command]
+pub async fn copy_to_clipboard(app: AppHandle, text: String) -> Result<(), String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    app.clipboard().write_text(text).map_err(|e| e.to_string())
+}
+
+pub(crate) fn open_memory_window_inner(app: &AppHandle, id: &str) -> Result<(), String> {
+    let query: String = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("id", id)
+        .finish();
+    let page = format!("memory.html?{}", query);
+    // One detail window at a time; a new click shows its memory there
+    if let Some(win) = app.get_webview_window("memory") {
+        let target = serde_json::Value::from(page);
+        win.eval(&format!("location.replace({})", target))
+            .map_err(|e| e.to_string())?;
+        return win.set_focus().map_err(|e| e.to_string());
+    }
+    WebviewWindowBuilder::new(app, "memory", tauri::WebviewUrl::App(page.into()))
+        .title("Memory")
+        .inner_size(460.0, 420.0)
+        .min_inner_size(360.0, 300.0)
+        .center()
+        .visible(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Open the detail window for a memory.
+#[tauri::command]
+pub async fn open_memory_window(app: AppHandle, id: String) -> Result<(), String> {
+    open_memory_window_inner(&app, &id)
+}
+
+/// Ask where to save an export, suggesting a dated file name. `None` if
+/// the dialog was dismissed.
+#[tauri::command]
+pub async fn choose_export_path(
+    app: AppHandle,
+    format: export::Format,
+) -> Result<Option<String>, String> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let extension = format.extension();
+    let name = format!(
+        "signet-memories-{}.{}",
+        chrono::Local::now().format("%Y%m%d"),
+        extension
+    );
+    let label = match format {
+        export::Format::Json => "JSON",
+        export::Format::Markdown => "Markdown",
+    };
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let mut dialog = app
+        .dialog()
+        .file()
+        .set_file_name(&name)
+        .add_filter(label, &[extension]);
+    if let Some(dir) = dirs::document_dir().or_else(dirs::home_dir) {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog.save_file(move |path| {
+        let _ = tx.send(path);
+    });
+    let path = rx.await.map_err(|e| e.to_string())?;
+    path.map(|p| p.into_path().map(|p| p.display().to_string()))
+        .transpose()
+        .map_err(|e| e.to_string())
+}
+
+/// Export memories to `path`, reporting progress through
+/// `export_progress` while it runs. Returns how many were written.
+#[tauri::command]
+pub async fn export_memories(
+    app: AppHandle,
+    path: String,
+    format: export::Format,
+    filter: Option<export::Filter>,
+) -> Result<u64, String> {
+    let path = std::path::PathBuf::from(path);
+    let count = export::run(&path, format, &filter.unwrap_or_default()).await?;
+    crate::notify::export_finished(&app, count, &path);
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn export_progress() -> export::Progress {
+    export::progress()
+}
+
+#[tauri::command]
+pub async fn cancel_export() {
+    export::cancel();
+}
+
+#[tauri::command]
+pub async fn quit_export_window(app: AppHandle) -> Result<(), String> {
+    if let Some(win) = app.get_webview_window("export") {
+        win.close().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn quit_memory_window(app: AppHandle) -> Result<(), String> {
+    if let Some(win) = app.get_webview_window("memory") {
+        win.close().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Daemon event stream messages the tray reacts to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DaemonEvent {
+    /// The stream is open; polling can slow down
+    Connected,
+    /// The stream ended or failed; polling takes over again
+    Disconnected,
+    MemoryCreated,
+    /// A pipeline job completed or went dead
+    JobFinished,
+    /// Capture was paused or resumed
+    CaptureStateChanged,
+}
+
+/// The daemon sends a keepalive comment every 15s, so a longer silence
+/// means the connection is dead even if the socket is still open.
+const EVENT_STREAM_IDLE: std::time::Duration = std::time::Duration::from_secs(45);
+
+/// Map one SSE block (`event:`/`data:` lines) to a tray event. The type is
+/// taken from the `event:` field, or else the `type` of the JSON data.
+fn parse_event_block(block: &str) -> Option<DaemonEvent> {
+    let mut event_type = None;
+    let mut data = String::new();
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event_type = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push_str(value.trim_start());
+        }
+    }
+    let event_type = event_type.or_else(|| {
+        serde_json::from_str::<serde_json::Value>(&data)
+            .ok()?
+            .get("type")?
+            .as_str()
+            .map(str::to_string)
+    })?;
+    match event_type.as_str() {
+        "connected" => Some(DaemonEvent::Connected),
+        "memory-created" => Some(DaemonEvent::MemoryCreated),
+        "job-finished" => Some(DaemonEvent::JobFinished),
+        "capture-state" => Some(DaemonEvent::CaptureStateChanged),
+        _ => None,
+    }
+}
+
+/// Follow the daemon's `/api/events` SSE stream, forwarding the events the
+/// tray cares about. Returns when the stream ends, fails, goes quiet, or
+/// nobody is listening any more.
+pub(crate) async fn follow_daemon_events(
+    client: &reqwest::Client,
+    base: &str,
+    events: &tokio::sync::mpsc::UnboundedSender<DaemonEvent>,
+) -> Result<(), String> {
+    let mut req = client
+        .get(format!("{}/api/events", base))
+        .header("Accept", "text/event-stream");
+    if let Some(bearer) = token::read() {
+        req = req.bearer_auth(bearer);
+    }
+    let mut res = req
         .send()
         .await
-        .map_err(|e| format!("Failed to send: {}", e))?;
+        .map_err(|e| api::send_error(&e))?;
 
     if !res.status().is_success() {
-        let status = res.status();
-        let text = res.text().await.unwrap_or_default();
-        return Err(format!("HTTP {}: {}", status, text));
+        return Err(format!("HTTP {}", res.status()));
     }
 
-    let text = res.text().await.map_err(|e| format!("Failed to read body: {}", e))?;
-    Ok(text)
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        let chunk = tokio::time::timeout(EVENT_STREAM_IDLE, res.chunk())
+            .await
+            .map_err(|_| "event stream went quiet".to_string())?
+            .map_err(|e| format!("Failed to read stream: {}", e))?;
+        let Some(chunk) = chunk else {
+            return Ok(());
+        };
+        buf.extend(chunk.iter().filter(|b| **b != b'\r'));
+
+        while let Some(end) = buf.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = buf.drain(..end + 2).collect();
+            let block = String::from_utf8_lossy(&block);
+            if let Some(event) = parse_event_block(&block) {
+                if events.send(event).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
 }
 
 #[tauri::command]
@@ -315,11 +1229,432 @@ pub async fn quit_search_window(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// Check for app updates. Currently stubbed — requires
-/// tauri-plugin-updater and a signing keypair (Phase 4).
+#[derive(Serialize)]
+pub struct AutostartStatus {
+    /// The tray launches at login
+    pub tray: bool,
+    /// The daemon's systemd user unit is enabled (Linux only)
+    pub daemon_service: Option<bool>,
+}
+
+#[tauri::command]
+pub async fn get_autostart_status() -> AutostartStatus {
+    #[cfg(target_os = "linux")]
+    let daemon_service = Some(autostart::is_daemon_service_enabled());
+    #[cfg(not(target_os = "linux"))]
+    let daemon_service = None;
+
+    AutostartStatus {
+        tray: autostart::is_autostart_enabled(),
+        daemon_service,
+    }
+}
+
+#[tauri::command]
+pub async fn set_autostart(enabled: bool) -> Result<(), String> {
+    if enabled {
+        autostart::ensure_autostart();
+    } else {
+        autostart::remove_autostart();
+    }
+    if autostart::is_autostart_enabled() != enabled {
+        return Err("failed to update login item".to_string());
+    }
+    Ok(())
+}
+
+/// Enable or disable `signet.service` as a systemd user unit, so the
+/// daemon starts with the session independently of the tray.
+#[tauri::command]
+pub async fn set_daemon_autostart(enabled: bool) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        if enabled {
+            autostart::enable_daemon_service()
+        } else {
+            autostart::disable_daemon_service()
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = enabled;
+        Err("daemon autostart is only managed by the tray on Linux".to_string())
+    }
+}
+
+/// The daemon URL requests currently go to.
+#[tauri::command]
+pub async fn get_daemon_url() -> String {
+    daemon_url()
+}
+
+/// Save a daemon URL to the tray settings, or clear it with `None` to go
+/// back to the environment and service definition. The poller picks the
+/// change up on its next tick; the dashboard window on its next launch.
+#[tauri::command]
+pub async fn set_daemon_url(app: AppHandle, url: Option<String>) -> Result<String, String> {
+    let mut prefs = TraySettings::load();
+    prefs.daemon_url = match url.as_deref().map(str::trim) {
+        Some("") | None => None,
+        Some(raw) => Some(settings::normalize_daemon_url(raw)?),
+    };
+    prefs.save()?;
+    retarget_dashboard(&app);
+    Ok(daemon_url())
+}
+
+#[tauri::command]
+pub async fn list_profiles() -> Profiles {
+    Profiles::load()
+}
+
+/// Add a profile or replace the one with the same name.
+#[tauri::command]
+pub async fn save_profile(app: AppHandle, profile: Profile) -> Result<(), String> {
+    profile::upsert(profile)?;
+    refresh_tray(&app)
+}
+
+#[tauri::command]
+pub async fn remove_profile(app: AppHandle, name: String) -> Result<(), String> {
+    profile::remove(&name)?;
+    retarget_dashboard(&app);
+    refresh_tray(&app)
+}
+
+pub(crate) fn switch_profile_inner(app: &AppHandle, name: Option<&str>) -> Result<(), String> {
+    profile::switch(name)?;
+    retarget_dashboard(app);
+    // Show the new daemon as stopped until the poller has seen it
+    apply_tray_state(app, &TrayState::Stopped)
+}
+
+/// Switch to a named profile, or back to the default daemon with `null`.
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, name: Option<String>) -> Result<(), String> {
+    switch_profile_inner(&app, name.as_deref())
+}
+
+/// Save TLS options for an https daemon: a PEM CA file to trust and/or
+/// the SHA-256 fingerprint of the daemon's certificate to pin. Empty or
+/// `None` clears an option.
+#[tauri::command]
+pub async fn set_daemon_tls(
+    ca_cert: Option<String>,
+    pinned_sha256: Option<String>,
+) -> Result<(), String> {
+    let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let ca_cert = clean(ca_cert);
+    let pinned_sha256 = clean(pinned_sha256);
+    if let Some(path) = &ca_cert {
+        if !std::path::Path::new(path).is_file() {
+            return Err(format!("CA certificate not found: {}", path));
+        }
+    }
+    if let Some(pin) = &pinned_sha256 {
+        tls::parse_fingerprint(pin)?;
+    }
+    let mut prefs = TraySettings::load();
+    prefs.ca_cert = ca_cert;
+    prefs.pinned_cert_sha256 = pinned_sha256;
+    prefs.save()
+}
+
+/// The proxy settings: `proxy` (a URL or `none`) and `no_proxy`, each
+/// `null` when the environment decides.
+#[tauri::command]
+pub async fn get_proxy() -> serde_json::Value {
+    let prefs = TraySettings::load();
+    serde_json::json!({
+        "proxy": prefs.proxy,
+        "no_proxy": prefs.no_proxy,
+    })
+}
+
+/// Save a proxy URL (or `none` to connect directly) and the hosts to
+/// exempt from it. Empty or `None` clears an option, handing it back to
+/// `HTTP(S)_PROXY` / `NO_PROXY`. Takes effect with the next request.
+#[tauri::command]
+pub async fn set_proxy(proxy: Option<String>, no_proxy: Option<String>) -> Result<(), String> {
+    let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let proxy = clean(proxy);
+    if let Some(url) = proxy.as_deref().filter(|p| !p.eq_ignore_ascii_case("none")) {
+        crate::proxy::validate(url)?;
+    }
+    let mut prefs = TraySettings::load();
+    prefs.proxy = proxy;
+    prefs.no_proxy = clean(no_proxy);
+    prefs.save()
+}
+
+#[tauri::command]
+pub async fn get_token_status() -> TokenStatus {
+    token::status(api::auth_rejected())
+}
+
+/// Ask the daemon for a fresh token and store it. Uses the current token
+/// to authenticate, so this also serves as the first "generate" when the
+/// daemon trusts the tray without one (hybrid mode on localhost).
+pub(crate) async fn rotate_token_inner() -> Result<TokenStatus, String> {
+    let current = token::read();
+    let body = token::mint_request(current.as_deref());
+    let text = api::post_json("/api/auth/token", &body, std::time::Duration::from_secs(10)).await?;
+    let minted: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let new_token = minted["token"]
+        .as_str()
+        .ok_or("Daemon did not return a token")?;
+    token::write(new_token)?;
+    Ok(token::status(false))
+}
+
+#[tauri::command]
+pub async fn rotate_token() -> Result<TokenStatus, String> {
+    rotate_token_inner().await
+}
+
+/// Re-pair with a token pasted by the user (e.g. from `/api/auth/token` on
+/// another machine). The token is checked against `/api/auth/whoami`
+/// before it replaces the stored one.
+#[tauri::command]
+pub async fn pair_with_token(app: AppHandle, token: String) -> Result<TokenStatus, String> {
+    let token = token.trim().to_string();
+    if token.is_empty() {
+        return Err("Token is empty".to_string());
+    }
+    let whoami = api::get_json_as(
+        "/api/auth/whoami",
+        Some(&token),
+        std::time::Duration::from_secs(10),
+    )
+    .await?;
+    let local_mode = whoami["mode"].as_str() == Some("local");
+    if !local_mode && whoami["authenticated"].as_bool() != Some(true) {
+        return Err("The daemon did not accept this token".to_string());
+    }
+    token::write(&token)?;
+    if let Some(win) = app.get_webview_window("pair") {
+        let _ = win.close();
+    }
+    Ok(token::status(false))
+}
+
+#[tauri::command]
+pub async fn quit_pair_window(app: AppHandle) -> Result<(), String> {
+    if let Some(win) = app.get_webview_window("pair") {
+        win.close().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Set the dead-job warning threshold; 0 turns warnings off and `None`
+/// restores the default.
+#[tauri::command]
+pub async fn set_dead_job_threshold(threshold: Option<u64>) -> Result<(), String> {
+    let mut prefs = TraySettings::load();
+    prefs.dead_job_threshold = threshold;
+    prefs.save()
+}
+
+#[tauri::command]
+pub async fn get_start_on_launch() -> bool {
+    settings::start_daemon_on_launch()
+}
+
+/// Turn starting the daemon at tray launch on or off.
+#[tauri::command]
+pub async fn set_start_on_launch(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut prefs = TraySettings::load();
+    prefs.start_daemon_on_launch = Some(enabled);
+    prefs.save()?;
+    refresh_tray(&app)
+}
+
+#[tauri::command]
+pub async fn get_supervision() -> bool {
+    settings::supervision_enabled()
+}
+
+/// Turn automatic restarts of a crashed daemon on or off.
+#[tauri::command]
+pub async fn set_supervision(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut prefs = TraySettings::load();
+    prefs.supervise_daemon = Some(enabled);
+    prefs.save()?;
+    refresh_tray(&app)
+}
+
+#[tauri::command]
+pub async fn get_milestone_notifications() -> bool {
+    settings::milestone_notifications()
+}
+
+/// Turn milestone notifications on or off.
+#[tauri::command]
+pub async fn set_milestone_notifications(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut prefs = TraySettings::load();
+    prefs.milestone_notifications = Some(enabled);
+    prefs.save()?;
+    refresh_tray(&app)
+}
+
+#[tauri::command]
+pub async fn get_daily_summary() -> bool {
+    settings::daily_summary()
+}
+
+/// Turn the end-of-day summary notification on or off.
+#[tauri::command]
+pub async fn set_daily_summary(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut prefs = TraySettings::load();
+    prefs.daily_summary = Some(enabled);
+    prefs.save()?;
+    refresh_tray(&app)
+}
+
+#[tauri::command]
+pub async fn get_title_display() -> serde_json::Value {
+    serde_json::json!({
+        "display": settings::title_display(),
+        "compact": settings::compact_title(),
+    })
+}
+
+/// Choose what the menu bar title shows. Either part is left alone when
+/// `None`.
+#[tauri::command]
+pub async fn set_title_display(
+    app: AppHandle,
+    display: Option<settings::TitleDisplay>,
+    compact: Option<bool>,
+) -> Result<(), String> {
+    let mut prefs = TraySettings::load();
+    if display.is_some() {
+        prefs.title_display = display;
+    }
+    if compact.is_some() {
+        prefs.compact_title = compact;
+    }
+    prefs.save()?;
+    refresh_tray(&app)
+}
+
+/// Switch the menu language, or follow the system again with `None`.
+pub(crate) fn set_language_inner(app: &AppHandle, language: Option<Locale>) -> Result<(), String> {
+    let mut prefs = TraySettings::load();
+    prefs.language = language;
+    prefs.save()?;
+    i18n::reset();
+    refresh_tray(app)
+}
+
+/// The chosen language (`null` when following the system) and the one
+/// the menu is actually shown in.
+#[tauri::command]
+pub async fn get_language() -> serde_json::Value {
+    serde_json::json!({
+        "language": settings::language(),
+        "active": i18n::locale(),
+    })
+}
+
+#[tauri::command]
+pub async fn set_language(app: AppHandle, language: Option<Locale>) -> Result<(), String> {
+    set_language_inner(&app, language)
+}
+
+/// Check for a newer release now. Opens the release notes window when
+/// there is one, and otherwise says the tray is up to date. Returns the
+/// newer version.
+#[tauri::command]
+pub async fn check_for_update(app: AppHandle) -> Result<Option<String>, String> {
+    match update::check_now(&app).await {
+        Ok(Some(release)) => {
+            open_update_window_inner(&app)?;
+            Ok(Some(release.version))
+        }
+        Ok(None) => {
+            crate::notify::up_to_date(&app, &app.package_info().version.to_string());
+            Ok(None)
+        }
+        Err(e) => {
+            crate::notify::update_check_failed(&app, &e);
+            Err(e)
+        }
+    }
+}
+
+/// The newer release found by the last check, if any.
+#[tauri::command]
+pub async fn get_available_update() -> Option<update::Release> {
+    update::available()
+}
+
+pub(crate) fn open_update_window_inner(app: &AppHandle) -> Result<(), String> {
+    if let Some(win) = app.get_webview_window("update") {
+        // Reload so a newer release found since it opened is shown
+        let _ = win.eval("location.reload()");
+        return win.set_focus().map_err(|e| e.to_string());
+    }
+    WebviewWindowBuilder::new(app, "update", tauri::WebviewUrl::App("update.html".into()))
+        .title("Software Update")
+        .inner_size(520.0, 480.0)
+        .min_inner_size(400.0, 320.0)
+        .center()
+        .visible(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Open the release notes window for the available update.
+#[tauri::command]
+pub async fn open_update_window(app: AppHandle) -> Result<(), String> {
+    open_update_window_inner(&app)
+}
+
+/// Open the available release's page, where the installers are.
+#[tauri::command]
+pub async fn open_release_page(app: AppHandle) -> Result<(), String> {
+    use tauri_plugin_shell::ShellExt;
+
+    let release = update::available().ok_or("no update available")?;
+    #[allow(deprecated)]
+    let opened = app.shell().open(release.url, None);
+    opened.map_err(|e| e.to_string())
+}
+
+/// Download and install the available update, then restart into it.
+/// Only returns on failure.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    update::install(&app).await
+}
+
+#[tauri::command]
+pub async fn update_install_progress() -> update::InstallProgress {
+    update::install_progress()
+}
+
+#[tauri::command]
+pub async fn quit_update_window(app: AppHandle) -> Result<(), String> {
+    if let Some(win) = app.get_webview_window("update") {
+        win.close().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_update_checks() -> bool {
+    settings::update_checks()
+}
+
+/// Turn the background update check on or off.
 #[tauri::command]
-pub async fn check_for_update(_app: AppHandle) -> Result<Option<String>, String> {
-    Ok(None) // No updater configured yet
+pub async fn set_update_checks(enabled: bool) -> Result<(), String> {
+    let mut prefs = TraySettings::load();
+    prefs.update_checks = Some(enabled);
+    prefs.save()
 }
 
 #[tauri::command]
@@ -0,0 +1,85 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::net::TcpListener;
+
+use serde_json::Value;
+
+use crate::settings;
+
+/// Prefix shared by port-conflict errors, so the poller and menus can tell
+/// them from a daemon that is simply down.
+pub const ERROR_PREFIX: &str = "Port conflict:";
+
+/// How far past the configured port to look for a free one
+const ALTERNATE_PORT_RANGE: u16 = 20;
+
+/// Only the end of the log matters for the latest start (64 KiB)
+const LOG_TAIL_BYTES: u64 = 64 * 1024;
+
+pub fn is_conflict(err: &str) -> bool {
+    err.starts_with(ERROR_PREFIX)
+}
+
+/// Whether a `/health` body came from a Signet daemon rather than whatever
+/// else might be listening on its port.
+pub fn is_signet_health(body: &Value) -> bool {
+    body["status"].is_string() && body["version"].is_string()
+}
+
+/// Something answered at the daemon URL, but not as Signet.
+pub fn answered_by_other() -> String {
+    let (host, port) = settings::daemon_socket_addr();
+    format!(
+        "{} another program is answering on {}:{}",
+        ERROR_PREFIX, host, port
+    )
+}
+
+/// The daemon could not bind its port.
+pub fn port_in_use() -> String {
+    let (_, port) = settings::daemon_socket_addr();
+    format!("{} port {} is already in use", ERROR_PREFIX, port)
+}
+
+/// Whether the latest daemon start logged a bind failure: an
+/// `EADDRINUSE` with no "Server listening" after it.
+pub fn bind_error_logged() -> bool {
+    let Some(log) = crate::notify::log_target().filter(|p| p.is_file()) else {
+        return false;
+    };
+    let Ok(mut file) = std::fs::File::open(log) else {
+        return false;
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if file.seek(SeekFrom::Start(len.saturating_sub(LOG_TAIL_BYTES))).is_err() {
+        return false;
+    }
+    let mut buf = Vec::new();
+    if file.read_to_end(&mut buf).is_err() {
+        return false;
+    }
+    let text = String::from_utf8_lossy(&buf).to_lowercase();
+    let bind_error = text
+        .rfind("eaddrinuse")
+        .max(text.rfind("address already in use"));
+    match (bind_error, text.rfind("server listening")) {
+        (Some(err), Some(listening)) => err > listening,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+/// The first port after the configured one that nothing is bound to on
+/// loopback. Only meaningful for a local daemon.
+pub fn free_port() -> Option<u16> {
+    let (_, port) = settings::daemon_socket_addr();
+    (1..=ALTERNATE_PORT_RANGE)
+        .filter_map(|offset| port.checked_add(offset))
+        .find(|p| TcpListener::bind(("127.0.0.1", *p)).is_ok())
+}
+
+/// Whether the tray can move the daemon to another port itself. A daemon
+/// run by systemd or launchd takes its port from the service definition,
+/// so there the tray can only point at the port, not change it.
+pub fn can_move_port() -> bool {
+    settings::daemon_is_local() && !settings::service_installed()
+}
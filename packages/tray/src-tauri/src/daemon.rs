@@ -1,19 +1,68 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
 use crate::platform;
 
+// Supervised restart backoff: 2s, 4s, 8s... capped at 5 minutes, and at
+// most MAX_RESTARTS attempts per outage.
+const RESTART_BACKOFF_MIN: Duration = Duration::from_secs(2);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(300);
+const MAX_RESTARTS: u32 = 5;
+
+/// Start attempts since the daemon was last seen running.
+static START_ATTEMPTS: AtomicU32 = AtomicU32::new(0);
+static LAST_START: Mutex<Option<Instant>> = Mutex::new(None);
+static LAST_STOP: Mutex<Option<Instant>> = Mutex::new(None);
+
 pub fn start() -> Result<(), Box<dyn std::error::Error>> {
+    START_ATTEMPTS.fetch_add(1, Ordering::Relaxed);
+    *LAST_START.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
     let manager = platform::create_manager();
     manager.start()
 }
 
 pub fn stop() -> Result<(), Box<dyn std::error::Error>> {
+    *LAST_STOP.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
     let manager = platform::create_manager();
     manager.stop()
 }
 
+/// Hand the daemon over to a systemd user unit or launchd agent. Counts
+/// as a stop request, since installing restarts the daemon.
+pub fn install_service() -> Result<(), Box<dyn std::error::Error>> {
+    if crate::profile::active().is_some() {
+        return Err("Only the default daemon can be installed as a service".into());
+    }
+    *LAST_STOP.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+    let manager = platform::create_manager();
+    manager.install_service()
+}
+
+/// Whether the tray asked the daemon to stop within `window`, i.e. whether
+/// a daemon going away now was expected.
+pub fn stop_requested_within(window: Duration) -> bool {
+    LAST_STOP
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some_and(|at| at.elapsed() < window)
+}
+
+/// Start attempts since the daemon last came up, and when the latest was.
+pub fn start_attempts() -> (u32, Option<Instant>) {
+    let last = *LAST_START.lock().unwrap_or_else(|e| e.into_inner());
+    (START_ATTEMPTS.load(Ordering::Relaxed), last)
+}
+
+/// Called when the daemon is seen running.
+pub fn reset_start_attempts() {
+    START_ATTEMPTS.store(0, Ordering::Relaxed);
+}
+
 pub fn read_pid() -> Result<Option<u32>, Box<dyn std::error::Error>> {
-    let pid_path = dirs::home_dir()
+    let pid_path = crate::settings::daemon_dir()
         .ok_or("no home dir")?
-        .join(".agents/.daemon/pid");
+        .join("pid");
 
     if !pid_path.exists() {
         return Ok(None);
@@ -56,3 +105,50 @@ fn process_alive(pid: u32) -> bool {
         }
     }
 }
+
+/// What the supervisor did on a tick.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Supervision {
+    /// Waiting out the backoff
+    Waiting,
+    /// Just tried restart number `n`
+    Restarted(u32),
+    /// The cap was reached; no more attempts this outage
+    GaveUp,
+}
+
+/// Restarts a daemon that died on its own, backing off exponentially.
+/// Reset whenever the daemon is seen running.
+#[derive(Default)]
+pub struct Supervisor {
+    restarts: u32,
+    next_at: Option<Instant>,
+}
+
+impl Supervisor {
+    fn backoff(restarts: u32) -> Duration {
+        RESTART_BACKOFF_MIN
+            .saturating_mul(1 << restarts.min(16))
+            .min(RESTART_BACKOFF_MAX)
+    }
+
+    /// Call on every poll while the daemon is down after a crash.
+    pub fn tick(&mut self) -> Supervision {
+        if self.restarts >= MAX_RESTARTS {
+            return Supervision::GaveUp;
+        }
+        let now = Instant::now();
+        let next_at = *self.next_at.get_or_insert(now + RESTART_BACKOFF_MIN);
+        if now < next_at {
+            return Supervision::Waiting;
+        }
+        let _ = start();
+        self.next_at = Some(now + Self::backoff(self.restarts + 1));
+        self.restarts += 1;
+        Supervision::Restarted(self.restarts)
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,313 @@
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::api;
+
+/// Memories fetched per request
+const PAGE_SIZE: u64 = 500;
+
+static PROGRESS: Mutex<Progress> = Mutex::new(Progress {
+    running: false,
+    scanned: 0,
+    total: 0,
+    exported: 0,
+});
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Json,
+    Markdown,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Markdown => "md",
+        }
+    }
+}
+
+/// Which memories to export. Every field is optional; an empty filter
+/// exports everything.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Filter {
+    /// Only memories created on or after this date (`YYYY-MM-DD`)
+    pub since: Option<String>,
+    /// Only memories of this type, e.g. `fact` or `decision`
+    #[serde(rename = "type")]
+    pub memory_type: Option<String>,
+    /// Only memories carrying this tag
+    pub tag: Option<String>,
+    pub min_importance: Option<f64>,
+    pub pinned_only: bool,
+}
+
+impl Filter {
+    fn matches(&self, memory: &Value) -> bool {
+        let since = self.since.as_deref().filter(|s| !s.is_empty());
+        if let Some(since) = since {
+            // ISO timestamps sort as strings, so a date prefix compares correctly
+            if memory["created_at"].as_str().unwrap_or_default() < since {
+                return false;
+            }
+        }
+        if let Some(kind) = self.memory_type.as_deref().filter(|s| !s.is_empty()) {
+            if memory["type"].as_str() != Some(kind) {
+                return false;
+            }
+        }
+        if let Some(tag) = self.tag.as_deref().filter(|s| !s.is_empty()) {
+            if !tags(memory).iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                return false;
+            }
+        }
+        if let Some(min) = self.min_importance {
+            if memory["importance"].as_f64().unwrap_or(0.0) < min {
+                return false;
+            }
+        }
+        if self.pinned_only && !is_pinned(memory) {
+            return false;
+        }
+        true
+    }
+
+    /// Memories are listed newest first, so once they are older than
+    /// `since` nothing later in the list can match.
+    fn past_since(&self, memory: &Value) -> bool {
+        self.since
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .is_some_and(|since| memory["created_at"].as_str().unwrap_or_default() < since)
+    }
+}
+
+/// How far the current (or last) export has got.
+#[derive(Serialize, Clone)]
+pub struct Progress {
+    pub running: bool,
+    /// Memories read from the daemon so far
+    pub scanned: u64,
+    /// Memories the daemon holds
+    pub total: u64,
+    /// Memories written to the file so far
+    pub exported: u64,
+}
+
+pub fn progress() -> Progress {
+    PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn set_progress(update: impl FnOnce(&mut Progress)) {
+    update(&mut PROGRESS.lock().unwrap_or_else(|e| e.into_inner()));
+}
+
+pub fn cancel() {
+    CANCELLED.store(true, Ordering::Relaxed);
+}
+
+/// Tags are stored either as a JSON array or comma separated.
+fn tags(memory: &Value) -> Vec<String> {
+    match &memory["tags"] {
+        Value::Array(list) => list
+            .iter()
+            .filter_map(|t| t.as_str().map(str::to_string))
+            .collect(),
+        Value::String(raw) => serde_json::from_str::<Vec<String>>(raw).unwrap_or_else(|_| {
+            raw.split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        }),
+        _ => Vec::new(),
+    }
+}
+
+fn is_pinned(memory: &Value) -> bool {
+    memory["pinned"].as_bool().unwrap_or(false) || memory["pinned"].as_u64().unwrap_or(0) != 0
+}
+
+/// One memory as a Markdown section.
+fn markdown_entry(memory: &Value) -> String {
+    let created = memory["created_at"].as_str().unwrap_or_default();
+    let created = chrono::DateTime::parse_from_rfc3339(created)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| created.to_string());
+    let mut heading = format!("## {}", created);
+    if let Some(kind) = memory["type"].as_str() {
+        heading.push_str(&format!(" · {}", kind));
+    }
+    if is_pinned(memory) {
+        heading.push_str(" · 📌");
+    }
+
+    let mut out = format!(
+        "{}\n\n{}\n\n",
+        heading,
+        memory["content"].as_str().unwrap_or_default().trim()
+    );
+    let tags = tags(memory);
+    if !tags.is_empty() {
+        out.push_str(&format!("- Tags: {}\n", tags.join(", ")));
+    }
+    if let Some(importance) = memory["importance"].as_f64() {
+        out.push_str(&format!("- Importance: {:.2}\n", importance));
+    }
+    if let Some(who) = memory["who"].as_str() {
+        out.push_str(&format!("- From: {}\n", who));
+    }
+    out.push_str(&format!(
+        "- ID: `{}`\n\n",
+        memory["id"].as_str().unwrap_or_default()
+    ));
+    out
+}
+
+/// Write memories as they arrive, so a large export never holds more
+/// than one page in memory.
+struct Writer {
+    out: BufWriter<std::fs::File>,
+    format: Format,
+    written: u64,
+}
+
+impl Writer {
+    fn begin(&mut self) -> std::io::Result<()> {
+        match self.format {
+            Format::Json => self.out.write_all(b"[\n"),
+            Format::Markdown => write!(
+                self.out,
+                "# Signet Memories\n\nExported {}\n\n",
+                chrono::Local::now().format("%Y-%m-%d %H:%M")
+            ),
+        }
+    }
+
+    fn entry(&mut self, memory: &Value) -> std::io::Result<()> {
+        match self.format {
+            Format::Json => {
+                if self.written > 0 {
+                    self.out.write_all(b",\n")?;
+                }
+                let json = serde_json::to_string_pretty(memory).unwrap_or_default();
+                // Indent each object one level inside the array
+                self.out.write_all(b"  ")?;
+                self.out.write_all(json.replace('\n', "\n  ").as_bytes())?;
+            }
+            Format::Markdown => self.out.write_all(markdown_entry(memory).as_bytes())?,
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> std::io::Result<()> {
+        if self.format == Format::Json {
+            self.out.write_all(b"\n]\n")?;
+        }
+        self.out.flush()
+    }
+}
+
+/// `<path>.part`, where the export is written until it completes.
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+async fn export_pages(writer: &mut Writer, filter: &Filter) -> Result<(), String> {
+    let write_err = |e: std::io::Error| format!("Failed to write export: {}", e);
+    writer.begin().map_err(write_err)?;
+    let mut offset = 0;
+    loop {
+        if CANCELLED.load(Ordering::Relaxed) {
+            return Err("Export cancelled".to_string());
+        }
+        let data = api::get_json(
+            &format!("/api/memories?limit={}&offset={}", PAGE_SIZE, offset),
+            Duration::from_secs(30),
+        )
+        .await?;
+        if let Some(error) = data["error"].as_str() {
+            return Err(error.to_string());
+        }
+        let page = data["memories"].as_array().cloned().unwrap_or_default();
+        let total = data["stats"]["total"].as_u64().unwrap_or(0);
+        let mut done = page.len() < PAGE_SIZE as usize;
+        for memory in &page {
+            if filter.past_since(memory) {
+                done = true;
+                break;
+            }
+            if filter.matches(memory) {
+                writer.entry(memory).map_err(write_err)?;
+            }
+        }
+        offset += page.len() as u64;
+        let exported = writer.written;
+        set_progress(|p| {
+            p.scanned = offset;
+            p.total = total.max(offset);
+            p.exported = exported;
+        });
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+/// Export memories matching `filter` to `path`. The file is written under
+/// a `.part` name and only renamed into place once complete, so a failed
+/// or cancelled export never leaves a truncated file behind. Returns how
+/// many memories were written.
+pub async fn run(path: &Path, format: Format, filter: &Filter) -> Result<u64, String> {
+    {
+        let mut progress = PROGRESS.lock().unwrap_or_else(|e| e.into_inner());
+        if progress.running {
+            return Err("An export is already running".to_string());
+        }
+        *progress = Progress {
+            running: true,
+            scanned: 0,
+            total: 0,
+            exported: 0,
+        };
+    }
+    CANCELLED.store(false, Ordering::Relaxed);
+
+    let part = part_path(path);
+    let result = async {
+        let file = std::fs::File::create(&part)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = Writer {
+            out: BufWriter::new(file),
+            format,
+            written: 0,
+        };
+        export_pages(&mut writer, filter).await?;
+        let written = writer.written;
+        writer
+            .finish()
+            .map_err(|e| format!("Failed to write export: {}", e))?;
+        std::fs::rename(&part, path)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(written)
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&part);
+    }
+    set_progress(|p| p.running = false);
+    result
+}
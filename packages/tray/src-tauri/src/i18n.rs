@@ -0,0 +1,450 @@
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+/// Languages the tray menu is translated into.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    Fr,
+    De,
+    Ja,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 4] = [Locale::En, Locale::Fr, Locale::De, Locale::Ja];
+
+    /// Match a language tag such as `fr-CA`, `de_DE.UTF-8` or `ja`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let lang = tag
+            .split(['-', '_', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Locale::En),
+            "fr" => Some(Locale::Fr),
+            "de" => Some(Locale::De),
+            "ja" => Some(Locale::Ja),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr",
+            Locale::De => "de",
+            Locale::Ja => "ja",
+        }
+    }
+
+    /// The language's own name, as shown in the Language submenu.
+    pub fn name(self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Fr => "Français",
+            Locale::De => "Deutsch",
+            Locale::Ja => "日本語",
+        }
+    }
+
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Fr => FR,
+            Locale::De => DE,
+            Locale::Ja => JA,
+        }
+    }
+}
+
+/// The locale in use, resolved once and kept until `reset`.
+static CURRENT: Mutex<Option<Locale>> = Mutex::new(None);
+
+/// The system's language, falling back to English when it has none of
+/// ours.
+fn system_locale() -> Locale {
+    sys_locale::get_locales()
+        .find_map(|tag| Locale::from_tag(&tag))
+        .unwrap_or(Locale::En)
+}
+
+/// The chosen language from the tray settings, else the system's.
+pub fn locale() -> Locale {
+    let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    *current.get_or_insert_with(|| crate::settings::language().unwrap_or_else(system_locale))
+}
+
+/// Forget the resolved locale, after the language setting changed.
+pub fn reset() {
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The translation of `key`, falling back to English and then to the key
+/// itself.
+pub fn t(key: &'static str) -> &'static str {
+    let find = |locale: Locale| {
+        locale
+            .table()
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, text)| *text)
+    };
+    find(locale()).or_else(|| find(Locale::En)).unwrap_or(key)
+}
+
+/// `t` with `{0}`, `{1}`, ... replaced by `args`. Placeholders are numbered
+/// so translations can reorder them.
+pub fn tf(key: &'static str, args: &[&dyn Display]) -> String {
+    let mut text = t(key).to_string();
+    for (i, arg) in args.iter().enumerate() {
+        text = text.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    text
+}
+
+const EN: &[(&str, &str)] = &[
+    ("status_running", "Signet v{0} — Running"),
+    ("status_stopped", "Signet — Stopped"),
+    ("status_error", "Signet — Error: {0}"),
+    ("status_checking", "Signet — Checking..."),
+    ("memories_with_today", "{0} memories ({1} today)"),
+    ("memories", "{0} memories"),
+    ("memories_loading", "Memories: loading..."),
+    ("embeddings_coverage", "Embeddings: {0}% coverage"),
+    ("embeddings_loading", "Embeddings: loading..."),
+    ("backfilling", "Backfilling embeddings: {0} of {1}"),
+    ("queue_pending", "Queue: {0} pending"),
+    ("queue_loading", "Queue: loading..."),
+    ("health_status", "Health: {0} ({1}/100)"),
+    ("health_score", "Health: {0}/100"),
+    ("health_loading", "Health: loading..."),
+    ("resources", "CPU {0}% · RAM {1} · Up {2}"),
+    ("dead_jobs", "{0} dead jobs"),
+    ("captures_queued_one", "1 capture queued"),
+    ("captures_queued", "{0} captures queued"),
+    ("quick_capture", "Quick Capture..."),
+    ("search_memories", "Search Memories..."),
+    ("export_memories", "Export Memories..."),
+    ("backfill_embeddings", "Backfill Embeddings"),
+    ("resume_capture", "Resume Memory Capture"),
+    ("pause_capture", "Pause Memory Capture"),
+    ("pause_1h", "For 1 Hour"),
+    ("pause_4h", "For 4 Hours"),
+    ("pause_until_resumed", "Until Resumed"),
+    ("capture_paused", "Capture paused"),
+    ("capture_paused_until", "Capture paused until {0}"),
+    ("date_time", "%b %-d, %H:%M"),
+    ("recent_memories", "Recent Memories"),
+    ("job_queue", "Job Queue"),
+    ("job_queue_dead", "Job Queue ({0} dead)"),
+    ("queue_counts", "{0}: {1} pending · {2} dead"),
+    ("queue_extraction", "Extraction"),
+    ("queue_summaries", "Summaries"),
+    ("retry_dead", "Retry Dead Jobs"),
+    ("purge_dead", "Purge Dead Jobs..."),
+    ("predictor", "Predictor"),
+    ("sidecar_down", "Sidecar not running"),
+    ("model_version", "Model v{0}"),
+    ("model_unknown", "Model: unknown"),
+    ("trained_ago", "Trained {0}"),
+    ("not_trained", "Not trained yet"),
+    ("training_pairs", "{0} training pairs"),
+    ("last_run_samples", "Last run: {0} samples"),
+    ("training", "Training..."),
+    ("retrain_now", "Retrain Now"),
+    ("open_dashboard", "Open Dashboard"),
+    ("open_logs", "Open Logs"),
+    ("open_data_dir", "Open Data Folder"),
+    ("diagnostics_bundle", "Create Diagnostics Bundle..."),
+    ("start_daemon", "Start Daemon"),
+    ("stop_daemon", "Stop Daemon"),
+    ("restart_daemon", "Restart Daemon"),
+    ("rotate_token", "Rotate Token"),
+    ("start_at_login", "Start at Login"),
+    ("start_with_signet", "Start Daemon with Signet"),
+    ("install_service", "Install as Service"),
+    ("restart_automatically", "Restart Automatically"),
+    ("profile", "Profile: {0}"),
+    ("profile_default", "Default"),
+    ("notifications", "Notifications"),
+    ("milestones", "Milestones"),
+    ("daily_summary", "Daily Summary"),
+    ("menu_bar_title", "Menu Bar Title"),
+    ("title_total", "Total Memories"),
+    ("title_today", "Today's Memories"),
+    ("title_hidden", "No Count"),
+    ("compact_numbers", "Compact Numbers (12.4k)"),
+    ("language", "Language"),
+    ("language_system", "System"),
+    ("update_available", "Update Available: v{0}"),
+    ("check_updates", "Check for Updates..."),
+    ("quit", "Quit Signet"),
+    ("start_alt_port", "Start Daemon on Port {0}"),
+    ("pair_daemon", "Re-pair with Daemon..."),
+    ("just_now", "just now"),
+    ("minutes_ago", "{0}m ago"),
+    ("hours_ago", "{0}h ago"),
+    ("days_ago", "{0}d ago"),
+];
+
+const FR: &[(&str, &str)] = &[
+    ("status_running", "Signet v{0} — En marche"),
+    ("status_stopped", "Signet — Arrêté"),
+    ("status_error", "Signet — Erreur : {0}"),
+    ("status_checking", "Signet — Vérification..."),
+    ("memories_with_today", "{0} souvenirs ({1} aujourd'hui)"),
+    ("memories", "{0} souvenirs"),
+    ("memories_loading", "Souvenirs : chargement..."),
+    ("embeddings_coverage", "Embeddings : {0} % de couverture"),
+    ("embeddings_loading", "Embeddings : chargement..."),
+    ("backfilling", "Génération des embeddings : {0} sur {1}"),
+    ("queue_pending", "File : {0} en attente"),
+    ("queue_loading", "File : chargement..."),
+    ("health_status", "Santé : {0} ({1}/100)"),
+    ("health_score", "Santé : {0}/100"),
+    ("health_loading", "Santé : chargement..."),
+    ("resources", "CPU {0} % · RAM {1} · Actif depuis {2}"),
+    ("dead_jobs", "{0} tâches mortes"),
+    ("captures_queued_one", "1 capture en attente"),
+    ("captures_queued", "{0} captures en attente"),
+    ("quick_capture", "Capture rapide..."),
+    ("search_memories", "Rechercher dans les souvenirs..."),
+    ("export_memories", "Exporter les souvenirs..."),
+    ("backfill_embeddings", "Générer les embeddings manquants"),
+    ("resume_capture", "Reprendre la capture"),
+    ("pause_capture", "Suspendre la capture"),
+    ("pause_1h", "Pendant 1 heure"),
+    ("pause_4h", "Pendant 4 heures"),
+    ("pause_until_resumed", "Jusqu'à la reprise"),
+    ("capture_paused", "Capture suspendue"),
+    ("capture_paused_until", "Capture suspendue jusqu'à {0}"),
+    ("date_time", "%-d/%m %H:%M"),
+    ("recent_memories", "Souvenirs récents"),
+    ("job_queue", "File des tâches"),
+    ("job_queue_dead", "File des tâches ({0} mortes)"),
+    ("queue_counts", "{0} : {1} en attente · {2} mortes"),
+    ("queue_extraction", "Extraction"),
+    ("queue_summaries", "Résumés"),
+    ("retry_dead", "Relancer les tâches mortes"),
+    ("purge_dead", "Purger les tâches mortes..."),
+    ("predictor", "Prédicteur"),
+    ("sidecar_down", "Sidecar arrêté"),
+    ("model_version", "Modèle v{0}"),
+    ("model_unknown", "Modèle : inconnu"),
+    ("trained_ago", "Entraîné {0}"),
+    ("not_trained", "Pas encore entraîné"),
+    ("training_pairs", "{0} paires d'entraînement"),
+    (
+        "last_run_samples",
+        "Dernier entraînement : {0} échantillons",
+    ),
+    ("training", "Entraînement..."),
+    ("retrain_now", "Réentraîner maintenant"),
+    ("open_dashboard", "Ouvrir le tableau de bord"),
+    ("open_logs", "Ouvrir les journaux"),
+    ("open_data_dir", "Ouvrir le dossier de données"),
+    ("diagnostics_bundle", "Créer un paquet de diagnostic..."),
+    ("start_daemon", "Démarrer le daemon"),
+    ("stop_daemon", "Arrêter le daemon"),
+    ("restart_daemon", "Redémarrer le daemon"),
+    ("rotate_token", "Renouveler le jeton"),
+    ("start_at_login", "Lancer à l'ouverture de session"),
+    ("start_with_signet", "Démarrer le daemon avec Signet"),
+    ("install_service", "Installer comme service"),
+    ("restart_automatically", "Redémarrer automatiquement"),
+    ("profile", "Profil : {0}"),
+    ("profile_default", "Par défaut"),
+    ("notifications", "Notifications"),
+    ("milestones", "Étapes clés"),
+    ("daily_summary", "Résumé quotidien"),
+    ("menu_bar_title", "Titre de la barre de menus"),
+    ("title_total", "Total des souvenirs"),
+    ("title_today", "Souvenirs du jour"),
+    ("title_hidden", "Aucun nombre"),
+    ("compact_numbers", "Nombres abrégés (12.4k)"),
+    ("language", "Langue"),
+    ("language_system", "Système"),
+    ("update_available", "Mise à jour disponible : v{0}"),
+    ("check_updates", "Rechercher des mises à jour..."),
+    ("quit", "Quitter Signet"),
+    ("start_alt_port", "Démarrer le daemon sur le port {0}"),
+    ("pair_daemon", "Réassocier au daemon..."),
+    ("just_now", "à l'instant"),
+    ("minutes_ago", "il y a {0} min"),
+    ("hours_ago", "il y a {0} h"),
+    ("days_ago", "il y a {0} j"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("status_running", "Signet v{0} — Läuft"),
+    ("status_stopped", "Signet — Gestoppt"),
+    ("status_error", "Signet — Fehler: {0}"),
+    ("status_checking", "Signet — Wird geprüft..."),
+    ("memories_with_today", "{0} Erinnerungen ({1} heute)"),
+    ("memories", "{0} Erinnerungen"),
+    ("memories_loading", "Erinnerungen: wird geladen..."),
+    ("embeddings_coverage", "Embeddings: {0} % Abdeckung"),
+    ("embeddings_loading", "Embeddings: wird geladen..."),
+    ("backfilling", "Embeddings werden nachgeholt: {0} von {1}"),
+    ("queue_pending", "Warteschlange: {0} ausstehend"),
+    ("queue_loading", "Warteschlange: wird geladen..."),
+    ("health_status", "Zustand: {0} ({1}/100)"),
+    ("health_score", "Zustand: {0}/100"),
+    ("health_loading", "Zustand: wird geladen..."),
+    ("resources", "CPU {0} % · RAM {1} · Laufzeit {2}"),
+    ("dead_jobs", "{0} fehlgeschlagene Jobs"),
+    ("captures_queued_one", "1 Notiz in der Warteschlange"),
+    ("captures_queued", "{0} Notizen in der Warteschlange"),
+    ("quick_capture", "Schnellnotiz..."),
+    ("search_memories", "Erinnerungen durchsuchen..."),
+    ("export_memories", "Erinnerungen exportieren..."),
+    ("backfill_embeddings", "Fehlende Embeddings erzeugen"),
+    ("resume_capture", "Erfassung fortsetzen"),
+    ("pause_capture", "Erfassung pausieren"),
+    ("pause_1h", "Für 1 Stunde"),
+    ("pause_4h", "Für 4 Stunden"),
+    ("pause_until_resumed", "Bis zur Fortsetzung"),
+    ("capture_paused", "Erfassung pausiert"),
+    ("capture_paused_until", "Erfassung pausiert bis {0}"),
+    ("date_time", "%-d.%-m. %H:%M"),
+    ("recent_memories", "Letzte Erinnerungen"),
+    ("job_queue", "Job-Warteschlange"),
+    ("job_queue_dead", "Job-Warteschlange ({0} fehlgeschlagen)"),
+    ("queue_counts", "{0}: {1} ausstehend · {2} fehlgeschlagen"),
+    ("queue_extraction", "Extraktion"),
+    ("queue_summaries", "Zusammenfassungen"),
+    ("retry_dead", "Fehlgeschlagene Jobs wiederholen"),
+    ("purge_dead", "Fehlgeschlagene Jobs löschen..."),
+    ("predictor", "Prädiktor"),
+    ("sidecar_down", "Sidecar läuft nicht"),
+    ("model_version", "Modell v{0}"),
+    ("model_unknown", "Modell: unbekannt"),
+    ("trained_ago", "Trainiert {0}"),
+    ("not_trained", "Noch nicht trainiert"),
+    ("training_pairs", "{0} Trainingspaare"),
+    ("last_run_samples", "Letzter Lauf: {0} Stichproben"),
+    ("training", "Training läuft..."),
+    ("retrain_now", "Jetzt neu trainieren"),
+    ("open_dashboard", "Dashboard öffnen"),
+    ("open_logs", "Logs öffnen"),
+    ("open_data_dir", "Datenordner öffnen"),
+    ("diagnostics_bundle", "Diagnosepaket erstellen..."),
+    ("start_daemon", "Daemon starten"),
+    ("stop_daemon", "Daemon stoppen"),
+    ("restart_daemon", "Daemon neu starten"),
+    ("rotate_token", "Token erneuern"),
+    ("start_at_login", "Bei Anmeldung starten"),
+    ("start_with_signet", "Daemon mit Signet starten"),
+    ("install_service", "Als Dienst installieren"),
+    ("restart_automatically", "Automatisch neu starten"),
+    ("profile", "Profil: {0}"),
+    ("profile_default", "Standard"),
+    ("notifications", "Benachrichtigungen"),
+    ("milestones", "Meilensteine"),
+    ("daily_summary", "Tageszusammenfassung"),
+    ("menu_bar_title", "Menüleistentitel"),
+    ("title_total", "Alle Erinnerungen"),
+    ("title_today", "Heutige Erinnerungen"),
+    ("title_hidden", "Keine Anzahl"),
+    ("compact_numbers", "Kurze Zahlen (12.4k)"),
+    ("language", "Sprache"),
+    ("language_system", "System"),
+    ("update_available", "Update verfügbar: v{0}"),
+    ("check_updates", "Nach Updates suchen..."),
+    ("quit", "Signet beenden"),
+    ("start_alt_port", "Daemon auf Port {0} starten"),
+    ("pair_daemon", "Erneut mit Daemon koppeln..."),
+    ("just_now", "gerade eben"),
+    ("minutes_ago", "vor {0} Min."),
+    ("hours_ago", "vor {0} Std."),
+    ("days_ago", "vor {0} T."),
+];
+
+const JA: &[(&str, &str)] = &[
+    ("status_running", "Signet v{0} — 実行中"),
+    ("status_stopped", "Signet — 停止中"),
+    ("status_error", "Signet — エラー: {0}"),
+    ("status_checking", "Signet — 確認中..."),
+    ("memories_with_today", "メモリ {0} 件（今日 {1} 件）"),
+    ("memories", "メモリ {0} 件"),
+    ("memories_loading", "メモリ: 読み込み中..."),
+    ("embeddings_coverage", "埋め込み: カバー率 {0}%"),
+    ("embeddings_loading", "埋め込み: 読み込み中..."),
+    ("backfilling", "埋め込みを補完中: {0} / {1}"),
+    ("queue_pending", "キュー: {0} 件待機中"),
+    ("queue_loading", "キュー: 読み込み中..."),
+    ("health_status", "状態: {0}（{1}/100）"),
+    ("health_score", "状態: {0}/100"),
+    ("health_loading", "状態: 読み込み中..."),
+    ("resources", "CPU {0}% · RAM {1} · 稼働 {2}"),
+    ("dead_jobs", "失敗ジョブ {0} 件"),
+    ("captures_queued_one", "キャプチャ 1 件が送信待ち"),
+    ("captures_queued", "キャプチャ {0} 件が送信待ち"),
+    ("quick_capture", "クイックキャプチャ..."),
+    ("search_memories", "メモリを検索..."),
+    ("export_memories", "メモリをエクスポート..."),
+    ("backfill_embeddings", "埋め込みを補完"),
+    ("resume_capture", "メモリの記録を再開"),
+    ("pause_capture", "メモリの記録を一時停止"),
+    ("pause_1h", "1 時間"),
+    ("pause_4h", "4 時間"),
+    ("pause_until_resumed", "再開するまで"),
+    ("capture_paused", "記録を一時停止中"),
+    ("capture_paused_until", "{0} まで記録を一時停止中"),
+    ("date_time", "%-m/%-d %H:%M"),
+    ("recent_memories", "最近のメモリ"),
+    ("job_queue", "ジョブキュー"),
+    ("job_queue_dead", "ジョブキュー（失敗 {0} 件）"),
+    ("queue_counts", "{0}: 待機 {1} · 失敗 {2}"),
+    ("queue_extraction", "抽出"),
+    ("queue_summaries", "要約"),
+    ("retry_dead", "失敗ジョブを再試行"),
+    ("purge_dead", "失敗ジョブを削除..."),
+    ("predictor", "予測モデル"),
+    ("sidecar_down", "サイドカー停止中"),
+    ("model_version", "モデル v{0}"),
+    ("model_unknown", "モデル: 不明"),
+    ("trained_ago", "{0}に学習"),
+    ("not_trained", "未学習"),
+    ("training_pairs", "学習ペア {0} 件"),
+    ("last_run_samples", "前回: {0} サンプル"),
+    ("training", "学習中..."),
+    ("retrain_now", "今すぐ再学習"),
+    ("open_dashboard", "ダッシュボードを開く"),
+    ("open_logs", "ログを開く"),
+    ("open_data_dir", "データフォルダを開く"),
+    ("diagnostics_bundle", "診断バンドルを作成..."),
+    ("start_daemon", "デーモンを起動"),
+    ("stop_daemon", "デーモンを停止"),
+    ("restart_daemon", "デーモンを再起動"),
+    ("rotate_token", "トークンを更新"),
+    ("start_at_login", "ログイン時に起動"),
+    ("start_with_signet", "Signet と一緒にデーモンを起動"),
+    ("install_service", "サービスとしてインストール"),
+    ("restart_automatically", "自動的に再起動"),
+    ("profile", "プロファイル: {0}"),
+    ("profile_default", "デフォルト"),
+    ("notifications", "通知"),
+    ("milestones", "マイルストーン"),
+    ("daily_summary", "日次サマリー"),
+    ("menu_bar_title", "メニューバーの表示"),
+    ("title_total", "メモリ総数"),
+    ("title_today", "今日のメモリ"),
+    ("title_hidden", "件数を表示しない"),
+    ("compact_numbers", "短縮表記（12.4k）"),
+    ("language", "言語"),
+    ("language_system", "システム"),
+    ("update_available", "アップデートがあります: v{0}"),
+    ("check_updates", "アップデートを確認..."),
+    ("quit", "Signet を終了"),
+    ("start_alt_port", "ポート {0} でデーモンを起動"),
+    ("pair_daemon", "デーモンと再ペアリング..."),
+    ("just_now", "たった今"),
+    ("minutes_ago", "{0}分前"),
+    ("hours_ago", "{0}時間前"),
+    ("days_ago", "{0}日前"),
+];
@@ -1,11 +1,25 @@
+mod api;
+mod bundle;
 mod commands;
+mod conflict;
 mod daemon;
+mod export;
+mod i18n;
+mod milestones;
 mod platform;
+mod notify;
+mod poller;
+mod profile;
+mod proxy;
+mod settings;
+mod spool;
+mod stats;
+mod tls;
+mod token;
 mod tray;
+mod update;
 
 use tauri::Manager;
-#[cfg(not(debug_assertions))]
-use tauri::{WebviewUrl, WebviewWindowBuilder};
 
 pub fn run() {
     // Wayland compatibility: NVIDIA drivers need explicit sync disabled
@@ -26,6 +40,21 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        // Tell the dashboard where the daemon is before its scripts run.
+        // A later switch is kept in sessionStorage (see retarget_dashboard).
+        .plugin(
+            tauri::plugin::Builder::new("daemon-url")
+                .js_init_script(format!(
+                    "window.__SIGNET_DAEMON_URL__ = sessionStorage.getItem({}) || {};",
+                    serde_json::Value::from(commands::DASHBOARD_URL_KEY),
+                    serde_json::Value::from(settings::daemon_url())
+                ))
+                .build(),
+        )
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             if let Some(win) = app.get_webview_window("main") {
                 let _ = win.show();
@@ -37,6 +66,33 @@ pub fn run() {
             commands::stop_daemon,
             commands::restart_daemon,
             commands::get_daemon_pid,
+            commands::open_logs,
+            commands::open_data_dir,
+            commands::get_memory,
+            commands::pin_memory,
+            commands::update_memory,
+            commands::delete_memory,
+            commands::get_capture_pause,
+            commands::pause_capture,
+            commands::resume_capture,
+            commands::retrain_predictor,
+            commands::backfill_embeddings,
+            commands::get_backfill_progress,
+            commands::get_job_queues,
+            commands::retry_dead_jobs,
+            commands::purge_dead_jobs,
+            commands::copy_to_clipboard,
+            commands::choose_export_path,
+            commands::export_memories,
+            commands::export_progress,
+            commands::cancel_export,
+            commands::quit_export_window,
+            commands::open_memory_window,
+            commands::quit_memory_window,
+            commands::list_profiles,
+            commands::save_profile,
+            commands::remove_profile,
+            commands::switch_profile,
             commands::open_dashboard,
             commands::update_tray,
             commands::quick_capture,
@@ -45,6 +101,42 @@ pub fn run() {
             commands::quit_search_window,
             commands::quit_app,
             commands::check_for_update,
+            commands::get_autostart_status,
+            commands::set_autostart,
+            commands::set_daemon_autostart,
+            commands::get_daemon_url,
+            commands::set_daemon_url,
+            commands::set_daemon_tls,
+            commands::get_proxy,
+            commands::set_proxy,
+            commands::set_dead_job_threshold,
+            commands::create_diagnostics_bundle,
+            commands::start_on_alternate_port,
+            commands::install_service,
+            commands::get_start_on_launch,
+            commands::set_start_on_launch,
+            commands::get_supervision,
+            commands::set_supervision,
+            commands::get_milestone_notifications,
+            commands::set_milestone_notifications,
+            commands::get_daily_summary,
+            commands::set_daily_summary,
+            commands::get_title_display,
+            commands::set_title_display,
+            commands::get_language,
+            commands::set_language,
+            commands::get_available_update,
+            commands::open_update_window,
+            commands::open_release_page,
+            commands::install_update,
+            commands::update_install_progress,
+            commands::quit_update_window,
+            commands::get_update_checks,
+            commands::set_update_checks,
+            commands::get_token_status,
+            commands::rotate_token,
+            commands::pair_with_token,
+            commands::quit_pair_window,
         ])
         .on_window_event(|window, event| {
             if window.label() == "main" {
@@ -55,25 +147,26 @@ pub fn run() {
             }
         })
         .setup(|app| {
+            // Linux login items are opt-in via the menu or set_autostart.
             #[cfg(any(target_os = "macos", target_os = "windows"))]
             platform::autostart::ensure_autostart();
 
             tray::setup(app)?;
+            poller::spawn(app.handle().clone());
+            update::spawn(app.handle().clone());
 
-            // In release builds, a hidden window runs the tray polling JS.
-            #[cfg(not(debug_assertions))]
-            WebviewWindowBuilder::new(app, "tray-worker", WebviewUrl::App("tray.html".into()))
-                .visible(false)
-                .build()?;
-
-            // Auto-start daemon if nothing is listening on the configured port.
-            // Uses a TCP connect probe instead of PID files, which may not exist
-            // when the daemon was started outside the tray app.
-            let port = commands::daemon_port();
-            let daemon_up =
-                std::net::TcpStream::connect(("127.0.0.1", port)).is_ok();
-            if !daemon_up {
-                let _ = daemon::start();
+            // Auto-start daemon if nothing is listening on the configured port
+            // and the user hasn't turned this off. Uses a TCP connect probe
+            // instead of PID files, which may not exist when the daemon was
+            // started outside the tray app. A remote daemon is never started
+            // from here.
+            if settings::start_daemon_on_launch() && settings::daemon_is_local() {
+                let (host, port) = settings::daemon_socket_addr();
+                let daemon_up =
+                    std::net::TcpStream::connect((host.as_str(), port)).is_ok();
+                if !daemon_up {
+                    let _ = daemon::start();
+                }
             }
 
             // Debug: open devtools (also fixes WebKit2GTK input regions on Wayland)
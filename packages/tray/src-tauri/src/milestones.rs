@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::api;
+use crate::commands::PredictorStatus;
+use crate::notify;
+use crate::settings;
+
+/// Memory counts worth a notification
+const MEMORY_MILESTONES: [u64; 4] = [1_000, 10_000, 100_000, 1_000_000];
+
+/// Local hour from which the day's summary is sent
+const DAILY_SUMMARY_HOUR: u32 = 18;
+
+/// Memories fetched per request when summarising the day
+const PAGE_SIZE: u64 = 500;
+
+/// What has already been announced, so restarts don't repeat anything.
+/// `None` means not yet observed: the first observation is recorded
+/// silently, so upgrading the tray doesn't celebrate old achievements.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct State {
+    /// Highest memory milestone reached
+    memory_milestone: Option<u64>,
+    /// Whether the predictor has a trained model
+    predictor_trained: Option<bool>,
+    /// Local date (`YYYY-MM-DD`) of the last daily summary
+    summary_date: Option<String>,
+}
+
+/// Each profile talks to its own daemon, so each keeps its own state.
+fn state_path() -> Option<PathBuf> {
+    let name = match crate::profile::active() {
+        Some(profile) => {
+            let safe: String = profile
+                .name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect();
+            format!("milestones-{}.json", safe)
+        }
+        None => "milestones.json".to_string(),
+    };
+    Some(dirs::data_local_dir()?.join("ai.signet.app").join(name))
+}
+
+impl State {
+    fn load() -> Self {
+        state_path()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let Some(path) = state_path() else { return };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+}
+
+/// Record the memory count, announcing a newly crossed milestone.
+/// Milestones are tracked even while notifications are off, so turning
+/// them on later doesn't announce a stale one.
+pub fn memory_count(app: &AppHandle, total: u64) {
+    let reached = MEMORY_MILESTONES
+        .iter()
+        .rev()
+        .find(|&&m| total >= m)
+        .copied()
+        .unwrap_or(0);
+    let mut state = State::load();
+    let previous = state.memory_milestone;
+    if previous.is_some_and(|p| reached <= p) {
+        return;
+    }
+    state.memory_milestone = Some(reached);
+    state.save();
+    if previous.is_some() && settings::milestone_notifications() {
+        notify::memory_milestone(app, reached);
+    }
+}
+
+/// Record whether the predictor is trained, announcing its first run.
+pub fn predictor(app: &AppHandle, status: Option<&PredictorStatus>) {
+    let Some(status) = status else { return };
+    let trained = status.last_trained.is_some();
+    let mut state = State::load();
+    let previous = state.predictor_trained;
+    if previous == Some(true) || previous == Some(trained) {
+        return;
+    }
+    state.predictor_trained = Some(trained);
+    state.save();
+    if previous == Some(false) && trained && settings::milestone_notifications() {
+        notify::predictor_first_trained(app, status.training_pairs);
+    }
+}
+
+/// Memories captured since local midnight, and the project with the most.
+async fn summarise_today(midnight: DateTime<Local>) -> Result<(u64, Option<(String, u64)>), String> {
+    let mut count = 0;
+    let mut projects: HashMap<String, u64> = HashMap::new();
+    let mut offset = 0;
+    loop {
+        let data = api::get_json(
+            &format!("/api/memories?limit={}&offset={}", PAGE_SIZE, offset),
+            Duration::from_secs(30),
+        )
+        .await?;
+        let page = data["memories"].as_array().cloned().unwrap_or_default();
+        for memory in &page {
+            let created = memory["created_at"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+            // Listed newest first, so the first older one ends the day
+            if !created.is_some_and(|t| t >= midnight) {
+                return Ok((count, top_project(projects)));
+            }
+            count += 1;
+            if let Some(project) = memory["project"].as_str().filter(|p| !p.is_empty()) {
+                *projects.entry(project_name(project)).or_default() += 1;
+            }
+        }
+        if page.len() < PAGE_SIZE as usize {
+            return Ok((count, top_project(projects)));
+        }
+        offset += page.len() as u64;
+    }
+}
+
+fn top_project(projects: HashMap<String, u64>) -> Option<(String, u64)> {
+    projects.into_iter().max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+}
+
+/// Projects are recorded as working-directory paths; show the last part.
+fn project_name(project: &str) -> String {
+    project
+        .trim_end_matches(['/', '\\'])
+        .rsplit(['/', '\\'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(project)
+        .to_string()
+}
+
+/// Send the day's summary once per day, on the first poll after
+/// `DAILY_SUMMARY_HOUR`. A day with no captures is skipped quietly.
+pub async fn daily_summary(app: &AppHandle) {
+    if !settings::daily_summary() {
+        return;
+    }
+    let now = Local::now();
+    if now.hour() < DAILY_SUMMARY_HOUR {
+        return;
+    }
+    let today = now.format("%Y-%m-%d").to_string();
+    let mut state = State::load();
+    if state.summary_date.as_deref() == Some(today.as_str()) {
+        return;
+    }
+    let Some(midnight) = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| Local.from_local_datetime(&t).earliest())
+    else {
+        return;
+    };
+    // Left unmarked on failure so the next poll tries again
+    let Ok((count, top)) = summarise_today(midnight).await else {
+        return;
+    };
+    state.summary_date = Some(today);
+    state.save();
+    if count > 0 {
+        notify::daily_summary(app, count, top);
+    }
+}
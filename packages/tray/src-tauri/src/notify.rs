@@ -0,0 +1,250 @@
+use std::path::PathBuf;
+
+use tauri::AppHandle;
+
+/// The daemon's log directory: `SIGNET_LOG_DIR`, else
+/// `<data dir>/.daemon/logs`.
+pub(crate) fn log_dir() -> Option<PathBuf> {
+    match std::env::var_os("SIGNET_LOG_DIR") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => Some(crate::settings::daemon_dir()?.join("logs")),
+    }
+}
+
+/// The log most likely to explain a crash: `SIGNET_LOG_FILE` if set, else
+/// today's `signet-YYYY-MM-DD.log`, else the log directory itself.
+pub(crate) fn log_target() -> Option<PathBuf> {
+    if let Some(file) = std::env::var_os("SIGNET_LOG_FILE").filter(|f| !f.is_empty()) {
+        return Some(PathBuf::from(file));
+    }
+    let dir = log_dir()?;
+    let today = dir.join(format!(
+        "signet-{}.log",
+        chrono::Local::now().format("%Y-%m-%d")
+    ));
+    Some(if today.exists() { today } else { dir })
+}
+
+/// Open the daemon log in the system's default viewer.
+pub fn open_logs(app: &AppHandle) -> Result<(), String> {
+    use tauri_plugin_shell::ShellExt;
+
+    let target = log_target().ok_or("no home directory")?;
+    #[allow(deprecated)]
+    let opened = app.shell().open(target.to_string_lossy().to_string(), None);
+    opened.map_err(|e| e.to_string())
+}
+
+/// Open the daemon's data directory in the system file manager.
+pub fn open_data_dir(app: &AppHandle) -> Result<(), String> {
+    use tauri_plugin_shell::ShellExt;
+
+    let dir = crate::settings::data_dir().ok_or("no home directory")?;
+    #[allow(deprecated)]
+    let opened = app.shell().open(dir.to_string_lossy().to_string(), None);
+    opened.map_err(|e| e.to_string())
+}
+
+/// Show a notification whose click opens the daemon log. On Linux the
+/// click is wired through notify-rust directly, since the notification
+/// plugin has no click callback on desktop; elsewhere the notification is
+/// informational and the menu's "Open Logs" item does the rest.
+#[cfg(target_os = "linux")]
+fn show_with_logs(app: &AppHandle, title: &str, body: &str) {
+    let shown = notify_rust::Notification::new()
+        .appname("Signet")
+        .summary(title)
+        .body(body)
+        .icon("signet")
+        .action("default", "Open Logs")
+        .show();
+    let Ok(handle) = shown else { return };
+    let app = app.clone();
+    // wait_for_action blocks until the notification is clicked or closed
+    std::thread::spawn(move || {
+        handle.wait_for_action(|action| {
+            if action == "default" {
+                let _ = open_logs(&app);
+            }
+        });
+    });
+}
+
+#[cfg(not(target_os = "linux"))]
+fn show_with_logs(app: &AppHandle, title: &str, body: &str) {
+    show(app, title, body);
+}
+
+fn show(app: &AppHandle, title: &str, body: &str) {
+    use tauri_plugin_notification::NotificationExt;
+
+    let _ = app.notification().builder().title(title).body(body).show();
+}
+
+pub fn daemon_stopped_unexpectedly(app: &AppHandle) {
+    show_with_logs(
+        app,
+        "Signet daemon stopped",
+        "The daemon stopped unexpectedly. Click to open the logs.",
+    );
+}
+
+pub fn daemon_recovered(app: &AppHandle) {
+    show_with_logs(app, "Signet daemon is back", "The daemon is running again.");
+}
+
+pub fn daemon_failed_to_start(app: &AppHandle, attempts: u32) {
+    let body = if attempts == 1 {
+        "The daemon did not come up. Click to open the logs.".to_string()
+    } else {
+        format!(
+            "The daemon did not come up after {} attempts. Click to open the logs.",
+            attempts
+        )
+    };
+    show_with_logs(app, "Signet daemon failed to start", &body);
+}
+
+pub fn diagnostics_bundle_failed(app: &AppHandle, error: &str) {
+    show(app, "Could not create diagnostics bundle", error);
+}
+
+pub fn service_install_failed(app: &AppHandle, error: &str) {
+    show(app, "Could not install the Signet service", error);
+}
+
+pub fn capture_pause_failed(app: &AppHandle, error: &str) {
+    show(app, "Could not change memory capture", error);
+}
+
+pub fn export_finished(app: &AppHandle, count: u64, path: &std::path::Path) {
+    let noun = if count == 1 { "memory" } else { "memories" };
+    show(
+        app,
+        "Export complete",
+        &format!("{} {} written to {}", count, noun, path.display()),
+    );
+}
+
+pub fn predictor_trained(app: &AppHandle, outcome: &crate::commands::TrainOutcome) {
+    let loss = outcome
+        .loss
+        .map(|l| format!(", loss {:.3}", l))
+        .unwrap_or_default();
+    show(
+        app,
+        "Predictor retrained",
+        &format!(
+            "Model v{} trained on {} samples{}.",
+            outcome.model_version, outcome.samples_used, loss
+        ),
+    );
+}
+
+pub fn predictor_canary_failed(app: &AppHandle) {
+    show_with_logs(
+        app,
+        "Predictor failed its canary checks",
+        "The new model failed its canary checks (flat scores or an unstable top 5). \
+         Click to open the logs.",
+    );
+}
+
+pub fn predictor_train_failed(app: &AppHandle, error: &str) {
+    show(app, "Predictor retrain failed", error);
+}
+
+pub fn embeddings_backfilled(app: &AppHandle, written: u64, detail: &str) {
+    let noun = if written == 1 { "memory" } else { "memories" };
+    show(
+        app,
+        &format!("Embedded {} {}", written, noun),
+        detail,
+    );
+}
+
+pub fn embedding_backfill_failed(app: &AppHandle, error: &str) {
+    show_with_logs(app, "Embedding backfill failed", error);
+}
+
+pub fn dead_jobs_retried(app: &AppHandle, count: u64) {
+    let noun = if count == 1 { "job" } else { "jobs" };
+    show(
+        app,
+        &format!("Retrying {} dead {}", count, noun),
+        "They are back in the queue and will be processed shortly.",
+    );
+}
+
+pub fn dead_jobs_purged(app: &AppHandle, count: u64) {
+    let noun = if count == 1 { "job" } else { "jobs" };
+    show(
+        app,
+        &format!("Purged {} dead {}", count, noun),
+        "They have been removed from the queue.",
+    );
+}
+
+pub fn queue_action_failed(app: &AppHandle, error: &str) {
+    show_with_logs(app, "Queue action failed", error);
+}
+
+pub fn memory_milestone(app: &AppHandle, count: u64) {
+    show(
+        app,
+        &format!("{} memories", crate::tray::format_number(count)),
+        "Signet has passed another milestone.",
+    );
+}
+
+pub fn predictor_first_trained(app: &AppHandle, pairs: Option<u64>) {
+    let body = match pairs {
+        Some(pairs) => format!(
+            "The predictor finished its first training run on {} pairs and now helps rank recall.",
+            crate::tray::format_number(pairs)
+        ),
+        None => "The predictor finished its first training run and now helps rank recall.".to_string(),
+    };
+    show(app, "Predictor trained", &body);
+}
+
+pub fn daily_summary(app: &AppHandle, count: u64, top_project: Option<(String, u64)>) {
+    let noun = if count == 1 { "memory" } else { "memories" };
+    let mut body = format!("Captured {} {} today.", crate::tray::format_number(count), noun);
+    if let Some((project, in_project)) = top_project {
+        body.push_str(&format!(" Most were in {} ({}).", project, in_project));
+    }
+    show(app, "Today in Signet", &body);
+}
+
+pub fn update_available(app: &AppHandle, version: &str) {
+    show(
+        app,
+        &format!("Signet v{} is available", version),
+        "Choose \"Update Available\" in the tray menu to see what's new.",
+    );
+}
+
+pub fn up_to_date(app: &AppHandle, version: &str) {
+    show(app, "Signet is up to date", &format!("v{} is the latest version.", version));
+}
+
+pub fn update_check_failed(app: &AppHandle, error: &str) {
+    show(app, "Could not check for updates", error);
+}
+
+pub fn memory_copied(app: &AppHandle, preview: &str) {
+    show(app, "Memory copied", preview);
+}
+
+pub fn dead_jobs_over_threshold(app: &AppHandle, dead: u64, threshold: u64) {
+    show(
+        app,
+        "Signet has dead jobs",
+        &format!(
+            "{} extraction/summary jobs have failed permanently (threshold {}). \
+             Retry or purge them from the Queue menu.",
+            dead, threshold
+        ),
+    );
+}
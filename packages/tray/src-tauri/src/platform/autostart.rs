@@ -1,7 +1,10 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use objc2::runtime::AnyClass;
+use objc2_service_management::{SMAppService, SMAppServiceStatus};
+
 const LAUNCHD_LABEL: &str = "ai.signet.tray";
 const PLIST_FILENAME: &str = "ai.signet.tray.plist";
 
@@ -21,6 +24,21 @@ fn app_bundle_path() -> Option<PathBuf> {
     }
 }
 
+/// Whether macOS is running the app from a randomized, read-only copy
+/// (Gatekeeper path randomization, for apps opened straight from a
+/// download). That path is gone after a reboot.
+fn is_translocated(app_path: &Path) -> bool {
+    app_path.to_string_lossy().contains("/AppTranslocation/")
+}
+
+/// The app's own login item, on macOS 13 and later. Unlike a LaunchAgent
+/// it is tracked by bundle, so it survives the app moving and shows up
+/// under the app's name in System Settings > Login Items.
+fn login_item() -> Option<objc2::rc::Retained<SMAppService>> {
+    AnyClass::get(c"SMAppService")?;
+    Some(unsafe { SMAppService::mainAppService() })
+}
+
 fn launch_agents_dir() -> Option<PathBuf> {
     let home = dirs::home_dir()?;
     Some(home.join("Library/LaunchAgents"))
@@ -54,14 +72,38 @@ fn generate_plist(app_path: &str) -> String {
     )
 }
 
-/// Install LaunchAgent plist if not present, or update if app has moved.
-/// No-ops if not running inside a .app bundle.
+/// Register the tray as a login item: with SMAppService where available,
+/// falling back to a LaunchAgent plist on older macOS. No-ops if not
+/// running inside a .app bundle.
 pub fn ensure_autostart() {
     let Some(app_path) = app_bundle_path() else {
         // Not in a .app bundle (dev mode) — skip
         return;
     };
 
+    if let Some(item) = login_item() {
+        let status = unsafe { item.status() };
+        // Turned off in System Settings: leave the user's choice alone
+        if status == SMAppServiceStatus::RequiresApproval {
+            return;
+        }
+        let registered = status == SMAppServiceStatus::Enabled
+            || unsafe { item.registerAndReturnError() }.is_ok();
+        if registered {
+            // A LaunchAgent from an older version would launch a second copy
+            remove_plist();
+            return;
+        }
+    }
+
+    if is_translocated(&app_path) {
+        return;
+    }
+    ensure_plist(&app_path);
+}
+
+/// Install LaunchAgent plist if not present, or update if app has moved.
+fn ensure_plist(app_path: &Path) {
     let app_str = app_path.to_string_lossy().to_string();
     let Some(plist) = plist_path() else { return };
 
@@ -92,8 +134,18 @@ pub fn ensure_autostart() {
     }
 }
 
-/// Remove LaunchAgent plist and unload the service.
+/// Unregister the login item and remove any LaunchAgent plist.
 pub fn remove_autostart() {
+    if let Some(item) = login_item() {
+        if unsafe { item.status() } != SMAppServiceStatus::NotRegistered {
+            let _ = unsafe { item.unregisterAndReturnError() };
+        }
+    }
+    remove_plist();
+}
+
+/// Remove LaunchAgent plist and unload the service.
+fn remove_plist() {
     let Some(plist) = plist_path() else { return };
 
     if plist.exists() {
@@ -104,7 +156,10 @@ pub fn remove_autostart() {
     }
 }
 
-/// Check if the autostart plist exists.
+/// Whether the tray launches at login: its login item is enabled, or
+/// the autostart plist exists.
 pub fn is_autostart_enabled() -> bool {
-    plist_path().map(|p| p.exists()).unwrap_or(false)
+    let registered =
+        login_item().is_some_and(|item| unsafe { item.status() } == SMAppServiceStatus::Enabled);
+    registered || plist_path().map(|p| p.exists()).unwrap_or(false)
 }
@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const DESKTOP_FILENAME: &str = "signet.desktop";
+const SERVICE_NAME: &str = "signet.service";
+
+/// XDG autostart directory, honouring $XDG_CONFIG_HOME.
+fn autostart_dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("autostart"))
+}
+
+fn desktop_entry_path() -> Option<PathBuf> {
+    Some(autostart_dir()?.join(DESKTOP_FILENAME))
+}
+
+/// Path to launch at login. Inside an AppImage, current_exe() points into
+/// a temporary mount, so prefer the image itself.
+fn launch_path() -> Option<String> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Some(appimage.to_string_lossy().to_string());
+    }
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.to_string_lossy().to_string())
+}
+
+/// Quote an Exec argument per the Desktop Entry spec.
+fn quote_exec_arg(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn generate_desktop_entry(exe: &str) -> String {
+    format!(
+        "[Desktop Entry]
+Type=Application
+Name=Signet
+Comment=Signet memory daemon tray
+Exec={}
+Icon=signet
+Terminal=false
+X-GNOME-Autostart-enabled=true
+",
+        quote_exec_arg(exe)
+    )
+}
+
+/// Write the XDG autostart entry, or rewrite it if the app has moved.
+pub fn ensure_autostart() {
+    let Some(exe) = launch_path() else { return };
+    let Some(entry) = desktop_entry_path() else { return };
+
+    let content = generate_desktop_entry(&exe);
+    if fs::read_to_string(&entry).is_ok_and(|existing| existing == content) {
+        return;
+    }
+
+    if let Some(dir) = autostart_dir() {
+        let _ = fs::create_dir_all(&dir);
+    }
+    let _ = fs::write(&entry, content);
+}
+
+/// Remove the XDG autostart entry.
+pub fn remove_autostart() {
+    if let Some(entry) = desktop_entry_path() {
+        let _ = fs::remove_file(entry);
+    }
+}
+
+/// Check if the autostart entry exists and is not hidden.
+pub fn is_autostart_enabled() -> bool {
+    desktop_entry_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .map(|content| !content.lines().any(|l| l.trim() == "Hidden=true"))
+        .unwrap_or(false)
+}
+
+fn systemctl_user(action: &str) -> Result<(), String> {
+    let output = Command::new("systemctl")
+        .args(["--user", action, SERVICE_NAME])
+        .output()
+        .map_err(|e| format!("systemctl not available: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("systemctl {action} failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Enable the daemon's systemd user unit so it starts with the session.
+pub fn enable_daemon_service() -> Result<(), String> {
+    systemctl_user("enable")
+}
+
+/// Disable the daemon's systemd user unit. Does not stop a running daemon.
+pub fn disable_daemon_service() -> Result<(), String> {
+    systemctl_user("disable")
+}
+
+/// Check if the daemon's systemd user unit is enabled.
+pub fn is_daemon_service_enabled() -> bool {
+    Command::new("systemctl")
+        .args(["--user", "is-enabled", "--quiet", SERVICE_NAME])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
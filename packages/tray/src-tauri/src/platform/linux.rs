@@ -5,9 +5,10 @@ use super::DaemonManager;
 pub struct LinuxManager;
 
 impl LinuxManager {
+    /// The unit runs the default daemon, so a profile's daemon is always
+    /// managed directly.
     fn systemd_unit_exists(&self) -> bool {
-        let home = dirs::home_dir().unwrap_or_default();
-        home.join(".config/systemd/user/signet.service").exists()
+        crate::settings::service_installed()
     }
 
     fn find_bun(&self) -> Option<String> {
@@ -62,6 +63,18 @@ impl LinuxManager {
 
         None
     }
+
+    /// The script the unit should run: the global `signet-daemon`, else a
+    /// known daemon.js.
+    fn find_daemon_entry(&self) -> Option<String> {
+        Command::new("which")
+            .arg("signet-daemon")
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .or_else(|| self.find_daemon_js())
+    }
 }
 
 impl DaemonManager for LinuxManager {
@@ -90,6 +103,7 @@ impl DaemonManager for LinuxManager {
                 let bin = String::from_utf8_lossy(&output.stdout).trim().to_string();
                 Command::new(&bun)
                     .arg(&bin)
+                    .envs(crate::settings::daemon_spawn_env())
                     .spawn()?;
                 return Ok(());
             }
@@ -99,6 +113,7 @@ impl DaemonManager for LinuxManager {
         if let Some(daemon_js) = self.find_daemon_js() {
             Command::new(&bun)
                 .arg(&daemon_js)
+                .envs(crate::settings::daemon_spawn_env())
                 .spawn()?;
             return Ok(());
         }
@@ -106,6 +121,7 @@ impl DaemonManager for LinuxManager {
         // Last resort: bunx
         Command::new(&bun)
             .args(["x", "signetai", "daemon", "start"])
+            .envs(crate::settings::daemon_spawn_env())
             .spawn()?;
 
         Ok(())
@@ -126,8 +142,9 @@ impl DaemonManager for LinuxManager {
         }
 
         // Direct process: read PID file, send SIGTERM
-        let home = dirs::home_dir().ok_or("no home dir")?;
-        let pid_path = home.join(".agents/.daemon/pid");
+        let pid_path = crate::settings::daemon_dir()
+            .ok_or("no home dir")?
+            .join("pid");
 
         if !pid_path.exists() {
             return Ok(()); // Already stopped
@@ -162,12 +179,12 @@ impl DaemonManager for LinuxManager {
                 .unwrap_or(false);
         }
 
-        let home = match dirs::home_dir() {
-            Some(h) => h,
+        let dir = match crate::settings::daemon_dir() {
+            Some(d) => d,
             None => return false,
         };
 
-        let pid_path = home.join(".agents/.daemon/pid");
+        let pid_path = dir.join("pid");
         if !pid_path.exists() {
             return false;
         }
@@ -183,4 +200,64 @@ impl DaemonManager for LinuxManager {
             Err(_) => false,
         }
     }
+
+    fn install_service(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let home = dirs::home_dir().ok_or("no home dir")?;
+        let bun = self
+            .find_bun()
+            .ok_or("bun not found — install bun to run signet daemon")?;
+        let entry = self
+            .find_daemon_entry()
+            .ok_or("signet daemon not found — install signetai globally first")?;
+        let agents = crate::settings::data_dir().ok_or("no home dir")?;
+        let logs = agents.join(".daemon/logs");
+        let (_, port) = crate::settings::daemon_socket_addr();
+
+        // Same unit `signet daemon install` writes
+        let unit = format!(
+            "[Unit]\n\
+             Description=Signet Daemon\n\
+             After=network.target\n\
+             \n\
+             [Service]\n\
+             Type=simple\n\
+             ExecStart={bun} {entry}\n\
+             Environment=SIGNET_PORT={port}\n\
+             Environment=SIGNET_PATH={agents}\n\
+             WorkingDirectory={agents}\n\
+             Restart=always\n\
+             RestartSec=5\n\
+             \n\
+             StandardOutput=append:{logs}/daemon.out.log\n\
+             StandardError=append:{logs}/daemon.err.log\n\
+             \n\
+             [Install]\n\
+             WantedBy=default.target\n",
+            agents = agents.display(),
+            logs = logs.display(),
+        );
+
+        // A directly spawned daemon would hold the port the unit needs
+        if !self.systemd_unit_exists() {
+            self.stop()?;
+        }
+
+        let unit_dir = home.join(".config/systemd/user");
+        std::fs::create_dir_all(&unit_dir)?;
+        std::fs::create_dir_all(&logs)?;
+        std::fs::write(unit_dir.join("signet.service"), unit)?;
+
+        for args in [
+            &["--user", "daemon-reload"][..],
+            &["--user", "enable", "signet.service"],
+            &["--user", "restart", "signet.service"],
+        ] {
+            let output = Command::new("systemctl").args(args).output()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("systemctl {} failed: {stderr}", args[1]).into());
+            }
+        }
+        Ok(())
+    }
 }
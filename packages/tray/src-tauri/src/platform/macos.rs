@@ -7,12 +7,10 @@ pub struct MacosManager;
 const LAUNCHD_LABEL: &str = "ai.signet.daemon";
 
 impl MacosManager {
-    /// Check if a launchd plist exists for the signet daemon.
+    /// Check if a launchd plist exists for the signet daemon. It runs the
+    /// default daemon, so a profile's daemon is always managed directly.
     fn launchd_plist_exists(&self) -> bool {
-        let home = dirs::home_dir().unwrap_or_default();
-        home.join("Library/LaunchAgents")
-            .join(format!("{LAUNCHD_LABEL}.plist"))
-            .exists()
+        crate::settings::service_installed()
     }
 
     /// Check if the launchd service is currently loaded.
@@ -151,6 +149,7 @@ impl DaemonManager for MacosManager {
         if let Some(signet) = self.find_signet_cli() {
             Command::new(&signet)
                 .args(["daemon", "start"])
+                .envs(crate::settings::daemon_spawn_env())
                 .spawn()?;
             return Ok(());
         }
@@ -164,6 +163,7 @@ impl DaemonManager for MacosManager {
         if let Some(daemon_js) = self.find_daemon_js() {
             Command::new(&bun)
                 .arg(&daemon_js)
+                .envs(crate::settings::daemon_spawn_env())
                 .spawn()?;
             return Ok(());
         }
@@ -171,6 +171,7 @@ impl DaemonManager for MacosManager {
         // Last resort: bunx
         Command::new(&bun)
             .args(["x", "signetai", "daemon", "start"])
+            .envs(crate::settings::daemon_spawn_env())
             .spawn()?;
 
         Ok(())
@@ -198,8 +199,9 @@ impl DaemonManager for MacosManager {
         }
 
         // Direct process: read PID file, send SIGTERM
-        let home = dirs::home_dir().ok_or("no home dir")?;
-        let pid_path = home.join(".agents/.daemon/pid");
+        let pid_path = crate::settings::daemon_dir()
+            .ok_or("no home dir")?
+            .join("pid");
 
         if !pid_path.exists() {
             return Ok(()); // Already stopped
@@ -237,11 +239,11 @@ impl DaemonManager for MacosManager {
         // Check launchd first
         if self.launchd_plist_exists() && self.launchd_is_loaded() {
             // Service is loaded — check if the PID is alive
-            let home = match dirs::home_dir() {
-                Some(h) => h,
+            let dir = match crate::settings::daemon_dir() {
+                Some(d) => d,
                 None => return false,
             };
-            let pid_path = home.join(".agents/.daemon/pid");
+            let pid_path = dir.join("pid");
             if let Ok(content) = std::fs::read_to_string(&pid_path) {
                 if let Ok(pid) = content.trim().parse::<i32>() {
                     return Self::process_alive(pid);
@@ -252,12 +254,12 @@ impl DaemonManager for MacosManager {
         }
 
         // Fall back to PID file check
-        let home = match dirs::home_dir() {
-            Some(h) => h,
+        let dir = match crate::settings::daemon_dir() {
+            Some(d) => d,
             None => return false,
         };
 
-        let pid_path = home.join(".agents/.daemon/pid");
+        let pid_path = dir.join("pid");
         if !pid_path.exists() {
             return false;
         }
@@ -273,4 +275,75 @@ impl DaemonManager for MacosManager {
             Err(_) => false,
         }
     }
+
+    fn install_service(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let home = dirs::home_dir().ok_or("no home dir")?;
+        let bun = self
+            .find_bun()
+            .ok_or("bun not found — install bun to run signet daemon")?;
+        let daemon_js = self
+            .find_daemon_js()
+            .ok_or("signet daemon not found — install signetai globally first")?;
+        let agents = crate::settings::data_dir().ok_or("no home dir")?;
+        let logs = agents.join(".daemon/logs");
+        let (_, port) = crate::settings::daemon_socket_addr();
+
+        // Same agent `signet daemon install` writes
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{LAUNCHD_LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{bun}</string>
+        <string>{daemon_js}</string>
+    </array>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>SIGNET_PORT</key>
+        <string>{port}</string>
+        <key>SIGNET_PATH</key>
+        <string>{agents}</string>
+        <key>PATH</key>
+        <string>/usr/local/bin:/opt/homebrew/bin:/usr/bin:/bin</string>
+    </dict>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>StandardOutPath</key>
+    <string>{logs}/daemon.out.log</string>
+    <key>StandardErrorPath</key>
+    <string>{logs}/daemon.err.log</string>
+    <key>WorkingDirectory</key>
+    <string>{agents}</string>
+</dict>
+</plist>
+"#,
+            agents = agents.display(),
+            logs = logs.display(),
+        );
+
+        // Unloads an existing agent, or stops a directly spawned daemon
+        // that would hold the port
+        self.stop()?;
+
+        let agent_dir = home.join("Library/LaunchAgents");
+        std::fs::create_dir_all(&agent_dir)?;
+        std::fs::create_dir_all(&logs)?;
+        let path = agent_dir.join(format!("{LAUNCHD_LABEL}.plist"));
+        std::fs::write(&path, plist)?;
+
+        let output = Command::new("launchctl")
+            .args(["load", &path.to_string_lossy()])
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("launchctl load failed: {stderr}").into());
+        }
+        Ok(())
+    }
 }
@@ -2,6 +2,9 @@ pub trait DaemonManager {
     fn start(&self) -> Result<(), Box<dyn std::error::Error>>;
     fn stop(&self) -> Result<(), Box<dyn std::error::Error>>;
     fn is_running(&self) -> bool;
+    /// Write and enable the platform's per-user service for the daemon,
+    /// so it is managed by systemd or launchd instead of spawned directly.
+    fn install_service(&self) -> Result<(), Box<dyn std::error::Error>>;
 }
 
 #[cfg(target_os = "linux")]
@@ -18,6 +21,10 @@ pub mod autostart;
 #[path = "autostart_windows.rs"]
 pub mod autostart;
 
+#[cfg(target_os = "linux")]
+#[path = "autostart_linux.rs"]
+pub mod autostart;
+
 pub fn create_manager() -> Box<dyn DaemonManager> {
     #[cfg(target_os = "linux")]
     { Box::new(linux::LinuxManager) }
@@ -73,8 +73,7 @@ impl WindowsManager {
     }
 
     fn read_pid(&self) -> Option<u32> {
-        let home = dirs::home_dir()?;
-        let pid_path = home.join(".agents/.daemon/pid");
+        let pid_path = crate::settings::daemon_dir()?.join("pid");
         let content = std::fs::read_to_string(&pid_path).ok()?;
         content.trim().parse().ok()
     }
@@ -124,6 +123,7 @@ impl DaemonManager for WindowsManager {
             Command::new(&signet)
                 .args(["daemon", "start"])
                 .creation_flags(CREATE_NO_WINDOW)
+                .envs(crate::settings::daemon_spawn_env())
                 .spawn()?;
             return Ok(());
         }
@@ -138,6 +138,7 @@ impl DaemonManager for WindowsManager {
             Command::new(&bun)
                 .arg(&daemon_js)
                 .creation_flags(CREATE_NO_WINDOW)
+                .envs(crate::settings::daemon_spawn_env())
                 .spawn()?;
             return Ok(());
         }
@@ -146,6 +147,7 @@ impl DaemonManager for WindowsManager {
         Command::new(&bun)
             .args(["x", "signetai", "daemon", "start"])
             .creation_flags(CREATE_NO_WINDOW)
+            .envs(crate::settings::daemon_spawn_env())
             .spawn()?;
 
         Ok(())
@@ -168,8 +170,8 @@ impl DaemonManager for WindowsManager {
         }
 
         // Clean up PID file
-        if let Some(home) = dirs::home_dir() {
-            let pid_path = home.join(".agents/.daemon/pid");
+        if let Some(dir) = crate::settings::daemon_dir() {
+            let pid_path = dir.join("pid");
             let _ = std::fs::remove_file(&pid_path);
         }
 
@@ -182,4 +184,8 @@ impl DaemonManager for WindowsManager {
             None => false,
         }
     }
+
+    fn install_service(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Err("Installing the daemon as a service is not supported on Windows".into())
+    }
 }
@@ -0,0 +1,559 @@
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::api;
+use crate::commands::{self, CapturePause, DaemonEvent, JobQueues, PredictorStatus, RecentMemory, TrayState};
+use crate::conflict;
+use crate::daemon::{self, Supervision, Supervisor};
+use crate::milestones;
+use crate::notify;
+use crate::profile;
+use crate::settings;
+use crate::spool;
+use crate::stats::{Collector, ProcessStats};
+use crate::token;
+use crate::tray;
+
+// Poll intervals
+const HEALTH_RUNNING: Duration = Duration::from_secs(5);
+const HEALTH_STOPPED: Duration = Duration::from_secs(2);
+const MEMORIES_EVERY: Duration = Duration::from_secs(15);
+const DIAGNOSTICS_EVERY: Duration = Duration::from_secs(30);
+const EMBEDDINGS_EVERY: Duration = Duration::from_secs(60);
+
+// While the event stream is up, changes arrive as events and polling is
+// only a safety net.
+const HEALTH_STREAMING: Duration = Duration::from_secs(30);
+const REFRESH_STREAMING: Duration = Duration::from_secs(300);
+
+// Reconnect backoff for the event stream
+const EVENTS_RETRY_MIN: Duration = Duration::from_secs(2);
+const EVENTS_RETRY_MAX: Duration = Duration::from_secs(60);
+
+// If the daemon doesn't come alive within 15s of the tray starting it at
+// launch, show an error
+const AUTO_START_TIMEOUT: Duration = Duration::from_secs(15);
+
+// A stop within this long of the tray asking for one is not a crash
+const EXPECTED_STOP_WINDOW: Duration = Duration::from_secs(30);
+
+// How often to try rotating a token that is close to expiry
+const TOKEN_ROTATE_RETRY: Duration = Duration::from_secs(3600);
+
+struct Health {
+    version: String,
+}
+
+struct Memories {
+    recent: Vec<RecentMemory>,
+    total: u64,
+    with_embeddings: u64,
+    critical: u64,
+    today: u64,
+}
+
+struct Diagnostics {
+    health_score: f64,
+    health_status: String,
+    queue_depth: u64,
+}
+
+/// Everything the running menu shows, accumulated across endpoints that
+/// are polled at different rates.
+#[derive(Default)]
+struct Snapshot {
+    health: Option<Health>,
+    memories: Option<Memories>,
+    diagnostics: Option<Diagnostics>,
+    embedding_provider: Option<String>,
+    job_queues: Option<JobQueues>,
+    resources: Option<ProcessStats>,
+    capture_pause: Option<CapturePause>,
+    predictor: Option<PredictorStatus>,
+    /// Memories per hour, smoothed across polls
+    ingestion_rate: Option<f64>,
+    last_count: Option<(u64, Instant)>,
+}
+
+impl Snapshot {
+    /// Forget per-run data when the daemon goes away. The ingestion rate
+    /// carries over, as the TypeScript poller did.
+    fn reset(&mut self) {
+        self.health = None;
+        self.memories = None;
+        self.diagnostics = None;
+        self.embedding_provider = None;
+        self.job_queues = None;
+        self.resources = None;
+        self.capture_pause = None;
+        self.predictor = None;
+    }
+
+    fn record_count(&mut self, total: u64) {
+        let now = Instant::now();
+        if let Some((last, at)) = self.last_count {
+            let hours = now.duration_since(at).as_secs_f64() / 3600.0;
+            if hours > 0.0 && total >= last {
+                // Exponential moving average
+                let instant = (total - last) as f64 / hours;
+                self.ingestion_rate = Some(match self.ingestion_rate {
+                    Some(rate) => 0.3 * instant + 0.7 * rate,
+                    None => instant,
+                });
+            }
+        }
+        self.last_count = Some((total, now));
+    }
+
+    fn state(&self) -> TrayState {
+        let Some(health) = &self.health else {
+            return TrayState::Stopped;
+        };
+        let memories = self.memories.as_ref();
+        let diagnostics = self.diagnostics.as_ref();
+        TrayState::Running {
+            version: health.version.clone(),
+            health_score: diagnostics.map(|d| d.health_score),
+            health_status: diagnostics.map(|d| d.health_status.clone()),
+            memory_count: memories.map(|m| m.total),
+            memories_today: memories.map(|m| m.today),
+            critical_memories: memories.map(|m| m.critical),
+            embedding_coverage: memories
+                .filter(|m| m.total > 0)
+                .map(|m| m.with_embeddings as f64 / m.total as f64),
+            embedding_provider: self.embedding_provider.clone(),
+            queue_depth: diagnostics.map(|d| d.queue_depth),
+            recent_memories: memories.map(|m| m.recent.clone()),
+            ingestion_rate: self.ingestion_rate,
+            job_queues: self.job_queues,
+            resources: self.resources.clone(),
+            capture_pause: self.capture_pause.clone(),
+            predictor: self.predictor.clone(),
+        }
+    }
+}
+
+/// Read `/health`. A response that isn't Signet's (wrong shape, or a 4xx
+/// other than an auth failure) means another program has the port.
+async fn fetch_health() -> Result<Health, String> {
+    let res = api::get("/health", Duration::from_secs(3)).await?;
+    let data: Option<Value> = serde_json::from_slice(&res.body).ok();
+    match data {
+        Some(data) if res.is_success() && conflict::is_signet_health(&data) => Ok(Health {
+            version: data["version"].as_str().unwrap_or("unknown").to_string(),
+        }),
+        _ if matches!(res.status, 401 | 403) || res.status >= 500 => {
+            Err(format!("HTTP {}: {}", res.status, res.text()))
+        }
+        _ => Err(conflict::answered_by_other()),
+    }
+}
+
+fn local_midnight() -> Option<chrono::DateTime<chrono::Local>> {
+    chrono::Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(chrono::Local).single())
+}
+
+/// Count memories created since local midnight from the returned page,
+/// for daemons that don't report `stats.since`. Only the page is seen, so
+/// this is approximate.
+fn count_today(memories: &[RecentMemory]) -> u64 {
+    let Some(midnight) = local_midnight() else {
+        return 0;
+    };
+    memories
+        .iter()
+        .filter_map(|m| chrono::DateTime::parse_from_rfc3339(&m.created_at).ok())
+        .filter(|ts| *ts >= midnight)
+        .count() as u64
+}
+
+async fn fetch_memories() -> Option<Memories> {
+    let path = match local_midnight() {
+        Some(midnight) => format!(
+            "/api/memories?limit=10&since={}",
+            midnight
+                .with_timezone(&chrono::Utc)
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+        ),
+        None => "/api/memories?limit=10".to_string(),
+    };
+    let data = api::get_json(&path, Duration::from_secs(5)).await.ok()?;
+    let recent: Vec<RecentMemory> = data["memories"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .map(|m| RecentMemory {
+                    id: m["id"].as_str().unwrap_or_default().to_string(),
+                    content: m["content"].as_str().unwrap_or_default().to_string(),
+                    created_at: m["created_at"].as_str().unwrap_or_default().to_string(),
+                    who: m["who"].as_str().unwrap_or("unknown").to_string(),
+                    importance: m["importance"].as_f64().unwrap_or(0.0),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let stats = &data["stats"];
+    Some(Memories {
+        today: stats["since"].as_u64().unwrap_or_else(|| count_today(&recent)),
+        recent,
+        total: stats["total"].as_u64().unwrap_or(0),
+        with_embeddings: stats["withEmbeddings"].as_u64().unwrap_or(0),
+        critical: stats["critical"].as_u64().unwrap_or(0),
+    })
+}
+
+async fn fetch_diagnostics() -> Option<Diagnostics> {
+    let data = api::get_json("/api/diagnostics", Duration::from_secs(5)).await.ok()?;
+    Some(Diagnostics {
+        health_score: data["composite"]["score"].as_f64().unwrap_or(0.0),
+        health_status: data["composite"]["status"]
+            .as_str()
+            .unwrap_or("unknown")
+            .to_string(),
+        queue_depth: data["queue"]["depth"].as_u64().unwrap_or(0),
+    })
+}
+
+/// Predictor state plus the sample count of its latest training run.
+/// `None` when the predictor is disabled or the daemon has no predictor.
+async fn fetch_predictor() -> Option<PredictorStatus> {
+    let data = api::get_json("/api/predictor/status", Duration::from_secs(5)).await.ok()?;
+    if !data["enabled"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    let status = &data["status"];
+    let last_run_samples = api::get_json("/api/predictor/training?limit=1", Duration::from_secs(5))
+        .await
+        .ok()
+        .and_then(|runs| runs["items"][0]["sampleCount"].as_u64());
+    Some(PredictorStatus {
+        alive: data["alive"].as_bool().unwrap_or(false),
+        model_version: status["model_version"].as_u64(),
+        last_trained: status["last_trained"].as_str().map(str::to_string),
+        training_pairs: status["training_pairs"].as_u64(),
+        last_run_samples,
+    })
+}
+
+async fn fetch_embedding_provider() -> Option<String> {
+    let data = api::get_json("/api/embeddings/status", Duration::from_secs(5)).await.ok()?;
+    Some(data["provider"].as_str().unwrap_or("unknown").to_string())
+}
+
+/// When each endpoint is next due
+struct Schedule {
+    health: Instant,
+    memories: Instant,
+    diagnostics: Instant,
+    embeddings: Instant,
+}
+
+impl Schedule {
+    fn all_due_now() -> Self {
+        let now = Instant::now();
+        Self {
+            health: now,
+            memories: now,
+            diagnostics: now,
+            embeddings: now,
+        }
+    }
+
+    /// Earliest time anything needs polling. Secondary endpoints only
+    /// count while the daemon is running.
+    fn next_due(&self, running: bool) -> Instant {
+        if running {
+            self.health
+                .min(self.memories)
+                .min(self.diagnostics)
+                .min(self.embeddings)
+        } else {
+            self.health
+        }
+    }
+}
+
+/// Start the background poller. It owns tray state: the menu, title,
+/// tooltip and icon are rebuilt here whenever the polled state changes,
+/// with no webview involved. A second task follows the daemon's event
+/// stream so changes show up without waiting for the next poll.
+pub fn spawn(app: AppHandle) {
+    let (events_tx, events_rx) = tokio::sync::mpsc::unbounded_channel();
+    tauri::async_runtime::spawn(subscribe(events_tx));
+    tauri::async_runtime::spawn(run(app, events_rx));
+}
+
+/// Keep an event stream open, reconnecting with backoff. Every drop is
+/// reported so the poller can fall back to its normal intervals.
+async fn subscribe(events: tokio::sync::mpsc::UnboundedSender<DaemonEvent>) {
+    let mut retry = EVENTS_RETRY_MIN;
+    loop {
+        let opened = Instant::now();
+        let mut switched = false;
+        if let Ok(client) = api::client() {
+            // A profile switch drops the stream so it reconnects to the
+            // new daemon
+            tokio::select! {
+                _ = commands::follow_daemon_events(&client, &commands::daemon_url(), &events) => {}
+                _ = profile::changed() => switched = true,
+            }
+        }
+        if events.send(DaemonEvent::Disconnected).is_err() {
+            return;
+        }
+        if switched {
+            retry = EVENTS_RETRY_MIN;
+            continue;
+        }
+        // A stream that stayed up for a while earns a fast reconnect
+        retry = if opened.elapsed() > EVENTS_RETRY_MAX {
+            EVENTS_RETRY_MIN
+        } else {
+            (retry * 2).min(EVENTS_RETRY_MAX)
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(retry) => {}
+            _ = profile::changed() => retry = EVENTS_RETRY_MIN,
+        }
+    }
+}
+
+async fn run(app: AppHandle, mut events: tokio::sync::mpsc::UnboundedReceiver<DaemonEvent>) {
+    let started = Instant::now();
+    let mut ever_running = false;
+    let mut streaming = false;
+    let mut snapshot = Snapshot::default();
+    // Set while something answers at the daemon URL but can't be used:
+    // its certificate is not trusted, or it isn't Signet
+    let mut unusable: Option<String> = None;
+    let mut last_rotation: Option<Instant> = None;
+    // Set when the daemon went away without being asked to
+    let mut crashed = false;
+    // Start attempt count last reported as failed
+    let mut failure_reported = 0;
+    let mut dead_jobs_alerted = false;
+    let mut supervisor = Supervisor::default();
+    let mut process_stats = Collector::new();
+    let mut schedule = Schedule::all_due_now();
+    // Menus also show the capture spool size, so track it alongside state
+    let mut applied: Option<(TrayState, usize)> = None;
+    let mut profile_seen = profile::generation();
+
+    loop {
+        let now = Instant::now();
+
+        // After a profile switch everything known is about another daemon
+        if profile::generation() != profile_seen {
+            profile_seen = profile::generation();
+            snapshot = Snapshot::default();
+            unusable = None;
+            crashed = false;
+            failure_reported = 0;
+            dead_jobs_alerted = false;
+            daemon::reset_start_attempts();
+            supervisor.reset();
+            process_stats = Collector::new();
+            schedule = Schedule::all_due_now();
+            applied = None;
+        }
+
+        let health_polled = now >= schedule.health;
+        if health_polled {
+            match fetch_health().await {
+                Ok(health) => {
+                    unusable = None;
+                    if snapshot.health.is_none() {
+                        // Just came online — fetch everything right away
+                        schedule = Schedule::all_due_now();
+                        if crashed {
+                            notify::daemon_recovered(&app);
+                            crashed = false;
+                        }
+                        daemon::reset_start_attempts();
+                        supervisor.reset();
+                        failure_reported = 0;
+                        // Auto-show dashboard only on first discovery, not on reconnects
+                        if !ever_running {
+                            let _ = commands::open_dashboard_inner(&app);
+                        }
+                        ever_running = true;
+                    }
+                    snapshot.health = Some(health);
+                }
+                Err(e) => {
+                    let was_running = snapshot.health.is_some();
+                    snapshot.reset();
+                    unusable = (api::is_tls_error(&e) || conflict::is_conflict(&e)).then_some(e);
+                    // A daemon we started that couldn't bind its port
+                    if unusable.is_none()
+                        && settings::daemon_is_local()
+                        && daemon::start_attempts().0 > 0
+                        && conflict::bind_error_logged()
+                    {
+                        unusable = Some(conflict::port_in_use());
+                    }
+                    // A TLS, auth or port failure means something answers but won't talk
+                    let unreachable = unusable.is_none() && !api::auth_rejected();
+                    if was_running
+                        && unreachable
+                        && !daemon::stop_requested_within(EXPECTED_STOP_WINDOW)
+                    {
+                        notify::daemon_stopped_unexpectedly(&app);
+                        crashed = true;
+                    }
+                }
+            }
+
+            // A stop from the user ends the outage as far as supervision goes
+            if daemon::stop_requested_within(EXPECTED_STOP_WINDOW) {
+                crashed = false;
+            }
+
+            // Restart a crashed local daemon
+            let supervising = crashed
+                && snapshot.health.is_none()
+                && unusable.is_none()
+                && settings::supervision_enabled()
+                && settings::daemon_is_local()
+                && supervisor.tick() != Supervision::GaveUp;
+
+            // While restarts are still being tried, hold the failure
+            // notification until supervision gives up
+            if snapshot.health.is_none() && !supervising {
+                let (attempts, last_start) = daemon::start_attempts();
+                let gave_up = last_start.is_some_and(|at| at.elapsed() > AUTO_START_TIMEOUT);
+                if attempts > failure_reported && gave_up {
+                    notify::daemon_failed_to_start(&app, attempts);
+                    failure_reported = attempts;
+                }
+            }
+            schedule.health = now
+                + match (snapshot.health.is_some(), streaming) {
+                    (false, _) => HEALTH_STOPPED,
+                    (true, false) => HEALTH_RUNNING,
+                    (true, true) => HEALTH_STREAMING,
+                };
+        }
+
+        if snapshot.health.is_some() {
+            // Process stats follow the health rate; a remote daemon's
+            // process can't be seen from here
+            if health_polled && settings::daemon_is_local() {
+                snapshot.resources = process_stats.sample();
+            }
+            // The pause indicator follows the health rate too, so a timed
+            // pause clears soon after it ends
+            if health_polled {
+                if let Ok(pause) = commands::fetch_capture_pause().await {
+                    snapshot.capture_pause = pause;
+                }
+            }
+            let every = |polling: Duration| if streaming { REFRESH_STREAMING } else { polling };
+            if now >= schedule.memories {
+                if let Some(memories) = fetch_memories().await {
+                    snapshot.record_count(memories.total);
+                    milestones::memory_count(&app, memories.total);
+                    snapshot.memories = Some(memories);
+                }
+                schedule.memories = now + every(MEMORIES_EVERY);
+            }
+            if now >= schedule.diagnostics {
+                if let Some(diagnostics) = fetch_diagnostics().await {
+                    snapshot.diagnostics = Some(diagnostics);
+                }
+                if let Ok(queues) = commands::fetch_job_queues().await {
+                    snapshot.job_queues = Some(queues);
+                    let dead = queues.dead();
+                    // Notify once per crossing, not on every poll above it
+                    let over = tray::dead_jobs_over_threshold(Some(dead));
+                    if over && !dead_jobs_alerted {
+                        if let Some(threshold) = settings::dead_job_threshold() {
+                            notify::dead_jobs_over_threshold(&app, dead, threshold);
+                        }
+                    }
+                    dead_jobs_alerted = over;
+                }
+                snapshot.predictor = fetch_predictor().await;
+                milestones::predictor(&app, snapshot.predictor.as_ref());
+                milestones::daily_summary(&app).await;
+                schedule.diagnostics = now + every(DIAGNOSTICS_EVERY);
+            }
+            if now >= schedule.embeddings {
+                if let Some(provider) = fetch_embedding_provider().await {
+                    snapshot.embedding_provider = Some(provider);
+                }
+                schedule.embeddings = now + every(EMBEDDINGS_EVERY);
+            }
+
+            if spool::len() > 0 {
+                commands::flush_capture_spool().await;
+            }
+
+            // Rotate the token before it expires rather than waiting for 401s
+            if token::due_for_rotation()
+                && last_rotation.is_none_or(|at| at.elapsed() > TOKEN_ROTATE_RETRY)
+            {
+                last_rotation = Some(now);
+                let _ = commands::rotate_token_inner().await;
+            }
+        }
+
+        let state = if let Some(message) = &unusable {
+            TrayState::Error {
+                message: message.clone(),
+            }
+        } else if api::auth_rejected() {
+            TrayState::Error {
+                message: "Daemon rejected the token — re-pair to reconnect".to_string(),
+            }
+        } else if snapshot.health.is_none()
+            && !ever_running
+            && daemon::start_attempts().0 > 0
+            && started.elapsed() > AUTO_START_TIMEOUT
+        {
+            TrayState::Error {
+                message: "Daemon failed to start within 15 seconds".to_string(),
+            }
+        } else {
+            snapshot.state()
+        };
+
+        // Only touch the tray when something actually changed
+        let queued = spool::len();
+        let changed = applied
+            .as_ref()
+            .is_none_or(|(shown, shown_queued)| *shown != state || *shown_queued != queued);
+        if changed && commands::apply_tray_state(&app, &state).is_ok()
+        {
+            applied = Some((state, queued));
+        }
+
+        let wake = tokio::time::Instant::from_std(schedule.next_due(snapshot.health.is_some()));
+        tokio::select! {
+            _ = tokio::time::sleep_until(wake) => {}
+            _ = profile::changed() => {}
+            Some(event) = events.recv() => {
+                let now = Instant::now();
+                match event {
+                    DaemonEvent::Connected => streaming = true,
+                    DaemonEvent::Disconnected => {
+                        // Events may have been missed; catch up by polling
+                        if streaming {
+                            schedule = Schedule::all_due_now();
+                        }
+                        streaming = false;
+                    }
+                    DaemonEvent::MemoryCreated => schedule.memories = now,
+                    DaemonEvent::JobFinished => schedule.diagnostics = now,
+                    // The pause indicator is read alongside health
+                    DaemonEvent::CaptureStateChanged => schedule.health = now,
+                }
+            }
+        }
+    }
+}
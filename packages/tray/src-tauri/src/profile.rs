@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+/// Bumped on every profile switch, so long-running tasks can tell that
+/// the daemon they were talking to is no longer the active one.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static CHANGED: Notify = Notify::const_new();
+
+/// A named daemon the tray can switch to, e.g. a work and a personal
+/// daemon on different ports with separate data directories.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub daemon_url: String,
+    /// The daemon's `SIGNET_PATH`. Defaults to `~/.agents`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<String>,
+}
+
+impl Profile {
+    /// `data_dir` with a leading `~` expanded.
+    pub fn data_path(&self) -> Option<PathBuf> {
+        let dir = self.data_dir.as_deref()?.trim();
+        match dir.strip_prefix("~/") {
+            Some(rest) => Some(dirs::home_dir()?.join(rest)),
+            None if dir == "~" => dirs::home_dir(),
+            None => Some(PathBuf::from(dir)),
+        }
+    }
+}
+
+/// Every configured profile and which one is active. With no active
+/// profile the tray uses the default daemon, resolved as before profiles
+/// existed.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct Profiles {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<String>,
+    pub profiles: Vec<Profile>,
+}
+
+/// `profiles.json` next to the tray settings file.
+fn profiles_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("ai.signet.app").join("profiles.json"))
+}
+
+impl Profiles {
+    pub fn load() -> Self {
+        profiles_path()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = profiles_path().ok_or("no config directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// The active profile, or `None` for the default daemon.
+pub fn active() -> Option<Profile> {
+    let profiles = Profiles::load();
+    let name = profiles.active?;
+    profiles.profiles.into_iter().find(|p| p.name == name)
+}
+
+/// Make `name` the active profile, or go back to the default daemon with
+/// `None`.
+pub fn switch(name: Option<&str>) -> Result<(), String> {
+    let mut profiles = Profiles::load();
+    if let Some(name) = name {
+        if !profiles.profiles.iter().any(|p| p.name == name) {
+            return Err(format!("No profile named {}", name));
+        }
+    }
+    profiles.active = name.map(str::to_string);
+    profiles.save()?;
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    CHANGED.notify_waiters();
+    Ok(())
+}
+
+/// Add a profile, replacing any existing one with the same name.
+pub fn upsert(mut profile: Profile) -> Result<(), String> {
+    profile.name = profile.name.trim().to_string();
+    if profile.name.is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+    profile.daemon_url = crate::settings::normalize_daemon_url(&profile.daemon_url)?;
+    let mut profiles = Profiles::load();
+    match profiles.profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => profiles.profiles.push(profile),
+    }
+    profiles.save()
+}
+
+/// Remove a profile. Removing the active one switches back to the default
+/// daemon.
+pub fn remove(name: &str) -> Result<(), String> {
+    let mut profiles = Profiles::load();
+    profiles.profiles.retain(|p| p.name != name);
+    let was_active = profiles.active.as_deref() == Some(name);
+    if was_active {
+        profiles.active = None;
+    }
+    profiles.save()?;
+    if was_active {
+        GENERATION.fetch_add(1, Ordering::Relaxed);
+        CHANGED.notify_waiters();
+    }
+    Ok(())
+}
+
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Resolves on the next profile switch.
+pub async fn changed() {
+    CHANGED.notified().await
+}
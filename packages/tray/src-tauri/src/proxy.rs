@@ -0,0 +1,103 @@
+use reqwest::{NoProxy, Proxy};
+
+use crate::settings::TraySettings;
+
+/// Always reached directly: a local daemon must never go through a
+/// corporate proxy, whatever `NO_PROXY` says.
+const LOOPBACK: &str = "localhost,127.0.0.0/8,::1";
+
+/// Proxy options for the tray's HTTP requests.
+#[derive(PartialEq)]
+pub struct ProxyOptions {
+    /// Proxy for http requests
+    pub http: Option<String>,
+    /// Proxy for https requests
+    pub https: Option<String>,
+    /// Comma-separated hosts, domains and CIDR ranges to reach directly
+    pub no_proxy: String,
+    /// The settings turned proxies off
+    pub direct: bool,
+}
+
+/// The first of `names` set to something non-empty in the environment.
+fn env(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+impl ProxyOptions {
+    /// `proxy` and `no_proxy` from the tray settings, falling back to
+    /// `HTTPS_PROXY` / `HTTP_PROXY` / `ALL_PROXY` and `NO_PROXY` (either
+    /// case). A `proxy` of `none` turns proxies off even when the
+    /// environment sets one. Loopback addresses are always excluded.
+    pub fn load() -> Self {
+        let prefs = TraySettings::load();
+        let no_proxy = prefs
+            .no_proxy
+            .or_else(|| env(&["NO_PROXY", "no_proxy"]))
+            .map(|list| format!("{},{}", list, LOOPBACK))
+            .unwrap_or_else(|| LOOPBACK.to_string());
+        match prefs.proxy.as_deref().map(str::trim) {
+            Some(p) if p.eq_ignore_ascii_case("none") => Self {
+                http: None,
+                https: None,
+                no_proxy,
+                direct: true,
+            },
+            Some(p) if !p.is_empty() => Self {
+                http: Some(p.to_string()),
+                https: Some(p.to_string()),
+                no_proxy,
+                direct: false,
+            },
+            _ => {
+                let all = env(&["ALL_PROXY", "all_proxy"]);
+                Self {
+                    http: env(&["HTTP_PROXY", "http_proxy"]).or_else(|| all.clone()),
+                    https: env(&["HTTPS_PROXY", "https_proxy"]).or(all),
+                    no_proxy,
+                    direct: false,
+                }
+            }
+        }
+    }
+}
+
+/// Check that a proxy URL is one `configure` can use.
+pub fn validate(url: &str) -> Result<(), String> {
+    Proxy::all(url)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid proxy {}: {}", url, e))
+}
+
+/// Apply proxy options to a client builder. A configured proxy replaces
+/// the builder's own lookup, so loopback is exempt and the settings win
+/// over the environment. With none configured, the builder keeps the
+/// system proxy it reads on macOS and Windows.
+pub fn configure(
+    builder: reqwest::ClientBuilder,
+    options: &ProxyOptions,
+) -> Result<reqwest::ClientBuilder, String> {
+    if options.direct {
+        return Ok(builder.no_proxy());
+    }
+    if options.http.is_none() && options.https.is_none() {
+        return Ok(builder);
+    }
+    let mut builder = builder.no_proxy();
+    let proxy = |url: &str, make: fn(&str) -> reqwest::Result<Proxy>| {
+        make(url)
+            .map(|p| p.no_proxy(NoProxy::from_string(&options.no_proxy)))
+            .map_err(|e| format!("Invalid proxy {}: {}", url, e))
+    };
+    if let Some(url) = &options.http {
+        builder = builder.proxy(proxy(url, |u| Proxy::http(u))?);
+    }
+    if let Some(url) = &options.https {
+        builder = builder.proxy(proxy(url, |u| Proxy::https(u))?);
+    }
+    Ok(builder)
+}
@@ -0,0 +1,327 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Locale;
+
+const DEFAULT_HOST: &str = "localhost";
+const DEFAULT_PORT: u16 = 3850;
+const DEFAULT_DEAD_JOB_THRESHOLD: u64 = 10;
+
+/// Persisted tray preferences, stored as JSON in the app config directory.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct TraySettings {
+    /// Full daemon URL, e.g. `http://127.0.0.1:4000`. Takes precedence over
+    /// the host and port found in the environment or the daemon's service
+    /// definition.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daemon_url: Option<String>,
+    /// PEM file with an extra CA to trust for an https daemon URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    /// SHA-256 fingerprint of the daemon's certificate to pin.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_cert_sha256: Option<String>,
+    /// Proxy URL for the tray's requests, e.g. `http://proxy:8080`, or
+    /// `none` to connect directly. Overrides `HTTP(S)_PROXY`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Hosts to reach without the proxy, as in `NO_PROXY`. Overrides
+    /// `NO_PROXY`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// Dead jobs (memory + summary) at which the tray warns. 0 disables.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_job_threshold: Option<u64>,
+    /// Start a local daemon when the tray launches, if it isn't already
+    /// running. On by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_daemon_on_launch: Option<bool>,
+    /// Restart a crashed local daemon automatically. On by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supervise_daemon: Option<bool>,
+    /// Notify at memory-count milestones and the predictor's first
+    /// training run. On by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone_notifications: Option<bool>,
+    /// Notify with a summary of the day's captures each evening. Off by
+    /// default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_summary: Option<bool>,
+    /// Which memory count the menu bar title shows. Total by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title_display: Option<TitleDisplay>,
+    /// Abbreviate the title count, e.g. "12.4k". Off by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compact_title: Option<bool>,
+    /// Language of the tray menu. Follows the system language by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<Locale>,
+    /// Check for new releases in the background. On by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_checks: Option<bool>,
+}
+
+/// What the menu bar title next to the icon shows.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleDisplay {
+    /// Total memories
+    #[default]
+    Total,
+    /// Memories created since local midnight
+    Today,
+    /// No count; only status badges
+    Hidden,
+}
+
+/// `~/.config/ai.signet.app/settings.json` on Linux, and the platform
+/// equivalent elsewhere (same directory Tauri uses for the app identifier).
+fn settings_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("ai.signet.app").join("settings.json"))
+}
+
+impl TraySettings {
+    /// Read settings, falling back to defaults if the file is missing or
+    /// unreadable.
+    pub fn load() -> Self {
+        settings_path()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = settings_path().ok_or("no config directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// Dead-job count that triggers a warning, or `None` if warnings are off.
+pub fn dead_job_threshold() -> Option<u64> {
+    let threshold = TraySettings::load()
+        .dead_job_threshold
+        .unwrap_or(DEFAULT_DEAD_JOB_THRESHOLD);
+    (threshold > 0).then_some(threshold)
+}
+
+/// Whether the tray starts the daemon when it launches.
+pub fn start_daemon_on_launch() -> bool {
+    TraySettings::load().start_daemon_on_launch.unwrap_or(true)
+}
+
+/// Whether the tray restarts a crashed daemon on its own.
+pub fn supervision_enabled() -> bool {
+    TraySettings::load().supervise_daemon.unwrap_or(true)
+}
+
+/// Whether milestone notifications are shown.
+pub fn milestone_notifications() -> bool {
+    TraySettings::load().milestone_notifications.unwrap_or(true)
+}
+
+/// Whether the end-of-day summary notification is shown.
+pub fn daily_summary() -> bool {
+    TraySettings::load().daily_summary.unwrap_or(false)
+}
+
+/// Which count the menu bar title shows.
+pub fn title_display() -> TitleDisplay {
+    TraySettings::load().title_display.unwrap_or_default()
+}
+
+/// Whether the title count is abbreviated.
+pub fn compact_title() -> bool {
+    TraySettings::load().compact_title.unwrap_or(false)
+}
+
+/// Whether the tray checks for new releases in the background.
+pub fn update_checks() -> bool {
+    TraySettings::load().update_checks.unwrap_or(true)
+}
+
+/// The chosen menu language, or `None` to follow the system.
+pub fn language() -> Option<Locale> {
+    TraySettings::load().language
+}
+
+/// Validate a daemon URL and strip any trailing slash. Only http(s) URLs
+/// with a host are accepted.
+pub fn normalize_daemon_url(raw: &str) -> Result<String, String> {
+    let parsed = url::Url::parse(raw.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Unsupported scheme: {}", parsed.scheme()));
+    }
+    if parsed.host_str().is_none() {
+        return Err("URL has no host".to_string());
+    }
+    Ok(parsed.as_str().trim_end_matches('/').to_string())
+}
+
+/// Read a variable from the environment block of the daemon's service
+/// definition (systemd user unit or launchd plist), which is where
+/// `signet daemon install` records a non-default port.
+#[cfg(target_os = "linux")]
+fn service_file_env(name: &str) -> Option<String> {
+    let unit = dirs::config_dir()?.join("systemd/user/signet.service");
+    let content = std::fs::read_to_string(unit).ok()?;
+    let prefix = format!("{}=", name);
+    // Later assignments win, as in systemd
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Environment="))
+        .flat_map(|assignments| assignments.split_whitespace())
+        .filter_map(|a| a.trim_matches('"').strip_prefix(prefix.as_str()))
+        .last()
+        .map(str::to_string)
+}
+
+#[cfg(target_os = "macos")]
+fn service_file_env(name: &str) -> Option<String> {
+    let plist = dirs::home_dir()?.join("Library/LaunchAgents/ai.signet.daemon.plist");
+    let content = std::fs::read_to_string(plist).ok()?;
+    let key = format!("<key>{}</key>", name);
+    let after = &content[content.find(&key)? + key.len()..];
+    let start = after.find("<string>")? + "<string>".len();
+    let end = after[start..].find("</string>")?;
+    Some(after[start..start + end].trim().to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn service_file_env(_name: &str) -> Option<String> {
+    None
+}
+
+/// The service definition describes the default daemon only, so it is
+/// ignored while a profile is active.
+fn service_env(name: &str) -> Option<String> {
+    if crate::profile::active().is_some() {
+        return None;
+    }
+    service_file_env(name)
+}
+
+/// Whether the daemon runs as a systemd user unit or launchd agent. The
+/// service belongs to the default daemon; profile daemons never have one.
+#[cfg(target_os = "linux")]
+pub fn service_installed() -> bool {
+    crate::profile::active().is_none()
+        && dirs::config_dir().is_some_and(|d| d.join("systemd/user/signet.service").exists())
+}
+
+#[cfg(target_os = "macos")]
+pub fn service_installed() -> bool {
+    crate::profile::active().is_none()
+        && dirs::home_dir()
+            .is_some_and(|h| h.join("Library/LaunchAgents/ai.signet.daemon.plist").exists())
+}
+
+#[cfg(target_os = "windows")]
+pub fn service_installed() -> bool {
+    false
+}
+
+fn env_nonempty(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// The daemon's data directory: the active profile's, else `SIGNET_PATH`
+/// from the environment or the service definition, else `~/.agents`.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = crate::profile::active().and_then(|p| p.data_path()) {
+        return Some(dir);
+    }
+    match env_nonempty("SIGNET_PATH").or_else(|| service_env("SIGNET_PATH")) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(dirs::home_dir()?.join(".agents")),
+    }
+}
+
+/// `<data dir>/.daemon`, where the daemon keeps its PID file, logs, socket
+/// and the tray's token.
+pub fn daemon_dir() -> Option<PathBuf> {
+    Some(data_dir()?.join(".daemon"))
+}
+
+/// Daemon port: `SIGNET_PORT`, then the service definition, then 3850.
+pub fn daemon_port() -> u16 {
+    env_nonempty("SIGNET_PORT")
+        .or_else(|| service_env("SIGNET_PORT"))
+        .and_then(|p| p.parse::<u16>().ok())
+        .unwrap_or(DEFAULT_PORT)
+}
+
+/// Daemon host for local calls. Bind-all addresses are reached via loopback.
+fn daemon_host() -> String {
+    match env_nonempty("SIGNET_HOST").or_else(|| service_env("SIGNET_HOST")) {
+        Some(host) if host == "0.0.0.0" => "127.0.0.1".to_string(),
+        Some(host) if host == "::" => "[::1]".to_string(),
+        Some(host) if host.contains(':') && !host.starts_with('[') => format!("[{}]", host),
+        Some(host) => host,
+        None => DEFAULT_HOST.to_string(),
+    }
+}
+
+/// Resolve the daemon base URL, without a trailing slash. In order:
+/// `SIGNET_DAEMON_URL`, the active profile, the tray settings file, then
+/// `SIGNET_HOST` / `SIGNET_PORT` from the environment or the daemon's
+/// service definition.
+pub fn daemon_url() -> String {
+    let explicit = env_nonempty("SIGNET_DAEMON_URL")
+        .or_else(|| crate::profile::active().map(|p| p.daemon_url))
+        .or_else(|| TraySettings::load().daemon_url);
+    if let Some(url) = explicit.and_then(|u| normalize_daemon_url(&u).ok()) {
+        return url;
+    }
+    format!("http://{}:{}", daemon_host(), daemon_port())
+}
+
+/// Whether the resolved daemon runs on this machine, i.e. whether the tray
+/// should try to start or stop it.
+pub fn daemon_is_local() -> bool {
+    let Ok(parsed) = url::Url::parse(&daemon_url()) else {
+        return true;
+    };
+    match parsed.host() {
+        Some(url::Host::Domain(domain)) => domain == "localhost",
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => true,
+    }
+}
+
+/// Host and port of the resolved daemon, for TCP probes.
+pub fn daemon_socket_addr() -> (String, u16) {
+    url::Url::parse(&daemon_url())
+        .ok()
+        .and_then(|u| {
+            let port = u.port_or_known_default()?;
+            let host = u.host_str()?.trim_matches(|c| c == '[' || c == ']').to_string();
+            Some((host, port))
+        })
+        .unwrap_or_else(|| (DEFAULT_HOST.to_string(), DEFAULT_PORT))
+}
+
+/// Environment for daemons the tray spawns directly, so they listen where
+/// the tray looks for them.
+pub fn daemon_spawn_env() -> Vec<(&'static str, String)> {
+    if !daemon_is_local() {
+        return Vec::new();
+    }
+    let (_, port) = daemon_socket_addr();
+    let mut env = vec![("SIGNET_PORT", port.to_string())];
+    if let Some(dir) = crate::profile::active().and_then(|p| p.data_path()) {
+        env.push(("SIGNET_PATH", dir.to_string_lossy().to_string()));
+    }
+    env
+}
@@ -0,0 +1,96 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+/// Serialises access to the spool file between quick capture and the
+/// poller's flush.
+static SPOOL_LOCK: Mutex<()> = Mutex::new(());
+
+/// One JSON capture body per line, oldest first. Each profile has its own
+/// spool so captures reach the daemon they were meant for.
+fn spool_path() -> Option<PathBuf> {
+    let name = match crate::profile::active() {
+        Some(profile) => {
+            let safe: String = profile
+                .name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect();
+            format!("capture-spool-{}.jsonl", safe)
+        }
+        None => "capture-spool.jsonl".to_string(),
+    };
+    Some(dirs::data_local_dir()?.join("ai.signet.app").join(name))
+}
+
+fn read_lines(path: &PathBuf) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .map(|s| {
+            s.lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn write_lines(path: &PathBuf, lines: &[String]) -> Result<(), String> {
+    if lines.is_empty() {
+        let _ = std::fs::remove_file(path);
+        return Ok(());
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Append a capture body that could not be delivered.
+pub fn enqueue(body: &Value) -> Result<(), String> {
+    let path = spool_path().ok_or("no data directory")?;
+    let _guard = SPOOL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", body).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Number of captures waiting to be delivered.
+pub fn len() -> usize {
+    let Some(path) = spool_path() else { return 0 };
+    let _guard = SPOOL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    read_lines(&path).len()
+}
+
+/// Take every queued capture, leaving the spool empty. Whatever is not
+/// delivered must be handed back with `restore`.
+pub fn take() -> Vec<Value> {
+    let Some(path) = spool_path() else { return Vec::new() };
+    let _guard = SPOOL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let lines = read_lines(&path);
+    let _ = std::fs::remove_file(&path);
+    // Unparseable lines are dropped rather than blocking the queue forever
+    lines
+        .iter()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect()
+}
+
+/// Put undelivered captures back in front of anything queued meanwhile.
+pub fn restore(pending: &[Value]) -> Result<(), String> {
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let path = spool_path().ok_or("no data directory")?;
+    let _guard = SPOOL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut lines: Vec<String> = pending.iter().map(|v| v.to_string()).collect();
+    lines.extend(read_lines(&path));
+    write_lines(&path, &lines)
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::daemon;
+
+/// CPU, memory and uptime of the daemon process.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProcessStats {
+    /// Whole percent of one core; can exceed 100 on multi-core machines
+    pub cpu_percent: u32,
+    /// Resident set size in bytes, in whole MiB
+    pub rss_bytes: u64,
+    /// Seconds since the process started, in whole minutes
+    pub uptime_secs: u64,
+}
+
+/// Samples the daemon process named in its pid file. CPU usage is measured
+/// between two refreshes, so one collector is kept across polls; the first
+/// sample after the PID changes reports 0%.
+pub struct Collector {
+    system: System,
+    pid: Option<Pid>,
+}
+
+impl Collector {
+    pub fn new() -> Self {
+        Self {
+            system: System::new(),
+            pid: None,
+        }
+    }
+
+    /// Stats for the running daemon, or `None` if there is no pid file or
+    /// the process cannot be read.
+    pub fn sample(&mut self) -> Option<ProcessStats> {
+        let pid = Pid::from_u32(daemon::read_pid().ok().flatten()?);
+        if self.pid != Some(pid) {
+            // Forget the previous process so its CPU time isn't compared
+            self.system = System::new();
+            self.pid = Some(pid);
+        }
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[pid]),
+            true,
+            ProcessRefreshKind::new().with_cpu().with_memory(),
+        );
+        let process = self.system.process(pid)?;
+        // Rounded so the menu is not rebuilt for every small fluctuation
+        Some(ProcessStats {
+            cpu_percent: process.cpu_usage().round() as u32,
+            rss_bytes: process.memory() >> 20 << 20,
+            uptime_secs: process.run_time() / 60 * 60,
+        })
+    }
+}
+
+/// "1.2 GB", "340 MB"
+pub fn format_bytes(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    const GB: f64 = MB * 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= GB {
+        format!("{:.1} GB", bytes / GB)
+    } else {
+        format!("{:.0} MB", bytes / MB)
+    }
+}
+
+/// "3d 4h", "2h 15m", "12m"
+pub fn format_uptime(secs: u64) -> String {
+    let minutes = secs / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use sha2::{Digest, Sha256};
+
+use crate::settings::TraySettings;
+
+/// TLS options for talking to a remote daemon.
+#[derive(PartialEq)]
+pub struct TlsOptions {
+    /// PEM file with an extra CA to trust, for self-signed homelab setups.
+    pub ca_cert: Option<String>,
+    /// SHA-256 of the daemon's certificate. When set, that certificate is
+    /// accepted regardless of issuer and anything else is refused.
+    pub pinned_sha256: Option<[u8; 32]>,
+}
+
+impl TlsOptions {
+    /// `SIGNET_CA_CERT` / `SIGNET_CERT_SHA256`, falling back to the tray
+    /// settings file.
+    pub fn load() -> Result<Self, String> {
+        let prefs = TraySettings::load();
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let pinned = env("SIGNET_CERT_SHA256").or(prefs.pinned_cert_sha256);
+        Ok(Self {
+            ca_cert: env("SIGNET_CA_CERT").or(prefs.ca_cert),
+            pinned_sha256: pinned
+                .as_deref()
+                .map(parse_fingerprint)
+                .transpose()
+                .map_err(|e| format!("{} {}", ERROR_PREFIX, e))?,
+        })
+    }
+}
+
+/// Parse a SHA-256 fingerprint as printed by
+/// `openssl x509 -noout -fingerprint -sha256`, with or without colons.
+pub fn parse_fingerprint(raw: &str) -> Result<[u8; 32], String> {
+    let hex: String = raw
+        .trim()
+        .trim_start_matches("sha256:")
+        .chars()
+        .filter(|c| !matches!(c, ':' | ' '))
+        .collect();
+    if hex.len() != 64 {
+        return Err(format!(
+            "Pinned certificate must be a SHA-256 fingerprint (64 hex digits), got {} digits",
+            hex.len()
+        ));
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("Invalid hex in pinned certificate: {}", raw))?;
+    }
+    Ok(out)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Accepts exactly one certificate, identified by its SHA-256. Handshake
+/// signatures are still checked, so the peer must hold the private key.
+#[derive(Debug)]
+struct PinnedCert {
+    sha256: [u8; 32],
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCert {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let digest: [u8; 32] = Sha256::digest(end_entity.as_ref()).into();
+        if digest == self.sha256 {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(format!(
+                "certificate {} does not match the pinned {}",
+                to_hex(&digest),
+                to_hex(&self.sha256)
+            )))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Apply TLS options to a client builder. Always uses rustls so that
+/// certificate failures can be recognised and reported (see `describe`).
+pub fn configure(
+    builder: reqwest::ClientBuilder,
+    options: &TlsOptions,
+) -> Result<reqwest::ClientBuilder, String> {
+    if let Some(sha256) = options.pinned_sha256 {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = rustls::ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| format!("TLS error: setup failed: {}", e))?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedCert { sha256, provider }))
+            .with_no_client_auth();
+        return Ok(builder.use_preconfigured_tls(config));
+    }
+
+    let mut builder = builder.use_rustls_tls();
+    if let Some(path) = &options.ca_cert {
+        let pem = std::fs::read(path)
+            .map_err(|e| format!("TLS error: cannot read CA certificate {}: {}", path, e))?;
+        let cert = reqwest::Certificate::from_pem(&pem)
+            .map_err(|e| format!("TLS error: invalid CA certificate {}: {}", path, e))?;
+        builder = builder.add_root_certificate(cert);
+    }
+    Ok(builder)
+}
+
+/// Prefix shared by every TLS-related error, so callers can tell "daemon
+/// unreachable" from "daemon reachable but not trusted".
+pub const ERROR_PREFIX: &str = "TLS error:";
+
+/// If a request failed during the TLS handshake, explain why. Returns
+/// `None` for anything that is not a TLS failure.
+pub fn describe(err: &reqwest::Error) -> Option<String> {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(e) = source {
+        if let Some(tls) = e.downcast_ref::<rustls::Error>() {
+            return Some(format!("TLS error: {}", tls));
+        }
+        if let Some(tls) = e
+            .downcast_ref::<std::io::Error>()
+            .and_then(|io| io.get_ref())
+            .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+        {
+            return Some(format!("TLS error: {}", tls));
+        }
+        source = e.source();
+    }
+    None
+}
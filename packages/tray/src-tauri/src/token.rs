@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Tokens the tray mints for itself last this long (30 days).
+const DEFAULT_TTL_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+/// Rotate once less than this much lifetime is left (3 days).
+const ROTATE_WITHIN_SECONDS: i64 = 3 * 24 * 60 * 60;
+
+/// `<data dir>/.daemon/local.token`, the token the tray sends as a bearer.
+/// Each profile's daemon has its own.
+fn token_path() -> Option<PathBuf> {
+    Some(crate::settings::daemon_dir()?.join("local.token"))
+}
+
+/// The token to send: `SIGNET_TOKEN`, else the token file.
+pub fn read() -> Option<String> {
+    if let Ok(token) = std::env::var("SIGNET_TOKEN") {
+        if !token.trim().is_empty() {
+            return Some(token.trim().to_string());
+        }
+    }
+    let content = std::fs::read_to_string(token_path()?).ok()?;
+    let token = content.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// Replace the token file. Written to a temp file first so a crash never
+/// leaves a half-written token, and readable only by the owner.
+pub fn write(token: &str) -> Result<(), String> {
+    let path = token_path().ok_or("no home directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let tmp = path.with_extension("token.tmp");
+    std::fs::write(&tmp, format!("{}\n", token.trim()))
+        .map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600));
+    }
+    std::fs::rename(&tmp, &path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// The claims the daemon signs into a token. The signature is not checked
+/// here; the daemon does that.
+#[derive(Deserialize)]
+pub struct Claims {
+    pub role: String,
+    #[serde(default)]
+    pub scope: serde_json::Value,
+    pub exp: i64,
+}
+
+/// Decode the payload half of a `<payload>.<signature>` daemon token.
+pub fn claims(token: &str) -> Option<Claims> {
+    let (payload, _) = token.split_once('.')?;
+    let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+#[derive(Serialize, Clone)]
+pub struct TokenStatus {
+    /// Whether a token is configured at all
+    pub present: bool,
+    /// Expiry as Unix seconds, when the token can be decoded
+    pub expires_at: Option<i64>,
+    pub role: Option<String>,
+    /// Past its expiry, or the daemon has rejected it
+    pub stale: bool,
+}
+
+pub fn status(rejected: bool) -> TokenStatus {
+    let token = read();
+    let claims = token.as_deref().and_then(claims);
+    let now = chrono::Utc::now().timestamp();
+    let expired = claims.as_ref().is_some_and(|c| c.exp <= now);
+    TokenStatus {
+        present: token.is_some(),
+        expires_at: claims.as_ref().map(|c| c.exp),
+        role: claims.map(|c| c.role),
+        stale: rejected || expired,
+    }
+}
+
+/// Whether the current token is close enough to expiry to rotate.
+pub fn due_for_rotation() -> bool {
+    let now = chrono::Utc::now().timestamp();
+    read()
+        .as_deref()
+        .and_then(claims)
+        .is_some_and(|c| c.exp > now && c.exp - now < ROTATE_WITHIN_SECONDS)
+}
+
+/// Body for the daemon's `POST /api/auth/token`. A rotated token keeps the
+/// role and scope of the one it replaces; a first token is an admin token,
+/// which the daemon only issues to callers it already trusts (e.g. from
+/// localhost in hybrid mode).
+pub fn mint_request(current: Option<&str>) -> serde_json::Value {
+    let claims = current.and_then(claims);
+    let role = claims.as_ref().map(|c| c.role.as_str()).unwrap_or("admin");
+    let scope = claims
+        .as_ref()
+        .map(|c| c.scope.clone())
+        .filter(|s| s.is_object())
+        .unwrap_or_else(|| serde_json::json!({}));
+    serde_json::json!({
+        "role": role,
+        "scope": scope,
+        "ttlSeconds": DEFAULT_TTL_SECONDS,
+    })
+}
@@ -1,3 +1,5 @@
+use std::sync::{Mutex, OnceLock};
+
 use tauri::{
     image::Image,
     menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder},
@@ -6,17 +8,25 @@ use tauri::{
 };
 
 use crate::commands;
+use crate::i18n::{self, t, tf, Locale};
+use crate::stats::{self, ProcessStats};
 
 pub const TRAY_ID: &str = "signet-tray";
 
+/// Memories behind the "Recent Memories" items, keyed by menu id, so a
+/// click can open the full memory rather than the truncated label.
+static RECENT_MENU: Mutex<Vec<(String, commands::RecentMemory)>> = Mutex::new(Vec::new());
+
 // Embed icons at compile time so they work in release builds.
-// Use @2x (44x44) on macOS for retina crispness; 1x (22x22) elsewhere.
+// macOS gets template images: black with alpha, which the system tints to
+// suit a light or dark menu bar. They are @2x (44x44) for retina
+// crispness. Elsewhere the icons are colored 1x (22x22).
 #[cfg(target_os = "macos")]
-const ICON_RUNNING: &[u8] = include_bytes!("../../icons/signet-running@2x.png");
+const ICON_RUNNING: &[u8] = include_bytes!("../../icons/signet-running-template@2x.png");
 #[cfg(target_os = "macos")]
-const ICON_STOPPED: &[u8] = include_bytes!("../../icons/signet-stopped@2x.png");
+const ICON_STOPPED: &[u8] = include_bytes!("../../icons/signet-stopped-template@2x.png");
 #[cfg(target_os = "macos")]
-const ICON_ERROR: &[u8] = include_bytes!("../../icons/signet-error@2x.png");
+const ICON_ERROR: &[u8] = include_bytes!("../../icons/signet-error-template@2x.png");
 
 #[cfg(not(target_os = "macos"))]
 const ICON_RUNNING: &[u8] = include_bytes!("../../icons/signet-running.png");
@@ -25,6 +35,48 @@ const ICON_STOPPED: &[u8] = include_bytes!("../../icons/signet-stopped.png");
 #[cfg(not(target_os = "macos"))]
 const ICON_ERROR: &[u8] = include_bytes!("../../icons/signet-error.png");
 
+#[cfg(target_os = "macos")]
+const ICON_PAUSED: &[u8] = include_bytes!("../../icons/signet-paused-template@2x.png");
+#[cfg(not(target_os = "macos"))]
+const ICON_PAUSED: &[u8] = include_bytes!("../../icons/signet-paused.png");
+
+// Frames of the processing spinner, a ring with a bright arc going round
+#[cfg(target_os = "macos")]
+const ICON_PROCESSING: [&[u8]; 8] = [
+    include_bytes!("../../icons/signet-processing-0-template@2x.png"),
+    include_bytes!("../../icons/signet-processing-1-template@2x.png"),
+    include_bytes!("../../icons/signet-processing-2-template@2x.png"),
+    include_bytes!("../../icons/signet-processing-3-template@2x.png"),
+    include_bytes!("../../icons/signet-processing-4-template@2x.png"),
+    include_bytes!("../../icons/signet-processing-5-template@2x.png"),
+    include_bytes!("../../icons/signet-processing-6-template@2x.png"),
+    include_bytes!("../../icons/signet-processing-7-template@2x.png"),
+];
+#[cfg(not(target_os = "macos"))]
+const ICON_PROCESSING: [&[u8]; 8] = [
+    include_bytes!("../../icons/signet-processing-0.png"),
+    include_bytes!("../../icons/signet-processing-1.png"),
+    include_bytes!("../../icons/signet-processing-2.png"),
+    include_bytes!("../../icons/signet-processing-3.png"),
+    include_bytes!("../../icons/signet-processing-4.png"),
+    include_bytes!("../../icons/signet-processing-5.png"),
+    include_bytes!("../../icons/signet-processing-6.png"),
+    include_bytes!("../../icons/signet-processing-7.png"),
+];
+
+/// Time each spinner frame is shown
+const PROCESSING_FRAME: std::time::Duration = std::time::Duration::from_millis(120);
+
+/// Pending jobs at which the icon switches to the processing spinner
+pub const PROCESSING_QUEUE_DEPTH: u64 = 20;
+
+/// The icon state last shown. The spinner keeps running while this is
+/// "processing".
+static ICON_STATE: Mutex<Option<&'static str>> = Mutex::new(None);
+
+/// Decoded spinner frames, so the ticker doesn't decode a PNG per frame
+static PROCESSING_FRAMES: OnceLock<Vec<Image<'static>>> = OnceLock::new();
+
 fn decode_png(data: &[u8]) -> Image<'static> {
     let decoder = png::Decoder::new(data);
     let mut reader = decoder.read_info().expect("valid PNG header");
@@ -61,19 +113,71 @@ fn decode_png(data: &[u8]) -> Image<'static> {
 pub fn icon_for_state(state: &str) -> Image<'static> {
     let bytes = match state {
         "running" => ICON_RUNNING,
+        "processing" => return processing_frame(0),
+        "paused" => ICON_PAUSED,
         "error" => ICON_ERROR,
         _ => ICON_STOPPED,
     };
     decode_png(bytes)
 }
 
+/// Set the tray icon, marking it as a template image on macOS.
+fn show_icon(tray: &tauri::tray::TrayIcon, icon: Image<'static>) {
+    let _ = tray.set_icon(Some(icon));
+    #[cfg(target_os = "macos")]
+    let _ = tray.set_icon_as_template(true);
+}
+
+fn processing_frame(frame: usize) -> Image<'static> {
+    let frames = PROCESSING_FRAMES
+        .get_or_init(|| ICON_PROCESSING.iter().map(|bytes| decode_png(bytes)).collect());
+    frames[frame % frames.len()].clone()
+}
+
+/// Show the icon for `state` ("running", "processing", "paused",
+/// "stopped" or "error"). Entering "processing" starts the spinner, which
+/// stops by itself once another state is shown.
+pub fn set_icon_state(app: &tauri::AppHandle, state: &'static str) {
+    let mut current = ICON_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    if *current == Some(state) {
+        return;
+    }
+    let animating = *current == Some("processing");
+    *current = Some(state);
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        show_icon(&tray, icon_for_state(state));
+    }
+    if state == "processing" && !animating {
+        tauri::async_runtime::spawn(animate_processing(app.clone()));
+    }
+}
+
+/// Step through the spinner frames until the icon leaves "processing".
+/// The state lock is held while a frame is set, so a frame can never land
+/// after the icon has moved on.
+async fn animate_processing(app: tauri::AppHandle) {
+    let mut frame = 0;
+    loop {
+        tokio::time::sleep(PROCESSING_FRAME).await;
+        let current = ICON_STATE.lock().unwrap_or_else(|e| e.into_inner());
+        if *current != Some("processing") {
+            return;
+        }
+        frame += 1;
+        if let Some(tray) = app.tray_by_id(TRAY_ID) {
+            show_icon(&tray, processing_frame(frame));
+        }
+    }
+}
+
 pub fn setup(app: &App) -> Result<(), Box<dyn std::error::Error>> {
     let menu = build_stopped_menu(app)?;
 
     TrayIconBuilder::with_id(TRAY_ID)
         .icon(icon_for_state("stopped"))
+        .icon_as_template(cfg!(target_os = "macos"))
         .menu(&menu)
-        .tooltip("Signet — Checking...")
+        .tooltip(t("status_checking"))
         .on_menu_event(handle_menu_event)
         .build(app)?;
 
@@ -88,6 +192,33 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
         "open-dashboard" => {
             let _ = commands::open_dashboard_inner(app);
         }
+        "open-logs" => {
+            let _ = crate::notify::open_logs(app);
+        }
+        "open-data-dir" => {
+            let _ = crate::notify::open_data_dir(app);
+        }
+        "start-alt-port" => {
+            tauri::async_runtime::spawn(async move {
+                let _ = commands::start_on_alternate_port_inner().await;
+            });
+        }
+        "install-service" => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = commands::install_service_inner(&handle).await {
+                    crate::notify::service_install_failed(&handle, &e);
+                }
+            });
+        }
+        "diagnostics-bundle" => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = commands::create_diagnostics_bundle().await {
+                    crate::notify::diagnostics_bundle_failed(&handle, &e);
+                }
+            });
+        }
         "start-daemon" => {
             let handle = app.clone();
             tauri::async_runtime::spawn(async move {
@@ -112,6 +243,64 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
         "search-memories" => {
             open_search_window(app);
         }
+        "pair-daemon" => {
+            open_pair_window(app);
+        }
+        "export-memories" => {
+            open_export_window(app);
+        }
+        "capture-pause-60" | "capture-pause-240" | "capture-pause-indefinite" => {
+            let minutes = id_str
+                .strip_prefix("capture-pause-")
+                .and_then(|m| m.parse().ok());
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = commands::pause_capture_inner(&handle, minutes).await {
+                    crate::notify::capture_pause_failed(&handle, &e);
+                }
+            });
+        }
+        "capture-resume" => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = commands::resume_capture_inner(&handle).await {
+                    crate::notify::capture_pause_failed(&handle, &e);
+                }
+            });
+        }
+        "backfill-embeddings" => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = commands::backfill_embeddings_inner(&handle).await;
+            });
+        }
+        "predictor-retrain" => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = commands::retrain_predictor_inner(&handle).await;
+            });
+        }
+        "queue-retry-dead" => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = commands::retry_dead_jobs_inner(&handle).await;
+            });
+        }
+        "queue-purge-dead" => {
+            // Asks for confirmation before deleting anything
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = commands::purge_dead_jobs(handle).await;
+            });
+        }
+        "rotate-token" => {
+            tauri::async_runtime::spawn(async move {
+                let _ = commands::rotate_token_inner().await;
+            });
+        }
+        "update-available" => {
+            let _ = commands::open_update_window_inner(app);
+        }
         "check-for-update" => {
             let handle = app.clone();
             tauri::async_runtime::spawn(async move {
@@ -119,32 +308,95 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
             });
         }
         "toggle-autostart" => {
-            #[cfg(any(target_os = "macos", target_os = "windows"))]
-            {
-                use crate::platform::autostart;
-                if autostart::is_autostart_enabled() {
-                    autostart::remove_autostart();
-                } else {
-                    autostart::ensure_autostart();
-                }
-                // Rebuild the current menu to reflect the new state
-                if let Some(tray) = app.tray_by_id(TRAY_ID) {
-                    if let Ok(menu) = build_stopped_menu(app) {
-                        let _ = tray.set_menu(Some(menu));
-                    }
-                }
+            use crate::platform::autostart;
+            if autostart::is_autostart_enabled() {
+                autostart::remove_autostart();
+            } else {
+                autostart::ensure_autostart();
             }
+            // Rebuild the current menu to reflect the new state
+            let _ = commands::refresh_tray(app);
+        }
+        "toggle-supervise" => {
+            let mut prefs = crate::settings::TraySettings::load();
+            prefs.supervise_daemon = Some(!crate::settings::supervision_enabled());
+            let _ = prefs.save();
+            let _ = commands::refresh_tray(app);
+        }
+        "toggle-launch-start" => {
+            let mut prefs = crate::settings::TraySettings::load();
+            prefs.start_daemon_on_launch = Some(!crate::settings::start_daemon_on_launch());
+            let _ = prefs.save();
+            let _ = commands::refresh_tray(app);
+        }
+        "toggle-milestones" => {
+            let mut prefs = crate::settings::TraySettings::load();
+            prefs.milestone_notifications = Some(!crate::settings::milestone_notifications());
+            let _ = prefs.save();
+            let _ = commands::refresh_tray(app);
+        }
+        "toggle-daily-summary" => {
+            let mut prefs = crate::settings::TraySettings::load();
+            prefs.daily_summary = Some(!crate::settings::daily_summary());
+            let _ = prefs.save();
+            let _ = commands::refresh_tray(app);
+        }
+        "title-total" | "title-today" | "title-hidden" => {
+            use crate::settings::TitleDisplay;
+            let mut prefs = crate::settings::TraySettings::load();
+            prefs.title_display = Some(match id_str {
+                "title-today" => TitleDisplay::Today,
+                "title-hidden" => TitleDisplay::Hidden,
+                _ => TitleDisplay::Total,
+            });
+            let _ = prefs.save();
+            let _ = commands::refresh_tray(app);
+        }
+        "toggle-compact-title" => {
+            let mut prefs = crate::settings::TraySettings::load();
+            prefs.compact_title = Some(!crate::settings::compact_title());
+            let _ = prefs.save();
+            let _ = commands::refresh_tray(app);
+        }
+        "language-system" | "language-en" | "language-fr" | "language-de" | "language-ja" => {
+            let _ = commands::set_language_inner(app, Locale::from_tag(&id_str["language-".len()..]));
+        }
+        "profile-default" => {
+            let _ = commands::switch_profile_inner(app, None);
         }
         "quit" => {
             app.exit(0);
         }
-        _ => {
-            // Handle recent memory clicks (copy content to clipboard)
-            if id_str.starts_with("recent-memory-") {
-                // The content is stored in the menu item text; users can see it in the menu.
-                // We don't need clipboard for submenu items — they're informational.
+        _ if id_str.starts_with("profile-") => {
+            let index = id_str["profile-".len()..].parse::<usize>().ok();
+            let profiles = crate::profile::Profiles::load().profiles;
+            if let Some(profile) = index.and_then(|i| profiles.get(i)) {
+                let _ = commands::switch_profile_inner(app, Some(&profile.name));
             }
         }
+        _ if id_str.starts_with("recent-memory-") => {
+            open_recent_memory(app, id_str);
+        }
+        _ => {}
+    }
+}
+
+/// Open the detail window for a recent memory. Memories listed without an
+/// id (older daemons) fall back to copying the content.
+fn open_recent_memory(app: &tauri::AppHandle, menu_id: &str) {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let memory = RECENT_MENU
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|(id, _)| id == menu_id)
+        .map(|(_, mem)| mem.clone());
+    let Some(memory) = memory else { return };
+    if !memory.id.is_empty() {
+        let _ = commands::open_memory_window_inner(app, &memory.id);
+    } else if app.clipboard().write_text(memory.content.clone()).is_ok() {
+        crate::notify::memory_copied(app, &truncate(&memory.content.replace('\n', " "), 80));
     }
 }
 
@@ -183,8 +435,41 @@ fn open_search_window(app: &tauri::AppHandle) {
         .build();
 }
 
+fn open_pair_window(app: &tauri::AppHandle) {
+    if let Some(win) = app.get_webview_window("pair") {
+        let _ = win.set_focus();
+        return;
+    }
+
+    let url = WebviewUrl::App("pair.html".into());
+    let _ = WebviewWindowBuilder::new(app, "pair", url)
+        .title("Re-pair with Daemon")
+        .inner_size(420.0, 200.0)
+        .resizable(false)
+        .always_on_top(true)
+        .center()
+        .visible(true)
+        .build();
+}
+
+fn open_export_window(app: &tauri::AppHandle) {
+    if let Some(win) = app.get_webview_window("export") {
+        let _ = win.set_focus();
+        return;
+    }
+
+    let url = WebviewUrl::App("export.html".into());
+    let _ = WebviewWindowBuilder::new(app, "export", url)
+        .title("Export Memories")
+        .inner_size(420.0, 360.0)
+        .resizable(false)
+        .center()
+        .visible(true)
+        .build();
+}
+
 /// Format a number with thousands separators (e.g. 4605 -> "4,605")
-fn format_number(n: u64) -> String {
+pub(crate) fn format_number(n: u64) -> String {
     let s = n.to_string();
     let mut result = String::with_capacity(s.len() + s.len() / 3);
     for (i, c) in s.chars().rev().enumerate() {
@@ -205,7 +490,7 @@ fn time_ago(iso: &str) -> String {
             let dur = now.signed_duration_since(naive);
             return format_duration(dur);
         }
-        return "just now".to_string();
+        return t("just_now").to_string();
     };
     let now = chrono::Utc::now();
     let dur = now.signed_duration_since(ts.with_timezone(&chrono::Utc));
@@ -215,16 +500,16 @@ fn time_ago(iso: &str) -> String {
 fn format_duration(dur: chrono::Duration) -> String {
     let secs = dur.num_seconds();
     if secs < 60 {
-        "just now".to_string()
+        t("just_now").to_string()
     } else if secs < 3600 {
         let m = secs / 60;
-        format!("{}m ago", m)
+        tf("minutes_ago", &[&m])
     } else if secs < 86400 {
         let h = secs / 3600;
-        format!("{}h ago", h)
+        tf("hours_ago", &[&h])
     } else {
         let d = secs / 86400;
-        format!("{}d ago", d)
+        tf("days_ago", &[&d])
     }
 }
 
@@ -239,6 +524,287 @@ fn truncate(s: &str, max: usize) -> String {
     }
 }
 
+/// A toggle or option label, with ✓ when it is on.
+fn checked(label: &str, on: bool) -> String {
+    if on {
+        format!("{} ✓", label)
+    } else {
+        label.to_string()
+    }
+}
+
+/// "Capture paused until 14:30", or "Capture paused" when it runs until
+/// resumed. Pauses ending on another day include the date.
+pub fn capture_pause_label(pause: &commands::CapturePause) -> String {
+    let until = pause
+        .until
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&chrono::Local));
+    match until {
+        Some(t) if t.date_naive() == chrono::Local::now().date_naive() => {
+            tf("capture_paused_until", &[&t.format("%H:%M")])
+        }
+        Some(t) => tf("capture_paused_until", &[&t.format(i18n::t("date_time"))]),
+        None => i18n::t("capture_paused").to_string(),
+    }
+}
+
+/// "Job Queue" submenu: pending and dead jobs per type, with actions for
+/// the dead ones.
+fn queue_submenu(
+    app: &tauri::AppHandle,
+    queues: &commands::JobQueues,
+) -> Result<tauri::menu::Submenu<tauri::Wry>, Box<dyn std::error::Error>> {
+    let info = |id: &str, name: &str, counts: &commands::QueueCounts| {
+        MenuItemBuilder::with_id(
+            id,
+            tf(
+                "queue_counts",
+                &[&name, &format_number(counts.pending), &format_number(counts.dead)],
+            ),
+        )
+        .enabled(false)
+        .build(app)
+    };
+
+    let dead = queues.dead();
+    let title = if dead > 0 {
+        format!("🗂️ {}", tf("job_queue_dead", &[&format_number(dead)]))
+    } else {
+        format!("🗂️ {}", t("job_queue"))
+    };
+    Ok(SubmenuBuilder::new(app, title)
+        .item(&info("info-queue-memory", t("queue_extraction"), &queues.memory)?)
+        .item(&info("info-queue-summary", t("queue_summaries"), &queues.summary)?)
+        .separator()
+        .item(
+            &MenuItemBuilder::with_id("queue-retry-dead", t("retry_dead"))
+                .enabled(dead > 0)
+                .build(app)?,
+        )
+        .item(
+            &MenuItemBuilder::with_id("queue-purge-dead", t("purge_dead"))
+                .enabled(dead > 0)
+                .build(app)?,
+        )
+        .build()?)
+}
+
+/// "Predictor" submenu: model version, training recency and sample counts,
+/// plus a retrain trigger.
+fn predictor_submenu(
+    app: &tauri::AppHandle,
+    status: &commands::PredictorStatus,
+) -> Result<tauri::menu::Submenu<tauri::Wry>, Box<dyn std::error::Error>> {
+    let info = |id: &str, label: String| MenuItemBuilder::with_id(id, label).enabled(false).build(app);
+
+    let version = match (status.alive, status.model_version) {
+        (false, _) => t("sidecar_down").to_string(),
+        (true, Some(v)) => tf("model_version", &[&v]),
+        (true, None) => t("model_unknown").to_string(),
+    };
+    let trained = match &status.last_trained {
+        Some(at) => tf("trained_ago", &[&time_ago(at)]),
+        None => t("not_trained").to_string(),
+    };
+    let mut submenu = SubmenuBuilder::new(app, format!("🎯 {}", t("predictor")))
+        .item(&info("info-predictor-version", version)?)
+        .item(&info("info-predictor-trained", trained)?);
+    if let Some(pairs) = status.training_pairs {
+        submenu = submenu.item(&info(
+            "info-predictor-pairs",
+            tf("training_pairs", &[&format_number(pairs)]),
+        )?);
+    }
+    if let Some(samples) = status.last_run_samples {
+        submenu = submenu.item(&info(
+            "info-predictor-samples",
+            tf("last_run_samples", &[&format_number(samples)]),
+        )?);
+    }
+
+    let training = commands::predictor_training();
+    let retrain = MenuItemBuilder::with_id(
+        "predictor-retrain",
+        if training { t("training") } else { t("retrain_now") },
+    )
+    .enabled(status.alive && !training)
+    .build(app)?;
+    Ok(submenu.separator().item(&retrain).build()?)
+}
+
+/// "Start Daemon with Signet" toggle for starting the daemon at launch.
+fn launch_start_item(
+    app: &impl Manager<tauri::Wry>,
+) -> Result<tauri::menu::MenuItem<tauri::Wry>, Box<dyn std::error::Error>> {
+    let label = checked(t("start_with_signet"), crate::settings::start_daemon_on_launch());
+    Ok(MenuItemBuilder::with_id("toggle-launch-start", label).build(app)?)
+}
+
+/// "Install as Service" for a local daemon that systemd or launchd doesn't
+/// manage yet. Not offered on Windows, which has no service support.
+fn install_service_item(
+    app: &impl Manager<tauri::Wry>,
+) -> Result<Option<tauri::menu::MenuItem<tauri::Wry>>, Box<dyn std::error::Error>> {
+    if cfg!(target_os = "windows")
+        || !crate::settings::daemon_is_local()
+        || crate::settings::service_installed()
+    {
+        return Ok(None);
+    }
+    Ok(Some(
+        MenuItemBuilder::with_id("install-service", t("install_service")).build(app)?,
+    ))
+}
+
+/// "Profile" submenu listing the default daemon and every configured
+/// profile, with ✓ on the active one. Hidden until a profile exists.
+fn profile_submenu(
+    app: &impl Manager<tauri::Wry>,
+) -> Result<Option<tauri::menu::Submenu<tauri::Wry>>, Box<dyn std::error::Error>> {
+    let profiles = crate::profile::Profiles::load();
+    if profiles.profiles.is_empty() {
+        return Ok(None);
+    }
+    let active = crate::profile::active().map(|p| p.name);
+    let title = tf("profile", &[&active.as_deref().unwrap_or(t("profile_default"))]);
+    let mut submenu = SubmenuBuilder::new(app, title).item(
+        &MenuItemBuilder::with_id("profile-default", checked(t("profile_default"), active.is_none()))
+            .build(app)?,
+    );
+    // Indexed ids, since profile names may contain anything
+    for (i, profile) in profiles.profiles.iter().enumerate() {
+        let on = active.as_deref() == Some(profile.name.as_str());
+        submenu = submenu.item(
+            &MenuItemBuilder::with_id(
+                format!("profile-{}", i),
+                checked(&profile.name, on),
+            )
+            .build(app)?,
+        );
+    }
+    Ok(Some(submenu.build()?))
+}
+
+/// "Restart Automatically" toggle for daemon supervision.
+fn supervise_item(
+    app: &impl Manager<tauri::Wry>,
+) -> Result<tauri::menu::MenuItem<tauri::Wry>, Box<dyn std::error::Error>> {
+    let label = checked(t("restart_automatically"), crate::settings::supervision_enabled());
+    Ok(MenuItemBuilder::with_id("toggle-supervise", label).build(app)?)
+}
+
+/// "Notifications" submenu toggling the optional milestone and daily
+/// summary notifications.
+fn notifications_submenu(
+    app: &impl Manager<tauri::Wry>,
+) -> Result<tauri::menu::Submenu<tauri::Wry>, Box<dyn std::error::Error>> {
+    Ok(SubmenuBuilder::new(app, t("notifications"))
+        .item(
+            &MenuItemBuilder::with_id(
+                "toggle-milestones",
+                checked(t("milestones"), crate::settings::milestone_notifications()),
+            )
+            .build(app)?,
+        )
+        .item(
+            &MenuItemBuilder::with_id(
+                "toggle-daily-summary",
+                checked(t("daily_summary"), crate::settings::daily_summary()),
+            )
+            .build(app)?,
+        )
+        .build()?)
+}
+
+/// "Menu Bar Title" submenu choosing which count sits next to the icon.
+fn title_submenu(
+    app: &impl Manager<tauri::Wry>,
+) -> Result<tauri::menu::Submenu<tauri::Wry>, Box<dyn std::error::Error>> {
+    use crate::settings::TitleDisplay;
+
+    let display = crate::settings::title_display();
+    let option = |id: &str, label: &str, value: TitleDisplay| {
+        MenuItemBuilder::with_id(id, checked(label, display == value)).build(app)
+    };
+    let compact = checked(t("compact_numbers"), crate::settings::compact_title());
+    Ok(SubmenuBuilder::new(app, t("menu_bar_title"))
+        .item(&option("title-total", t("title_total"), TitleDisplay::Total)?)
+        .item(&option("title-today", t("title_today"), TitleDisplay::Today)?)
+        .item(&option("title-hidden", t("title_hidden"), TitleDisplay::Hidden)?)
+        .separator()
+        .item(&MenuItemBuilder::with_id("toggle-compact-title", compact).build(app)?)
+        .build()?)
+}
+
+/// "Language" submenu: follow the system, or pick a translation. Languages
+/// are listed by their own names.
+fn language_submenu(
+    app: &impl Manager<tauri::Wry>,
+) -> Result<tauri::menu::Submenu<tauri::Wry>, Box<dyn std::error::Error>> {
+    let chosen = crate::settings::language();
+    let mut submenu = SubmenuBuilder::new(app, t("language")).item(
+        &MenuItemBuilder::with_id("language-system", checked(t("language_system"), chosen.is_none()))
+            .build(app)?,
+    );
+    submenu = submenu.separator();
+    for locale in Locale::ALL {
+        submenu = submenu.item(
+            &MenuItemBuilder::with_id(
+                format!("language-{}", locale.code()),
+                checked(locale.name(), chosen == Some(locale)),
+            )
+            .build(app)?,
+        );
+    }
+    Ok(submenu.build()?)
+}
+
+/// "Update Available: vX.Y.Z", shown once a check finds a newer release.
+fn update_item(
+    app: &impl Manager<tauri::Wry>,
+) -> Result<Option<tauri::menu::MenuItem<tauri::Wry>>, Box<dyn std::error::Error>> {
+    let Some(release) = crate::update::available() else {
+        return Ok(None);
+    };
+    Ok(Some(
+        MenuItemBuilder::with_id(
+            "update-available",
+            format!("⬆️ {}", tf("update_available", &[&release.version])),
+        )
+        .build(app)?,
+    ))
+}
+
+/// Whether a dead-job count has reached the configured warning threshold.
+pub fn dead_jobs_over_threshold(dead_jobs: Option<u64>) -> bool {
+    match (dead_jobs, crate::settings::dead_job_threshold()) {
+        (Some(dead), Some(threshold)) => dead >= threshold,
+        _ => false,
+    }
+}
+
+/// "N captures queued" line, shown while quick captures wait in the spool.
+fn queued_captures_item(
+    app: &impl Manager<tauri::Wry>,
+) -> Result<Option<tauri::menu::MenuItem<tauri::Wry>>, Box<dyn std::error::Error>> {
+    let queued = crate::spool::len();
+    if queued == 0 {
+        return Ok(None);
+    }
+    let label = if queued == 1 {
+        format!("📥 {}", t("captures_queued_one"))
+    } else {
+        format!("📥 {}", tf("captures_queued", &[&format_number(queued as u64)]))
+    };
+    Ok(Some(
+        MenuItemBuilder::with_id("info-queued-captures", label)
+            .enabled(false)
+            .build(app)?,
+    ))
+}
+
 pub fn build_running_menu(
     app: &tauri::AppHandle,
     version: &str,
@@ -252,6 +818,10 @@ pub fn build_running_menu(
     queue_depth: Option<u64>,
     recent_memories: &[commands::RecentMemory],
     _ingestion_rate: Option<f64>,
+    job_queues: Option<&commands::JobQueues>,
+    resources: Option<&ProcessStats>,
+    capture_pause: Option<&commands::CapturePause>,
+    predictor: Option<&commands::PredictorStatus>,
 ) -> Result<tauri::menu::Menu<tauri::Wry>, Box<dyn std::error::Error>> {
     let mut builder = MenuBuilder::new(app);
 
@@ -259,7 +829,7 @@ pub fn build_running_menu(
     builder = builder.item(
         &MenuItemBuilder::with_id(
             "status",
-            format!("Signet v{version} — Running"),
+            tf("status_running", &[&version]),
         )
         .enabled(false)
         .build(app)?,
@@ -270,10 +840,13 @@ pub fn build_running_menu(
     // Stats section
     let mem_label = match (memory_count, memories_today) {
         (Some(total), Some(today)) => {
-            format!("📊 {} memories ({} today)", format_number(total), format_number(today))
+            format!(
+                "📊 {}",
+                tf("memories_with_today", &[&format_number(total), &format_number(today)])
+            )
         }
-        (Some(total), None) => format!("📊 {} memories", format_number(total)),
-        _ => "📊 Memories: loading...".to_string(),
+        (Some(total), None) => format!("📊 {}", tf("memories", &[&format_number(total)])),
+        _ => format!("📊 {}", t("memories_loading")),
     };
     builder = builder.item(
         &MenuItemBuilder::with_id("info-memories", &mem_label)
@@ -287,19 +860,35 @@ pub fn build_running_menu(
             let provider_suffix = embedding_provider
                 .map(|p| format!(" ({})", p))
                 .unwrap_or_default();
-            format!("🧠 Embeddings: {}% coverage{}", pct, provider_suffix)
+            format!("🧠 {}{}", tf("embeddings_coverage", &[&pct]), provider_suffix)
         }
-        None => "🧠 Embeddings: loading...".to_string(),
+        None => format!("🧠 {}", t("embeddings_loading")),
     };
     builder = builder.item(
         &MenuItemBuilder::with_id("info-embeddings", &embed_label)
             .enabled(false)
             .build(app)?,
     );
+    if let Some(progress) = commands::backfill_progress() {
+        builder = builder.item(
+            &MenuItemBuilder::with_id(
+                "info-backfill",
+                format!(
+                    "⏳ {}",
+                    tf(
+                        "backfilling",
+                        &[&format_number(progress.done), &format_number(progress.of)]
+                    )
+                ),
+            )
+            .enabled(false)
+            .build(app)?,
+        );
+    }
 
     let queue_label = match queue_depth {
-        Some(depth) => format!("⚡ Queue: {} pending", format_number(depth)),
-        None => "⚡ Queue: loading...".to_string(),
+        Some(depth) => format!("⚡ {}", tf("queue_pending", &[&format_number(depth)])),
+        None => format!("⚡ {}", t("queue_loading")),
     };
     builder = builder.item(
         &MenuItemBuilder::with_id("info-queue", &queue_label)
@@ -317,37 +906,113 @@ pub fn build_running_menu(
             } else {
                 "❤️"
             };
-            format!("{} Health: {} ({}/100)", emoji, status, score_display)
+            format!("{} {}", emoji, tf("health_status", &[&status, &score_display]))
         }
         (Some(score), None) => {
             let score_display = (score * 100.0).round() as u32;
-            format!("💚 Health: {}/100", score_display)
+            format!("💚 {}", tf("health_score", &[&score_display]))
         }
-        _ => "💚 Health: loading...".to_string(),
+        _ => format!("💚 {}", t("health_loading")),
     };
     builder = builder.item(
         &MenuItemBuilder::with_id("info-health", &health_label)
             .enabled(false)
             .build(app)?,
     );
+    if let Some(usage) = resources {
+        builder = builder.item(
+            &MenuItemBuilder::with_id(
+                "info-resources",
+                format!(
+                    "🖥️ {}",
+                    tf(
+                        "resources",
+                        &[
+                            &usage.cpu_percent,
+                            &stats::format_bytes(usage.rss_bytes),
+                            &stats::format_uptime(usage.uptime_secs),
+                        ]
+                    )
+                ),
+            )
+            .enabled(false)
+            .build(app)?,
+        );
+    }
+    if let Some(item) = queued_captures_item(app)? {
+        builder = builder.item(&item);
+    }
+    let dead_jobs = job_queues.map(|q| q.dead());
+    if dead_jobs_over_threshold(dead_jobs) {
+        let dead = dead_jobs.unwrap_or_default();
+        builder = builder.item(
+            &MenuItemBuilder::with_id(
+                "info-dead-jobs",
+                format!("⚠️ {}", tf("dead_jobs", &[&format_number(dead)])),
+            )
+            .enabled(false)
+            .build(app)?,
+        );
+    }
 
     builder = builder.item(&PredefinedMenuItem::separator(app)?);
 
     // Actions
     builder = builder.item(
-        &MenuItemBuilder::with_id("quick-capture", "✏️ Quick Capture...")
+        &MenuItemBuilder::with_id("quick-capture", format!("✏️ {}", t("quick_capture")))
+            .build(app)?,
+    );
+    builder = builder.item(
+        &MenuItemBuilder::with_id("search-memories", format!("🔍 {}", t("search_memories")))
             .build(app)?,
     );
     builder = builder.item(
-        &MenuItemBuilder::with_id("search-memories", "🔍 Search Memories...")
+        &MenuItemBuilder::with_id("export-memories", format!("📤 {}", t("export_memories")))
             .build(app)?,
     );
+    // Offered while coverage is short of 100%
+    if embedding_coverage.is_some_and(|cov| cov < 1.0) && commands::backfill_progress().is_none() {
+        builder = builder.item(
+            &MenuItemBuilder::with_id("backfill-embeddings", format!("🧠 {}", t("backfill_embeddings")))
+                .build(app)?,
+        );
+    }
+
+    // Capture pause
+    match capture_pause {
+        Some(pause) => {
+            builder = builder.item(
+                &MenuItemBuilder::with_id(
+                    "info-capture-paused",
+                    format!("⏸️ {}", capture_pause_label(pause)),
+                )
+                .enabled(false)
+                .build(app)?,
+            );
+            builder = builder.item(
+                &MenuItemBuilder::with_id("capture-resume", format!("▶️ {}", t("resume_capture")))
+                    .build(app)?,
+            );
+        }
+        None => {
+            let submenu = SubmenuBuilder::new(app, format!("⏸️ {}", t("pause_capture")))
+                .item(&MenuItemBuilder::with_id("capture-pause-60", t("pause_1h")).build(app)?)
+                .item(&MenuItemBuilder::with_id("capture-pause-240", t("pause_4h")).build(app)?)
+                .item(
+                    &MenuItemBuilder::with_id("capture-pause-indefinite", t("pause_until_resumed"))
+                        .build(app)?,
+                )
+                .build()?;
+            builder = builder.item(&submenu);
+        }
+    }
 
     builder = builder.item(&PredefinedMenuItem::separator(app)?);
 
     // Recent memories submenu
+    let mut recent_menu = Vec::new();
     if !recent_memories.is_empty() {
-        let mut submenu = SubmenuBuilder::new(app, "Recent Memories");
+        let mut submenu = SubmenuBuilder::new(app, t("recent_memories"));
 
         for (i, mem) in recent_memories.iter().take(10).enumerate() {
             let content_preview = truncate(
@@ -358,52 +1023,93 @@ pub fn build_running_menu(
             let label = format!("\"{}\" — {}", content_preview, ago);
             let id = format!("recent-memory-{}", i);
 
-            submenu = submenu.item(
-                &MenuItemBuilder::with_id(&id, &label)
-                    .enabled(false)
-                    .build(app)?,
-            );
+            submenu = submenu.item(&MenuItemBuilder::with_id(&id, &label).build(app)?);
+            recent_menu.push((id, mem.clone()));
         }
 
         builder = builder.item(&submenu.build()?);
         builder = builder.item(&PredefinedMenuItem::separator(app)?);
     }
+    *RECENT_MENU.lock().unwrap_or_else(|e| e.into_inner()) = recent_menu;
+
+    if let Some(queues) = job_queues {
+        builder = builder.item(&queue_submenu(app, queues)?);
+    }
+    if let Some(status) = predictor {
+        builder = builder.item(&predictor_submenu(app, status)?);
+    }
+    if job_queues.is_some() || predictor.is_some() {
+        builder = builder.item(&PredefinedMenuItem::separator(app)?);
+    }
 
     // Controls
     builder = builder.item(
-        &MenuItemBuilder::with_id("open-dashboard", "Open Dashboard")
+        &MenuItemBuilder::with_id("open-dashboard", t("open_dashboard"))
             .build(app)?,
     );
     builder = builder.item(
-        &MenuItemBuilder::with_id("stop-daemon", "Stop Daemon")
+        &MenuItemBuilder::with_id("open-logs", t("open_logs"))
             .build(app)?,
     );
     builder = builder.item(
-        &MenuItemBuilder::with_id("restart-daemon", "Restart Daemon")
+        &MenuItemBuilder::with_id("open-data-dir", t("open_data_dir"))
             .build(app)?,
     );
+    builder = builder.item(
+        &MenuItemBuilder::with_id("diagnostics-bundle", t("diagnostics_bundle"))
+            .build(app)?,
+    );
+    builder = builder.item(
+        &MenuItemBuilder::with_id("stop-daemon", t("stop_daemon"))
+            .build(app)?,
+    );
+    builder = builder.item(
+        &MenuItemBuilder::with_id("restart-daemon", t("restart_daemon"))
+            .build(app)?,
+    );
+    if crate::token::read().is_some() {
+        builder = builder.item(
+            &MenuItemBuilder::with_id("rotate-token", t("rotate_token"))
+                .build(app)?,
+        );
+    }
+
+    // Profile switcher
+    if let Some(submenu) = profile_submenu(app)? {
+        builder = builder.item(&submenu);
+        builder = builder.item(&PredefinedMenuItem::separator(app)?);
+    }
 
-    // Autostart toggle (macOS and Windows)
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    // Autostart toggle
     {
-        let autostart_label = if crate::platform::autostart::is_autostart_enabled() {
-            "Start at Login ✓"
-        } else {
-            "Start at Login"
-        };
+        let autostart_label = checked(
+            t("start_at_login"),
+            crate::platform::autostart::is_autostart_enabled(),
+        );
         builder = builder.item(
             &MenuItemBuilder::with_id("toggle-autostart", autostart_label)
                 .build(app)?,
         );
+        builder = builder.item(&launch_start_item(app)?);
+        builder = builder.item(&supervise_item(app)?);
+        if let Some(item) = install_service_item(app)? {
+            builder = builder.item(&item);
+        }
+        builder = builder.item(&notifications_submenu(app)?);
+        builder = builder.item(&title_submenu(app)?);
+        builder = builder.item(&language_submenu(app)?);
     }
 
     builder = builder.item(&PredefinedMenuItem::separator(app)?);
+    if let Some(item) = update_item(app)? {
+        builder = builder.item(&item);
+    }
     builder = builder.item(
-        &MenuItemBuilder::with_id("check-for-update", "Check for Updates...")
+        &MenuItemBuilder::with_id("check-for-update", t("check_updates"))
             .build(app)?,
     );
     builder = builder.item(
-        &MenuItemBuilder::with_id("quit", "Quit Signet")
+        &MenuItemBuilder::with_id("quit", t("quit"))
             .build(app)?,
     );
 
@@ -415,45 +1121,70 @@ pub fn build_stopped_menu(
 ) -> Result<tauri::menu::Menu<tauri::Wry>, Box<dyn std::error::Error>> {
     let menu = MenuBuilder::new(app)
         .item(
-            &MenuItemBuilder::with_id("status", "Signet — Stopped")
+            &MenuItemBuilder::with_id("status", t("status_stopped"))
                 .enabled(false)
                 .build(app)?,
-        )
+        );
+    let menu = match queued_captures_item(app)? {
+        Some(item) => menu.item(&item),
+        None => menu,
+    };
+    let menu = menu
         .item(&PredefinedMenuItem::separator(app)?)
         .item(
-            &MenuItemBuilder::with_id("start-daemon", "Start Daemon")
+            &MenuItemBuilder::with_id("start-daemon", t("start_daemon"))
                 .build(app)?,
         )
         .item(
-            &MenuItemBuilder::with_id("open-dashboard", "Open Dashboard")
+            &MenuItemBuilder::with_id("open-dashboard", t("open_dashboard"))
                 .build(app)?,
         )
-        .item(&PredefinedMenuItem::separator(app)?);
-
-    // Autostart toggle (macOS and Windows)
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
-    let menu = {
-        let autostart_label = if crate::platform::autostart::is_autostart_enabled() {
-            "Start at Login ✓"
-        } else {
-            "Start at Login"
-        };
-        menu.item(
-            &MenuItemBuilder::with_id("toggle-autostart", autostart_label)
+        .item(
+            &MenuItemBuilder::with_id("open-logs", t("open_logs"))
+                .build(app)?,
+        )
+        .item(
+            &MenuItemBuilder::with_id("open-data-dir", t("open_data_dir"))
                 .build(app)?,
         )
-        .item(&PredefinedMenuItem::separator(app)?)
         .item(
-            &MenuItemBuilder::with_id("quit", "Quit Signet")
+            &MenuItemBuilder::with_id("diagnostics-bundle", t("diagnostics_bundle"))
                 .build(app)?,
         )
-        .build()?
+        .item(&PredefinedMenuItem::separator(app)?);
+
+    // Profile switcher
+    let menu = match profile_submenu(app)? {
+        Some(submenu) => menu
+            .item(&submenu)
+            .item(&PredefinedMenuItem::separator(app)?),
+        None => menu,
     };
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    // Autostart toggle
+    let autostart_label = checked(
+        t("start_at_login"),
+        crate::platform::autostart::is_autostart_enabled(),
+    );
+    let menu = menu
+        .item(
+            &MenuItemBuilder::with_id("toggle-autostart", autostart_label)
+                .build(app)?,
+        )
+        .item(&launch_start_item(app)?)
+        .item(&supervise_item(app)?);
+    let menu = match install_service_item(app)? {
+        Some(item) => menu.item(&item),
+        None => menu,
+    };
+    let menu = menu.item(&PredefinedMenuItem::separator(app)?);
+    let menu = match update_item(app)? {
+        Some(item) => menu.item(&item),
+        None => menu,
+    };
     let menu = menu
         .item(
-            &MenuItemBuilder::with_id("quit", "Quit Signet")
+            &MenuItemBuilder::with_id("quit", t("quit"))
                 .build(app)?,
         )
         .build()?;
@@ -469,50 +1200,91 @@ pub fn build_error_menu(
         .item(
             &MenuItemBuilder::with_id(
                 "status",
-                format!("Signet — Error: {error}"),
+                tf("status_error", &[&error]),
             )
             .enabled(false)
             .build(app)?,
+        );
+    let menu = match queued_captures_item(app)? {
+        Some(item) => menu.item(&item),
+        None => menu,
+    };
+    let menu = menu.item(&PredefinedMenuItem::separator(app)?);
+    let alternate_port = if crate::conflict::is_conflict(error) && crate::conflict::can_move_port() {
+        crate::conflict::free_port()
+    } else {
+        None
+    };
+    let menu = match alternate_port {
+        Some(port) => menu.item(
+            &MenuItemBuilder::with_id("start-alt-port", tf("start_alt_port", &[&port]))
+                .build(app)?,
+        ),
+        None => menu,
+    };
+    let menu = menu
+        .item(
+            &MenuItemBuilder::with_id("start-daemon", t("start_daemon"))
+                .build(app)?,
         )
-        .item(&PredefinedMenuItem::separator(app)?)
         .item(
-            &MenuItemBuilder::with_id("start-daemon", "Start Daemon")
+            &MenuItemBuilder::with_id("restart-daemon", t("restart_daemon"))
                 .build(app)?,
         )
         .item(
-            &MenuItemBuilder::with_id("restart-daemon", "Restart Daemon")
+            &MenuItemBuilder::with_id("open-dashboard", t("open_dashboard"))
                 .build(app)?,
         )
         .item(
-            &MenuItemBuilder::with_id("open-dashboard", "Open Dashboard")
+            &MenuItemBuilder::with_id("open-logs", t("open_logs"))
                 .build(app)?,
         )
-        .item(&PredefinedMenuItem::separator(app)?);
-
-    // Autostart toggle (macOS and Windows)
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
-    let menu = {
-        let autostart_label = if crate::platform::autostart::is_autostart_enabled() {
-            "Start at Login ✓"
-        } else {
-            "Start at Login"
-        };
-        menu.item(
-            &MenuItemBuilder::with_id("toggle-autostart", autostart_label)
+        .item(
+            &MenuItemBuilder::with_id("open-data-dir", t("open_data_dir"))
                 .build(app)?,
         )
-        .item(&PredefinedMenuItem::separator(app)?)
         .item(
-            &MenuItemBuilder::with_id("quit", "Quit Signet")
+            &MenuItemBuilder::with_id("diagnostics-bundle", t("diagnostics_bundle"))
+                .build(app)?,
+        );
+    let menu = if crate::api::auth_rejected() {
+        menu.item(
+            &MenuItemBuilder::with_id("pair-daemon", t("pair_daemon"))
                 .build(app)?,
         )
-        .build()?
+    } else {
+        menu
     };
+    let menu = menu.item(&PredefinedMenuItem::separator(app)?);
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    // Profile switcher
+    let menu = match profile_submenu(app)? {
+        Some(submenu) => menu
+            .item(&submenu)
+            .item(&PredefinedMenuItem::separator(app)?),
+        None => menu,
+    };
+
+    // Autostart toggle
+    let autostart_label = checked(
+        t("start_at_login"),
+        crate::platform::autostart::is_autostart_enabled(),
+    );
+    let menu = menu
+        .item(
+            &MenuItemBuilder::with_id("toggle-autostart", autostart_label)
+                .build(app)?,
+        )
+        .item(&launch_start_item(app)?)
+        .item(&supervise_item(app)?)
+        .item(&PredefinedMenuItem::separator(app)?);
+    let menu = match update_item(app)? {
+        Some(item) => menu.item(&item),
+        None => menu,
+    };
     let menu = menu
         .item(
-            &MenuItemBuilder::with_id("quit", "Quit Signet")
+            &MenuItemBuilder::with_id("quit", t("quit"))
                 .build(app)?,
         )
         .build()?;
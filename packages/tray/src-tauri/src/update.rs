@@ -0,0 +1,345 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::commands;
+use crate::daemon;
+use crate::export;
+use crate::notify;
+use crate::proxy::{self, ProxyOptions};
+use crate::settings;
+
+/// Latest published release, with its notes
+const GITHUB_LATEST: &str = "https://api.github.com/repos/Signet-AI/signetai/releases/latest";
+
+/// Fallback when GitHub is unreachable or rate limited; version only
+const NPM_LATEST: &str = "https://registry.npmjs.org/signetai/latest";
+
+/// Where a release found through npm is shown
+const RELEASES_PAGE: &str = "https://github.com/Signet-AI/signetai/releases";
+
+/// How often the background check runs
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Signed update manifest that release builds attach to each release
+const UPDATER_ENDPOINT: &str =
+    "https://github.com/Signet-AI/signetai/releases/latest/download/latest.json";
+
+/// Public key the update artifacts are signed with. Set at build time by
+/// release builds; without it the tray can't verify, and so won't
+/// install, an update.
+const UPDATER_PUBKEY: Option<&str> = option_env!("SIGNET_UPDATER_PUBKEY");
+
+/// How long a daemon the tray just started may take to come up before an
+/// update stops waiting for it
+const DAEMON_START_GRACE: Duration = Duration::from_secs(60);
+
+/// How often an install waiting for tray work rechecks it
+const IDLE_POLL: Duration = Duration::from_secs(2);
+
+/// Wait after launch before the first check, so it doesn't compete with
+/// starting the daemon
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+
+/// A published release newer than the running tray.
+#[derive(Serialize, Clone)]
+pub struct Release {
+    pub version: String,
+    /// The running version, for the release notes window
+    pub current: String,
+    /// Release notes as Markdown. `None` when found through npm.
+    pub notes: Option<String>,
+    pub url: String,
+    pub published_at: Option<String>,
+    /// Whether this build can install it itself
+    pub installable: bool,
+}
+
+/// How far installing an update has got, for the release notes window.
+#[derive(Serialize, Clone)]
+pub struct InstallProgress {
+    pub running: bool,
+    pub downloaded: u64,
+    /// Download size, once the server has said
+    pub total: Option<u64>,
+    /// What is happening now, e.g. "Downloading..."
+    pub status: String,
+}
+
+/// The newer release from the last successful check
+static AVAILABLE: Mutex<Option<Release>> = Mutex::new(None);
+
+/// The last version announced with a notification, so each is announced
+/// once per run
+static ANNOUNCED: Mutex<Option<String>> = Mutex::new(None);
+
+static INSTALLING: AtomicBool = AtomicBool::new(false);
+static INSTALL: Mutex<InstallProgress> = Mutex::new(InstallProgress {
+    running: false,
+    downloaded: 0,
+    total: None,
+    status: String::new(),
+});
+
+#[derive(Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    html_url: String,
+    body: Option<String>,
+    published_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NpmPackage {
+    version: String,
+}
+
+/// The newer release, if the last check found one.
+pub fn available() -> Option<Release> {
+    AVAILABLE.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    proxy::configure(reqwest::Client::builder(), &ProxyOptions::load())?
+        .user_agent(concat!("signet-tray/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// The latest release, from GitHub or else npm. `current` is left for the
+/// caller to fill in.
+async fn fetch_latest() -> Result<Release, String> {
+    let client = client()?;
+    let github = async {
+        let res = client
+            .get(GITHUB_LATEST)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !res.status().is_success() {
+            return Err(format!("GitHub releases lookup failed ({})", res.status()));
+        }
+        res.json::<GitHubRelease>().await.map_err(|e| e.to_string())
+    };
+    let github_err = match github.await {
+        Ok(release) => {
+            return Ok(Release {
+                version: release.tag_name.trim_start_matches('v').to_string(),
+                current: String::new(),
+                notes: release.body.filter(|b| !b.trim().is_empty()),
+                url: release.html_url,
+                published_at: release.published_at,
+                installable: false,
+            })
+        }
+        Err(e) => e,
+    };
+
+    let res = client
+        .get(NPM_LATEST)
+        .send()
+        .await
+        .map_err(|e| format!("{}; npm: {}", github_err, e))?;
+    if !res.status().is_success() {
+        return Err(format!(
+            "{}; npm lookup failed ({})",
+            github_err,
+            res.status()
+        ));
+    }
+    let package: NpmPackage = res.json().await.map_err(|e| e.to_string())?;
+    Ok(Release {
+        url: format!("{}/tag/v{}", RELEASES_PAGE, package.version),
+        version: package.version,
+        current: String::new(),
+        notes: None,
+        published_at: None,
+        installable: false,
+    })
+}
+
+/// Numeric `major.minor.patch` parts, ignoring any pre-release or build
+/// suffix.
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+fn is_newer(latest: &str, current: &str) -> bool {
+    version_parts(latest) > version_parts(current)
+}
+
+/// Check for a newer release and remember the result, refreshing the tray
+/// when what's available changes. Returns the newer release, if any.
+pub async fn check(app: &AppHandle) -> Result<Option<Release>, String> {
+    let current = app.package_info().version.to_string();
+    let mut latest = fetch_latest().await?;
+    latest.current = current.clone();
+    latest.installable = updater_pubkey().is_some();
+    let newer = is_newer(&latest.version, &current).then_some(latest);
+
+    let changed = {
+        let mut available = AVAILABLE.lock().unwrap_or_else(|e| e.into_inner());
+        let changed = available.as_ref().map(|r| &r.version) != newer.as_ref().map(|r| &r.version);
+        available.clone_from(&newer);
+        changed
+    };
+    if changed {
+        let _ = commands::refresh_tray(app);
+    }
+    Ok(newer)
+}
+
+/// Notify about a newer release, once per version.
+fn announce(app: &AppHandle, release: &Release) {
+    let mut announced = ANNOUNCED.lock().unwrap_or_else(|e| e.into_inner());
+    if announced.as_deref() != Some(release.version.as_str()) {
+        *announced = Some(release.version.clone());
+        notify::update_available(app, &release.version);
+    }
+}
+
+/// Check at the user's request. The result is shown to them directly, so
+/// the background check won't announce it again.
+pub async fn check_now(app: &AppHandle) -> Result<Option<Release>, String> {
+    let newer = check(app).await?;
+    if let Some(release) = &newer {
+        *ANNOUNCED.lock().unwrap_or_else(|e| e.into_inner()) = Some(release.version.clone());
+    }
+    Ok(newer)
+}
+
+/// Check shortly after launch and then every `CHECK_INTERVAL`, while
+/// update checks are on.
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(FIRST_CHECK_DELAY).await;
+        loop {
+            // A failed check is retried at the next interval
+            if settings::update_checks() {
+                if let Ok(Some(release)) = check(&app).await {
+                    announce(&app, &release);
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+fn updater_pubkey() -> Option<&'static str> {
+    UPDATER_PUBKEY.filter(|key| !key.is_empty())
+}
+
+pub fn install_progress() -> InstallProgress {
+    INSTALL.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn set_status(status: &str) {
+    INSTALL.lock().unwrap_or_else(|e| e.into_inner()).status = status.to_string();
+}
+
+/// Tray work that restarting now would cut short, as "waiting for ..."
+/// text. The daemon itself is unaffected by the restart.
+fn busy() -> Option<&'static str> {
+    let (attempts, last_start) = daemon::start_attempts();
+    if export::progress().running {
+        Some("Waiting for the export to finish...")
+    } else if commands::backfill_progress().is_some() {
+        Some("Waiting for the embedding backfill to finish...")
+    } else if commands::predictor_training() {
+        Some("Waiting for predictor training to finish...")
+    } else if attempts > 0 && last_start.is_some_and(|at| at.elapsed() < DAEMON_START_GRACE) {
+        // Restarting now could have the new tray start a second daemon
+        Some("Waiting for the daemon to start...")
+    } else {
+        None
+    }
+}
+
+/// Download, verify and install the latest release, then restart the
+/// tray into it. The daemon keeps running throughout: the tray waits for
+/// its own in-flight work, holds the capture spool so no queued capture
+/// is mid-delivery, and never stops or restarts the daemon. Only returns
+/// on failure.
+pub async fn install(app: &AppHandle) -> Result<(), String> {
+    use tauri_plugin_updater::UpdaterExt;
+
+    let pubkey = updater_pubkey().ok_or(
+        "This build can't install updates. Download the new version from the release page.",
+    )?;
+    if INSTALLING.swap(true, Ordering::SeqCst) {
+        return Err("An update is already being installed".to_string());
+    }
+    *INSTALL.lock().unwrap_or_else(|e| e.into_inner()) = InstallProgress {
+        running: true,
+        downloaded: 0,
+        total: None,
+        status: "Checking...".to_string(),
+    };
+
+    let result = async {
+        let endpoint = url::Url::parse(UPDATER_ENDPOINT).map_err(|e| e.to_string())?;
+        let updater = app
+            .updater_builder()
+            .pubkey(pubkey)
+            .endpoints(vec![endpoint])
+            .map_err(|e| e.to_string())?
+            .build()
+            .map_err(|e| e.to_string())?;
+        let update = updater
+            .check()
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("No installable update for this platform")?;
+
+        set_status("Downloading...");
+        // The signature is verified as part of the download
+        let bytes = update
+            .download(
+                |chunk, total| {
+                    let mut progress = INSTALL.lock().unwrap_or_else(|e| e.into_inner());
+                    progress.downloaded += chunk as u64;
+                    progress.total = total;
+                },
+                || {},
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        while let Some(waiting) = busy() {
+            set_status(waiting);
+            tokio::time::sleep(IDLE_POLL).await;
+        }
+        // Held until the restart, so no flush takes captures off disk
+        let spool = commands::hold_capture_spool().await;
+
+        set_status("Installing...");
+        update.install(bytes).map_err(|e| e.to_string())?;
+        Ok::<_, String>(spool)
+    }
+    .await;
+
+    let _spool = match result {
+        Ok(spool) => spool,
+        Err(e) => {
+            INSTALLING.store(false, Ordering::SeqCst);
+            let mut progress = INSTALL.lock().unwrap_or_else(|e| e.into_inner());
+            progress.running = false;
+            progress.status = format!("Error: {}", e);
+            return Err(e);
+        }
+    };
+    set_status("Restarting...");
+    app.restart()
+}